@@ -0,0 +1,194 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use lockfree_snowflake::bit_layout::BitLayout;
+use lockfree_snowflake::clock::SystemClock;
+use lockfree_snowflake::generator_policy::OverflowPolicy;
+use lockfree_snowflake::sharded_snowflake_generator::ShardedSnowflakeGenerator;
+use lockfree_snowflake::snowflake_error::SnowflakeIdEGeneratorError;
+use lockfree_snowflake::snowflake_id_generator::SnowFlakeIdGenerator;
+use lockfree_snowflake::thread_local_generator::ThreadLocalGenerator;
+use lockfree_snowflake::THE_EPOCH_MILLIS;
+use std::hint::black_box;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::Relaxed;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const THREADS: [usize; 3] = [2, 4, 8];
+
+// The default layout caps a machine at 4096 IDs per millisecond (~244ns per ID), which
+// would hide the cost of the generator itself, so the benches use the widest sequence
+// field and wait for the next millisecond instead of failing once it runs out.
+const LAYOUT: BitLayout = match BitLayout::new(37, 10, 16) {
+    Ok(layout) => layout,
+    Err(_) => panic!(),
+};
+
+fn generator() -> SnowFlakeIdGenerator<SystemClock> {
+    SnowFlakeIdGenerator::with_epoch_millis(SystemClock, THE_EPOCH_MILLIS, 42, LAYOUT)
+        .unwrap()
+        .with_overflow_policy(OverflowPolicy::SpinUntilNextMillis)
+}
+
+fn sharded(shard_bits: u32) -> ShardedSnowflakeGenerator<SystemClock> {
+    ShardedSnowflakeGenerator::with_epoch_millis(
+        SystemClock,
+        THE_EPOCH_MILLIS,
+        42,
+        shard_bits,
+        LAYOUT,
+    )
+    .unwrap()
+    .with_overflow_policy(OverflowPolicy::SpinUntilNextMillis)
+}
+
+fn generate_one(generate: impl Fn() -> Result<u64, SnowflakeIdEGeneratorError>) -> u64 {
+    loop {
+        if let Ok(id) = generate() {
+            return id;
+        }
+    }
+}
+
+// Splits `iters` IDs across `threads` threads and returns the wall time of the slowest one.
+fn contended(iters: u64, threads: usize, work: impl Fn(u64) + Sync) -> Duration {
+    let per_thread = iters / threads as u64 + 1;
+
+    thread::scope(|scope| {
+        let start = Instant::now();
+
+        let handles: Vec<_> = (0..threads)
+            .map(|_| scope.spawn(|| work(per_thread)))
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        start.elapsed()
+    })
+}
+
+fn single_thread(c: &mut Criterion) {
+    let mut group = c.benchmark_group("single_thread");
+    group.throughput(Throughput::Elements(1));
+
+    let target = generator();
+    group.bench_function("snowflake", |b| {
+        b.iter(|| black_box(generate_one(|| target.try_generate().map(|x| x.as_u64()))))
+    });
+
+    let target =
+        ThreadLocalGenerator::with_epoch_millis(SystemClock, THE_EPOCH_MILLIS, 42, 0, LAYOUT)
+            .unwrap();
+    let local = target.local().unwrap();
+    group.bench_function("thread_local", |b| {
+        b.iter(|| black_box(generate_one(|| local.try_generate().map(|x| x.as_u64()))))
+    });
+
+    group.finish();
+}
+
+fn contention(c: &mut Criterion) {
+    let mut group = c.benchmark_group("contention");
+    group.throughput(Throughput::Elements(1));
+
+    for threads in THREADS {
+        let target = generator();
+        group.bench_with_input(
+            BenchmarkId::new("snowflake", threads),
+            &threads,
+            |b, &threads| {
+                b.iter_custom(|iters| {
+                    contended(iters, threads, |count| {
+                        for _ in 0..count {
+                            black_box(generate_one(|| target.try_generate().map(|x| x.as_u64())));
+                        }
+                    })
+                })
+            },
+        );
+
+        let target = sharded(threads.trailing_zeros());
+        group.bench_with_input(
+            BenchmarkId::new("sharded", threads),
+            &threads,
+            |b, &threads| {
+                b.iter_custom(|iters| {
+                    contended(iters, threads, |count| {
+                        for _ in 0..count {
+                            black_box(generate_one(|| target.try_generate().map(|x| x.as_u64())));
+                        }
+                    })
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+// One generator per thread, allocated side by side. Nothing is shared logically, so any
+// slowdown over the thread count is false sharing between neighbouring generators.
+fn independent(c: &mut Criterion) {
+    let mut group = c.benchmark_group("independent");
+    group.throughput(Throughput::Elements(1));
+
+    for threads in THREADS {
+        let targets: Vec<_> = (0..threads as u16)
+            .map(|machine_id| {
+                SnowFlakeIdGenerator::with_epoch_millis(
+                    SystemClock,
+                    THE_EPOCH_MILLIS,
+                    machine_id,
+                    LAYOUT,
+                )
+                .unwrap()
+                .with_overflow_policy(OverflowPolicy::SpinUntilNextMillis)
+            })
+            .collect();
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(threads),
+            &threads,
+            |b, &threads| {
+                b.iter_custom(|iters| {
+                    let next = AtomicUsize::new(0);
+
+                    contended(iters, threads, |count| {
+                        let target = &targets[next.fetch_add(1, Relaxed)];
+                        for _ in 0..count {
+                            black_box(generate_one(|| target.try_generate().map(|x| x.as_u64())));
+                        }
+                    })
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch");
+
+    for size in [16usize, 256, 4096] {
+        let target = generator();
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            b.iter(|| {
+                let mut remaining = size;
+                while remaining > 0 {
+                    if let Some(batch) = target.generate_batch(remaining) {
+                        remaining -= batch.len();
+                        black_box(batch);
+                    }
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, single_thread, contention, independent, batch);
+criterion_main!(benches);
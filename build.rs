@@ -0,0 +1,6 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "server")]
+    tonic_build::compile_protos("proto/snowflake.proto").unwrap();
+}
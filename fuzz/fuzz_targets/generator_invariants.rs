@@ -0,0 +1,54 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use lockfree_snowflake::bit_layout::BitLayout;
+use lockfree_snowflake::generator_policy::ClockRegressionPolicy;
+use lockfree_snowflake::snowflake_id_generator::SnowFlakeIdGenerator;
+use lockfree_snowflake::testing::ManualClock;
+use lockfree_snowflake::THE_EPOCH_MILLIS;
+use std::time::Duration;
+
+#[derive(Arbitrary, Debug)]
+enum Step {
+    Generate,
+    Advance(u8),
+    Rewind(u8),
+}
+
+// Whatever the clock does, every issued ID carries the configured machine id and sorts
+// after the one before it.
+fuzz_target!(|input: (BitLayout, u16, bool, Vec<Step>)| {
+    let (layout, machine_id, reuse_last, steps) = input;
+    let machine_id = machine_id & layout.max_machine_id();
+    let policy = if reuse_last {
+        ClockRegressionPolicy::UseLastTimestamp
+    } else {
+        ClockRegressionPolicy::Reject
+    };
+
+    let clock = ManualClock::new(THE_EPOCH_MILLIS + 1_000);
+    let generator = SnowFlakeIdGenerator::with_epoch_millis(
+        clock.clone(),
+        THE_EPOCH_MILLIS,
+        machine_id,
+        layout,
+    )
+    .unwrap()
+    .with_clock_regression_policy(policy);
+    let mut last = None;
+
+    for step in steps {
+        match step {
+            Step::Generate => {
+                if let Ok(id) = generator.try_generate() {
+                    assert_eq!(layout.machine_id(id), machine_id);
+                    assert!(last.is_none_or(|last| id > last));
+                    last = Some(id);
+                }
+            }
+            Step::Advance(millis) => clock.advance(Duration::from_millis(millis as u64)),
+            Step::Rewind(millis) => clock.rewind(Duration::from_millis(millis as u64)),
+        }
+    }
+});
@@ -0,0 +1,26 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use lockfree_snowflake::snow_flake_id::{SnowflakeId, MAX_DECIMAL_LEN};
+
+fuzz_target!(|input: (SnowflakeId, &str)| {
+    let (id, str) = input;
+
+    assert_eq!(id.to_string().parse::<SnowflakeId>(), Ok(id));
+    assert_eq!(SnowflakeId::from_hex_str(&format!("{:x}", id)), Ok(id));
+    assert_eq!(SnowflakeId::from_base62(&id.to_base62()), Ok(id));
+    assert_eq!(SnowflakeId::from_base58(&id.to_base58()), Ok(id));
+
+    let mut buf = [0u8; MAX_DECIMAL_LEN];
+    let len = id.write_decimal(&mut buf);
+    assert_eq!(&buf[..len], id.to_string().as_bytes());
+
+    // Whatever the parsers accept has to survive a trip through the matching encoder.
+    if let Ok(parsed) = str.parse::<SnowflakeId>() {
+        assert_eq!(parsed.to_string().parse::<SnowflakeId>(), Ok(parsed));
+    }
+
+    if let Ok(parsed) = SnowflakeId::from_base62(str) {
+        assert_eq!(SnowflakeId::from_base62(&parsed.to_base62()), Ok(parsed));
+    }
+});
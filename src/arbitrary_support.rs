@@ -0,0 +1,70 @@
+use crate::bit_layout::BitLayout;
+use crate::snow_flake_id::SnowflakeId;
+use crate::snowflake_id_128::SnowflakeId128;
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+// Every u64 is a valid ID, so the raw value is used as is.
+impl<'a> Arbitrary<'a> for SnowflakeId {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u64::arbitrary(u).map(SnowflakeId::from)
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        u64::size_hint(depth)
+    }
+}
+
+impl<'a> Arbitrary<'a> for SnowflakeId128 {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u128::arbitrary(u).map(SnowflakeId128::from)
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        u128::size_hint(depth)
+    }
+}
+
+// Only layouts `BitLayout::new` accepts, so fuzzers don't waste inputs on rejected ones.
+impl<'a> Arbitrary<'a> for BitLayout {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let sequence_bits = u.int_in_range(0..=u16::BITS)?;
+        let machine_bits = u.int_in_range(0..=u16::BITS)?;
+        let timestamp_bits = u.int_in_range(1..=u64::BITS - machine_bits - sequence_bits)?;
+
+        Ok(BitLayout::new(timestamp_bits, machine_bits, sequence_bits).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::snow_flake_id::SnowflakeId;
+    use crate::snowflake_id_128::SnowflakeId128;
+    use arbitrary::{Arbitrary, Unstructured};
+
+    const SAMPLE_SCR: u64 = 175_928_847_299_678_215;
+
+    #[test]
+    fn snowflake_id_test() {
+        let bytes = SAMPLE_SCR.to_le_bytes();
+
+        let actual = SnowflakeId::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        let expected = u64::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        assert_eq!(actual.as_u64(), expected);
+
+        let bytes = [0xab; 16];
+        let actual = SnowflakeId128::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        assert_eq!(actual.as_u128(), u128::from_le_bytes(bytes));
+    }
+
+    #[test]
+    fn bit_layout_test() {
+        for seed in 0..=u8::MAX {
+            let bytes = [seed, seed.wrapping_mul(31), seed.wrapping_mul(97), !seed];
+            let actual = BitLayout::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+
+            assert!(actual.timestamp_bits() >= 1);
+            assert!(actual.timestamp_bits() + actual.machine_bits() + actual.sequence_bits() <= 64);
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use lockfree_snowflake::bit_layout::BitLayout;
+use lockfree_snowflake::clock::SystemClock;
+use lockfree_snowflake::encoding;
+use lockfree_snowflake::epochs;
+use lockfree_snowflake::snow_flake_id::SnowflakeId;
+use lockfree_snowflake::snowflake_id_generator::SnowFlakeIdGenerator;
+use lockfree_snowflake::THE_EPOCH_MILLIS;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+usage:
+  snowflake decode <id> [--epoch <epoch>] [--layout <ts,machine,seq>]
+  snowflake generate [-n <count>] [--machine-id <id>] [--epoch <epoch>] [--layout <ts,machine,seq>]
+  snowflake convert <value> [--from dec|hex|base62] [--to dec|hex|base62]
+
+<epoch> is milliseconds since the Unix epoch or one of
+default, unix, twitter, discord, instagram, sonyflake.
+<id> and <value> accept decimal, 0x-prefixed hex or base62 (with --from).";
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+enum Radix {
+    Decimal,
+    Hex,
+    Base62,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+enum Command {
+    Decode {
+        id: SnowflakeId,
+        epoch_millis: i64,
+        layout: BitLayout,
+    },
+    Generate {
+        count: usize,
+        machine_id: u16,
+        epoch_millis: i64,
+        layout: BitLayout,
+    },
+    Convert {
+        value: u64,
+        to: Radix,
+    },
+}
+
+fn parse_epoch(str: &str) -> Result<i64, String> {
+    epochs::parse_millis(str).ok_or_else(|| format!("invalid epoch: {}", str))
+}
+
+fn parse_layout(str: &str) -> Result<BitLayout, String> {
+    let bits = str
+        .split(',')
+        .map(|x| x.trim().parse::<u32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("invalid layout: {}", str))?;
+
+    match bits[..] {
+        [timestamp, machine, sequence] => BitLayout::new(timestamp, machine, sequence)
+            .map_err(|e| format!("invalid layout: {}", e)),
+        _ => Err(format!("invalid layout: {}", str)),
+    }
+}
+
+fn parse_radix(str: &str) -> Result<Radix, String> {
+    match str {
+        "dec" => Ok(Radix::Decimal),
+        "hex" => Ok(Radix::Hex),
+        "base62" => Ok(Radix::Base62),
+        _ => Err(format!("invalid radix: {}", str)),
+    }
+}
+
+fn parse_value(str: &str, radix: Option<Radix>) -> Result<u64, String> {
+    let radix = radix.unwrap_or(if str.starts_with("0x") {
+        Radix::Hex
+    } else {
+        Radix::Decimal
+    });
+
+    match radix {
+        Radix::Decimal => str.parse().map_err(|_| format!("invalid decimal: {}", str)),
+        Radix::Hex => u64::from_str_radix(str.trim_start_matches("0x"), 16)
+            .map_err(|_| format!("invalid hex: {}", str)),
+        Radix::Base62 => {
+            encoding::from_base62(str).map_err(|e| format!("invalid base62: {} ({})", str, e))
+        }
+    }
+}
+
+fn format_value(value: u64, radix: Radix) -> String {
+    match radix {
+        Radix::Decimal => value.to_string(),
+        Radix::Hex => format!("{:#x}", value),
+        Radix::Base62 => encoding::to_base62(value),
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let (command, rest) = args.split_first().ok_or("missing command")?;
+
+    let mut positional = Vec::new();
+    let mut epoch_millis = THE_EPOCH_MILLIS;
+    let mut layout = BitLayout::DEFAULT;
+    let mut count = 1;
+    let mut machine_id = 0;
+    let mut from = None;
+    let mut to = Radix::Decimal;
+
+    let mut iter = rest.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || {
+            iter.next()
+                .ok_or_else(|| format!("missing value for {}", arg))
+        };
+
+        match arg.as_str() {
+            "--epoch" => epoch_millis = parse_epoch(value()?)?,
+            "--layout" => layout = parse_layout(value()?)?,
+            "-n" | "--count" => {
+                let str = value()?;
+                count = str.parse().map_err(|_| format!("invalid count: {}", str))?
+            }
+            "--machine-id" => {
+                let str = value()?;
+                machine_id = str
+                    .parse()
+                    .map_err(|_| format!("invalid machine id: {}", str))?
+            }
+            "--from" => from = Some(parse_radix(value()?)?),
+            "--to" => to = parse_radix(value()?)?,
+            _ if arg.starts_with('-') => return Err(format!("unknown option: {}", arg)),
+            _ => positional.push(arg.as_str()),
+        }
+    }
+
+    match (command.as_str(), &positional[..]) {
+        ("decode", [id]) => Ok(Command::Decode {
+            id: SnowflakeId::from(parse_value(id, from)?),
+            epoch_millis,
+            layout,
+        }),
+        ("generate", []) => Ok(Command::Generate {
+            count,
+            machine_id,
+            epoch_millis,
+            layout,
+        }),
+        ("convert", [value]) => Ok(Command::Convert {
+            value: parse_value(value, from)?,
+            to,
+        }),
+        ("decode" | "generate" | "convert", _) => Err("wrong number of arguments".to_string()),
+        _ => Err(format!("unknown command: {}", command)),
+    }
+}
+
+fn decode(id: SnowflakeId, epoch_millis: i64, layout: &BitLayout) -> Vec<String> {
+    let raw_timestamp = layout.raw_timestamp(id);
+    let timestamp = DateTime::<Utc>::from_timestamp_millis(epoch_millis + raw_timestamp as i64)
+        .map_or_else(
+            || "out of range".to_string(),
+            |x| x.to_rfc3339_opts(SecondsFormat::Millis, true),
+        );
+
+    vec![
+        format!("id:            {}", id),
+        format!("timestamp:     {}", timestamp),
+        format!("raw_timestamp: {}", raw_timestamp),
+        format!("machine_id:    {}", layout.machine_id(id)),
+        format!("sequence:      {}", layout.sequence(id)),
+    ]
+}
+
+fn run(command: Command) -> Result<Vec<String>, String> {
+    match command {
+        Command::Decode {
+            id,
+            epoch_millis,
+            layout,
+        } => Ok(decode(id, epoch_millis, &layout)),
+        Command::Generate {
+            count,
+            machine_id,
+            epoch_millis,
+            layout,
+        } => {
+            let generator = SnowFlakeIdGenerator::with_epoch_millis(
+                SystemClock,
+                epoch_millis,
+                machine_id,
+                layout,
+            )
+            .map_err(|e| e.to_string())?;
+
+            let mut ids = Vec::with_capacity(count);
+            while ids.len() < count {
+                if let Some(batch) = generator.generate_batch(count - ids.len()) {
+                    ids.extend(batch.map(|id| id.to_string()));
+                }
+            }
+
+            Ok(ids)
+        }
+        Command::Convert { value, to } => Ok(vec![format_value(value, to)]),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match parse_args(&args).and_then(run) {
+        Ok(lines) => {
+            for line in lines {
+                println!("{}", line);
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_args, run, Command, Radix};
+    use lockfree_snowflake::bit_layout::BitLayout;
+    use lockfree_snowflake::epochs;
+    use lockfree_snowflake::snow_flake_id::SnowflakeId;
+
+    const SAMPLE_SCR: u64 = 175_928_847_299_678_215;
+
+    fn args(str: &str) -> Vec<String> {
+        str.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn decode_test() {
+        let command = parse_args(&args("decode 175928847299678215 --epoch discord")).unwrap();
+        assert_eq!(
+            command,
+            Command::Decode {
+                id: SnowflakeId::from(SAMPLE_SCR),
+                epoch_millis: epochs::DISCORD_MILLIS,
+                layout: BitLayout::DEFAULT,
+            }
+        );
+
+        let actual = run(command).unwrap();
+        assert_eq!(actual[1], "timestamp:     2016-04-30T11:18:25.796Z");
+        assert_eq!(actual[2], "raw_timestamp: 41944705796");
+        assert_eq!(actual[3], "machine_id:    169");
+        assert_eq!(actual[4], "sequence:      7");
+    }
+
+    #[test]
+    fn decode_with_layout_test() {
+        let command = parse_args(&args("decode 0x5000042 --epoch 0 --layout 39,16,8")).unwrap();
+        let actual = run(command).unwrap();
+
+        assert_eq!(actual[1], "timestamp:     1970-01-01T00:00:00.005Z");
+        assert_eq!(actual[3], "machine_id:    0");
+        assert_eq!(actual[4], "sequence:      66");
+    }
+
+    #[test]
+    fn generate_test() {
+        let command = parse_args(&args("generate -n 5000 --machine-id 42")).unwrap();
+        let actual = run(command).unwrap();
+
+        assert_eq!(actual.len(), 5000);
+        for id in actual {
+            assert_eq!(id.parse::<SnowflakeId>().unwrap().machine_id(), 42);
+        }
+    }
+
+    #[test]
+    fn convert_test() {
+        let actual = run(parse_args(&args("convert 255 --to hex")).unwrap()).unwrap();
+        assert_eq!(actual, ["0xff"]);
+
+        let actual = run(parse_args(&args("convert 0xff --to base62")).unwrap()).unwrap();
+        assert_eq!(actual, ["47"]);
+
+        let command = parse_args(&args("convert 47 --from base62")).unwrap();
+        assert_eq!(
+            command,
+            Command::Convert {
+                value: 255,
+                to: Radix::Decimal
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_args_test() {
+        assert!(parse_args(&[]).is_err());
+        assert!(parse_args(&args("explode 1")).is_err());
+        assert!(parse_args(&args("decode")).is_err());
+        assert!(parse_args(&args("decode 1 --epoch mars")).is_err());
+        assert!(parse_args(&args("decode 1 --layout 50,10,12")).is_err());
+        assert!(parse_args(&args("convert zz --from hex")).is_err());
+        assert!(parse_args(&args("generate --verbose")).is_err());
+        assert!(parse_args(&args("generate -n")).is_err());
+        assert!(run(parse_args(&args("generate --machine-id 4096")).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,328 @@
+use crate::bit_layout::BitLayoutError::{MachineBits, SequenceBits, TimestampBits, TotalBits};
+use crate::snow_flake_id::SnowflakeId;
+use crate::snow_flake_id::SnowflakeIdError;
+use core::fmt::{Debug, Display, Formatter};
+
+#[derive(PartialEq, Eq)]
+pub enum BitLayoutError {
+    TotalBits,
+    TimestampBits,
+    MachineBits,
+    SequenceBits,
+}
+
+impl BitLayoutError {
+    fn format(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let str = match self {
+            TotalBits => "BitLayoutError::TotalBits",
+            TimestampBits => "BitLayoutError::TimestampBits",
+            MachineBits => "BitLayoutError::MachineBits",
+            SequenceBits => "BitLayoutError::SequenceBits",
+        };
+
+        write!(f, "{}", str)
+    }
+}
+
+impl Debug for BitLayoutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+impl Display for BitLayoutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BitLayoutError {}
+
+const MAX_FIELD_BITS: u32 = u16::BITS;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BitLayout {
+    timestamp_bits: u32,
+    machine_bits: u32,
+    sequence_bits: u32,
+}
+
+impl Default for BitLayout {
+    fn default() -> Self {
+        BitLayout::DEFAULT
+    }
+}
+
+const fn mask(bits: u32) -> u64 {
+    if bits == 0 {
+        0
+    } else {
+        u64::MAX >> (u64::BITS - bits)
+    }
+}
+
+impl BitLayout {
+    pub const DEFAULT: BitLayout = BitLayout {
+        timestamp_bits: 42,
+        machine_bits: 10,
+        sequence_bits: 12,
+    };
+
+    pub const TWITTER: BitLayout = BitLayout {
+        timestamp_bits: 41,
+        machine_bits: 10,
+        sequence_bits: 12,
+    };
+
+    // `DEFAULT` with the timestamp capped at 41 bits, so the sign bit is always zero and
+    // every ID is a positive `i64` (Java and Kotlin `long`s, signed BIGINT columns). That
+    // is exactly `TWITTER`; the name states the intent. The fields sit where `DEFAULT`
+    // puts them, so the plain accessors decode these IDs too.
+    pub const SIGN_SAFE: BitLayout = BitLayout::TWITTER;
+
+    pub const INSTAGRAM: BitLayout = BitLayout {
+        timestamp_bits: 41,
+        machine_bits: 13,
+        sequence_bits: 10,
+    };
+
+    pub const fn new(
+        timestamp_bits: u32,
+        machine_bits: u32,
+        sequence_bits: u32,
+    ) -> Result<Self, BitLayoutError> {
+        if timestamp_bits == 0 || timestamp_bits > u64::BITS {
+            Err(TimestampBits)
+        } else if machine_bits > MAX_FIELD_BITS {
+            Err(MachineBits)
+        } else if sequence_bits > MAX_FIELD_BITS {
+            Err(SequenceBits)
+        } else if timestamp_bits + machine_bits + sequence_bits > u64::BITS {
+            Err(TotalBits)
+        } else {
+            Ok(BitLayout {
+                timestamp_bits,
+                machine_bits,
+                sequence_bits,
+            })
+        }
+    }
+
+    pub const fn timestamp_bits(&self) -> u32 {
+        self.timestamp_bits
+    }
+
+    pub const fn machine_bits(&self) -> u32 {
+        self.machine_bits
+    }
+
+    pub const fn sequence_bits(&self) -> u32 {
+        self.sequence_bits
+    }
+
+    pub const fn is_sign_safe(&self) -> bool {
+        self.timestamp_bits + self.machine_bits + self.sequence_bits < u64::BITS
+    }
+
+    pub const fn max_timestamp(&self) -> u64 {
+        mask(self.timestamp_bits)
+    }
+
+    pub const fn max_machine_id(&self) -> u16 {
+        mask(self.machine_bits) as u16
+    }
+
+    pub const fn max_sequence(&self) -> u16 {
+        mask(self.sequence_bits) as u16
+    }
+
+    const fn machine_shift(&self) -> u32 {
+        self.sequence_bits
+    }
+
+    const fn timestamp_shift(&self) -> u32 {
+        self.sequence_bits + self.machine_bits
+    }
+
+    pub const fn compose(
+        &self,
+        timestamp: u64,
+        machine_id: u16,
+        sequence: u16,
+    ) -> Result<SnowflakeId, SnowflakeIdError> {
+        if timestamp > self.max_timestamp() {
+            Err(SnowflakeIdError::Timestamp {
+                value: timestamp,
+                max: self.max_timestamp(),
+            })
+        } else if machine_id > self.max_machine_id() {
+            Err(SnowflakeIdError::MachineId {
+                value: machine_id as u64,
+                max: self.max_machine_id() as u64,
+            })
+        } else if sequence > self.max_sequence() {
+            Err(SnowflakeIdError::Sequence {
+                value: sequence as u64,
+                max: self.max_sequence() as u64,
+            })
+        } else {
+            let mut tmp = timestamp << self.timestamp_shift();
+            tmp |= (machine_id as u64) << self.machine_shift();
+            tmp |= sequence as u64;
+
+            Ok(SnowflakeId::from_u64(tmp))
+        }
+    }
+
+    pub const fn raw_timestamp(&self, id: SnowflakeId) -> u64 {
+        (id.as_u64() >> self.timestamp_shift()) & self.max_timestamp()
+    }
+
+    pub const fn machine_id(&self, id: SnowflakeId) -> u16 {
+        ((id.as_u64() >> self.machine_shift()) & mask(self.machine_bits)) as u16
+    }
+
+    pub const fn sequence(&self, id: SnowflakeId) -> u16 {
+        (id.as_u64() & mask(self.sequence_bits)) as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::{BitLayout, BitLayoutError};
+    use crate::snow_flake_id::{SnowflakeId, SnowflakeIdError};
+
+    const SAMPLE_SCR: u64 = 175_928_847_299_678_215;
+
+    #[test]
+    fn bit_layout_error_debug_test() {
+        let targets = [
+            BitLayoutError::TotalBits,
+            BitLayoutError::TimestampBits,
+            BitLayoutError::MachineBits,
+            BitLayoutError::SequenceBits,
+        ];
+        let expected = [
+            "BitLayoutError::TotalBits",
+            "BitLayoutError::TimestampBits",
+            "BitLayoutError::MachineBits",
+            "BitLayoutError::SequenceBits",
+        ];
+
+        for elem in targets.iter().zip(expected) {
+            assert_eq!(format!("{}", elem.0), elem.1);
+            assert_eq!(format!("{:?}", elem.0), elem.1);
+        }
+    }
+
+    #[test]
+    fn default_test() {
+        let actual = BitLayout::default();
+        assert_eq!(actual, BitLayout::DEFAULT);
+        assert_eq!(actual.timestamp_bits(), 42);
+        assert_eq!(actual.machine_bits(), 10);
+        assert_eq!(actual.sequence_bits(), 12);
+
+        assert_eq!(actual.max_timestamp(), 0x03_ff_ff_ff_ff_ff);
+        assert_eq!(actual.max_machine_id(), 0x03_ff);
+        assert_eq!(actual.max_sequence(), 0x0f_ff);
+    }
+
+    #[test]
+    fn preset_test() {
+        assert_eq!(BitLayout::TWITTER, BitLayout::new(41, 10, 12).unwrap());
+        assert_eq!(BitLayout::INSTAGRAM, BitLayout::new(41, 13, 10).unwrap());
+        assert_eq!(BitLayout::SIGN_SAFE, BitLayout::TWITTER);
+    }
+
+    #[test]
+    fn is_sign_safe_test() {
+        assert!(BitLayout::SIGN_SAFE.is_sign_safe());
+        assert!(BitLayout::TWITTER.is_sign_safe());
+        assert!(!BitLayout::INSTAGRAM.is_sign_safe());
+        assert!(!BitLayout::DEFAULT.is_sign_safe());
+        assert!(!BitLayout::new(64, 0, 0).unwrap().is_sign_safe());
+    }
+
+    #[test]
+    fn new_test() {
+        let actual = BitLayout::new(39, 16, 8).unwrap();
+        assert_eq!(actual.max_timestamp(), (1u64 << 39) - 1);
+        assert_eq!(actual.max_machine_id(), u16::MAX);
+        assert_eq!(actual.max_sequence(), 0xff);
+
+        let actual = BitLayout::new(41, 10, 12).unwrap();
+        assert_eq!(actual.max_timestamp(), (1u64 << 41) - 1);
+    }
+
+    #[test]
+    fn invalid_new_test() {
+        assert_eq!(
+            BitLayout::new(0, 10, 12),
+            Err(BitLayoutError::TimestampBits)
+        );
+        assert_eq!(BitLayout::new(42, 17, 5), Err(BitLayoutError::MachineBits));
+        assert_eq!(BitLayout::new(42, 5, 17), Err(BitLayoutError::SequenceBits));
+        assert_eq!(BitLayout::new(43, 10, 12), Err(BitLayoutError::TotalBits));
+    }
+
+    #[test]
+    fn compose_test() {
+        let actual = BitLayout::DEFAULT.compose(41_944_705_796, 169, 7).unwrap();
+        assert_eq!(actual.as_u64(), SAMPLE_SCR);
+    }
+
+    #[test]
+    fn out_of_range_compose_test() {
+        let layout = BitLayout::new(39, 16, 8).unwrap();
+
+        assert_eq!(
+            layout.compose(1 << 39, 0, 0),
+            Err(SnowflakeIdError::Timestamp {
+                value: 1 << 39,
+                max: (1 << 39) - 1
+            })
+        );
+        assert_eq!(
+            layout.compose(0, 0, 0x100),
+            Err(SnowflakeIdError::Sequence {
+                value: 0x100,
+                max: 0xff
+            })
+        );
+
+        let layout = BitLayout::new(41, 5, 5).unwrap();
+        assert_eq!(
+            layout.compose(0, 32, 0),
+            Err(SnowflakeIdError::MachineId { value: 32, max: 31 })
+        );
+    }
+
+    #[test]
+    fn decompose_test() {
+        let layout = BitLayout::new(39, 16, 8).unwrap();
+        let id = layout.compose(12_345_678, 0xbeef, 0x42).unwrap();
+
+        assert_eq!(layout.raw_timestamp(id), 12_345_678);
+        assert_eq!(layout.machine_id(id), 0xbeef);
+        assert_eq!(layout.sequence(id), 0x42);
+
+        let id = SnowflakeId::from(SAMPLE_SCR);
+        assert_eq!(BitLayout::DEFAULT.raw_timestamp(id), 41_944_705_796);
+        assert_eq!(BitLayout::DEFAULT.machine_id(id), 169);
+        assert_eq!(BitLayout::DEFAULT.sequence(id), 7);
+    }
+
+    #[test]
+    fn zero_width_field_test() {
+        let layout = BitLayout::new(52, 0, 12).unwrap();
+        let id = layout.compose(3, 0, 9).unwrap();
+
+        assert_eq!(layout.max_machine_id(), 0);
+        assert_eq!(layout.machine_id(id), 0);
+        assert_eq!(layout.raw_timestamp(id), 3);
+        assert_eq!(layout.sequence(id), 9);
+    }
+}
@@ -0,0 +1,192 @@
+use crate::decoder::{DecodedId, Decoder};
+use crate::snow_flake_id::SnowflakeId;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+use core::cmp::Ordering;
+use core::fmt::{Display, Formatter};
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+
+// An ID together with the epoch, layout and tick unit it was issued under, so decoding it
+// is one call instead of a call plus configuration. Generators hand these out from
+// `generate_bound`. Ordering and hashing go by the ID alone; compare bound IDs from the
+// same generator.
+//
+// It derefs to `SnowflakeId`; the layout-dependent accessors are shadowed here so they
+// decode with the bound layout, and `created_at` replaces the epoch-taking `timestamp`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct BoundId {
+    id: SnowflakeId,
+    decoder: Decoder,
+}
+
+pub type GeneratedId = BoundId;
+
+impl BoundId {
+    pub const fn new(id: SnowflakeId, decoder: Decoder) -> Self {
+        BoundId { id, decoder }
+    }
+
+    pub const fn id(&self) -> SnowflakeId {
+        self.id
+    }
+
+    pub const fn decoder(&self) -> &Decoder {
+        &self.decoder
+    }
+
+    pub fn decode(&self) -> DecodedId {
+        self.decoder.decode(self.id)
+    }
+
+    pub const fn raw_timestamp(&self) -> u64 {
+        self.decoder.layout().raw_timestamp(self.id)
+    }
+
+    pub const fn machine_id(&self) -> u16 {
+        self.decoder.layout().machine_id(self.id)
+    }
+
+    pub const fn sequence(&self) -> u16 {
+        self.decoder.layout().sequence(self.id)
+    }
+
+    pub fn unix_millis(&self) -> Option<i64> {
+        self.decoder.unix_millis(self.raw_timestamp())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl BoundId {
+    pub fn created_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp_millis(self.unix_millis()?)
+    }
+}
+
+impl From<BoundId> for SnowflakeId {
+    fn from(value: BoundId) -> Self {
+        value.id
+    }
+}
+
+impl Deref for BoundId {
+    type Target = SnowflakeId;
+
+    fn deref(&self) -> &Self::Target {
+        &self.id
+    }
+}
+
+impl AsRef<SnowflakeId> for BoundId {
+    fn as_ref(&self) -> &SnowflakeId {
+        &self.id
+    }
+}
+
+impl PartialOrd for BoundId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BoundId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id.cmp(&other.id).then_with(|| {
+            self.decoder
+                .epoch_millis()
+                .cmp(&other.decoder.epoch_millis())
+        })
+    }
+}
+
+impl Hash for BoundId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state)
+    }
+}
+
+impl Display for BoundId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.id, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::bound_id::{BoundId, GeneratedId};
+    use crate::decoder::Decoder;
+    use crate::epochs::DISCORD_MILLIS;
+    use crate::generator_policy::TickUnit;
+    use crate::snow_flake_id::SnowflakeId;
+
+    const SAMPLE_SCR: u64 = 175_928_847_299_678_215;
+
+    #[test]
+    fn accessor_test() {
+        let id = SnowflakeId::from(SAMPLE_SCR);
+        let decoder = Decoder::new(DISCORD_MILLIS, BitLayout::DEFAULT);
+        let target = BoundId::new(id, decoder);
+
+        assert_eq!(target.id(), id);
+        assert_eq!(target.decoder(), &decoder);
+        assert_eq!(target.decode(), decoder.decode(id));
+        assert_eq!(target.raw_timestamp(), id.raw_timestamp());
+        assert_eq!(target.machine_id(), id.machine_id());
+        assert_eq!(target.sequence(), id.sequence());
+        assert_eq!(
+            target.unix_millis(),
+            Some(DISCORD_MILLIS + id.raw_timestamp() as i64)
+        );
+        assert_eq!(SnowflakeId::from(target), id);
+        assert_eq!(target.to_string(), id.to_string());
+    }
+
+    #[test]
+    fn layout_and_tick_test() {
+        let layout = BitLayout::INSTAGRAM;
+        let id = SnowflakeId::with_layout(5, 8000, 3, &layout).unwrap();
+        let target = BoundId::new(
+            id,
+            Decoder::new(0, layout).with_tick_unit(TickUnit::Seconds),
+        );
+
+        assert_eq!(target.raw_timestamp(), 5);
+        assert_eq!(target.machine_id(), 8000);
+        assert_eq!(target.sequence(), 3);
+        assert_eq!(target.unix_millis(), Some(5_000));
+    }
+
+    #[test]
+    fn deref_test() {
+        let id = SnowflakeId::new(1_000, 7, 3).unwrap();
+        let target: GeneratedId = BoundId::new(id, Decoder::default());
+
+        assert_eq!(*target, id);
+        assert_eq!(target.as_u64(), id.as_u64());
+        assert_eq!(target.as_ref(), &id);
+    }
+
+    #[test]
+    fn ordering_test() {
+        let decoder = Decoder::default();
+        let first = BoundId::new(SnowflakeId::new(1, 9, 9).unwrap(), decoder);
+        let second = BoundId::new(SnowflakeId::new(2, 0, 0).unwrap(), decoder);
+
+        assert!(first < second);
+        assert_eq!(first.max(second), second);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn created_at_test() {
+        let target = BoundId::new(
+            SnowflakeId::new(1_500, 0, 0).unwrap(),
+            Decoder::new(0, BitLayout::DEFAULT),
+        );
+        assert_eq!(
+            target.created_at(),
+            chrono::DateTime::from_timestamp_millis(1_500)
+        );
+    }
+}
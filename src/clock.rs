@@ -0,0 +1,93 @@
+#[cfg(feature = "chrono")]
+use crate::timestamp::Timestamp;
+use alloc::boxed::Box;
+
+// Milliseconds since the Unix epoch. This is the only time source the generators need,
+// so embedded users can implement it on top of whatever counter their platform offers.
+// `now_micros` only matters for generators ticking in microseconds; the default is the
+// millisecond reading scaled up.
+pub trait Clock {
+    fn now_millis(&self) -> i64;
+
+    fn now_micros(&self) -> i64 {
+        self.now_millis().saturating_mul(1_000)
+    }
+}
+
+// A clock chosen at runtime. Generators over it share one type whatever the clock, so
+// they fit in one collection; see `DynGenerator`.
+pub type DynClock = Box<dyn Clock + Send + Sync>;
+
+impl Clock for DynClock {
+    fn now_millis(&self) -> i64 {
+        (**self).now_millis()
+    }
+
+    fn now_micros(&self) -> i64 {
+        (**self).now_micros()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<T: Timestamp> Clock for T {
+    fn now_millis(&self) -> i64 {
+        self.timestamp().timestamp_millis()
+    }
+
+    fn now_micros(&self) -> i64 {
+        self.timestamp().timestamp_micros()
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now_millis(&self) -> i64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|dur| dur.as_millis() as i64)
+            .unwrap_or_default()
+    }
+
+    fn now_micros(&self) -> i64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|dur| dur.as_micros() as i64)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "chrono"))]
+mod tests {
+    use crate::clock::{Clock, SystemClock};
+    use crate::timestamp::Timestamp;
+    use chrono::{DateTime, TimeZone, Utc};
+
+    struct Fixture;
+
+    impl Timestamp for Fixture {
+        fn timestamp(&self) -> DateTime<Utc> {
+            Utc.with_ymd_and_hms(2023, 9, 1, 0, 0, 0).unwrap()
+        }
+    }
+
+    #[test]
+    fn timestamp_clock_test() {
+        assert_eq!(Fixture.now_millis(), 1_693_526_400_000);
+        assert_eq!(Fixture.now_micros(), 1_693_526_400_000_000);
+    }
+
+    #[test]
+    fn system_clock_test() {
+        let expected = Utc::now().timestamp_millis();
+        let actual = SystemClock.now_millis();
+
+        assert!((actual - expected).abs() < 1_000);
+
+        let actual = SystemClock.now_micros();
+        assert!((actual - expected * 1_000).abs() < 1_000_000);
+    }
+}
@@ -0,0 +1,3 @@
+// Drop-in replacements for the APIs of other snowflake crates, backed by this crate's
+// lock-free generator.
+pub mod rs_snowflake;
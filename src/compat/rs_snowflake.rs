@@ -0,0 +1,242 @@
+use crate::bit_layout::BitLayout;
+use crate::clock::SystemClock;
+use crate::generator_policy::{ClockRegressionPolicy, OverflowPolicy};
+use crate::snow_flake_id::datacenter_machine_id;
+use crate::snowflake_id_generator::{IdBlock, SnowFlakeIdGenerator};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The block size the `snowflake` crate's bucket refills with: one millisecond's worth.
+const BUCKET_SIZE: usize = 4096;
+
+fn epoch_millis(epoch: SystemTime) -> i64 {
+    match epoch.duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_millis() as i64,
+        Err(e) => -(e.duration().as_millis() as i64),
+    }
+}
+
+// Mirrors `snowflake::SnowflakeIdGenerator` (the mutex-based `rs-snowflake` crate): the same
+// constructors, the same 5-bit machine and node ids, and IDs with the same bit layout, so
+// call sites switch over by changing the import. The generator takes `&self`, so it can be
+// shared between threads without the `Mutex` the original needs.
+//
+// The original's three flavours differ in how strictly they follow the clock; this one is
+// always real time. `generate` and `lazy_generate` are kept as aliases for source
+// compatibility.
+pub struct SnowflakeIdGenerator {
+    inner: SnowFlakeIdGenerator<SystemClock>,
+}
+
+impl SnowflakeIdGenerator {
+    // Counts from the Unix epoch, like the original.
+    pub fn new(machine_id: i32, node_id: i32) -> Self {
+        Self::with_epoch(machine_id, node_id, UNIX_EPOCH)
+    }
+
+    // Panics if either id does not fit in 5 bits or `epoch` is in the future; the original
+    // silently emitted overlapping IDs instead.
+    pub fn with_epoch(machine_id: i32, node_id: i32, epoch: SystemTime) -> Self {
+        let machine = u16::try_from(machine_id)
+            .ok()
+            .zip(u16::try_from(node_id).ok())
+            .and_then(|(machine, node)| datacenter_machine_id(machine, node))
+            .unwrap_or_else(|| {
+                panic!(
+                    "machine_id {} and node_id {} must both be in 0..32",
+                    machine_id, node_id
+                )
+            });
+
+        let inner = SnowFlakeIdGenerator::with_epoch_millis(
+            SystemClock,
+            epoch_millis(epoch),
+            machine,
+            BitLayout::DEFAULT,
+        )
+        .unwrap_or_else(|e| panic!("invalid snowflake epoch: {}", e))
+        .with_overflow_policy(OverflowPolicy::SpinUntilNextMillis)
+        .with_clock_regression_policy(ClockRegressionPolicy::WaitUntilCaughtUp);
+
+        SnowflakeIdGenerator { inner }
+    }
+
+    pub fn inner(&self) -> &SnowFlakeIdGenerator<SystemClock> {
+        &self.inner
+    }
+
+    pub fn real_time_generate(&self) -> i64 {
+        match self.inner.try_generate() {
+            Ok(id) => id.as_i64(),
+            Err(e) => panic!("snowflake generation failed: {}", e),
+        }
+    }
+
+    pub fn generate(&self) -> i64 {
+        self.real_time_generate()
+    }
+
+    pub fn lazy_generate(&self) -> i64 {
+        self.real_time_generate()
+    }
+}
+
+// Mirrors `snowflake::SnowflakeIdBucket`: IDs are reserved a millisecond's worth at a time
+// and handed out without touching the generator.
+pub struct SnowflakeIdBucket {
+    generator: SnowflakeIdGenerator,
+    block: IdBlock,
+}
+
+impl SnowflakeIdBucket {
+    pub fn new(machine_id: i32, node_id: i32) -> Self {
+        Self::from(SnowflakeIdGenerator::new(machine_id, node_id))
+    }
+
+    pub fn with_epoch(machine_id: i32, node_id: i32, epoch: SystemTime) -> Self {
+        Self::from(SnowflakeIdGenerator::with_epoch(machine_id, node_id, epoch))
+    }
+
+    pub fn get_id(&mut self) -> i64 {
+        loop {
+            if let Some(id) = self.block.next() {
+                return id.as_i64();
+            }
+
+            self.block = self
+                .generator
+                .inner
+                .reserve_block(BUCKET_SIZE)
+                .unwrap_or_else(|e| panic!("snowflake generation failed: {}", e));
+        }
+    }
+}
+
+impl From<SnowflakeIdGenerator> for SnowflakeIdBucket {
+    fn from(generator: SnowflakeIdGenerator) -> Self {
+        SnowflakeIdBucket {
+            generator,
+            block: IdBlock::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snow_flake_id::SnowflakeId;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    fn now_millis() -> i64 {
+        epoch_millis(SystemTime::now())
+    }
+
+    #[test]
+    fn layout_test() {
+        let gen = SnowflakeIdGenerator::new(3, 17);
+        let before = now_millis();
+        let raw = gen.real_time_generate();
+        let after = now_millis();
+
+        assert!(raw > 0);
+
+        // The original's layout: timestamp << 22 | machine << 17 | node << 12 | sequence.
+        let timestamp = raw >> 22;
+        assert!((before..=after).contains(&timestamp));
+        assert_eq!((raw >> 17) & 0x1f, 3);
+        assert_eq!((raw >> 12) & 0x1f, 17);
+
+        let id = SnowflakeId::from(raw as u64);
+        assert_eq!(id.datacenter_id(), 3);
+        assert_eq!(id.worker_id(), 17);
+    }
+
+    #[test]
+    fn with_epoch_test() {
+        let epoch = UNIX_EPOCH + Duration::from_millis(1_288_834_974_657);
+        let gen = SnowflakeIdGenerator::with_epoch(1, 2, epoch);
+        assert_eq!(gen.inner().epoch_millis(), 1_288_834_974_657);
+
+        let raw = gen.generate();
+        assert!((raw >> 22) <= now_millis() - 1_288_834_974_657);
+    }
+
+    #[test]
+    #[should_panic]
+    fn machine_id_out_of_range_test() {
+        SnowflakeIdGenerator::new(32, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn negative_node_id_test() {
+        SnowflakeIdGenerator::new(0, -1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn future_epoch_test() {
+        SnowflakeIdGenerator::with_epoch(0, 0, SystemTime::now() + Duration::from_secs(3_600));
+    }
+
+    #[test]
+    fn flavours_test() {
+        let gen = SnowflakeIdGenerator::new(0, 0);
+        let mut prev = 0;
+
+        // Enough to run out of at least one millisecond's sequence.
+        for i in 0..10_000 {
+            let id = match i % 3 {
+                0 => gen.real_time_generate(),
+                1 => gen.generate(),
+                _ => gen.lazy_generate(),
+            };
+
+            assert!(id > prev);
+            prev = id;
+        }
+    }
+
+    #[test]
+    fn shared_test() {
+        let gen = Arc::new(SnowflakeIdGenerator::new(1, 1));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let gen = gen.clone();
+                thread::spawn(move || {
+                    (0..5_000)
+                        .map(|_| gen.real_time_generate())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut set = HashSet::new();
+        for handle in handles {
+            assert!(handle.join().unwrap().into_iter().all(|id| set.insert(id)));
+        }
+
+        assert_eq!(set.len(), 20_000);
+    }
+
+    #[test]
+    fn bucket_test() {
+        let mut bucket = SnowflakeIdBucket::new(2, 5);
+        let mut prev = 0;
+
+        for _ in 0..10_000 {
+            let id = bucket.get_id();
+            assert!(id > prev);
+            assert_eq!((id >> 17) & 0x1f, 2);
+            assert_eq!((id >> 12) & 0x1f, 5);
+            prev = id;
+        }
+
+        let epoch = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let mut bucket = SnowflakeIdBucket::with_epoch(0, 0, epoch);
+        assert!((bucket.get_id() >> 22) <= now_millis() - 1_600_000_000_000);
+    }
+}
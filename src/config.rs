@@ -0,0 +1,213 @@
+use crate::bit_layout::BitLayout;
+use crate::clock::Clock;
+use crate::config::ConfigError::{
+    EpochInFuture, InvalidEpoch, InvalidMachineId, MachineIdOutOfRange, MissingMachineId,
+};
+use crate::epochs;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+use crate::THE_EPOCH_MILLIS;
+use std::fmt::{Debug, Display, Formatter};
+
+pub const MACHINE_ID_VAR: &str = "SNOWFLAKE_MACHINE_ID";
+pub const EPOCH_VAR: &str = "SNOWFLAKE_EPOCH";
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum ConfigError {
+    MissingMachineId,
+    InvalidMachineId,
+    InvalidEpoch,
+    MachineIdOutOfRange,
+    EpochInFuture,
+}
+
+impl ConfigError {
+    fn format(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            MissingMachineId => "ConfigError::MissingMachineId",
+            InvalidMachineId => "ConfigError::InvalidMachineId",
+            InvalidEpoch => "ConfigError::InvalidEpoch",
+            MachineIdOutOfRange => "ConfigError::MachineIdOutOfRange",
+            EpochInFuture => "ConfigError::EpochInFuture",
+        };
+
+        write!(f, "{}", str)
+    }
+}
+
+impl Debug for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.format(f)
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.format(f)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+fn default_epoch_millis() -> i64 {
+    THE_EPOCH_MILLIS
+}
+
+// Generator settings as they appear in a service's own configuration. With serde enabled
+// it deserializes from any format; `epoch_millis` falls back to the crate's epoch.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Config {
+    pub machine_id: u16,
+    #[cfg_attr(feature = "serde", serde(default = "default_epoch_millis"))]
+    pub epoch_millis: i64,
+}
+
+impl Config {
+    pub fn new(machine_id: u16) -> Self {
+        Config {
+            machine_id,
+            epoch_millis: default_epoch_millis(),
+        }
+    }
+
+    // Reads `SNOWFLAKE_MACHINE_ID` and the optional `SNOWFLAKE_EPOCH`, which takes raw
+    // milliseconds or a name from `epochs` such as `discord`.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Config::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let machine_id = lookup(MACHINE_ID_VAR)
+            .ok_or(MissingMachineId)?
+            .trim()
+            .parse()
+            .map_err(|_| InvalidMachineId)?;
+
+        let epoch_millis = match lookup(EPOCH_VAR) {
+            Some(str) => epochs::parse_millis(str.trim()).ok_or(InvalidEpoch)?,
+            None => default_epoch_millis(),
+        };
+
+        Ok(Config {
+            machine_id,
+            epoch_millis,
+        })
+    }
+
+    pub fn build<T: Clock>(&self, clock: T) -> Result<SnowFlakeIdGenerator<T>, ConfigError> {
+        SnowFlakeIdGenerator::with_epoch_millis(
+            clock,
+            self.epoch_millis,
+            self.machine_id,
+            BitLayout::DEFAULT,
+        )
+        .map_err(|e| match e {
+            SnowflakeIdEGeneratorError::EpochInFuture => EpochInFuture,
+            _ => MachineIdOutOfRange,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::Clock;
+    use crate::config::{Config, ConfigError, EPOCH_VAR, MACHINE_ID_VAR};
+    use crate::epochs::DISCORD_MILLIS;
+    use crate::THE_EPOCH_MILLIS;
+    use std::collections::HashMap;
+
+    struct Fixture(i64);
+
+    impl Clock for Fixture {
+        fn now_millis(&self) -> i64 {
+            self.0
+        }
+    }
+
+    fn lookup(vars: &[(&str, &str)]) -> Result<Config, ConfigError> {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        Config::from_lookup(|name| vars.get(name).map(|value| value.to_string()))
+    }
+
+    #[test]
+    fn config_error_debug_test() {
+        let targets = [
+            ConfigError::MissingMachineId,
+            ConfigError::InvalidMachineId,
+            ConfigError::InvalidEpoch,
+            ConfigError::MachineIdOutOfRange,
+            ConfigError::EpochInFuture,
+        ];
+        let expected = [
+            "ConfigError::MissingMachineId",
+            "ConfigError::InvalidMachineId",
+            "ConfigError::InvalidEpoch",
+            "ConfigError::MachineIdOutOfRange",
+            "ConfigError::EpochInFuture",
+        ];
+
+        for elem in targets.iter().zip(expected) {
+            assert_eq!(format!("{}", elem.0), elem.1);
+            assert_eq!(format!("{:?}", elem.0), elem.1);
+        }
+    }
+
+    #[test]
+    fn from_lookup_test() {
+        assert_eq!(lookup(&[(MACHINE_ID_VAR, "42")]), Ok(Config::new(42)));
+        assert_eq!(
+            lookup(&[(MACHINE_ID_VAR, " 7\n"), (EPOCH_VAR, "discord")]),
+            Ok(Config {
+                machine_id: 7,
+                epoch_millis: DISCORD_MILLIS,
+            })
+        );
+        assert_eq!(
+            lookup(&[(MACHINE_ID_VAR, "7"), (EPOCH_VAR, "1000")]).map(|c| c.epoch_millis),
+            Ok(1000)
+        );
+
+        assert_eq!(lookup(&[]), Err(ConfigError::MissingMachineId));
+        assert_eq!(
+            lookup(&[(MACHINE_ID_VAR, "-1")]),
+            Err(ConfigError::InvalidMachineId)
+        );
+        assert_eq!(
+            lookup(&[(MACHINE_ID_VAR, "1"), (EPOCH_VAR, "mars")]),
+            Err(ConfigError::InvalidEpoch)
+        );
+    }
+
+    #[test]
+    fn build_test() {
+        let actual = Config::new(42)
+            .build(Fixture(THE_EPOCH_MILLIS + 5))
+            .unwrap();
+        assert_eq!(actual.machine_id(), 42);
+        assert_eq!(actual.epoch_millis(), THE_EPOCH_MILLIS);
+        assert_eq!(actual.generate().unwrap().raw_timestamp(), 5);
+
+        assert!(matches!(
+            Config::new(1024).build(Fixture(THE_EPOCH_MILLIS)),
+            Err(ConfigError::MachineIdOutOfRange)
+        ));
+        assert!(matches!(
+            Config::new(1).build(Fixture(THE_EPOCH_MILLIS - 1)),
+            Err(ConfigError::EpochInFuture)
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_test() {
+        let actual: Config = serde_json::from_str(r#"{"machine_id":42}"#).unwrap();
+        assert_eq!(actual, Config::new(42));
+
+        let actual: Config =
+            serde_json::from_str(r#"{"machine_id":7,"epoch_millis":1420070400000}"#).unwrap();
+        assert_eq!(actual.epoch_millis, DISCORD_MILLIS);
+
+        assert!(serde_json::from_str::<Config>(r#"{"epoch_millis":0}"#).is_err());
+    }
+}
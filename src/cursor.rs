@@ -0,0 +1,332 @@
+use crate::cursor::CursorError::{Direction as InvalidDirection, InvalidCharacter, Length};
+use crate::snow_flake_id::SnowflakeId;
+use alloc::string::String;
+use core::fmt::{Debug, Display, Formatter};
+use core::str::FromStr;
+
+#[derive(PartialEq, Eq)]
+pub enum CursorError {
+    Length,
+    InvalidCharacter,
+    Direction,
+}
+
+impl CursorError {
+    fn format(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let str = match self {
+            Length => "CursorError::Length",
+            InvalidCharacter => "CursorError::InvalidCharacter",
+            InvalidDirection => "CursorError::Direction",
+        };
+
+        write!(f, "{}", str)
+    }
+}
+
+impl Debug for CursorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+impl Display for CursorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CursorError {}
+
+// `After` pages forward through IDs above the cursor, oldest first; `Before` pages
+// backward through IDs below it, newest first.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub enum Direction {
+    After,
+    Before,
+}
+
+impl Direction {
+    pub const fn reverse(self) -> Self {
+        match self {
+            Direction::After => Direction::Before,
+            Direction::Before => Direction::After,
+        }
+    }
+}
+
+pub const DEFAULT_LIMIT: u32 = 50;
+
+// The base64url (unpadded) length of the 13-byte payload: direction, limit, ID.
+pub const ENCODED_LEN: usize = 18;
+
+const PAYLOAD_LEN: usize = 13;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// A keyset pagination position. Since IDs sort by creation time, a page is just "the next
+// `limit` IDs past this one", e.g. `WHERE id > $1 ORDER BY id LIMIT $2` for `After`,
+// which stays stable while rows are inserted, unlike an offset. The encoded form is
+// opaque to clients and safe in URLs.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub struct Cursor {
+    id: SnowflakeId,
+    direction: Direction,
+    limit: u32,
+}
+
+impl Cursor {
+    pub const fn after(id: SnowflakeId) -> Self {
+        Cursor {
+            id,
+            direction: Direction::After,
+            limit: DEFAULT_LIMIT,
+        }
+    }
+
+    pub const fn before(id: SnowflakeId) -> Self {
+        Cursor {
+            id,
+            direction: Direction::Before,
+            limit: DEFAULT_LIMIT,
+        }
+    }
+
+    pub const fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub const fn id(&self) -> SnowflakeId {
+        self.id
+    }
+
+    pub const fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub const fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    // Whether `id` lies on the page side of the cursor. The cursor's own ID never does.
+    pub fn contains(&self, id: SnowflakeId) -> bool {
+        match self.direction {
+            Direction::After => id > self.id,
+            Direction::Before => id < self.id,
+        }
+    }
+
+    // The cursor for the page following one whose last row is `last`.
+    pub const fn next(&self, last: SnowflakeId) -> Self {
+        Cursor { id: last, ..*self }
+    }
+
+    // The cursor for the page preceding one whose first row is `first`.
+    pub const fn previous(&self, first: SnowflakeId) -> Self {
+        Cursor {
+            id: first,
+            direction: self.direction.reverse(),
+            limit: self.limit,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let mut payload = [0u8; PAYLOAD_LEN];
+        payload[0] = match self.direction {
+            Direction::After => 0,
+            Direction::Before => 1,
+        };
+        payload[1..5].copy_from_slice(&self.limit.to_be_bytes());
+        payload[5..].copy_from_slice(&self.id.as_u64().to_be_bytes());
+
+        let mut str = String::with_capacity(ENCODED_LEN);
+        for chunk in payload.chunks(3) {
+            let group = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - 8 * i));
+
+            for i in 0..=chunk.len() {
+                str.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            }
+        }
+
+        str
+    }
+
+    pub fn decode(str: &str) -> Result<Self, CursorError> {
+        if str.len() != ENCODED_LEN {
+            return Err(Length);
+        }
+
+        let mut payload = [0u8; PAYLOAD_LEN];
+        for (chunk, out) in str.as_bytes().chunks(4).zip(payload.chunks_mut(3)) {
+            let mut group = 0u32;
+            for (i, &c) in chunk.iter().enumerate() {
+                let digit = ALPHABET
+                    .iter()
+                    .position(|&x| x == c)
+                    .ok_or(InvalidCharacter)?;
+                group |= (digit as u32) << (18 - 6 * i);
+            }
+
+            // The bits past the last byte must be zero, so every cursor has one spelling.
+            if group & (0xff_ffff >> (8 * out.len())) != 0 {
+                return Err(InvalidCharacter);
+            }
+
+            for (i, b) in out.iter_mut().enumerate() {
+                *b = (group >> (16 - 8 * i)) as u8;
+            }
+        }
+
+        let direction = match payload[0] {
+            0 => Direction::After,
+            1 => Direction::Before,
+            _ => return Err(InvalidDirection),
+        };
+
+        Ok(Cursor {
+            id: SnowflakeId::from(u64::from_be_bytes(payload[5..].try_into().unwrap())),
+            direction,
+            limit: u32::from_be_bytes(payload[1..5].try_into().unwrap()),
+        })
+    }
+}
+
+impl Display for Cursor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.encode())
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = CursorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Cursor::decode(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cursor::{Cursor, CursorError, Direction, DEFAULT_LIMIT, ENCODED_LEN};
+    use crate::snow_flake_id::SnowflakeId;
+
+    #[test]
+    fn error_test() {
+        let targets = [
+            CursorError::Length,
+            CursorError::InvalidCharacter,
+            CursorError::Direction,
+        ];
+        let expected = [
+            "CursorError::Length",
+            "CursorError::InvalidCharacter",
+            "CursorError::Direction",
+        ];
+
+        for elem in targets.iter().zip(expected) {
+            assert_eq!(format!("{}", elem.0), elem.1);
+            assert_eq!(format!("{:?}", elem.0), elem.1);
+        }
+    }
+
+    #[test]
+    fn after_test() {
+        let target = Cursor::after(SnowflakeId::from(100u64));
+
+        assert_eq!(target.id(), SnowflakeId::from(100u64));
+        assert_eq!(target.direction(), Direction::After);
+        assert_eq!(target.limit(), DEFAULT_LIMIT);
+        assert!(target.contains(SnowflakeId::from(101u64)));
+        assert!(!target.contains(SnowflakeId::from(100u64)));
+        assert!(!target.contains(SnowflakeId::from(99u64)));
+    }
+
+    #[test]
+    fn before_test() {
+        let target = Cursor::before(SnowflakeId::from(100u64)).with_limit(10);
+
+        assert_eq!(target.direction(), Direction::Before);
+        assert_eq!(target.limit(), 10);
+        assert!(target.contains(SnowflakeId::from(99u64)));
+        assert!(!target.contains(SnowflakeId::from(100u64)));
+        assert!(!target.contains(SnowflakeId::from(101u64)));
+    }
+
+    #[test]
+    fn paging_test() {
+        let ids: Vec<_> = (0..10u64).map(|x| SnowflakeId::from(x * 10)).collect();
+        let page = |cursor: &Cursor| -> Vec<SnowflakeId> {
+            let mut page: Vec<_> = ids
+                .iter()
+                .copied()
+                .filter(|x| cursor.contains(*x))
+                .collect();
+            if cursor.direction() == Direction::Before {
+                page.reverse();
+            }
+            page.truncate(cursor.limit() as usize);
+            page
+        };
+
+        let first = Cursor::after(SnowflakeId::from(0u64)).with_limit(3);
+        let actual = page(&first);
+        assert_eq!(actual, ids[1..4]);
+
+        let second = first.next(*actual.last().unwrap());
+        let actual = page(&second);
+        assert_eq!(actual, ids[4..7]);
+
+        let back = second.previous(actual[0]);
+        assert_eq!(back.direction(), Direction::Before);
+        assert_eq!(back.limit(), 3);
+        assert_eq!(page(&back), [ids[3], ids[2], ids[1]]);
+    }
+
+    #[test]
+    fn encode_test() {
+        let target = Cursor::before(SnowflakeId::from(175_928_847_299_678_215u64)).with_limit(25);
+        let actual = target.encode();
+
+        assert_eq!(actual.len(), ENCODED_LEN);
+        assert_eq!(actual, "AQAAABkCcQZawQqQBw");
+        assert_eq!(target.to_string(), actual);
+        assert_eq!(Cursor::decode(&actual), Ok(target));
+        assert_eq!(actual.parse::<Cursor>(), Ok(target));
+
+        for cursor in [
+            Cursor::after(SnowflakeId::from(0u64)).with_limit(0),
+            Cursor::after(SnowflakeId::from(u64::MAX)).with_limit(u32::MAX),
+        ] {
+            let encoded = cursor.encode();
+            assert!(encoded
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'));
+            assert_eq!(Cursor::decode(&encoded), Ok(cursor));
+        }
+    }
+
+    #[test]
+    fn decode_error_test() {
+        assert_eq!(Cursor::decode(""), Err(CursorError::Length));
+        assert_eq!(
+            Cursor::decode("AQAAABkCcQZawQqQBwA"),
+            Err(CursorError::Length)
+        );
+        assert_eq!(
+            Cursor::decode("AQAAABkCcQZawQqQB+"),
+            Err(CursorError::InvalidCharacter)
+        );
+        assert_eq!(
+            Cursor::decode("AQAAABkCcQZawQqQBx"),
+            Err(CursorError::InvalidCharacter)
+        );
+        assert_eq!(
+            Cursor::decode("AgAAABkCcQZawQqQBw"),
+            Err(CursorError::Direction)
+        );
+    }
+}
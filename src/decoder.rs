@@ -0,0 +1,254 @@
+use crate::bit_layout::BitLayout;
+use crate::generator_policy::TickUnit;
+use crate::snow_flake_id::SnowflakeId;
+use crate::THE_EPOCH_MILLIS;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, TimeZone, Utc};
+
+// An ID split into its fields, with the timestamp already resolved against the decoder's
+// epoch. `unix_millis` is `None` when that sum overflows, which only happens for
+// hand-crafted IDs under a far-future epoch.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct DecodedId {
+    pub id: SnowflakeId,
+    pub unix_millis: Option<i64>,
+    pub raw_timestamp: u64,
+    pub machine_id: u16,
+    pub sequence: u16,
+}
+
+#[cfg(feature = "chrono")]
+impl DecodedId {
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp_millis(self.unix_millis?)
+    }
+}
+
+// Decodes IDs against a fixed epoch and layout, for bulk jobs that would otherwise repeat
+// both arguments on every call. Decoding is a handful of shifts, so one decoder can be
+// shared freely across threads.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Decoder {
+    epoch_millis: i64,
+    layout: BitLayout,
+    tick_unit: TickUnit,
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Decoder::new(THE_EPOCH_MILLIS, BitLayout::DEFAULT)
+    }
+}
+
+impl Decoder {
+    pub const fn new(epoch_millis: i64, layout: BitLayout) -> Self {
+        Decoder {
+            epoch_millis,
+            layout,
+            tick_unit: TickUnit::Millis,
+        }
+    }
+
+    // For IDs from a generator with a tick unit other than milliseconds. Sub-millisecond
+    // ticks are truncated in `unix_millis`.
+    pub const fn with_tick_unit(mut self, unit: TickUnit) -> Self {
+        self.tick_unit = unit;
+        self
+    }
+
+    pub const fn tick_unit(&self) -> TickUnit {
+        self.tick_unit
+    }
+
+    pub const fn epoch_millis(&self) -> i64 {
+        self.epoch_millis
+    }
+
+    pub const fn layout(&self) -> &BitLayout {
+        &self.layout
+    }
+
+    pub fn decode(&self, id: SnowflakeId) -> DecodedId {
+        let raw_timestamp = self.layout.raw_timestamp(id);
+
+        DecodedId {
+            id,
+            unix_millis: self.unix_millis(raw_timestamp),
+            raw_timestamp,
+            machine_id: self.layout.machine_id(id),
+            sequence: self.layout.sequence(id),
+        }
+    }
+
+    pub(crate) fn unix_millis(&self, raw_timestamp: u64) -> Option<i64> {
+        let millis = raw_timestamp as u128 * self.tick_unit.as_micros() as u128 / 1_000;
+        i64::try_from(millis)
+            .ok()
+            .and_then(|x| self.epoch_millis.checked_add(x))
+    }
+
+    pub fn iter_decode<I: IntoIterator<Item = u64>>(&self, ids: I) -> DecodeIter<'_, I::IntoIter> {
+        DecodeIter {
+            decoder: self,
+            ids: ids.into_iter(),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Decoder {
+    pub fn with_epoch<Tz: TimeZone>(the_epoch: DateTime<Tz>, layout: BitLayout) -> Self {
+        Decoder::new(the_epoch.timestamp_millis(), layout)
+    }
+}
+
+pub struct DecodeIter<'a, I> {
+    decoder: &'a Decoder,
+    ids: I,
+}
+
+impl<I: Iterator<Item = u64>> Iterator for DecodeIter<'_, I> {
+    type Item = DecodedId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.ids
+            .next()
+            .map(|x| self.decoder.decode(SnowflakeId::from(x)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ids.size_hint()
+    }
+}
+
+impl<I: ExactSizeIterator<Item = u64>> ExactSizeIterator for DecodeIter<'_, I> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::decoder::{DecodedId, Decoder};
+    use crate::epochs::DISCORD_MILLIS;
+    use crate::snow_flake_id::SnowflakeId;
+    use crate::THE_EPOCH_MILLIS;
+
+    const SAMPLE_SCR: u64 = 175_928_847_299_678_215;
+
+    #[test]
+    fn decode_test() {
+        let target = Decoder::new(DISCORD_MILLIS, BitLayout::DEFAULT);
+        let actual = target.decode(SnowflakeId::from(SAMPLE_SCR));
+
+        assert_eq!(
+            actual,
+            DecodedId {
+                id: SnowflakeId::from(SAMPLE_SCR),
+                unix_millis: Some(DISCORD_MILLIS + 41_944_705_796),
+                raw_timestamp: 41_944_705_796,
+                machine_id: 169,
+                sequence: 7,
+            }
+        );
+
+        let target = Decoder::new(i64::MAX, BitLayout::DEFAULT);
+        assert_eq!(
+            target.decode(SnowflakeId::from(SAMPLE_SCR)).unix_millis,
+            None
+        );
+
+        let layout = BitLayout::new(41, 12, 10).unwrap();
+        let actual = Decoder::new(0, layout).decode(SnowflakeId::from(SAMPLE_SCR));
+        assert_eq!(
+            actual.machine_id,
+            layout.machine_id(SnowflakeId::from(SAMPLE_SCR))
+        );
+        assert_eq!(
+            actual.sequence,
+            layout.sequence(SnowflakeId::from(SAMPLE_SCR))
+        );
+    }
+
+    #[test]
+    fn iter_decode_test() {
+        let target = Decoder::default();
+        assert_eq!(target.epoch_millis(), THE_EPOCH_MILLIS);
+        assert_eq!(target.layout(), &BitLayout::DEFAULT);
+
+        let ids = [
+            SnowflakeId::new(5, 1, 0).unwrap(),
+            SnowflakeId::new(5, 1, 1).unwrap(),
+            SnowflakeId::new(6, 2, 0).unwrap(),
+        ];
+
+        let actual = target.iter_decode(ids.iter().map(|x| x.as_u64()));
+        assert_eq!(actual.len(), 3);
+
+        let actual: Vec<_> = actual.collect();
+        assert_eq!(
+            actual.iter().map(|x| x.id).collect::<Vec<_>>(),
+            ids.to_vec()
+        );
+        assert_eq!(
+            actual.iter().map(|x| x.unix_millis).collect::<Vec<_>>(),
+            [
+                Some(THE_EPOCH_MILLIS + 5),
+                Some(THE_EPOCH_MILLIS + 5),
+                Some(THE_EPOCH_MILLIS + 6)
+            ]
+        );
+        assert_eq!(
+            actual
+                .iter()
+                .map(|x| (x.machine_id, x.sequence))
+                .collect::<Vec<_>>(),
+            [(1, 0), (1, 1), (2, 0)]
+        );
+    }
+
+    #[test]
+    fn tick_unit_test() {
+        use crate::generator_policy::TickUnit;
+
+        let id = SnowflakeId::new(1_234, 1, 2).unwrap();
+        let cases = [
+            (TickUnit::Micros, 1),
+            (TickUnit::Millis, 1_234),
+            (TickUnit::TenMillis, 12_340),
+            (TickUnit::Seconds, 1_234_000),
+        ];
+
+        for (unit, millis) in cases {
+            let target = Decoder::new(THE_EPOCH_MILLIS, BitLayout::DEFAULT).with_tick_unit(unit);
+            assert_eq!(target.tick_unit(), unit);
+
+            let actual = target.decode(id);
+            assert_eq!(actual.raw_timestamp, 1_234);
+            assert_eq!(actual.unix_millis, Some(THE_EPOCH_MILLIS + millis));
+        }
+
+        let target =
+            Decoder::new(0, BitLayout::new(64, 0, 0).unwrap()).with_tick_unit(TickUnit::Seconds);
+        assert_eq!(target.decode(SnowflakeId::from(u64::MAX)).unix_millis, None);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn timestamp_test() {
+        use chrono::{TimeZone, Utc};
+
+        let epoch = Utc.with_ymd_and_hms(2015, 1, 1, 0, 0, 0).unwrap();
+        let target = Decoder::with_epoch(epoch, BitLayout::DEFAULT);
+        assert_eq!(target.epoch_millis(), DISCORD_MILLIS);
+
+        let id = SnowflakeId::from(SAMPLE_SCR);
+        assert_eq!(
+            target.decode(id).timestamp(),
+            Some(id.timestamp(epoch, &Utc))
+        );
+        assert_eq!(
+            Decoder::new(i64::MAX, BitLayout::DEFAULT)
+                .decode(id)
+                .timestamp(),
+            None
+        );
+    }
+}
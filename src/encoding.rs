@@ -0,0 +1,264 @@
+use crate::encoding::EncodingError::{Empty, InvalidCharacter, Overflow};
+use crate::snow_flake_id::SnowflakeId;
+use crate::snowflake_id_128::SnowflakeId128;
+use alloc::string::String;
+use core::fmt::{Debug, Display, Formatter};
+
+#[derive(PartialEq, Eq)]
+pub enum EncodingError {
+    Empty,
+    InvalidCharacter,
+    Overflow,
+}
+
+impl EncodingError {
+    fn format(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let str = match self {
+            Empty => "EncodingError::Empty",
+            InvalidCharacter => "EncodingError::InvalidCharacter",
+            Overflow => "EncodingError::Overflow",
+        };
+
+        write!(f, "{}", str)
+    }
+}
+
+impl Debug for EncodingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+impl Display for EncodingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EncodingError {}
+
+pub const BASE62_ALPHABET: &[u8; 62] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+pub const BASE58_ALPHABET: &[u8; 58] =
+    b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+// `u64::MAX` in base62; a buffer this long fits any ID.
+pub const MAX_BASE62_LEN: usize = 11;
+
+// Writes the digits to the back of `buf` and returns where they start.
+fn encode_into<const N: usize>(mut value: u128, alphabet: &[u8; N], buf: &mut [u8]) -> usize {
+    let mut pos = buf.len();
+
+    loop {
+        pos -= 1;
+        buf[pos] = alphabet[(value % N as u128) as usize];
+        value /= N as u128;
+
+        if value == 0 {
+            break;
+        }
+    }
+
+    pos
+}
+
+fn encode<const N: usize>(value: u128, alphabet: &[u8; N]) -> String {
+    let mut buf = [0u8; 128];
+    let pos = encode_into(value, alphabet, &mut buf);
+
+    buf[pos..].iter().map(|&b| b as char).collect()
+}
+
+fn decode<const N: usize>(str: &str, alphabet: &[u8; N]) -> Result<u128, EncodingError> {
+    if str.is_empty() {
+        return Err(Empty);
+    }
+
+    str.bytes().try_fold(0u128, |acc, b| {
+        let digit = alphabet
+            .iter()
+            .position(|&c| c == b)
+            .ok_or(InvalidCharacter)?;
+
+        acc.checked_mul(N as u128)
+            .and_then(|acc| acc.checked_add(digit as u128))
+            .ok_or(Overflow)
+    })
+}
+
+fn narrow(value: u128) -> Result<u64, EncodingError> {
+    u64::try_from(value).map_err(|_| Overflow)
+}
+
+pub fn to_base62(value: u64) -> String {
+    encode(value as u128, BASE62_ALPHABET)
+}
+
+pub fn from_base62(str: &str) -> Result<u64, EncodingError> {
+    decode(str, BASE62_ALPHABET).and_then(narrow)
+}
+
+pub fn to_base58(value: u64) -> String {
+    encode(value as u128, BASE58_ALPHABET)
+}
+
+pub fn from_base58(str: &str) -> Result<u64, EncodingError> {
+    decode(str, BASE58_ALPHABET).and_then(narrow)
+}
+
+impl SnowflakeId {
+    pub fn to_base62(&self) -> String {
+        to_base62(self.as_u64())
+    }
+
+    pub fn from_base62(str: &str) -> Result<Self, EncodingError> {
+        from_base62(str).map(SnowflakeId::from)
+    }
+
+    // The allocation-free counterpart of `to_base62`; same contract as `write_decimal`,
+    // with `MAX_BASE62_LEN` bytes always sufficing.
+    pub fn write_base62(&self, buf: &mut [u8]) -> usize {
+        let mut tmp = [0u8; MAX_BASE62_LEN];
+        let pos = encode_into(self.as_u64() as u128, BASE62_ALPHABET, &mut tmp);
+
+        let len = tmp.len() - pos;
+        buf[..len].copy_from_slice(&tmp[pos..]);
+        len
+    }
+
+    pub fn to_base58(&self) -> String {
+        to_base58(self.as_u64())
+    }
+
+    pub fn from_base58(str: &str) -> Result<Self, EncodingError> {
+        from_base58(str).map(SnowflakeId::from)
+    }
+}
+
+impl SnowflakeId128 {
+    pub fn to_base62(&self) -> String {
+        encode(self.as_u128(), BASE62_ALPHABET)
+    }
+
+    pub fn from_base62(str: &str) -> Result<Self, EncodingError> {
+        decode(str, BASE62_ALPHABET).map(SnowflakeId128::from)
+    }
+
+    pub fn to_base58(&self) -> String {
+        encode(self.as_u128(), BASE58_ALPHABET)
+    }
+
+    pub fn from_base58(str: &str) -> Result<Self, EncodingError> {
+        decode(str, BASE58_ALPHABET).map(SnowflakeId128::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoding::{
+        from_base58, from_base62, to_base58, to_base62, EncodingError, MAX_BASE62_LEN,
+    };
+    use crate::snow_flake_id::SnowflakeId;
+    use crate::snowflake_id_128::SnowflakeId128;
+
+    const SAMPLE_SCR: u64 = 175_928_847_299_678_215;
+
+    #[test]
+    fn encoding_error_debug_test() {
+        let targets = [
+            EncodingError::Empty,
+            EncodingError::InvalidCharacter,
+            EncodingError::Overflow,
+        ];
+        let expected = [
+            "EncodingError::Empty",
+            "EncodingError::InvalidCharacter",
+            "EncodingError::Overflow",
+        ];
+
+        for elem in targets.iter().zip(expected) {
+            assert_eq!(format!("{}", elem.0), elem.1);
+            assert_eq!(format!("{:?}", elem.0), elem.1);
+        }
+    }
+
+    #[test]
+    fn base62_test() {
+        assert_eq!(to_base62(0), "0");
+        assert_eq!(to_base62(61), "z");
+        assert_eq!(to_base62(62), "10");
+        assert_eq!(to_base62(u64::MAX), "LygHa16AHYF");
+
+        assert_eq!(from_base62("0"), Ok(0));
+        assert_eq!(from_base62("10"), Ok(62));
+        assert_eq!(from_base62("LygHa16AHYF"), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn write_base62_test() {
+        let mut buf = [0u8; MAX_BASE62_LEN];
+
+        for value in [0, 61, 62, SAMPLE_SCR, u64::MAX] {
+            let len = SnowflakeId::from(value).write_base62(&mut buf);
+            assert_eq!(&buf[..len], to_base62(value).as_bytes());
+        }
+    }
+
+    #[test]
+    fn base58_test() {
+        assert_eq!(to_base58(0), "1");
+        assert_eq!(to_base58(57), "z");
+        assert_eq!(to_base58(58), "21");
+        assert_eq!(to_base58(u64::MAX), "jpXCZedGfVQ");
+
+        assert_eq!(from_base58("1"), Ok(0));
+        assert_eq!(from_base58("21"), Ok(58));
+        assert_eq!(from_base58("jpXCZedGfVQ"), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn invalid_decode_test() {
+        assert_eq!(from_base62(""), Err(EncodingError::Empty));
+        assert_eq!(from_base62("ab-c"), Err(EncodingError::InvalidCharacter));
+        assert_eq!(from_base62("LygHa16AHYG"), Err(EncodingError::Overflow));
+
+        assert_eq!(from_base58(""), Err(EncodingError::Empty));
+        assert_eq!(from_base58("0OIl"), Err(EncodingError::InvalidCharacter));
+        assert_eq!(from_base58("jpXCZedGfVR"), Err(EncodingError::Overflow));
+    }
+
+    #[test]
+    fn round_trip_test() {
+        for value in [0, 1, 42, SAMPLE_SCR, u64::MAX / 3, u64::MAX - 1, u64::MAX] {
+            let id = SnowflakeId::from(value);
+
+            assert_eq!(SnowflakeId::from_base62(&id.to_base62()), Ok(id));
+            assert_eq!(SnowflakeId::from_base58(&id.to_base58()), Ok(id));
+        }
+    }
+
+    #[test]
+    fn snowflake_id_128_round_trip_test() {
+        let max = SnowflakeId128::from(u128::MAX);
+        assert_eq!(max.to_base62(), "7n42DGM5Tflk9n8mt7Fhc7");
+        assert_eq!(
+            SnowflakeId128::from_base62("7n42DGM5Tflk9n8mt7Fhc8"),
+            Err(EncodingError::Overflow)
+        );
+
+        for value in [
+            0,
+            SAMPLE_SCR as u128,
+            u64::MAX as u128 + 1,
+            u128::MAX / 3,
+            u128::MAX,
+        ] {
+            let id = SnowflakeId128::from(value);
+
+            assert_eq!(SnowflakeId128::from_base62(&id.to_base62()), Ok(id));
+            assert_eq!(SnowflakeId128::from_base58(&id.to_base58()), Ok(id));
+        }
+    }
+}
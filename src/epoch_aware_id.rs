@@ -0,0 +1,179 @@
+use crate::bit_layout::BitLayout;
+use crate::snow_flake_id::SnowflakeId;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+use core::cmp::Ordering;
+
+// An ID tagged with the epoch (and layout) it was issued under, for the transition period
+// of an epoch migration when old and new IDs live side by side. Raw values from different
+// epochs don't compare meaningfully, so ordering goes by absolute issue time first, then
+// machine id and sequence; the tag only breaks the remaining ties.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub struct EpochAwareId {
+    id: SnowflakeId,
+    epoch_millis: i64,
+    layout: BitLayout,
+}
+
+impl EpochAwareId {
+    pub const fn new(id: SnowflakeId, epoch_millis: i64) -> Self {
+        Self::with_layout(id, epoch_millis, BitLayout::DEFAULT)
+    }
+
+    pub const fn with_layout(id: SnowflakeId, epoch_millis: i64, layout: BitLayout) -> Self {
+        EpochAwareId {
+            id,
+            epoch_millis,
+            layout,
+        }
+    }
+
+    pub const fn id(&self) -> SnowflakeId {
+        self.id
+    }
+
+    pub const fn epoch_millis(&self) -> i64 {
+        self.epoch_millis
+    }
+
+    pub const fn layout(&self) -> &BitLayout {
+        &self.layout
+    }
+
+    // Never overflows, unlike the `i64` sum.
+    fn absolute_millis(&self) -> i128 {
+        self.epoch_millis as i128 + self.layout.raw_timestamp(self.id) as i128
+    }
+
+    pub fn unix_millis(&self) -> Option<i64> {
+        i64::try_from(self.absolute_millis()).ok()
+    }
+
+    fn sort_key(&self) -> (i128, u16, u16, i64, u64, [u32; 3]) {
+        (
+            self.absolute_millis(),
+            self.layout.machine_id(self.id),
+            self.layout.sequence(self.id),
+            self.epoch_millis,
+            self.id.as_u64(),
+            [
+                self.layout.timestamp_bits(),
+                self.layout.machine_bits(),
+                self.layout.sequence_bits(),
+            ],
+        )
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl EpochAwareId {
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp_millis(self.unix_millis()?)
+    }
+}
+
+impl PartialOrd for EpochAwareId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for EpochAwareId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::epoch_aware_id::EpochAwareId;
+    use crate::epochs::DISCORD_MILLIS;
+    use crate::snow_flake_id::SnowflakeId;
+    use crate::THE_EPOCH_MILLIS;
+
+    const OLD_EPOCH: i64 = DISCORD_MILLIS;
+    const NEW_EPOCH: i64 = THE_EPOCH_MILLIS;
+
+    fn old(unix_millis: i64, machine_id: u16, sequence: u16) -> EpochAwareId {
+        let id = SnowflakeId::new((unix_millis - OLD_EPOCH) as u64, machine_id, sequence).unwrap();
+        EpochAwareId::new(id, OLD_EPOCH)
+    }
+
+    fn new(unix_millis: i64, machine_id: u16, sequence: u16) -> EpochAwareId {
+        let id = SnowflakeId::new((unix_millis - NEW_EPOCH) as u64, machine_id, sequence).unwrap();
+        EpochAwareId::new(id, NEW_EPOCH)
+    }
+
+    #[test]
+    fn accessor_test() {
+        let target = new(NEW_EPOCH + 5, 1, 2);
+
+        assert_eq!(target.id(), SnowflakeId::new(5, 1, 2).unwrap());
+        assert_eq!(target.epoch_millis(), NEW_EPOCH);
+        assert_eq!(target.layout(), &BitLayout::DEFAULT);
+        assert_eq!(target.unix_millis(), Some(NEW_EPOCH + 5));
+
+        let target = EpochAwareId::new(SnowflakeId::new(1, 0, 0).unwrap(), i64::MAX);
+        assert_eq!(target.unix_millis(), None);
+    }
+
+    #[test]
+    fn ord_test() {
+        let now = NEW_EPOCH + 1_000;
+
+        // The old IDs are numerically far larger, but issued around the same time.
+        assert!(old(now, 0, 0).id() > new(now + 1, 0, 0).id());
+
+        let mut actual = vec![
+            new(now + 1, 0, 0),
+            old(now + 2, 0, 0),
+            old(now, 1, 0),
+            new(now, 0, 5),
+            old(now - 1, 9, 9),
+        ];
+        actual.sort();
+
+        assert_eq!(
+            actual,
+            vec![
+                old(now - 1, 9, 9),
+                new(now, 0, 5),
+                old(now, 1, 0),
+                new(now + 1, 0, 0),
+                old(now + 2, 0, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn tie_break_test() {
+        let now = NEW_EPOCH + 1_000;
+        let a = old(now, 3, 4);
+        let b = new(now, 3, 4);
+
+        assert_ne!(a, b);
+        assert!(a < b);
+        assert_eq!(a.cmp(&a), core::cmp::Ordering::Equal);
+
+        let c = EpochAwareId::with_layout(
+            SnowflakeId::with_layout(1_000, 3, 4, &BitLayout::TWITTER).unwrap(),
+            NEW_EPOCH,
+            BitLayout::TWITTER,
+        );
+        assert_eq!(c.unix_millis(), b.unix_millis());
+        assert_ne!(c, b);
+        assert_ne!(c.cmp(&b), core::cmp::Ordering::Equal);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn timestamp_test() {
+        use chrono::DateTime;
+
+        assert_eq!(
+            old(NEW_EPOCH, 0, 0).timestamp(),
+            DateTime::from_timestamp_millis(NEW_EPOCH)
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use crate::bit_layout::BitLayout;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+
+pub const UNIX_MILLIS: i64 = 0;
+pub const TWITTER_MILLIS: i64 = 1_288_834_974_657;
+pub const DISCORD_MILLIS: i64 = 1_420_070_400_000;
+pub const INSTAGRAM_MILLIS: i64 = 1_314_220_021_721;
+pub const SONYFLAKE_MILLIS: i64 = 1_409_529_600_000;
+
+// Platforms whose IDs share the snowflake shape, with the epoch and layout each one uses.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub enum KnownEpoch {
+    Discord,
+    Instagram,
+    Twitter,
+}
+
+impl KnownEpoch {
+    pub const ALL: [KnownEpoch; 3] = [
+        KnownEpoch::Discord,
+        KnownEpoch::Instagram,
+        KnownEpoch::Twitter,
+    ];
+
+    pub const fn millis(&self) -> i64 {
+        match self {
+            KnownEpoch::Discord => DISCORD_MILLIS,
+            KnownEpoch::Instagram => INSTAGRAM_MILLIS,
+            KnownEpoch::Twitter => TWITTER_MILLIS,
+        }
+    }
+
+    pub const fn layout(&self) -> BitLayout {
+        match self {
+            KnownEpoch::Discord | KnownEpoch::Twitter => BitLayout::TWITTER,
+            KnownEpoch::Instagram => BitLayout::INSTAGRAM,
+        }
+    }
+
+    // The name `parse_millis` accepts for this epoch.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            KnownEpoch::Discord => "discord",
+            KnownEpoch::Instagram => "instagram",
+            KnownEpoch::Twitter => "twitter",
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl KnownEpoch {
+    pub const fn epoch(&self) -> DateTime<Utc> {
+        from_millis(self.millis())
+    }
+}
+
+// Accepts one of the well-known epoch names or raw milliseconds since the Unix epoch.
+pub fn parse_millis(str: &str) -> Option<i64> {
+    match str {
+        "default" => Some(crate::THE_EPOCH_MILLIS),
+        "unix" => Some(UNIX_MILLIS),
+        "twitter" => Some(TWITTER_MILLIS),
+        "discord" => Some(DISCORD_MILLIS),
+        "instagram" => Some(INSTAGRAM_MILLIS),
+        "sonyflake" => Some(SONYFLAKE_MILLIS),
+        _ => str.parse().ok(),
+    }
+}
+
+#[cfg(feature = "chrono")]
+const fn from_millis(millis: i64) -> DateTime<Utc> {
+    match DateTime::from_timestamp_millis(millis) {
+        Some(epoch) => epoch,
+        None => panic!("epoch out of range"),
+    }
+}
+
+// `THE_EPOCH` without the `LazyLock`, for `no_std` builds and const contexts.
+#[cfg(feature = "chrono")]
+pub const DEFAULT: DateTime<Utc> = from_millis(crate::THE_EPOCH_MILLIS);
+#[cfg(feature = "chrono")]
+pub const UNIX: DateTime<Utc> = from_millis(UNIX_MILLIS);
+#[cfg(feature = "chrono")]
+pub const TWITTER: DateTime<Utc> = from_millis(TWITTER_MILLIS);
+#[cfg(feature = "chrono")]
+pub const DISCORD: DateTime<Utc> = from_millis(DISCORD_MILLIS);
+#[cfg(feature = "chrono")]
+pub const INSTAGRAM: DateTime<Utc> = from_millis(INSTAGRAM_MILLIS);
+#[cfg(feature = "chrono")]
+pub const SONYFLAKE: DateTime<Utc> = from_millis(SONYFLAKE_MILLIS);
+
+#[cfg(all(test, feature = "chrono"))]
+mod tests {
+    use crate::epochs::{
+        parse_millis, KnownEpoch, DEFAULT, DISCORD, DISCORD_MILLIS, INSTAGRAM, SONYFLAKE, TWITTER,
+        UNIX,
+    };
+    use crate::THE_EPOCH_MILLIS;
+    use chrono::{Duration, TimeZone, Utc};
+
+    #[test]
+    fn default_test() {
+        assert_eq!(DEFAULT.timestamp_millis(), THE_EPOCH_MILLIS);
+    }
+
+    #[test]
+    fn unix_test() {
+        assert_eq!(UNIX, Utc.with_ymd_and_hms(1970, 1, 1, 0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn twitter_test() {
+        let expected =
+            Utc.with_ymd_and_hms(2010, 11, 4, 1, 42, 54).unwrap() + Duration::milliseconds(657);
+        assert_eq!(TWITTER, expected);
+    }
+
+    #[test]
+    fn discord_test() {
+        assert_eq!(DISCORD, Utc.with_ymd_and_hms(2015, 1, 1, 0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn instagram_test() {
+        let expected =
+            Utc.with_ymd_and_hms(2011, 8, 24, 21, 7, 1).unwrap() + Duration::milliseconds(721);
+        assert_eq!(INSTAGRAM, expected);
+    }
+
+    #[test]
+    fn sonyflake_test() {
+        assert_eq!(
+            SONYFLAKE,
+            Utc.with_ymd_and_hms(2014, 9, 1, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_millis_test() {
+        assert_eq!(parse_millis("default"), Some(THE_EPOCH_MILLIS));
+        assert_eq!(parse_millis("discord"), Some(DISCORD_MILLIS));
+        assert_eq!(parse_millis("1420070400000"), Some(DISCORD_MILLIS));
+        assert_eq!(parse_millis("-5"), Some(-5));
+        assert_eq!(parse_millis("mars"), None);
+        assert_eq!(parse_millis(""), None);
+    }
+
+    #[test]
+    fn known_epoch_test() {
+        for epoch in KnownEpoch::ALL {
+            assert_eq!(parse_millis(epoch.name()), Some(epoch.millis()));
+            assert_eq!(epoch.epoch().timestamp_millis(), epoch.millis());
+        }
+    }
+}
@@ -0,0 +1,202 @@
+// C bindings for the lock-free generator. The header is `include/lockfree_snowflake.h`,
+// generated with `cbindgen --config cbindgen.toml --output include/lockfree_snowflake.h`.
+// Link against the crate built with
+// `cargo rustc --release --features ffi --crate-type staticlib` (or `cdylib`).
+
+use crate::bit_layout::BitLayout;
+use crate::clock::SystemClock;
+use crate::generator_policy::OverflowPolicy;
+use crate::snow_flake_id::SnowflakeId;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+
+#[repr(C)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum SnowflakeStatus {
+    Ok = 0,
+    NullPointer,
+    MachineIdOutOfRange,
+    EpochInFuture,
+    ClockMovedBackwards,
+    TimestampOverflow,
+    Failed,
+}
+
+impl From<SnowflakeIdEGeneratorError> for SnowflakeStatus {
+    fn from(value: SnowflakeIdEGeneratorError) -> Self {
+        match value {
+            SnowflakeIdEGeneratorError::MachineIdOutOfRange { .. } => {
+                SnowflakeStatus::MachineIdOutOfRange
+            }
+            SnowflakeIdEGeneratorError::EpochInFuture => SnowflakeStatus::EpochInFuture,
+            SnowflakeIdEGeneratorError::ClockMovedBackwards => SnowflakeStatus::ClockMovedBackwards,
+            SnowflakeIdEGeneratorError::TimestampOverflow { .. } => {
+                SnowflakeStatus::TimestampOverflow
+            }
+            _ => SnowflakeStatus::Failed,
+        }
+    }
+}
+
+/// Opaque handle to a generator on the system clock with the default bit layout.
+pub struct SnowflakeGenerator(SnowFlakeIdGenerator<SystemClock>);
+
+#[repr(C)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct SnowflakeParts {
+    pub timestamp_millis: i64,
+    pub machine_id: u16,
+    pub sequence: u16,
+}
+
+/// Creates a generator and stores it in `*generator`. Release it with
+/// `snowflake_generator_free`.
+///
+/// # Safety
+///
+/// `generator` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn snowflake_generator_new(
+    epoch_millis: i64,
+    machine_id: u16,
+    generator: *mut *mut SnowflakeGenerator,
+) -> SnowflakeStatus {
+    if generator.is_null() {
+        return SnowflakeStatus::NullPointer;
+    }
+
+    match SnowFlakeIdGenerator::with_epoch_millis(
+        SystemClock,
+        epoch_millis,
+        machine_id,
+        BitLayout::DEFAULT,
+    ) {
+        Ok(inner) => {
+            let inner = inner.with_overflow_policy(OverflowPolicy::SpinUntilNextMillis);
+            *generator = Box::into_raw(Box::new(SnowflakeGenerator(inner)));
+            SnowflakeStatus::Ok
+        }
+        Err(e) => e.into(),
+    }
+}
+
+/// # Safety
+///
+/// `generator` must be null or a pointer from `snowflake_generator_new` that hasn't been
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn snowflake_generator_free(generator: *mut SnowflakeGenerator) {
+    if !generator.is_null() {
+        drop(Box::from_raw(generator));
+    }
+}
+
+/// Stores the next ID in `*id`. Safe to call from any number of threads at once.
+///
+/// # Safety
+///
+/// `generator` must be null or a live pointer from `snowflake_generator_new`, and `id`
+/// must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn snowflake_generate(
+    generator: *const SnowflakeGenerator,
+    id: *mut u64,
+) -> SnowflakeStatus {
+    if generator.is_null() || id.is_null() {
+        return SnowflakeStatus::NullPointer;
+    }
+
+    match (*generator).0.try_generate() {
+        Ok(generated) => {
+            *id = generated.as_u64();
+            SnowflakeStatus::Ok
+        }
+        Err(e) => e.into(),
+    }
+}
+
+/// Splits an ID made with the default layout; `timestamp_millis` is since the Unix epoch.
+#[no_mangle]
+pub extern "C" fn snowflake_decode(id: u64, epoch_millis: i64) -> SnowflakeParts {
+    let id = SnowflakeId::from(id);
+
+    SnowflakeParts {
+        timestamp_millis: epoch_millis.wrapping_add(id.raw_timestamp() as i64),
+        machine_id: id.machine_id(),
+        sequence: id.sequence(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ffi::{
+        snowflake_decode, snowflake_generate, snowflake_generator_free, snowflake_generator_new,
+        SnowflakeGenerator, SnowflakeParts, SnowflakeStatus,
+    };
+    use crate::snow_flake_id::SnowflakeId;
+    use crate::THE_EPOCH_MILLIS;
+    use std::ptr::null_mut;
+
+    #[test]
+    fn generate_test() {
+        let mut generator: *mut SnowflakeGenerator = null_mut();
+        let status = unsafe { snowflake_generator_new(THE_EPOCH_MILLIS, 42, &mut generator) };
+        assert_eq!(status, SnowflakeStatus::Ok);
+        assert!(!generator.is_null());
+
+        let mut previous = 0;
+        for _ in 0..10_000 {
+            let mut id = 0;
+            assert_eq!(
+                unsafe { snowflake_generate(generator, &mut id) },
+                SnowflakeStatus::Ok
+            );
+            assert!(id > previous);
+            assert_eq!(snowflake_decode(id, THE_EPOCH_MILLIS).machine_id, 42);
+            previous = id;
+        }
+
+        assert_eq!(
+            unsafe { snowflake_generate(generator, null_mut()) },
+            SnowflakeStatus::NullPointer
+        );
+
+        unsafe { snowflake_generator_free(generator) };
+    }
+
+    #[test]
+    fn invalid_new_test() {
+        let mut generator: *mut SnowflakeGenerator = null_mut();
+
+        let status = unsafe { snowflake_generator_new(THE_EPOCH_MILLIS, 1024, &mut generator) };
+        assert_eq!(status, SnowflakeStatus::MachineIdOutOfRange);
+        assert!(generator.is_null());
+
+        let status = unsafe { snowflake_generator_new(i64::MAX, 1, &mut generator) };
+        assert_eq!(status, SnowflakeStatus::EpochInFuture);
+
+        let status = unsafe { snowflake_generator_new(THE_EPOCH_MILLIS, 1, null_mut()) };
+        assert_eq!(status, SnowflakeStatus::NullPointer);
+
+        let mut id = 0;
+        assert_eq!(
+            unsafe { snowflake_generate(generator, &mut id) },
+            SnowflakeStatus::NullPointer
+        );
+        unsafe { snowflake_generator_free(generator) };
+    }
+
+    #[test]
+    fn decode_test() {
+        let id = SnowflakeId::new(1500, 169, 7).unwrap();
+
+        assert_eq!(
+            snowflake_decode(id.as_u64(), THE_EPOCH_MILLIS),
+            SnowflakeParts {
+                timestamp_millis: THE_EPOCH_MILLIS + 1500,
+                machine_id: 169,
+                sequence: 7,
+            }
+        );
+    }
+}
@@ -0,0 +1,303 @@
+use crate::clock::Clock;
+use crate::machine_id::MachineIdProvider;
+use crate::snow_flake_id::SnowflakeId;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_error::SnowflakeIdEGeneratorError::Forked;
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+// The process a generator belongs to. The pid alone can repeat: a grandchild may be
+// handed the pid of a grandparent that has since exited. The nonce, the process start
+// time in clock ticks since boot, tells those apart. It is 0 where `/proc` is missing.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub struct ProcessIdentity {
+    pid: u32,
+    nonce: u64,
+}
+
+impl ProcessIdentity {
+    pub fn current() -> Self {
+        ProcessIdentity {
+            pid: std::process::id(),
+            nonce: start_time(),
+        }
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+fn start_time() -> u64 {
+    // Field 22 of `/proc/self/stat`. The command name before it may hold spaces and
+    // parentheses, so count from the last `)`.
+    std::fs::read_to_string("/proc/self/stat")
+        .ok()
+        .and_then(|stat| {
+            let rest = &stat[stat.rfind(')')? + 1..];
+            rest.split_whitespace().nth(19)?.parse().ok()
+        })
+        .unwrap_or(0)
+}
+
+struct Generation<T: Clock> {
+    owner: ProcessIdentity,
+    generator: SnowFlakeIdGenerator<T>,
+}
+
+// A generator that notices when it has been carried into a child by `fork()`. The child
+// starts with a copy of the parent's state, machine ID included, so both would issue the
+// same IDs. Every call compares the pid against the one the generator was created in; on
+// a mismatch it fails with `Forked`, or, given a `MachineIdProvider`, switches to a fresh
+// machine ID from it and carries on. A provider handing back the old ID counts as a
+// failure.
+//
+// The pid check costs a syscall per ID. `with_strict_check` also compares the start-time
+// nonce, which reads `/proc` on every call and is meant for processes that fork
+// grandchildren.
+pub struct ForkSafeGenerator<T: Clock + Clone> {
+    current: AtomicPtr<Generation<T>>,
+    provider: Option<Box<dyn MachineIdProvider + Send + Sync>>,
+    strict: bool,
+    marker: PhantomData<Box<Generation<T>>>,
+}
+
+impl<T: Clock + Clone> ForkSafeGenerator<T> {
+    pub fn new(generator: SnowFlakeIdGenerator<T>) -> Self {
+        Self::with_owner(generator, ProcessIdentity::current())
+    }
+
+    fn with_owner(generator: SnowFlakeIdGenerator<T>, owner: ProcessIdentity) -> Self {
+        ForkSafeGenerator {
+            current: AtomicPtr::new(Box::into_raw(Box::new(Generation { owner, generator }))),
+            provider: None,
+            strict: false,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn with_provider<P: MachineIdProvider + Send + Sync + 'static>(
+        mut self,
+        provider: P,
+    ) -> Self {
+        self.provider = Some(Box::new(provider));
+        self
+    }
+
+    pub fn with_strict_check(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    fn generation(&self) -> &Generation<T> {
+        // Generations are only freed on drop; see `reacquire`.
+        unsafe { &*self.current.load(Ordering::Acquire) }
+    }
+
+    // The generator currently in use, without checking for a fork.
+    pub fn generator(&self) -> &SnowFlakeIdGenerator<T> {
+        &self.generation().generator
+    }
+
+    pub fn owner(&self) -> ProcessIdentity {
+        self.generation().owner
+    }
+
+    pub fn machine_id(&self) -> u16 {
+        self.generator().machine_id()
+    }
+
+    fn is_owner(&self, owner: &ProcessIdentity) -> bool {
+        owner.pid == std::process::id() && (!self.strict || owner.nonce == start_time())
+    }
+
+    fn checked(&self) -> Result<&SnowFlakeIdGenerator<T>, SnowflakeIdEGeneratorError> {
+        let stale = self.current.load(Ordering::Acquire);
+        // Generations are only freed on drop; see `reacquire`.
+        let generation = unsafe { &*stale };
+
+        if self.is_owner(&generation.owner) {
+            Ok(&generation.generator)
+        } else {
+            self.reacquire(stale)
+        }
+    }
+
+    fn reacquire(
+        &self,
+        stale: *mut Generation<T>,
+    ) -> Result<&SnowFlakeIdGenerator<T>, SnowflakeIdEGeneratorError> {
+        let provider = self.provider.as_ref().ok_or(Forked)?;
+        // Points into `self.current` until the swap below, and is never freed after it.
+        let previous = unsafe { &(*stale).generator };
+
+        let machine_id = provider.machine_id();
+        if machine_id == previous.machine_id() {
+            return Err(Forked);
+        }
+
+        let fresh = Box::into_raw(Box::new(Generation {
+            owner: ProcessIdentity::current(),
+            generator: previous.rebind(machine_id)?,
+        }));
+
+        // The replaced generation is leaked rather than freed: another thread of the child
+        // may still be reading it, and a process forks rarely. Threads racing here may each
+        // take an ID from the provider; only the first swap is kept.
+        match self
+            .current
+            .compare_exchange(stale, fresh, Ordering::AcqRel, Ordering::Acquire)
+        {
+            Ok(_) => Ok(unsafe { &(*fresh).generator }),
+            Err(winner) => {
+                drop(unsafe { Box::from_raw(fresh) });
+                Ok(unsafe { &(*winner).generator })
+            }
+        }
+    }
+
+    pub fn try_generate(&self) -> Result<SnowflakeId, SnowflakeIdEGeneratorError> {
+        self.checked()?.try_generate()
+    }
+
+    pub fn generate(&self) -> Option<SnowflakeId> {
+        self.try_generate().ok()
+    }
+}
+
+impl<T: Clock + Clone> Drop for ForkSafeGenerator<T> {
+    fn drop(&mut self) {
+        drop(unsafe { Box::from_raw(*self.current.get_mut()) });
+    }
+}
+
+impl<T: Clock + Clone> Debug for ForkSafeGenerator<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ForkSafeGenerator")
+            .field("owner", &self.owner())
+            .field("generator", self.generator())
+            .field("strict", &self.strict)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::fork_safe_generator::{ForkSafeGenerator, ProcessIdentity};
+    use crate::machine_id::MachineIdProvider;
+    use crate::snowflake_error::SnowflakeIdEGeneratorError::Forked;
+    use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+    use crate::testing::ManualClock;
+    use crate::THE_EPOCH_MILLIS;
+
+    struct Fixed(u16);
+
+    impl MachineIdProvider for Fixed {
+        fn machine_id(&self) -> u16 {
+            self.0
+        }
+    }
+
+    fn generator() -> SnowFlakeIdGenerator<ManualClock> {
+        SnowFlakeIdGenerator::with_epoch_millis(
+            ManualClock::new(THE_EPOCH_MILLIS + 10),
+            THE_EPOCH_MILLIS,
+            7,
+            BitLayout::DEFAULT,
+        )
+        .unwrap()
+    }
+
+    // Stands in for the copy a child inherits: owned by some other process.
+    fn forked() -> ForkSafeGenerator<ManualClock> {
+        let parent = ProcessIdentity {
+            pid: std::process::id().wrapping_add(1),
+            nonce: 0,
+        };
+        ForkSafeGenerator::with_owner(generator(), parent)
+    }
+
+    #[test]
+    fn process_identity_test() {
+        let actual = ProcessIdentity::current();
+        assert_eq!(actual.pid(), std::process::id());
+        assert_eq!(actual, ProcessIdentity::current());
+
+        if cfg!(target_os = "linux") {
+            assert_ne!(actual.nonce(), 0);
+        }
+    }
+
+    #[test]
+    fn same_process_test() {
+        let target = ForkSafeGenerator::new(generator()).with_strict_check(true);
+
+        assert!(target.is_strict());
+        assert_eq!(target.owner(), ProcessIdentity::current());
+
+        let first = target.try_generate().unwrap();
+        let second = target.try_generate().unwrap();
+        assert_eq!(first.machine_id(), 7);
+        assert_eq!(second.sequence(), first.sequence() + 1);
+        assert_eq!(target.generator().snapshot(), second);
+    }
+
+    #[test]
+    fn reject_test() {
+        let target = forked();
+
+        assert_eq!(target.try_generate(), Err(Forked));
+        assert_eq!(target.generate(), None);
+        assert_eq!(target.machine_id(), 7);
+    }
+
+    #[test]
+    fn reacquire_test() {
+        let target = forked();
+        let before = target.generator().try_generate().unwrap();
+
+        let target = target.with_provider(Fixed(9));
+        let first = target.try_generate().unwrap();
+        let second = target.try_generate().unwrap();
+
+        assert_eq!(target.owner(), ProcessIdentity::current());
+        assert_eq!(target.machine_id(), 9);
+        assert_eq!(first.machine_id(), 9);
+        assert_eq!(first.raw_timestamp(), before.raw_timestamp());
+        assert!(before < first && first < second);
+    }
+
+    #[test]
+    fn reacquire_same_id_test() {
+        let target = forked().with_provider(Fixed(7));
+
+        assert_eq!(target.try_generate(), Err(Forked));
+        assert_eq!(target.machine_id(), 7);
+    }
+
+    #[test]
+    fn reacquire_out_of_range_test() {
+        let target = forked().with_provider(Fixed(1024));
+
+        assert_eq!(
+            target.try_generate(),
+            Err(
+                crate::snowflake_error::SnowflakeIdEGeneratorError::MachineIdOutOfRange {
+                    value: 1024,
+                    max: 1023
+                }
+            )
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering::Relaxed;
+
+// Metric names emitted by `SnowFlakeIdGenerator` when the `metrics` feature is enabled.
+// Without the feature the recording helpers compile to nothing.
+pub const IDS_GENERATED: &str = "lockfree_snowflake_ids_generated_total";
+pub const CAS_RETRIES: &str = "lockfree_snowflake_cas_retries_total";
+pub const SEQUENCE_EXHAUSTED: &str = "lockfree_snowflake_sequence_exhausted_total";
+pub const CLOCK_REGRESSIONS: &str = "lockfree_snowflake_clock_regressions_total";
+pub const BATCH_SIZE: &str = "lockfree_snowflake_batch_size";
+
+#[cfg(feature = "metrics")]
+pub fn describe() {
+    metrics::describe_counter!(IDS_GENERATED, "IDs handed out by the generators");
+    metrics::describe_counter!(
+        CAS_RETRIES,
+        "Generation attempts that lost the compare-and-swap race"
+    );
+    metrics::describe_counter!(
+        SEQUENCE_EXHAUSTED,
+        "Generation attempts that found the sequence of the current millisecond used up"
+    );
+    metrics::describe_counter!(
+        CLOCK_REGRESSIONS,
+        "Clock readings older than the last issued timestamp"
+    );
+    metrics::describe_histogram!(BATCH_SIZE, "Number of IDs reserved per batch");
+}
+
+#[inline]
+pub(crate) fn record_generated(_count: u64) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(IDS_GENERATED).increment(_count);
+}
+
+#[inline]
+pub(crate) fn record_cas_retry() {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(CAS_RETRIES).increment(1);
+}
+
+#[inline]
+pub(crate) fn record_sequence_exhausted() {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(SEQUENCE_EXHAUSTED).increment(1);
+}
+
+#[inline]
+pub(crate) fn record_clock_regression() {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(CLOCK_REGRESSIONS).increment(1);
+}
+
+#[inline]
+pub(crate) fn record_batch_size(_size: usize) {
+    #[cfg(feature = "metrics")]
+    metrics::histogram!(BATCH_SIZE).record(_size as f64);
+}
+
+// Per-generator counters behind `SnowFlakeIdGenerator::stats`. They are always on and
+// independent of the `metrics` feature; relaxed ordering keeps them off the hot path's
+// critical section, so a snapshot is consistent per field but not across fields.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct GeneratorStats {
+    pub generated: u64,
+    pub cas_failures: u64,
+    pub sequence_exhausted: u64,
+    pub clock_regressions: u64,
+    pub max_timestamp: u64,
+}
+
+impl GeneratorStats {
+    // Combines the stats of several generators, e.g. the shards of one sharded generator.
+    pub fn merge(&self, other: &GeneratorStats) -> GeneratorStats {
+        GeneratorStats {
+            generated: self.generated + other.generated,
+            cas_failures: self.cas_failures + other.cas_failures,
+            sequence_exhausted: self.sequence_exhausted + other.sequence_exhausted,
+            clock_regressions: self.clock_regressions + other.clock_regressions,
+            max_timestamp: self.max_timestamp.max(other.max_timestamp),
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct StatsCounters {
+    generated: AtomicU64,
+    cas_failures: AtomicU64,
+    sequence_exhausted: AtomicU64,
+    clock_regressions: AtomicU64,
+    max_timestamp: AtomicU64,
+}
+
+impl StatsCounters {
+    pub(crate) fn record_generated(&self, count: u64, timestamp: u64) {
+        self.generated.fetch_add(count, Relaxed);
+        self.max_timestamp.fetch_max(timestamp, Relaxed);
+    }
+
+    pub(crate) fn record_cas_failure(&self) {
+        self.cas_failures.fetch_add(1, Relaxed);
+    }
+
+    pub(crate) fn record_sequence_exhausted(&self) {
+        self.sequence_exhausted.fetch_add(1, Relaxed);
+    }
+
+    pub(crate) fn record_clock_regression(&self) {
+        self.clock_regressions.fetch_add(1, Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> GeneratorStats {
+        GeneratorStats {
+            generated: self.generated.load(Relaxed),
+            cas_failures: self.cas_failures.load(Relaxed),
+            sequence_exhausted: self.sequence_exhausted.load(Relaxed),
+            clock_regressions: self.clock_regressions.load(Relaxed),
+            max_timestamp: self.max_timestamp.load(Relaxed),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+pub(crate) mod tests {
+    use metrics::{
+        Counter, CounterFn, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+        SharedString, Unit,
+    };
+    use std::collections::HashMap;
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::sync::{Arc, Mutex};
+
+    struct Cell(AtomicU64);
+
+    impl CounterFn for Cell {
+        fn increment(&self, value: u64) {
+            self.0.fetch_add(value, Relaxed);
+        }
+
+        fn absolute(&self, value: u64) {
+            self.0.fetch_max(value, Relaxed);
+        }
+    }
+
+    impl HistogramFn for Cell {
+        fn record(&self, value: f64) {
+            self.0.fetch_add(value as u64, Relaxed);
+        }
+    }
+
+    // Sums counters and histogram samples per metric name.
+    #[derive(Default)]
+    pub(crate) struct Fixture(Mutex<HashMap<String, Arc<Cell>>>);
+
+    impl Fixture {
+        fn cell(&self, key: &Key) -> Arc<Cell> {
+            self.0
+                .lock()
+                .unwrap()
+                .entry(key.name().to_string())
+                .or_insert_with(|| Arc::new(Cell(AtomicU64::new(0))))
+                .clone()
+        }
+
+        pub(crate) fn get(&self, name: &str) -> u64 {
+            self.0
+                .lock()
+                .unwrap()
+                .get(name)
+                .map_or(0, |cell| cell.0.load(Relaxed))
+        }
+    }
+
+    impl Recorder for Fixture {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            Counter::from_arc(self.cell(key))
+        }
+
+        fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::noop()
+        }
+
+        fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::from_arc(self.cell(key))
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use core::sync::atomic::Ordering;
+use core::time::Duration;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ClockRegressionPolicy {
+    #[default]
+    Reject,
+    WaitUntilCaughtUp,
+    UseLastTimestamp,
+}
+
+// What to do once every sequence number of the current millisecond has been issued.
+// `BorrowFromFuture` moves on to the next millisecond without waiting for the clock;
+// while the generator runs ahead, a clock reading behind the last timestamp is treated
+// as borrowed time rather than as a regression.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum OverflowPolicy {
+    #[default]
+    Error,
+    SpinUntilNextMillis,
+    BorrowFromFuture,
+}
+
+// Memory ordering of the generator's shared state. Uniqueness and monotonicity only need
+// `Relaxed`, since every ID is decided by a single CAS on one atomic. `AcquireRelease`
+// additionally makes the CAS publish whatever the issuing thread wrote before generating,
+// so a thread that observes an ID (through `generate` or `snapshot`) also observes those
+// writes. It costs nothing on x86 and little elsewhere, hence the default.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum MemoryOrderingPolicy {
+    Relaxed,
+    #[default]
+    AcquireRelease,
+}
+
+impl MemoryOrderingPolicy {
+    pub(crate) fn load(self) -> Ordering {
+        match self {
+            MemoryOrderingPolicy::Relaxed => Ordering::Relaxed,
+            MemoryOrderingPolicy::AcquireRelease => Ordering::Acquire,
+        }
+    }
+
+    pub(crate) fn read_modify_write(self) -> Ordering {
+        match self {
+            MemoryOrderingPolicy::Relaxed => Ordering::Relaxed,
+            MemoryOrderingPolicy::AcquireRelease => Ordering::AcqRel,
+        }
+    }
+}
+
+// What one step of the timestamp field means. Coarser ticks stretch the lifetime of a
+// layout (41 bits of seconds last ~70,000 years) at the cost of fewer IDs per second per
+// machine; `Micros` goes the other way. The generator scales the clock accordingly, and
+// anything decoding the IDs has to use the same unit.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TickUnit {
+    Micros,
+    #[default]
+    Millis,
+    TenMillis,
+    Seconds,
+}
+
+impl TickUnit {
+    pub const fn as_micros(&self) -> u64 {
+        match self {
+            TickUnit::Micros => 1,
+            TickUnit::Millis => 1_000,
+            TickUnit::TenMillis => 10_000,
+            TickUnit::Seconds => 1_000_000,
+        }
+    }
+
+    pub const fn duration(&self) -> Duration {
+        Duration::from_micros(self.as_micros())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generator_policy::{
+        ClockRegressionPolicy, MemoryOrderingPolicy, OverflowPolicy, TickUnit,
+    };
+    use core::sync::atomic::Ordering;
+
+    #[test]
+    fn default_test() {
+        assert_eq!(
+            ClockRegressionPolicy::default(),
+            ClockRegressionPolicy::Reject
+        );
+    }
+
+    #[test]
+    fn overflow_policy_default_test() {
+        assert_eq!(OverflowPolicy::default(), OverflowPolicy::Error);
+    }
+
+    #[test]
+    fn memory_ordering_policy_test() {
+        assert_eq!(
+            MemoryOrderingPolicy::default(),
+            MemoryOrderingPolicy::AcquireRelease
+        );
+
+        let target = MemoryOrderingPolicy::AcquireRelease;
+        assert_eq!(target.load(), Ordering::Acquire);
+        assert_eq!(target.read_modify_write(), Ordering::AcqRel);
+
+        let target = MemoryOrderingPolicy::Relaxed;
+        assert_eq!(target.load(), Ordering::Relaxed);
+        assert_eq!(target.read_modify_write(), Ordering::Relaxed);
+    }
+
+    #[test]
+    fn tick_unit_test() {
+        assert_eq!(TickUnit::default(), TickUnit::Millis);
+
+        assert_eq!(TickUnit::Micros.as_micros(), 1);
+        assert_eq!(
+            TickUnit::Millis.duration(),
+            core::time::Duration::from_millis(1)
+        );
+        assert_eq!(
+            TickUnit::TenMillis.duration(),
+            core::time::Duration::from_millis(10)
+        );
+        assert_eq!(
+            TickUnit::Seconds.duration(),
+            core::time::Duration::from_secs(1)
+        );
+    }
+}
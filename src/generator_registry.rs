@@ -0,0 +1,309 @@
+use crate::clock::Clock;
+use crate::snow_flake_id::SnowflakeId;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+use core::fmt::{Debug, Display, Formatter};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+#[derive(PartialEq, Eq)]
+pub enum GeneratorRegistryError {
+    AlreadyRegistered,
+    NotFound,
+    Generator(SnowflakeIdEGeneratorError),
+}
+
+impl GeneratorRegistryError {
+    fn format(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            GeneratorRegistryError::AlreadyRegistered => {
+                write!(f, "GeneratorRegistryError::AlreadyRegistered")
+            }
+            GeneratorRegistryError::NotFound => write!(f, "GeneratorRegistryError::NotFound"),
+            GeneratorRegistryError::Generator(e) => {
+                write!(f, "GeneratorRegistryError::Generator({})", e)
+            }
+        }
+    }
+}
+
+impl Debug for GeneratorRegistryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+impl Display for GeneratorRegistryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+impl std::error::Error for GeneratorRegistryError {}
+
+impl From<SnowflakeIdEGeneratorError> for GeneratorRegistryError {
+    fn from(value: SnowflakeIdEGeneratorError) -> Self {
+        GeneratorRegistryError::Generator(value)
+    }
+}
+
+// Named generators for services that issue IDs in several independent domains. Lookups
+// take a shared lock and hand out an `Arc`, so generating never holds the map lock.
+pub struct GeneratorRegistry<T: Clock> {
+    generators: RwLock<HashMap<String, Arc<SnowFlakeIdGenerator<T>>>>,
+}
+
+impl<T: Clock> Default for GeneratorRegistry<T> {
+    fn default() -> Self {
+        GeneratorRegistry::new()
+    }
+}
+
+impl<T: Clock> GeneratorRegistry<T> {
+    pub fn new() -> Self {
+        GeneratorRegistry {
+            generators: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn register(
+        &self,
+        name: impl Into<String>,
+        generator: SnowFlakeIdGenerator<T>,
+    ) -> Result<Arc<SnowFlakeIdGenerator<T>>, GeneratorRegistryError> {
+        let mut generators = self.generators.write().unwrap();
+        let name = name.into();
+
+        if generators.contains_key(&name) {
+            return Err(GeneratorRegistryError::AlreadyRegistered);
+        }
+
+        let generator = Arc::new(generator);
+        generators.insert(name, generator.clone());
+        Ok(generator)
+    }
+
+    pub fn get_or_register_with<F>(
+        &self,
+        name: &str,
+        init: F,
+    ) -> Result<Arc<SnowFlakeIdGenerator<T>>, SnowflakeIdEGeneratorError>
+    where
+        F: FnOnce() -> Result<SnowFlakeIdGenerator<T>, SnowflakeIdEGeneratorError>,
+    {
+        if let Some(generator) = self.get(name) {
+            return Ok(generator);
+        }
+
+        let mut generators = self.generators.write().unwrap();
+
+        if let Some(generator) = generators.get(name) {
+            return Ok(generator.clone());
+        }
+
+        let generator = Arc::new(init()?);
+        generators.insert(name.to_string(), generator.clone());
+        Ok(generator)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<SnowFlakeIdGenerator<T>>> {
+        self.generators.read().unwrap().get(name).cloned()
+    }
+
+    pub fn remove(&self, name: &str) -> Option<Arc<SnowFlakeIdGenerator<T>>> {
+        self.generators.write().unwrap().remove(name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.generators.read().unwrap().keys().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.generators.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn try_generate(&self, name: &str) -> Result<SnowflakeId, GeneratorRegistryError> {
+        let generator = self.get(name).ok_or(GeneratorRegistryError::NotFound)?;
+        Ok(generator.try_generate()?)
+    }
+
+    pub fn generate(&self, name: &str) -> Option<SnowflakeId> {
+        self.try_generate(name).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::clock::{Clock, SystemClock};
+    use crate::generator_registry::{GeneratorRegistry, GeneratorRegistryError};
+    use crate::snowflake_error::SnowflakeIdEGeneratorError;
+    use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+    use crate::THE_EPOCH_MILLIS;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use std::thread;
+
+    struct Fixture(i64);
+
+    impl Clock for Fixture {
+        fn now_millis(&self) -> i64 {
+            self.0
+        }
+    }
+
+    fn generator(machine_id: u16) -> SnowFlakeIdGenerator<Fixture> {
+        SnowFlakeIdGenerator::with_epoch_millis(
+            Fixture(THE_EPOCH_MILLIS + 5),
+            THE_EPOCH_MILLIS,
+            machine_id,
+            BitLayout::DEFAULT,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn format_test() {
+        let targets = [
+            GeneratorRegistryError::AlreadyRegistered,
+            GeneratorRegistryError::NotFound,
+            GeneratorRegistryError::Generator(SnowflakeIdEGeneratorError::MachineIdOutOfRange {
+                value: 1024,
+                max: 1023,
+            }),
+        ];
+        let expected = [
+            "GeneratorRegistryError::AlreadyRegistered",
+            "GeneratorRegistryError::NotFound",
+            "GeneratorRegistryError::Generator(SnowflakeIdEGeneratorError::MachineIdOutOfRange { value: 1024, max: 1023 })",
+        ];
+
+        for elem in targets.iter().zip(expected) {
+            assert_eq!(format!("{}", elem.0), elem.1);
+            assert_eq!(format!("{:?}", elem.0), elem.1);
+        }
+    }
+
+    #[test]
+    fn register_test() {
+        let target = GeneratorRegistry::new();
+        assert!(target.is_empty());
+
+        target.register("orders", generator(1)).unwrap();
+        target.register("users", generator(2)).unwrap();
+
+        assert!(matches!(
+            target.register("orders", generator(3)),
+            Err(GeneratorRegistryError::AlreadyRegistered)
+        ));
+
+        let mut names = target.names();
+        names.sort();
+        assert_eq!(names, ["orders", "users"]);
+        assert_eq!(target.len(), 2);
+
+        assert_eq!(target.get("orders").unwrap().machine_id(), 1);
+        assert_eq!(target.get("users").unwrap().machine_id(), 2);
+        assert!(target.get("payments").is_none());
+
+        assert_eq!(target.remove("orders").unwrap().machine_id(), 1);
+        assert!(target.get("orders").is_none());
+        assert_eq!(target.len(), 1);
+    }
+
+    #[test]
+    fn generate_test() {
+        let target = GeneratorRegistry::new();
+        target.register("orders", generator(1)).unwrap();
+        target.register("users", generator(2)).unwrap();
+
+        let order = target.get("orders").unwrap().generate().unwrap();
+        assert_eq!(order.machine_id(), 1);
+        assert_eq!(order.sequence(), 0);
+
+        // Domains are independent, so each starts its own sequence.
+        let user = target.generate("users").unwrap();
+        assert_eq!(user.machine_id(), 2);
+        assert_eq!(user.sequence(), 0);
+
+        assert_eq!(target.generate("orders").unwrap().sequence(), 1);
+        assert_eq!(
+            target.try_generate("payments"),
+            Err(GeneratorRegistryError::NotFound)
+        );
+    }
+
+    #[test]
+    fn get_or_register_with_test() {
+        let target = GeneratorRegistry::new();
+
+        let first = target
+            .get_or_register_with("orders", || Ok(generator(1)))
+            .unwrap();
+        let second = target
+            .get_or_register_with("orders", || panic!("already registered"))
+            .unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let actual = target.get_or_register_with("users", || {
+            SnowFlakeIdGenerator::with_epoch_millis(
+                Fixture(THE_EPOCH_MILLIS),
+                THE_EPOCH_MILLIS,
+                1024,
+                BitLayout::DEFAULT,
+            )
+        });
+        assert!(matches!(
+            actual,
+            Err(SnowflakeIdEGeneratorError::MachineIdOutOfRange { .. })
+        ));
+        assert!(target.get("users").is_none());
+    }
+
+    #[test]
+    fn multi_thread_test() {
+        let target = Arc::new(GeneratorRegistry::new());
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let target = target.clone();
+                thread::spawn(move || {
+                    let name = if i % 2 == 0 { "even" } else { "odd" };
+                    let generator = target
+                        .get_or_register_with(name, || {
+                            SnowFlakeIdGenerator::with_epoch_millis(
+                                SystemClock,
+                                THE_EPOCH_MILLIS,
+                                i % 2,
+                                BitLayout::DEFAULT,
+                            )
+                        })
+                        .unwrap();
+
+                    let mut ids = Vec::with_capacity(500);
+                    while ids.len() < 500 {
+                        if let Some(id) = generator.generate() {
+                            ids.push(id);
+                        }
+                    }
+
+                    ids
+                })
+            })
+            .collect();
+
+        let mut set = HashSet::new();
+        for handle in handles {
+            for id in handle.join().unwrap() {
+                assert!(set.insert(id));
+            }
+        }
+
+        assert_eq!(set.len(), 4000);
+        assert_eq!(target.len(), 2);
+    }
+}
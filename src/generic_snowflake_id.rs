@@ -0,0 +1,232 @@
+use crate::bit_layout::BitLayout;
+use crate::snow_flake_id::{SnowflakeId, SnowflakeIdError, SnowflakeIdParseError};
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
+// A snowflake ID whose layout is part of its type. The field widths are checked when the
+// type is first used: a budget that does not add up to exactly 64 bits, or a machine or
+// sequence field wider than 16 bits, fails to compile instead of failing at runtime. The
+// accessors fold down to constant shifts and masks, and IDs of different layouts are
+// different types.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub struct GenericSnowflakeId<const TS_BITS: u32, const MID_BITS: u32, const SEQ_BITS: u32>(u64);
+
+// `BitLayout::DEFAULT` as a type.
+pub type DefaultSnowflakeId = GenericSnowflakeId<42, 10, 12>;
+
+impl<const TS_BITS: u32, const MID_BITS: u32, const SEQ_BITS: u32>
+    GenericSnowflakeId<TS_BITS, MID_BITS, SEQ_BITS>
+{
+    pub const LAYOUT: BitLayout = {
+        assert!(
+            TS_BITS + MID_BITS + SEQ_BITS == u64::BITS,
+            "the fields of a GenericSnowflakeId must add up to 64 bits"
+        );
+
+        match BitLayout::new(TS_BITS, MID_BITS, SEQ_BITS) {
+            Ok(layout) => layout,
+            Err(_) => panic!("machine and sequence fields are limited to 16 bits"),
+        }
+    };
+
+    pub const fn new(
+        timestamp: u64,
+        machine_id: u16,
+        sequence: u16,
+    ) -> Result<Self, SnowflakeIdError> {
+        match Self::LAYOUT.compose(timestamp, machine_id, sequence) {
+            Ok(id) => Ok(GenericSnowflakeId(id.as_u64())),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub const fn checked_new(timestamp: u64, machine_id: u16, sequence: u16) -> Option<Self> {
+        match Self::new(timestamp, machine_id, sequence) {
+            Ok(id) => Some(id),
+            Err(_) => None,
+        }
+    }
+
+    pub const fn from_u64(value: u64) -> Self {
+        // Mentioning the layout is what makes an invalid budget a compile error.
+        let _ = Self::LAYOUT;
+        GenericSnowflakeId(value)
+    }
+
+    pub const fn layout() -> BitLayout {
+        Self::LAYOUT
+    }
+
+    pub const fn raw_timestamp(&self) -> u64 {
+        Self::LAYOUT.raw_timestamp(self.as_snowflake_id())
+    }
+
+    pub const fn machine_id(&self) -> u16 {
+        Self::LAYOUT.machine_id(self.as_snowflake_id())
+    }
+
+    pub const fn sequence(&self) -> u16 {
+        Self::LAYOUT.sequence(self.as_snowflake_id())
+    }
+
+    pub const fn as_u64(&self) -> u64 {
+        self.0
+    }
+
+    pub const fn as_i64(&self) -> i64 {
+        self.0 as i64
+    }
+
+    // The untyped ID; decode it with `Self::layout()`.
+    pub const fn as_snowflake_id(&self) -> SnowflakeId {
+        SnowflakeId::from_u64(self.0)
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn timestamp<TzIn: TimeZone, TzOut: TimeZone>(
+        &self,
+        the_epoch: DateTime<TzIn>,
+        time_zone: &TzOut,
+    ) -> DateTime<TzOut> {
+        let pivot = the_epoch.with_timezone(&Utc);
+        (pivot + Duration::milliseconds(self.raw_timestamp() as i64)).with_timezone(time_zone)
+    }
+}
+
+impl<const TS_BITS: u32, const MID_BITS: u32, const SEQ_BITS: u32> From<u64>
+    for GenericSnowflakeId<TS_BITS, MID_BITS, SEQ_BITS>
+{
+    fn from(value: u64) -> Self {
+        GenericSnowflakeId::from_u64(value)
+    }
+}
+
+impl<const TS_BITS: u32, const MID_BITS: u32, const SEQ_BITS: u32>
+    From<GenericSnowflakeId<TS_BITS, MID_BITS, SEQ_BITS>> for u64
+{
+    fn from(value: GenericSnowflakeId<TS_BITS, MID_BITS, SEQ_BITS>) -> Self {
+        value.as_u64()
+    }
+}
+
+impl<const TS_BITS: u32, const MID_BITS: u32, const SEQ_BITS: u32> From<SnowflakeId>
+    for GenericSnowflakeId<TS_BITS, MID_BITS, SEQ_BITS>
+{
+    fn from(value: SnowflakeId) -> Self {
+        GenericSnowflakeId::from_u64(value.as_u64())
+    }
+}
+
+impl<const TS_BITS: u32, const MID_BITS: u32, const SEQ_BITS: u32>
+    From<GenericSnowflakeId<TS_BITS, MID_BITS, SEQ_BITS>> for SnowflakeId
+{
+    fn from(value: GenericSnowflakeId<TS_BITS, MID_BITS, SEQ_BITS>) -> Self {
+        value.as_snowflake_id()
+    }
+}
+
+impl<const TS_BITS: u32, const MID_BITS: u32, const SEQ_BITS: u32> Display
+    for GenericSnowflakeId<TS_BITS, MID_BITS, SEQ_BITS>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl<const TS_BITS: u32, const MID_BITS: u32, const SEQ_BITS: u32> FromStr
+    for GenericSnowflakeId<TS_BITS, MID_BITS, SEQ_BITS>
+{
+    type Err = SnowflakeIdParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<SnowflakeId>().map(GenericSnowflakeId::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::generic_snowflake_id::{DefaultSnowflakeId, GenericSnowflakeId};
+    use crate::snow_flake_id::{SnowflakeId, SnowflakeIdError};
+
+    const SAMPLE_SCR: u64 = 175_928_847_299_678_215;
+
+    type Wide = GenericSnowflakeId<32, 16, 16>;
+
+    #[test]
+    fn default_test() {
+        let target = DefaultSnowflakeId::from_u64(SAMPLE_SCR);
+        let plain = SnowflakeId::from(SAMPLE_SCR);
+
+        assert_eq!(DefaultSnowflakeId::layout(), BitLayout::DEFAULT);
+        assert_eq!(target.raw_timestamp(), plain.raw_timestamp());
+        assert_eq!(target.machine_id(), plain.machine_id());
+        assert_eq!(target.sequence(), plain.sequence());
+        assert_eq!(target.as_u64(), SAMPLE_SCR);
+        assert_eq!(target.as_i64(), SAMPLE_SCR as i64);
+        assert_eq!(SnowflakeId::from(target), plain);
+        assert_eq!(DefaultSnowflakeId::from(plain), target);
+    }
+
+    #[test]
+    fn custom_layout_test() {
+        let target = Wide::new(0xdead_beef, 0xffff, 0x1234).unwrap();
+
+        assert_eq!(target.as_u64(), 0xdead_beef_ffff_1234);
+        assert_eq!(target.raw_timestamp(), 0xdead_beef);
+        assert_eq!(target.machine_id(), 0xffff);
+        assert_eq!(target.sequence(), 0x1234);
+        assert_eq!(Wide::layout(), BitLayout::new(32, 16, 16).unwrap());
+
+        assert_eq!(
+            Wide::new(1 << 32, 0, 0),
+            Err(SnowflakeIdError::Timestamp {
+                value: 1 << 32,
+                max: u32::MAX as u64
+            })
+        );
+        assert_eq!(Wide::checked_new(1 << 32, 0, 0), None);
+    }
+
+    #[test]
+    fn const_test() {
+        const ID: Option<Wide> = Wide::checked_new(1, 2, 3);
+        const MACHINE_ID: u16 = match ID {
+            Some(id) => id.machine_id(),
+            None => 0,
+        };
+
+        assert_eq!(MACHINE_ID, 2);
+    }
+
+    #[test]
+    fn text_test() {
+        let target = DefaultSnowflakeId::from_u64(SAMPLE_SCR);
+        assert_eq!(target.to_string(), "175928847299678215");
+        assert_eq!("175928847299678215".parse(), Ok(target));
+        assert!("x".parse::<DefaultSnowflakeId>().is_err());
+        assert_eq!(u64::from(target), SAMPLE_SCR);
+        assert_eq!(DefaultSnowflakeId::from(SAMPLE_SCR), target);
+    }
+
+    #[test]
+    fn ordering_test() {
+        let first = Wide::new(1, 0xffff, 0xffff).unwrap();
+        let second = Wide::new(2, 0, 0).unwrap();
+        assert!(first < second);
+    }
+
+    #[cfg(all(feature = "std", feature = "chrono"))]
+    #[test]
+    fn timestamp_test() {
+        use chrono::Utc;
+
+        let target = DefaultSnowflakeId::from_u64(SAMPLE_SCR);
+        assert_eq!(
+            target.timestamp(*crate::THE_EPOCH, &Utc),
+            SnowflakeId::from(SAMPLE_SCR).timestamp(*crate::THE_EPOCH, &Utc)
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use crate::clock::SystemClock;
+use crate::config::ConfigError;
+use crate::generator_policy::OverflowPolicy;
+use crate::snow_flake_id::SnowflakeId;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::LazyLock;
+
+#[derive(PartialEq, Eq)]
+pub enum GlobalError {
+    Config(ConfigError),
+    Generator(SnowflakeIdEGeneratorError),
+}
+
+impl GlobalError {
+    fn format(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GlobalError::Config(e) => write!(f, "GlobalError::Config({})", e),
+            GlobalError::Generator(e) => write!(f, "GlobalError::Generator({})", e),
+        }
+    }
+}
+
+impl Debug for GlobalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.format(f)
+    }
+}
+
+impl Display for GlobalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.format(f)
+    }
+}
+
+impl std::error::Error for GlobalError {}
+
+impl From<ConfigError> for GlobalError {
+    fn from(value: ConfigError) -> Self {
+        GlobalError::Config(value)
+    }
+}
+
+impl From<SnowflakeIdEGeneratorError> for GlobalError {
+    fn from(value: SnowflakeIdEGeneratorError) -> Self {
+        GlobalError::Generator(value)
+    }
+}
+
+// A process-wide generator for small applications that would rather not pass a handle
+// around. It is configured from `SNOWFLAKE_MACHINE_ID` and `SNOWFLAKE_EPOCH` (see
+// `Config::from_env`) on first use, and spins into the next millisecond instead of failing
+// when a millisecond's sequence runs out. The environment is read once; later changes to
+// it are not picked up.
+static GLOBAL: LazyLock<Result<SnowFlakeIdGenerator<SystemClock>, ConfigError>> =
+    LazyLock::new(|| {
+        SnowFlakeIdGenerator::from_env()
+            .map(|x| x.with_overflow_policy(OverflowPolicy::SpinUntilNextMillis))
+    });
+
+pub fn try_generator() -> Result<&'static SnowFlakeIdGenerator<SystemClock>, ConfigError> {
+    GLOBAL.as_ref().map_err(|e| *e)
+}
+
+// Panics if the environment does not describe a valid generator.
+pub fn generator() -> &'static SnowFlakeIdGenerator<SystemClock> {
+    match try_generator() {
+        Ok(generator) => generator,
+        Err(e) => panic!("the global snowflake generator is misconfigured: {}", e),
+    }
+}
+
+// Unlike `generate`, reports a misconfigured environment instead of panicking.
+pub fn try_generate() -> Result<SnowflakeId, GlobalError> {
+    Ok(try_generator()?.try_generate()?)
+}
+
+// Panics like `generator` if the environment does not describe a valid generator.
+pub fn generate() -> Option<SnowflakeId> {
+    generator().generate()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{ConfigError, EPOCH_VAR, MACHINE_ID_VAR};
+    use crate::generator_policy::OverflowPolicy;
+    use crate::global;
+    use crate::global::GlobalError;
+    use crate::snowflake_error::SnowflakeIdEGeneratorError;
+    use crate::THE_EPOCH_MILLIS;
+    use std::collections::HashSet;
+    use std::thread;
+
+    #[test]
+    fn error_test() {
+        let targets = [
+            GlobalError::Config(ConfigError::MissingMachineId),
+            GlobalError::Generator(SnowflakeIdEGeneratorError::ClockMovedBackwards),
+        ];
+        let expected = [
+            "GlobalError::Config(ConfigError::MissingMachineId)",
+            "GlobalError::Generator(SnowflakeIdEGeneratorError::ClockMovedBackwards)",
+        ];
+
+        for elem in targets.iter().zip(expected) {
+            assert_eq!(format!("{}", elem.0), elem.1);
+            assert_eq!(format!("{:?}", elem.0), elem.1);
+        }
+
+        assert_eq!(
+            GlobalError::from(ConfigError::InvalidEpoch),
+            GlobalError::Config(ConfigError::InvalidEpoch)
+        );
+        assert_eq!(
+            GlobalError::from(SnowflakeIdEGeneratorError::Contended),
+            GlobalError::Generator(SnowflakeIdEGeneratorError::Contended)
+        );
+    }
+
+    // The only test that touches the global, so it owns the environment it reads.
+    #[test]
+    fn generate_test() {
+        std::env::set_var(MACHINE_ID_VAR, "42");
+        std::env::remove_var(EPOCH_VAR);
+
+        let target = global::try_generator().unwrap();
+        assert!(std::ptr::eq(target, global::generator()));
+        assert_eq!(target.machine_id(), 42);
+        assert_eq!(target.epoch_millis(), THE_EPOCH_MILLIS);
+        assert_eq!(
+            target.overflow_policy(),
+            OverflowPolicy::SpinUntilNextMillis
+        );
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(|| {
+                    (0..1_000)
+                        .map(|_| global::generate().unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut set = HashSet::new();
+        for handle in handles {
+            for id in handle.join().unwrap() {
+                assert_eq!(id.machine_id(), 42);
+                assert!(set.insert(id));
+            }
+        }
+
+        assert_eq!(set.len(), 4_000);
+        assert!(global::try_generate().unwrap() > *set.iter().max().unwrap());
+    }
+}
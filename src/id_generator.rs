@@ -0,0 +1,141 @@
+use crate::clock::Clock;
+#[cfg(all(feature = "fork", unix))]
+use crate::fork_safe_generator::ForkSafeGenerator;
+#[cfg(feature = "std")]
+use crate::sharded_snowflake_generator::ShardedSnowflakeGenerator;
+use crate::shared_generator::SharedGenerator;
+use crate::snow_flake_id::SnowflakeId;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+
+// Common interface over the ID schemes, so callers can be written against any generator
+// (snowflake, sonyflake, a test double, ...) and swap implementations freely.
+pub trait IdGenerator {
+    type Id;
+    type Error;
+
+    fn generate(&self) -> Result<Self::Id, Self::Error>;
+}
+
+impl<T: Clock> IdGenerator for SnowFlakeIdGenerator<T> {
+    type Id = SnowflakeId;
+    type Error = SnowflakeIdEGeneratorError;
+
+    fn generate(&self) -> Result<Self::Id, Self::Error> {
+        self.try_generate()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Clock + Clone> IdGenerator for ShardedSnowflakeGenerator<T> {
+    type Id = SnowflakeId;
+    type Error = SnowflakeIdEGeneratorError;
+
+    fn generate(&self) -> Result<Self::Id, Self::Error> {
+        self.try_generate()
+    }
+}
+
+#[cfg(all(feature = "fork", unix))]
+impl<T: Clock + Clone> IdGenerator for ForkSafeGenerator<T> {
+    type Id = SnowflakeId;
+    type Error = SnowflakeIdEGeneratorError;
+
+    fn generate(&self) -> Result<Self::Id, Self::Error> {
+        self.try_generate()
+    }
+}
+
+impl<T: Clock> IdGenerator for SharedGenerator<T> {
+    type Id = SnowflakeId;
+    type Error = SnowflakeIdEGeneratorError;
+
+    fn generate(&self) -> Result<Self::Id, Self::Error> {
+        self.try_generate()
+    }
+}
+
+impl<G: IdGenerator + ?Sized> IdGenerator for &G {
+    type Id = G::Id;
+    type Error = G::Error;
+
+    fn generate(&self) -> Result<Self::Id, Self::Error> {
+        (**self).generate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::clock::Clock;
+    use crate::id_generator::IdGenerator;
+    use crate::snowflake_error::SnowflakeIdEGeneratorError;
+    use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+    use std::cell::Cell;
+
+    const NOW_MILLIS: i64 = 1_693_526_400_005;
+
+    struct FixedClock;
+
+    impl Clock for FixedClock {
+        fn now_millis(&self) -> i64 {
+            NOW_MILLIS
+        }
+    }
+
+    struct Counter(Cell<u32>);
+
+    impl IdGenerator for Counter {
+        type Id = u32;
+        type Error = ();
+
+        fn generate(&self) -> Result<Self::Id, Self::Error> {
+            let current = self.0.get();
+            self.0.set(current + 1);
+            Ok(current)
+        }
+    }
+
+    fn take<G: IdGenerator>(generator: G, count: usize) -> Result<Vec<G::Id>, G::Error> {
+        (0..count).map(|_| generator.generate()).collect()
+    }
+
+    #[test]
+    fn snowflake_generator_test() {
+        let generator = SnowFlakeIdGenerator::with_epoch_millis(
+            FixedClock,
+            1_693_526_400_000,
+            3,
+            BitLayout::DEFAULT,
+        )
+        .unwrap();
+
+        let actual = take(&generator, 3).unwrap();
+
+        for (i, id) in actual.iter().enumerate() {
+            assert_eq!(id.raw_timestamp(), 5);
+            assert_eq!(id.machine_id(), 3);
+            assert_eq!(id.sequence(), i as u16);
+        }
+    }
+
+    #[test]
+    fn snowflake_generator_error_test() {
+        let layout = BitLayout::new(42, 10, 1).unwrap();
+        let generator =
+            SnowFlakeIdGenerator::with_epoch_millis(FixedClock, 1_693_526_400_000, 3, layout)
+                .unwrap();
+
+        assert_eq!(
+            take(&generator, 3),
+            Err(SnowflakeIdEGeneratorError::SequenceExhausted)
+        );
+    }
+
+    #[test]
+    fn custom_generator_test() {
+        let generator = Counter(Cell::new(10));
+        assert_eq!(take(&generator, 3), Ok(vec![10, 11, 12]));
+        assert_eq!(generator.generate(), Ok(13));
+    }
+}
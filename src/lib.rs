@@ -1,12 +1,92 @@
-#![feature(lazy_cell)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
-use chrono::{DateTime, TimeZone, Utc};
+extern crate alloc;
+
+#[cfg(all(feature = "std", feature = "chrono"))]
+use chrono::{DateTime, Utc};
+#[cfg(all(feature = "std", feature = "chrono"))]
 use std::sync::LazyLock;
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary_support;
+#[cfg(feature = "arrow")]
+pub mod arrow_support;
+pub mod bit_layout;
+pub mod bound_id;
+pub mod clock;
+#[cfg(feature = "std")]
+pub mod compat;
+#[cfg(feature = "std")]
+pub mod config;
+pub mod cursor;
+pub mod decoder;
+#[cfg(feature = "encoding")]
+pub mod encoding;
+pub mod epoch_aware_id;
+pub mod epochs;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(all(feature = "fork", unix))]
+pub mod fork_safe_generator;
+pub mod generator_metrics;
+pub mod generator_policy;
+#[cfg(feature = "std")]
+pub mod generator_registry;
+pub mod generic_snowflake_id;
+#[cfg(feature = "global")]
+pub mod global;
+pub mod id_generator;
+#[cfg(feature = "std")]
+pub mod machine_id;
+#[cfg(feature = "std")]
+pub mod multi_domain_generator;
+pub mod obfuscate;
+#[cfg(feature = "std")]
+pub mod persistent_generator;
+#[cfg(feature = "prefetch")]
+pub mod prefetched_generator;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "rand")]
+pub mod rand_support;
+mod rate_limit;
+#[cfg(feature = "std")]
+pub mod self_test;
+#[cfg(feature = "serde")]
+pub mod serde_support;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "std")]
+pub mod sharded_snowflake_generator;
+pub mod shared_generator;
 pub mod snow_flake_id;
 pub mod snowflake_error;
+pub mod snowflake_generator_builder;
+pub mod snowflake_id_128;
 pub mod snowflake_id_generator;
+pub mod sonyflake_generator;
+pub mod sql;
+#[cfg(feature = "sqlx")]
+pub mod sqlx_support;
+mod sync;
+#[cfg(all(feature = "std", any(test, feature = "testing")))]
+pub mod testing;
+#[cfg(feature = "std")]
+pub mod thread_local_generator;
+#[cfg(feature = "time")]
+pub mod time_support;
+#[cfg(feature = "chrono")]
 pub mod timestamp;
+pub mod typed_id;
+pub mod ulid;
+pub mod uuid_short;
+#[cfg(feature = "uuid")]
+pub mod uuid_support;
+#[cfg(feature = "web")]
+pub mod web;
+
+pub const THE_EPOCH_MILLIS: i64 = 1_693_526_400_000;
 
+#[cfg(all(feature = "std", feature = "chrono"))]
 pub static THE_EPOCH: LazyLock<DateTime<Utc>> =
-	LazyLock::new(|| Utc::with_ymd_and_hms(&Utc, 2023, 09, 01, 0, 0, 0).unwrap());
+	LazyLock::new(|| DateTime::from_timestamp_millis(THE_EPOCH_MILLIS).unwrap());
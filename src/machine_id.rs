@@ -0,0 +1,294 @@
+use crate::bit_layout::BitLayout;
+use crate::machine_id::MachineIdError::{Backend, Conflict, Exhausted, NotFound, Unsupported};
+use std::fmt::{Debug, Display, Formatter};
+use std::net::{IpAddr, Ipv4Addr, UdpSocket};
+use std::path::Path;
+
+#[cfg(feature = "etcd")]
+pub mod etcd_lease;
+#[cfg(feature = "redis")]
+pub mod redis_lease;
+pub mod uniqueness_guard;
+
+#[derive(PartialEq, Eq)]
+pub enum MachineIdError {
+    NotFound,
+    Unsupported,
+    Exhausted,
+    Backend,
+    Conflict,
+}
+
+impl MachineIdError {
+    fn format(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            NotFound => "MachineIdError::NotFound",
+            Unsupported => "MachineIdError::Unsupported",
+            Exhausted => "MachineIdError::Exhausted",
+            Backend => "MachineIdError::Backend",
+            Conflict => "MachineIdError::Conflict",
+        };
+
+        write!(f, "{}", str)
+    }
+}
+
+impl Debug for MachineIdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.format(f)
+    }
+}
+
+impl Display for MachineIdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.format(f)
+    }
+}
+
+impl std::error::Error for MachineIdError {}
+
+pub trait MachineIdProvider {
+    fn machine_id(&self) -> u16;
+}
+
+pub fn mac_address_to_machine_id(mac: [u8; 6], layout: &BitLayout) -> u16 {
+    u16::from_be_bytes([mac[4], mac[5]]) & layout.max_machine_id()
+}
+
+pub fn ipv4_to_machine_id(ip: Ipv4Addr, layout: &BitLayout) -> u16 {
+    let octets = ip.octets();
+    u16::from_be_bytes([octets[2], octets[3]]) & layout.max_machine_id()
+}
+
+fn parse_mac_address(str: &str) -> Option<[u8; 6]> {
+    let mut mac = [0u8; 6];
+    let mut parts = str.trim().split(':');
+
+    for byte in mac.iter_mut() {
+        *byte = u8::from_str_radix(parts.next()?, 16).ok()?;
+    }
+
+    if parts.next().is_some() || mac == [0u8; 6] {
+        None
+    } else {
+        Some(mac)
+    }
+}
+
+fn mac_address_from_sys(dir: &Path) -> Result<[u8; 6], MachineIdError> {
+    let mut interfaces: Vec<_> = std::fs::read_dir(dir)
+        .map_err(|_| NotFound)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name() != "lo")
+        .map(|entry| entry.path())
+        .collect();
+
+    interfaces.sort();
+
+    interfaces
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path.join("address")).ok())
+        .find_map(|str| parse_mac_address(&str))
+        .ok_or(NotFound)
+}
+
+pub fn mac_address() -> Result<[u8; 6], MachineIdError> {
+    if cfg!(target_os = "linux") {
+        mac_address_from_sys(Path::new("/sys/class/net"))
+    } else {
+        Err(Unsupported)
+    }
+}
+
+pub fn private_ipv4() -> Result<Ipv4Addr, MachineIdError> {
+    // Connecting a UDP socket only selects the outgoing interface; nothing is sent.
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).map_err(|_| NotFound)?;
+    socket
+        .connect((Ipv4Addr::new(10, 255, 255, 255), 1))
+        .map_err(|_| NotFound)?;
+
+    match socket.local_addr().map_err(|_| NotFound)?.ip() {
+        IpAddr::V4(ip) if ip.is_private() => Ok(ip),
+        _ => Err(NotFound),
+    }
+}
+
+// `myapp-3` -> 3. StatefulSet pods are named `<statefulset>-<ordinal>`, so the ordinal is
+// stable across restarts and unique within the set.
+pub fn statefulset_ordinal(hostname: &str) -> Option<u16> {
+    let (_, ordinal) = hostname.trim().rsplit_once('-')?;
+
+    if ordinal.starts_with('+') {
+        None
+    } else {
+        ordinal.parse().ok()
+    }
+}
+
+fn hostname() -> Result<String, MachineIdError> {
+    match std::env::var("HOSTNAME") {
+        Ok(hostname) if !hostname.is_empty() => Ok(hostname),
+        _ => std::fs::read_to_string("/etc/hostname").map_err(|_| NotFound),
+    }
+}
+
+fn ordinal_to_machine_id(
+    hostname: &str,
+    offset: u16,
+    layout: &BitLayout,
+) -> Result<u16, MachineIdError> {
+    let ordinal = statefulset_ordinal(hostname).ok_or(NotFound)?;
+
+    match ordinal.checked_add(offset) {
+        Some(machine_id) if machine_id <= layout.max_machine_id() => Ok(machine_id),
+        _ => Err(Exhausted),
+    }
+}
+
+pub fn from_mac_address() -> Result<u16, MachineIdError> {
+    mac_address().map(|mac| mac_address_to_machine_id(mac, &BitLayout::DEFAULT))
+}
+
+pub fn from_private_ip() -> Result<u16, MachineIdError> {
+    private_ipv4().map(|ip| ipv4_to_machine_id(ip, &BitLayout::DEFAULT))
+}
+
+pub fn from_statefulset_hostname() -> Result<u16, MachineIdError> {
+    from_statefulset_hostname_with_offset(0)
+}
+
+// Several StatefulSets (e.g. one per namespace) can share a layout by giving each its own
+// range of machine IDs starting at `offset`.
+pub fn from_statefulset_hostname_with_offset(offset: u16) -> Result<u16, MachineIdError> {
+    ordinal_to_machine_id(&hostname()?, offset, &BitLayout::DEFAULT)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::machine_id::{
+        ipv4_to_machine_id, mac_address_from_sys, mac_address_to_machine_id, ordinal_to_machine_id,
+        parse_mac_address, statefulset_ordinal, MachineIdError,
+    };
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn machine_id_error_debug_test() {
+        let targets = [
+            MachineIdError::NotFound,
+            MachineIdError::Unsupported,
+            MachineIdError::Exhausted,
+            MachineIdError::Backend,
+            MachineIdError::Conflict,
+        ];
+        let expected = [
+            "MachineIdError::NotFound",
+            "MachineIdError::Unsupported",
+            "MachineIdError::Exhausted",
+            "MachineIdError::Backend",
+            "MachineIdError::Conflict",
+        ];
+
+        for elem in targets.iter().zip(expected) {
+            assert_eq!(format!("{}", elem.0), elem.1);
+            assert_eq!(format!("{:?}", elem.0), elem.1);
+        }
+    }
+
+    #[test]
+    fn mac_address_to_machine_id_test() {
+        let mac = [0x02, 0x42, 0xac, 0x11, 0xfe, 0xa9];
+
+        assert_eq!(mac_address_to_machine_id(mac, &BitLayout::DEFAULT), 0x02_a9);
+
+        let layout = BitLayout::new(39, 16, 8).unwrap();
+        assert_eq!(mac_address_to_machine_id(mac, &layout), 0xfe_a9);
+    }
+
+    #[test]
+    fn ipv4_to_machine_id_test() {
+        let ip = Ipv4Addr::new(10, 0, 7, 42);
+
+        assert_eq!(ipv4_to_machine_id(ip, &BitLayout::DEFAULT), 0x03_2a);
+
+        let layout = BitLayout::new(39, 16, 8).unwrap();
+        assert_eq!(ipv4_to_machine_id(ip, &layout), 0x07_2a);
+    }
+
+    #[test]
+    fn statefulset_ordinal_test() {
+        assert_eq!(statefulset_ordinal("myapp-3"), Some(3));
+        assert_eq!(statefulset_ordinal("my-app-12\n"), Some(12));
+        assert_eq!(statefulset_ordinal("myapp"), None);
+        assert_eq!(statefulset_ordinal("myapp-"), None);
+        assert_eq!(statefulset_ordinal("myapp-+3"), None);
+        assert_eq!(statefulset_ordinal("myapp-7d9f8b6c4-x2x7q"), None);
+        assert_eq!(statefulset_ordinal("myapp-70000"), None);
+    }
+
+    #[test]
+    fn ordinal_to_machine_id_test() {
+        assert_eq!(
+            ordinal_to_machine_id("myapp-3", 0, &BitLayout::DEFAULT),
+            Ok(3)
+        );
+        assert_eq!(
+            ordinal_to_machine_id("myapp-3", 100, &BitLayout::DEFAULT),
+            Ok(103)
+        );
+        assert_eq!(
+            ordinal_to_machine_id("myapp-1023", 0, &BitLayout::DEFAULT),
+            Ok(1023)
+        );
+        assert_eq!(
+            ordinal_to_machine_id("myapp-1000", 24, &BitLayout::DEFAULT),
+            Err(MachineIdError::Exhausted)
+        );
+        assert_eq!(
+            ordinal_to_machine_id("myapp-3", u16::MAX, &BitLayout::DEFAULT),
+            Err(MachineIdError::Exhausted)
+        );
+        assert_eq!(
+            ordinal_to_machine_id("localhost", 0, &BitLayout::DEFAULT),
+            Err(MachineIdError::NotFound)
+        );
+    }
+
+    #[test]
+    fn parse_mac_address_test() {
+        assert_eq!(
+            parse_mac_address("02:42:ac:11:00:02\n"),
+            Some([0x02, 0x42, 0xac, 0x11, 0x00, 0x02])
+        );
+        assert_eq!(parse_mac_address("00:00:00:00:00:00"), None);
+        assert_eq!(parse_mac_address("02:42:ac:11:00"), None);
+        assert_eq!(parse_mac_address("02:42:ac:11:00:02:03"), None);
+        assert_eq!(parse_mac_address("zz:42:ac:11:00:02"), None);
+    }
+
+    #[test]
+    fn mac_address_from_sys_test() {
+        let dir = std::env::temp_dir().join(format!("snowflake_sys_net_{}", std::process::id()));
+
+        for (name, address) in [
+            ("lo", "00:00:00:00:00:01"),
+            ("eth1", "02:42:ac:11:00:03"),
+            ("docker0", "00:00:00:00:00:00"),
+            ("eth0", "02:42:ac:11:00:02"),
+        ] {
+            std::fs::create_dir_all(dir.join(name)).unwrap();
+            std::fs::write(dir.join(name).join("address"), address).unwrap();
+        }
+
+        let actual = mac_address_from_sys(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(actual, Ok([0x02, 0x42, 0xac, 0x11, 0x00, 0x02]));
+    }
+
+    #[test]
+    fn missing_sys_test() {
+        let dir = std::env::temp_dir().join("snowflake_sys_net_missing");
+        assert_eq!(mac_address_from_sys(&dir), Err(MachineIdError::NotFound));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::bit_layout::BitLayout;
+use crate::machine_id::MachineIdError::{Backend, Exhausted};
+use crate::machine_id::{MachineIdError, MachineIdProvider};
+use etcd_client::{Client, Compare, CompareOp, PutOptions, Txn, TxnOp};
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::Relaxed;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+fn lease_key(prefix: &str, machine_id: u16) -> String {
+    format!("{}/{}", prefix, machine_id)
+}
+
+fn keep_alive_interval(ttl_secs: i64) -> Duration {
+    Duration::from_millis((ttl_secs.max(1) as u64) * 1000 / 3)
+}
+
+// Claims a machine id by creating `prefix/<id>` bound to an etcd lease, so the key
+// disappears with the lease if the process dies. The lease is kept alive by a tokio
+// task and revoked on `release` (or, best effort, on drop).
+pub struct EtcdMachineIdLease {
+    client: Client,
+    key: String,
+    lease_id: i64,
+    machine_id: u16,
+    lost: Arc<AtomicBool>,
+    keep_alive: Option<JoinHandle<()>>,
+}
+
+impl EtcdMachineIdLease {
+    pub async fn acquire(
+        mut client: Client,
+        prefix: &str,
+        ttl_secs: i64,
+        layout: &BitLayout,
+    ) -> Result<Self, MachineIdError> {
+        let lease_id = client
+            .lease_grant(ttl_secs, None)
+            .await
+            .map_err(|_| Backend)?
+            .id();
+
+        for machine_id in 0..=layout.max_machine_id() {
+            let key = lease_key(prefix, machine_id);
+
+            let txn = Txn::new()
+                .when([Compare::create_revision(key.as_str(), CompareOp::Equal, 0)])
+                .and_then([TxnOp::put(
+                    key.as_str(),
+                    std::process::id().to_string(),
+                    Some(PutOptions::new().with_lease(lease_id)),
+                )]);
+
+            let response = match client.txn(txn).await {
+                Ok(response) => response,
+                Err(_) => {
+                    let _ = client.lease_revoke(lease_id).await;
+                    return Err(Backend);
+                }
+            };
+
+            if response.succeeded() {
+                let (mut keeper, mut stream) = match client.lease_keep_alive(lease_id).await {
+                    Ok(pair) => pair,
+                    Err(_) => {
+                        let _ = client.lease_revoke(lease_id).await;
+                        return Err(Backend);
+                    }
+                };
+
+                let lost = Arc::new(AtomicBool::new(false));
+                let keep_alive = {
+                    let lost = lost.clone();
+
+                    tokio::spawn(async move {
+                        loop {
+                            tokio::time::sleep(keep_alive_interval(ttl_secs)).await;
+
+                            let alive = keeper.keep_alive().await.is_ok()
+                                && matches!(stream.message().await, Ok(Some(resp)) if resp.ttl() > 0);
+
+                            if !alive {
+                                lost.store(true, Relaxed);
+                                break;
+                            }
+                        }
+                    })
+                };
+
+                return Ok(EtcdMachineIdLease {
+                    client,
+                    key,
+                    lease_id,
+                    machine_id,
+                    lost,
+                    keep_alive: Some(keep_alive),
+                });
+            }
+        }
+
+        let _ = client.lease_revoke(lease_id).await;
+        Err(Exhausted)
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Relaxed)
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub async fn release(mut self) -> Result<(), MachineIdError> {
+        if let Some(keep_alive) = self.keep_alive.take() {
+            keep_alive.abort();
+        }
+
+        self.client
+            .lease_revoke(self.lease_id)
+            .await
+            .map(|_| ())
+            .map_err(|_| Backend)
+    }
+}
+
+impl MachineIdProvider for EtcdMachineIdLease {
+    fn machine_id(&self) -> u16 {
+        self.machine_id
+    }
+}
+
+impl Drop for EtcdMachineIdLease {
+    fn drop(&mut self) {
+        if let Some(keep_alive) = self.keep_alive.take() {
+            keep_alive.abort();
+
+            if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                let mut client = self.client.clone();
+                let lease_id = self.lease_id;
+
+                handle.spawn(async move {
+                    let _ = client.lease_revoke(lease_id).await;
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::machine_id::etcd_lease::{keep_alive_interval, lease_key};
+    use std::time::Duration;
+
+    #[test]
+    fn lease_key_test() {
+        assert_eq!(lease_key("/snowflake/machine", 42), "/snowflake/machine/42");
+    }
+
+    #[test]
+    fn keep_alive_interval_test() {
+        assert_eq!(keep_alive_interval(30), Duration::from_secs(10));
+        assert_eq!(keep_alive_interval(0), Duration::from_millis(333));
+    }
+}
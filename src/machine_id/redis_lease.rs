@@ -0,0 +1,215 @@
+use crate::bit_layout::BitLayout;
+use crate::machine_id::MachineIdError::{Backend, Exhausted};
+use crate::machine_id::{MachineIdError, MachineIdProvider};
+use redis::{Client, Connection, Script};
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::Relaxed;
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const RENEW_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("PEXPIRE", KEYS[1], ARGV[2])
+else
+    return 0
+end
+"#;
+
+pub(super) const RELEASE_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+else
+    return 0
+end
+"#;
+
+static TOKEN_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+pub(super) fn lease_key(prefix: &str, machine_id: u16) -> String {
+    format!("{}:{}", prefix, machine_id)
+}
+
+fn lease_token() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|dur| dur.as_nanos())
+        .unwrap_or_default();
+
+    format!(
+        "{}-{}-{}",
+        std::process::id(),
+        nanos,
+        TOKEN_COUNTER.fetch_add(1, Relaxed)
+    )
+}
+
+fn renewal_interval(ttl: Duration) -> Duration {
+    ttl / 3
+}
+
+fn try_lease(
+    connection: &mut Connection,
+    key: &str,
+    token: &str,
+    ttl: Duration,
+) -> Result<bool, MachineIdError> {
+    let reply: Option<String> = redis::cmd("SET")
+        .arg(key)
+        .arg(token)
+        .arg("NX")
+        .arg("PX")
+        .arg(ttl.as_millis() as u64)
+        .query(connection)
+        .map_err(|_| Backend)?;
+
+    Ok(reply.is_some())
+}
+
+// Holds a machine id claimed through `SET key token NX PX ttl`. A background thread
+// extends the TTL while the lease is alive, and the key is deleted on drop if this
+// process still owns it.
+pub struct RedisMachineIdLease {
+    client: Client,
+    key: String,
+    token: String,
+    machine_id: u16,
+    lost: Arc<AtomicBool>,
+    stop: Option<Sender<()>>,
+    renewal: Option<JoinHandle<()>>,
+}
+
+impl RedisMachineIdLease {
+    pub fn acquire(
+        client: Client,
+        prefix: &str,
+        ttl: Duration,
+        layout: &BitLayout,
+    ) -> Result<Self, MachineIdError> {
+        let mut connection = client.get_connection().map_err(|_| Backend)?;
+        let token = lease_token();
+
+        for machine_id in 0..=layout.max_machine_id() {
+            let key = lease_key(prefix, machine_id);
+
+            if try_lease(&mut connection, &key, &token, ttl)? {
+                let lost = Arc::new(AtomicBool::new(false));
+                let (stop, receiver) = channel::<()>();
+
+                let renewal = {
+                    let key = key.clone();
+                    let token = token.clone();
+                    let lost = lost.clone();
+                    let script = Script::new(RENEW_SCRIPT);
+
+                    std::thread::spawn(move || {
+                        while let Err(RecvTimeoutError::Timeout) =
+                            receiver.recv_timeout(renewal_interval(ttl))
+                        {
+                            let renewed: i64 = script
+                                .key(&key)
+                                .arg(&token)
+                                .arg(ttl.as_millis() as u64)
+                                .invoke(&mut connection)
+                                .unwrap_or(0);
+
+                            if renewed == 0 {
+                                lost.store(true, Relaxed);
+                            }
+                        }
+                    })
+                };
+
+                return Ok(RedisMachineIdLease {
+                    client,
+                    key,
+                    token,
+                    machine_id,
+                    lost,
+                    stop: Some(stop),
+                    renewal: Some(renewal),
+                });
+            }
+        }
+
+        Err(Exhausted)
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Relaxed)
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl MachineIdProvider for RedisMachineIdLease {
+    fn machine_id(&self) -> u16 {
+        self.machine_id
+    }
+}
+
+impl Drop for RedisMachineIdLease {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+
+        if let Some(renewal) = self.renewal.take() {
+            let _ = renewal.join();
+        }
+
+        if let Ok(mut connection) = self.client.get_connection() {
+            let _: Result<i64, _> = Script::new(RELEASE_SCRIPT)
+                .key(&self.key)
+                .arg(&self.token)
+                .invoke(&mut connection);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::machine_id::redis_lease::{
+        lease_key, lease_token, renewal_interval, RedisMachineIdLease,
+    };
+    use crate::machine_id::MachineIdError;
+    use std::time::Duration;
+
+    #[test]
+    fn lease_key_test() {
+        assert_eq!(lease_key("snowflake:machine", 42), "snowflake:machine:42");
+    }
+
+    #[test]
+    fn lease_token_test() {
+        let a = lease_token();
+        let b = lease_token();
+
+        assert_ne!(a, b);
+        assert!(a.starts_with(&format!("{}-", std::process::id())));
+    }
+
+    #[test]
+    fn renewal_interval_test() {
+        assert_eq!(
+            renewal_interval(Duration::from_secs(30)),
+            Duration::from_secs(10)
+        );
+    }
+
+    #[test]
+    fn unreachable_server_test() {
+        let client = redis::Client::open("redis://127.0.0.1:1/").unwrap();
+        let actual = RedisMachineIdLease::acquire(
+            client,
+            "snowflake",
+            Duration::from_secs(30),
+            &BitLayout::DEFAULT,
+        );
+
+        assert!(matches!(actual, Err(MachineIdError::Backend)));
+    }
+}
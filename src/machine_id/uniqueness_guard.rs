@@ -0,0 +1,410 @@
+use crate::machine_id::MachineIdError::{Backend, Conflict};
+use crate::machine_id::{MachineIdError, MachineIdProvider};
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::Relaxed;
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Where heartbeats live. `claim` records `nonce` as the live owner of `machine_id` for
+// `ttl`, unless a different nonce holds an unexpired heartbeat, in which case that nonce
+// is returned and nothing is written.
+pub trait HeartbeatStore {
+    fn claim(
+        &self,
+        machine_id: u16,
+        nonce: u64,
+        ttl: Duration,
+    ) -> Result<Option<u64>, MachineIdError>;
+
+    // Removes the heartbeat if it still belongs to `nonce`.
+    fn release(&self, machine_id: u16, nonce: u64) -> Result<(), MachineIdError>;
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|dur| dur.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+// Random per process start, so a restarted process never mistakes its predecessor's
+// heartbeat for its own.
+fn boot_nonce() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    hasher.write_u64(unix_millis());
+    hasher.finish()
+}
+
+fn heartbeat_ttl(interval: Duration) -> Duration {
+    interval * 3
+}
+
+// One `<machine_id>.heartbeat` file per machine id holding `<nonce> <unix millis>`, for
+// hosts sharing a directory (a mounted volume, or several processes on one box). Reading
+// and writing are not one atomic step, so two processes starting in the same instant can
+// both claim an id; the next beat of either one then reports the conflict.
+pub struct FileHeartbeatStore {
+    dir: PathBuf,
+}
+
+impl FileHeartbeatStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FileHeartbeatStore { dir: dir.into() }
+    }
+
+    fn path(&self, machine_id: u16) -> PathBuf {
+        self.dir.join(format!("{}.heartbeat", machine_id))
+    }
+
+    fn read(&self, machine_id: u16) -> Result<Option<(u64, u64)>, MachineIdError> {
+        let str = match fs::read_to_string(self.path(machine_id)) {
+            Ok(str) => str,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(_) => return Err(Backend),
+        };
+
+        // A torn or foreign file is treated as no heartbeat and overwritten.
+        let mut fields = str.split_whitespace().map(|x| x.parse::<u64>());
+        match (fields.next(), fields.next()) {
+            (Some(Ok(nonce)), Some(Ok(millis))) => Ok(Some((nonce, millis))),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl HeartbeatStore for FileHeartbeatStore {
+    fn claim(
+        &self,
+        machine_id: u16,
+        nonce: u64,
+        ttl: Duration,
+    ) -> Result<Option<u64>, MachineIdError> {
+        let now = unix_millis();
+
+        if let Some((owner, millis)) = self.read(machine_id)? {
+            if owner != nonce && now.saturating_sub(millis) < ttl.as_millis() as u64 {
+                return Ok(Some(owner));
+            }
+        }
+
+        let path = self.path(machine_id);
+        let tmp = path.with_extension(format!("{}.tmp", nonce));
+
+        fs::write(&tmp, format!("{} {}", nonce, now)).map_err(|_| Backend)?;
+        fs::rename(&tmp, &path).map_err(|_| Backend)?;
+        Ok(None)
+    }
+
+    fn release(&self, machine_id: u16, nonce: u64) -> Result<(), MachineIdError> {
+        match self.read(machine_id)? {
+            Some((owner, _)) if owner == nonce => {
+                fs::remove_file(self.path(machine_id)).map_err(|_| Backend)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "redis")]
+const CLAIM_SCRIPT: &str = r#"
+local owner = redis.call("GET", KEYS[1])
+if owner == false or owner == ARGV[1] then
+    redis.call("SET", KEYS[1], ARGV[1], "PX", ARGV[2])
+    return false
+else
+    return owner
+end
+"#;
+
+// Heartbeats as `<prefix>:<machine_id>` keys expiring after the TTL.
+#[cfg(feature = "redis")]
+pub struct RedisHeartbeatStore {
+    client: redis::Client,
+    prefix: String,
+}
+
+#[cfg(feature = "redis")]
+impl RedisHeartbeatStore {
+    pub fn new(client: redis::Client, prefix: impl Into<String>) -> Self {
+        RedisHeartbeatStore {
+            client,
+            prefix: prefix.into(),
+        }
+    }
+}
+
+#[cfg(feature = "redis")]
+impl HeartbeatStore for RedisHeartbeatStore {
+    fn claim(
+        &self,
+        machine_id: u16,
+        nonce: u64,
+        ttl: Duration,
+    ) -> Result<Option<u64>, MachineIdError> {
+        let mut connection = self.client.get_connection().map_err(|_| Backend)?;
+
+        let owner: Option<String> = redis::Script::new(CLAIM_SCRIPT)
+            .key(super::redis_lease::lease_key(&self.prefix, machine_id))
+            .arg(nonce.to_string())
+            .arg(ttl.as_millis() as u64)
+            .invoke(&mut connection)
+            .map_err(|_| Backend)?;
+
+        // Anything that is not one of our nonces still means the id is taken.
+        Ok(owner.map(|x| x.parse().unwrap_or_default()))
+    }
+
+    fn release(&self, machine_id: u16, nonce: u64) -> Result<(), MachineIdError> {
+        let mut connection = self.client.get_connection().map_err(|_| Backend)?;
+
+        redis::Script::new(super::redis_lease::RELEASE_SCRIPT)
+            .key(super::redis_lease::lease_key(&self.prefix, machine_id))
+            .arg(nonce.to_string())
+            .invoke::<i64>(&mut connection)
+            .map(|_| ())
+            .map_err(|_| Backend)
+    }
+}
+
+// Catches two live processes configured with the same machine id. Starting the guard
+// claims the id in the store, failing with `Conflict` if another process holds it; a
+// background thread then refreshes the heartbeat every `interval` (it expires after three
+// missed beats) and raises the conflict flag if someone else shows up. Callers decide how
+// loud to be: poll `check`, or call `assert_unique` before issuing IDs to panic instead.
+// Store errors during a beat are ignored, so a flaky backend does not take the service
+// down; the heartbeat is removed on drop.
+pub struct UniquenessGuard<S: HeartbeatStore + Send + Sync + 'static> {
+    store: Arc<S>,
+    machine_id: u16,
+    nonce: u64,
+    conflict: Arc<AtomicBool>,
+    stop: Option<Sender<()>>,
+    heartbeat: Option<JoinHandle<()>>,
+}
+
+impl<S: HeartbeatStore + Send + Sync + 'static> UniquenessGuard<S> {
+    pub fn start(store: S, machine_id: u16, interval: Duration) -> Result<Self, MachineIdError> {
+        let nonce = boot_nonce();
+        let ttl = heartbeat_ttl(interval);
+
+        if store.claim(machine_id, nonce, ttl)?.is_some() {
+            return Err(Conflict);
+        }
+
+        let store = Arc::new(store);
+        let conflict = Arc::new(AtomicBool::new(false));
+        let (stop, receiver) = channel::<()>();
+
+        let heartbeat = {
+            let store = store.clone();
+            let conflict = conflict.clone();
+
+            std::thread::spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = receiver.recv_timeout(interval) {
+                    if let Ok(Some(_)) = store.claim(machine_id, nonce, ttl) {
+                        conflict.store(true, Relaxed);
+                        break;
+                    }
+                }
+            })
+        };
+
+        Ok(UniquenessGuard {
+            store,
+            machine_id,
+            nonce,
+            conflict,
+            stop: Some(stop),
+            heartbeat: Some(heartbeat),
+        })
+    }
+
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn is_conflicted(&self) -> bool {
+        self.conflict.load(Relaxed)
+    }
+
+    pub fn check(&self) -> Result<(), MachineIdError> {
+        if self.is_conflicted() {
+            Err(Conflict)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn assert_unique(&self) {
+        if self.is_conflicted() {
+            panic!(
+                "machine id {} is claimed by another live process",
+                self.machine_id
+            );
+        }
+    }
+}
+
+impl<S: HeartbeatStore + Send + Sync + 'static> MachineIdProvider for UniquenessGuard<S> {
+    fn machine_id(&self) -> u16 {
+        self.machine_id
+    }
+}
+
+impl<S: HeartbeatStore + Send + Sync + 'static> Drop for UniquenessGuard<S> {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+
+        if let Some(heartbeat) = self.heartbeat.take() {
+            let _ = heartbeat.join();
+        }
+
+        if !self.is_conflicted() {
+            let _ = self.store.release(self.machine_id, self.nonce);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::machine_id::uniqueness_guard::{
+        boot_nonce, heartbeat_ttl, FileHeartbeatStore, HeartbeatStore, UniquenessGuard,
+    };
+    use crate::machine_id::{MachineIdError, MachineIdProvider};
+    use std::fs;
+    use std::path::PathBuf;
+    use std::thread;
+    use std::time::Duration;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "lockfree_snowflake_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn boot_nonce_test() {
+        assert_ne!(boot_nonce(), boot_nonce());
+        assert_eq!(
+            heartbeat_ttl(Duration::from_secs(10)),
+            Duration::from_secs(30)
+        );
+    }
+
+    #[test]
+    fn file_store_test() {
+        let dir = temp_dir("heartbeat_store");
+        let target = FileHeartbeatStore::new(&dir);
+        let ttl = Duration::from_secs(60);
+
+        assert_eq!(target.claim(7, 1, ttl), Ok(None));
+        assert_eq!(target.claim(7, 1, ttl), Ok(None));
+        assert_eq!(target.claim(7, 2, ttl), Ok(Some(1)));
+        assert_eq!(target.claim(8, 2, ttl), Ok(None));
+
+        // Someone else's release leaves the heartbeat alone.
+        target.release(7, 2).unwrap();
+        assert_eq!(target.claim(7, 2, ttl), Ok(Some(1)));
+
+        target.release(7, 1).unwrap();
+        assert_eq!(target.claim(7, 2, ttl), Ok(None));
+
+        // An expired heartbeat no longer counts.
+        assert_eq!(target.claim(7, 3, Duration::ZERO), Ok(None));
+
+        fs::write(dir.join("9.heartbeat"), "garbage").unwrap();
+        assert_eq!(target.claim(9, 1, ttl), Ok(None));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn guard_test() {
+        let dir = temp_dir("uniqueness_guard");
+        let interval = Duration::from_secs(60);
+
+        let target = UniquenessGuard::start(FileHeartbeatStore::new(&dir), 7, interval).unwrap();
+        assert_eq!(target.machine_id(), 7);
+        assert_eq!(target.check(), Ok(()));
+        target.assert_unique();
+
+        assert!(matches!(
+            UniquenessGuard::start(FileHeartbeatStore::new(&dir), 7, interval),
+            Err(MachineIdError::Conflict)
+        ));
+        assert!(UniquenessGuard::start(FileHeartbeatStore::new(&dir), 8, interval).is_ok());
+
+        drop(target);
+        assert!(!dir.join("7.heartbeat").exists());
+        assert!(UniquenessGuard::start(FileHeartbeatStore::new(&dir), 7, interval).is_ok());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn conflict_test() {
+        let dir = temp_dir("uniqueness_conflict");
+        let target =
+            UniquenessGuard::start(FileHeartbeatStore::new(&dir), 7, Duration::from_millis(10))
+                .unwrap();
+
+        // Another process that skipped the check and took the id over.
+        let other = target.nonce().wrapping_add(1);
+        FileHeartbeatStore::new(&dir)
+            .claim(7, other, Duration::ZERO)
+            .unwrap();
+
+        for _ in 0..500 {
+            if target.is_conflicted() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        assert_eq!(target.check(), Err(MachineIdError::Conflict));
+        assert!(
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| target.assert_unique()))
+                .is_err()
+        );
+
+        // The other owner's heartbeat survives our drop.
+        drop(target);
+        assert!(fs::read_to_string(dir.join("7.heartbeat"))
+            .unwrap()
+            .starts_with(&other.to_string()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "redis")]
+    #[test]
+    fn unreachable_redis_test() {
+        use crate::machine_id::uniqueness_guard::RedisHeartbeatStore;
+
+        let client = redis::Client::open("redis://127.0.0.1:1/").unwrap();
+        let actual = UniquenessGuard::start(
+            RedisHeartbeatStore::new(client, "snowflake:heartbeat"),
+            7,
+            Duration::from_secs(1),
+        );
+
+        assert!(matches!(actual, Err(MachineIdError::Backend)));
+    }
+}
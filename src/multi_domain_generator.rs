@@ -0,0 +1,377 @@
+use crate::bit_layout::BitLayout;
+use crate::clock::Clock;
+use crate::generator_metrics::GeneratorStats;
+use crate::generator_policy::{
+    ClockRegressionPolicy, MemoryOrderingPolicy, OverflowPolicy, TickUnit,
+};
+use crate::snow_flake_id::SnowflakeId;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_error::SnowflakeIdEGeneratorError::{
+    DomainBitsOutOfRange, MachineIdOutOfRange, UnknownDomain,
+};
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, TimeZone, Utc};
+use core::time::Duration;
+
+// Issues IDs for several entity types from one process. The top `domain_bits` of the
+// machine field carry the domain and the rest the machine id, so IDs of different domains
+// never collide and `domain_of` tells them apart by bit pattern alone. Every domain has
+// its own sequence, so a burst in one domain does not eat into another's millisecond.
+//
+// Domains are plain indices; an application enum becomes one through `From<E> for u16`.
+pub struct MultiDomainGenerator<T: Clock + Clone> {
+    domains: Vec<SnowFlakeIdGenerator<T>>,
+    machine_id: u16,
+    layout: BitLayout,
+    domain_bits: u32,
+}
+
+impl<T: Clock + Clone> MultiDomainGenerator<T> {
+    #[cfg(feature = "chrono")]
+    pub fn new<Tz: TimeZone>(
+        timestamp: T,
+        the_epoch: DateTime<Tz>,
+        machine_id: u16,
+        domain_bits: u32,
+    ) -> Result<Self, SnowflakeIdEGeneratorError> {
+        Self::with_layout(
+            timestamp,
+            the_epoch,
+            machine_id,
+            domain_bits,
+            BitLayout::DEFAULT,
+        )
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn with_layout<Tz: TimeZone>(
+        timestamp: T,
+        the_epoch: DateTime<Tz>,
+        machine_id: u16,
+        domain_bits: u32,
+        layout: BitLayout,
+    ) -> Result<Self, SnowflakeIdEGeneratorError> {
+        Self::with_epoch_millis(
+            timestamp,
+            the_epoch.timestamp_millis(),
+            machine_id,
+            domain_bits,
+            layout,
+        )
+    }
+
+    // `machine_id` has to fit in the machine bits the domain leaves over.
+    pub fn with_epoch_millis(
+        timestamp: T,
+        epoch_millis: i64,
+        machine_id: u16,
+        domain_bits: u32,
+        layout: BitLayout,
+    ) -> Result<Self, SnowflakeIdEGeneratorError> {
+        if domain_bits > layout.machine_bits() {
+            return Err(DomainBitsOutOfRange {
+                value: domain_bits as u64,
+                max: layout.machine_bits() as u64,
+            });
+        }
+
+        let machine_bits = layout.machine_bits() - domain_bits;
+        if machine_bits < u16::BITS && machine_id >> machine_bits != 0 {
+            return Err(MachineIdOutOfRange {
+                value: machine_id as u64,
+                max: (1u64 << machine_bits) - 1,
+            });
+        }
+
+        let mut domains = Vec::with_capacity(1 << domain_bits);
+
+        for domain in 0..(1u32 << domain_bits) {
+            let tagged = ((domain << machine_bits) | machine_id as u32) as u16;
+            domains.push(SnowFlakeIdGenerator::with_epoch_millis(
+                timestamp.clone(),
+                epoch_millis,
+                tagged,
+                layout,
+            )?);
+        }
+
+        Ok(MultiDomainGenerator {
+            domains,
+            machine_id,
+            layout,
+            domain_bits,
+        })
+    }
+
+    fn map(self, f: impl Fn(SnowFlakeIdGenerator<T>) -> SnowFlakeIdGenerator<T>) -> Self {
+        MultiDomainGenerator {
+            domains: self.domains.into_iter().map(f).collect(),
+            ..self
+        }
+    }
+
+    pub fn with_clock_regression_policy(self, policy: ClockRegressionPolicy) -> Self {
+        self.map(|domain| domain.with_clock_regression_policy(policy))
+    }
+
+    pub fn clock_regression_policy(&self) -> ClockRegressionPolicy {
+        self.domains[0].clock_regression_policy()
+    }
+
+    pub fn with_overflow_policy(self, policy: OverflowPolicy) -> Self {
+        self.map(|domain| domain.with_overflow_policy(policy))
+    }
+
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.domains[0].overflow_policy()
+    }
+
+    pub fn with_memory_ordering_policy(self, policy: MemoryOrderingPolicy) -> Self {
+        self.map(|domain| domain.with_memory_ordering_policy(policy))
+    }
+
+    pub fn memory_ordering_policy(&self) -> MemoryOrderingPolicy {
+        self.domains[0].memory_ordering_policy()
+    }
+
+    pub fn with_max_backward_skew(self, skew: Duration) -> Self {
+        self.map(|domain| domain.with_max_backward_skew(skew))
+    }
+
+    pub fn max_backward_skew(&self) -> Option<Duration> {
+        self.domains[0].max_backward_skew()
+    }
+
+    pub fn with_tick_unit(self, unit: TickUnit) -> Self {
+        self.map(|domain| domain.with_tick_unit(unit))
+    }
+
+    pub fn tick_unit(&self) -> TickUnit {
+        self.domains[0].tick_unit()
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn the_epoch<Tz: TimeZone>(&self, time_zone: &Tz) -> DateTime<Tz> {
+        self.domains[0].the_epoch(time_zone)
+    }
+
+    pub fn epoch_millis(&self) -> i64 {
+        self.domains[0].epoch_millis()
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn exhaustion_date(&self) -> Option<DateTime<Utc>> {
+        self.domains[0].exhaustion_date()
+    }
+
+    pub fn stats(&self) -> GeneratorStats {
+        self.domains
+            .iter()
+            .fold(GeneratorStats::default(), |acc, domain| {
+                acc.merge(&domain.stats())
+            })
+    }
+
+    pub fn machine_id(&self) -> u16 {
+        self.machine_id
+    }
+
+    pub fn layout(&self) -> &BitLayout {
+        &self.layout
+    }
+
+    pub fn domain_bits(&self) -> u32 {
+        self.domain_bits
+    }
+
+    pub fn domain_count(&self) -> usize {
+        self.domains.len()
+    }
+
+    // The generator behind one domain, e.g. for its stats or to hand a single domain to
+    // code that only knows `SnowFlakeIdGenerator`.
+    pub fn domain<D: Into<u16>>(&self, domain: D) -> Option<&SnowFlakeIdGenerator<T>> {
+        self.domains.get(domain.into() as usize)
+    }
+
+    // The domain an ID was issued for, assuming it came from a generator with this layout
+    // and domain split.
+    pub fn domain_of(&self, id: SnowflakeId) -> u16 {
+        let machine_bits = self.layout.machine_bits() - self.domain_bits;
+        (self.layout.machine_id(id) as u32 >> machine_bits) as u16
+    }
+
+    pub fn try_generate_for<D: Into<u16>>(
+        &self,
+        domain: D,
+    ) -> Result<SnowflakeId, SnowflakeIdEGeneratorError> {
+        self.domain(domain).ok_or(UnknownDomain)?.try_generate()
+    }
+
+    pub fn generate_for<D: Into<u16>>(&self, domain: D) -> Option<SnowflakeId> {
+        self.try_generate_for(domain).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::generator_policy::OverflowPolicy;
+    use crate::multi_domain_generator::MultiDomainGenerator;
+    use crate::snowflake_error::SnowflakeIdEGeneratorError;
+    use crate::testing::ManualClock;
+    use crate::THE_EPOCH_MILLIS;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[derive(Clone, Copy)]
+    enum Entity {
+        User,
+        Order,
+        Invoice,
+    }
+
+    impl From<Entity> for u16 {
+        fn from(value: Entity) -> Self {
+            value as u16
+        }
+    }
+
+    fn fixture(machine_id: u16, domain_bits: u32) -> MultiDomainGenerator<ManualClock> {
+        MultiDomainGenerator::with_epoch_millis(
+            ManualClock::new(THE_EPOCH_MILLIS + 10),
+            THE_EPOCH_MILLIS,
+            machine_id,
+            domain_bits,
+            BitLayout::DEFAULT,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn new_test() {
+        let target = fixture(5, 2);
+
+        assert_eq!(target.domain_count(), 4);
+        assert_eq!(target.domain_bits(), 2);
+        assert_eq!(target.machine_id(), 5);
+        assert_eq!(*target.layout(), BitLayout::DEFAULT);
+        assert_eq!(target.epoch_millis(), THE_EPOCH_MILLIS);
+        assert_eq!(target.domain(Entity::Order).unwrap().machine_id(), 256 | 5);
+        assert!(target.domain(4u16).is_none());
+    }
+
+    #[test]
+    fn invalid_test() {
+        let build = |machine_id, domain_bits| {
+            MultiDomainGenerator::with_epoch_millis(
+                ManualClock::new(THE_EPOCH_MILLIS),
+                THE_EPOCH_MILLIS,
+                machine_id,
+                domain_bits,
+                BitLayout::DEFAULT,
+            )
+        };
+
+        assert!(matches!(
+            build(0, 11),
+            Err(SnowflakeIdEGeneratorError::DomainBitsOutOfRange { value: 11, max: 10 })
+        ));
+        assert!(matches!(
+            build(256, 2),
+            Err(SnowflakeIdEGeneratorError::MachineIdOutOfRange {
+                value: 256,
+                max: 255
+            })
+        ));
+        assert!(build(255, 2).is_ok());
+        assert!(build(0, 10).is_ok());
+        assert!(build(1023, 0).is_ok());
+    }
+
+    #[test]
+    fn generate_for_test() {
+        let target = fixture(5, 2);
+
+        let user = target.generate_for(Entity::User).unwrap();
+        let order = target.generate_for(Entity::Order).unwrap();
+        let invoice = target.generate_for(Entity::Invoice).unwrap();
+
+        assert_eq!(target.domain_of(user), 0);
+        assert_eq!(target.domain_of(order), 1);
+        assert_eq!(target.domain_of(invoice), 2);
+
+        // Same millisecond, separate sequences.
+        assert_eq!(user.raw_timestamp(), order.raw_timestamp());
+        assert_eq!(user.sequence(), 0);
+        assert_eq!(order.sequence(), 0);
+        assert_ne!(user, order);
+
+        assert!(
+            [user, order, invoice]
+                .iter()
+                .all(|id| id.machine_id() & 0xff == 5),
+            "the low machine bits keep the machine id"
+        );
+
+        assert_eq!(
+            target.try_generate_for(4u16),
+            Err(SnowflakeIdEGeneratorError::UnknownDomain)
+        );
+        assert_eq!(target.generate_for(7u16), None);
+    }
+
+    #[test]
+    fn independent_sequences_test() {
+        let target = fixture(1, 1);
+
+        for _ in 0..4096 {
+            target.generate_for(0u16).unwrap();
+        }
+
+        assert_eq!(
+            target.try_generate_for(0u16),
+            Err(SnowflakeIdEGeneratorError::SequenceExhausted)
+        );
+        assert!(target.generate_for(1u16).is_some());
+        assert_eq!(target.stats().generated, 4097);
+    }
+
+    #[test]
+    fn policy_test() {
+        let target = fixture(1, 2).with_overflow_policy(OverflowPolicy::BorrowFromFuture);
+
+        assert_eq!(target.overflow_policy(), OverflowPolicy::BorrowFromFuture);
+        assert!((0..4u16).all(|domain| {
+            target.domain(domain).unwrap().overflow_policy() == OverflowPolicy::BorrowFromFuture
+        }));
+    }
+
+    #[test]
+    fn concurrent_test() {
+        let target = Arc::new(fixture(3, 2).with_overflow_policy(OverflowPolicy::BorrowFromFuture));
+
+        let handles: Vec<_> = (0..8u16)
+            .map(|i| {
+                let target = target.clone();
+                thread::spawn(move || {
+                    (0..2_000)
+                        .map(|_| target.generate_for(i % 4).unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut set = HashSet::new();
+        for (i, handle) in handles.into_iter().enumerate() {
+            for id in handle.join().unwrap() {
+                assert_eq!(target.domain_of(id), i as u16 % 4);
+                assert!(set.insert(id));
+            }
+        }
+
+        assert_eq!(set.len(), 16_000);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::snow_flake_id::SnowflakeId;
+use core::fmt::{Debug, Formatter};
+
+const ROUNDS: usize = 8;
+
+// The round keys of a balanced 64-bit Feistel network, expanded from a secret. The
+// permutation hides creation time, machine id and volume from casual observers of public
+// IDs; it is not a cipher to trust with anything that must stay secret.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct ObfuscationKey([u32; ROUNDS]);
+
+impl ObfuscationKey {
+    pub const fn new(secret: u64) -> Self {
+        let mut state = secret;
+        let mut keys = [0u32; ROUNDS];
+        let mut i = 0;
+
+        // splitmix64, so related secrets still yield unrelated round keys.
+        while i < ROUNDS {
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            keys[i] = (z ^ (z >> 31)) as u32;
+            i += 1;
+        }
+
+        ObfuscationKey(keys)
+    }
+}
+
+// Never print the round keys.
+impl Debug for ObfuscationKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ObfuscationKey(..)")
+    }
+}
+
+const fn round(half: u32, key: u32) -> u32 {
+    let mut x = (half ^ key).wrapping_mul(0x9e37_79b9);
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x85eb_ca6b);
+    x ^= x >> 13;
+    x
+}
+
+pub const fn obfuscate(value: u64, key: &ObfuscationKey) -> u64 {
+    let mut left = (value >> 32) as u32;
+    let mut right = value as u32;
+    let mut i = 0;
+
+    while i < ROUNDS {
+        let next = left ^ round(right, key.0[i]);
+        left = right;
+        right = next;
+        i += 1;
+    }
+
+    (left as u64) << 32 | right as u64
+}
+
+pub const fn deobfuscate(value: u64, key: &ObfuscationKey) -> u64 {
+    let mut left = (value >> 32) as u32;
+    let mut right = value as u32;
+    let mut i = ROUNDS;
+
+    while i > 0 {
+        i -= 1;
+        let prev = right ^ round(left, key.0[i]);
+        right = left;
+        left = prev;
+    }
+
+    (left as u64) << 32 | right as u64
+}
+
+impl SnowflakeId {
+    // A keyed bijection over u64: every ID maps to exactly one public value and back.
+    pub const fn obfuscate(&self, key: &ObfuscationKey) -> u64 {
+        obfuscate(self.as_u64(), key)
+    }
+
+    pub const fn deobfuscate(value: u64, key: &ObfuscationKey) -> SnowflakeId {
+        SnowflakeId::from_u64(deobfuscate(value, key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::obfuscate::{deobfuscate, obfuscate, ObfuscationKey};
+    use crate::snow_flake_id::SnowflakeId;
+
+    const SAMPLE_SCR: u64 = 175_928_847_299_678_215;
+    const KEY: ObfuscationKey = ObfuscationKey::new(0x5eed);
+
+    #[test]
+    fn round_trip_test() {
+        for value in [0, 1, SAMPLE_SCR, u64::MAX - 1, u64::MAX] {
+            assert_eq!(deobfuscate(obfuscate(value, &KEY), &KEY), value);
+        }
+
+        let id = SnowflakeId::from(SAMPLE_SCR);
+        assert_eq!(SnowflakeId::deobfuscate(id.obfuscate(&KEY), &KEY), id);
+    }
+
+    #[test]
+    fn snapshot_test() {
+        // Pinned so a change to the permutation can't silently break stored public IDs.
+        assert_eq!(obfuscate(SAMPLE_SCR, &KEY), 3_143_626_519_997_797_050);
+    }
+
+    #[test]
+    fn hides_structure_test() {
+        let first = SnowflakeId::new(5, 1, 0).unwrap();
+        let second = SnowflakeId::new(5, 1, 1).unwrap();
+
+        let diff = first.obfuscate(&KEY) ^ second.obfuscate(&KEY);
+        assert!(diff.count_ones() > 16);
+
+        let other = ObfuscationKey::new(0x5eee);
+        assert_ne!(first.obfuscate(&KEY), first.obfuscate(&other));
+        assert_ne!(
+            SnowflakeId::deobfuscate(first.obfuscate(&KEY), &other),
+            first
+        );
+    }
+
+    #[test]
+    fn debug_test() {
+        assert_eq!(format!("{:?}", KEY), "ObfuscationKey(..)");
+    }
+}
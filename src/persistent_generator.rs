@@ -0,0 +1,303 @@
+use crate::clock::Clock;
+use crate::id_generator::IdGenerator;
+use crate::snow_flake_id::SnowflakeId;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_error::SnowflakeIdEGeneratorError::PersistenceFailed;
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::{Acquire, Release};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// Keeps a watermark file ahead of every ID handed out: before an ID whose timestamp is
+// not yet covered is returned, the file is rewritten with a timestamp `flush_interval`
+// in the future. After a crash the restored generator therefore sees its clock as
+// behind, and its clock regression policy (usually `WaitUntilCaughtUp`) decides what
+// happens. A clean drop writes the exact last ID instead, so a graceful restart resumes
+// without waiting.
+pub struct PersistentGenerator<T: Clock> {
+    generator: SnowFlakeIdGenerator<T>,
+    path: PathBuf,
+    // `flush_interval` in the generator's ticks, rounded up.
+    flush_ticks: u64,
+    // First raw timestamp not covered by the watermark file.
+    covered_until: AtomicU64,
+    flush_lock: Mutex<()>,
+}
+
+impl<T: Clock> PersistentGenerator<T> {
+    pub fn open<P: AsRef<Path>>(
+        generator: SnowFlakeIdGenerator<T>,
+        path: P,
+        flush_interval: Duration,
+    ) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        let covered_until = match fs::read_to_string(&path) {
+            Ok(content) => {
+                let watermark = content
+                    .trim()
+                    .parse::<SnowflakeId>()
+                    .map_err(|e| io::Error::new(ErrorKind::InvalidData, e.to_string()))?;
+
+                generator.restore(watermark);
+                generator.layout().raw_timestamp(watermark)
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => 0,
+            Err(e) => return Err(e),
+        };
+
+        let tick_micros = generator.tick_unit().as_micros() as u128;
+        let flush_ticks = flush_interval.as_micros().div_ceil(tick_micros);
+
+        Ok(PersistentGenerator {
+            generator,
+            path,
+            flush_ticks: u64::try_from(flush_ticks).unwrap_or(u64::MAX),
+            covered_until: AtomicU64::new(covered_until),
+            flush_lock: Mutex::new(()),
+        })
+    }
+
+    pub fn generator(&self) -> &SnowFlakeIdGenerator<T> {
+        &self.generator
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn try_generate(&self) -> Result<SnowflakeId, SnowflakeIdEGeneratorError> {
+        let id = self.generator.try_generate()?;
+        let timestamp = self.generator.layout().raw_timestamp(id);
+
+        if timestamp >= self.covered_until.load(Acquire) {
+            let _guard = self.flush_lock.lock().map_err(|_| PersistenceFailed)?;
+
+            if timestamp >= self.covered_until.load(Acquire) {
+                let layout = self.generator.layout();
+                let watermark = timestamp
+                    .saturating_add(self.flush_ticks)
+                    .min(layout.max_timestamp());
+                let watermark = layout
+                    .compose(
+                        watermark,
+                        self.generator.machine_id(),
+                        layout.max_sequence(),
+                    )
+                    .map_err(|_| PersistenceFailed)?;
+
+                self.write(watermark).map_err(|_| PersistenceFailed)?;
+                self.covered_until
+                    .store(layout.raw_timestamp(watermark) + 1, Release);
+            }
+        }
+
+        Ok(id)
+    }
+
+    pub fn generate(&self) -> Option<SnowflakeId> {
+        self.try_generate().ok()
+    }
+
+    fn write(&self, watermark: SnowflakeId) -> io::Result<()> {
+        write_watermark(&self.path, watermark)
+    }
+}
+
+// Replaces the watermark file in one rename, so a crash mid-write leaves the old one.
+pub(crate) fn write_watermark(path: &Path, watermark: SnowflakeId) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+
+    let mut file = File::create(&tmp)?;
+    write!(file, "{}", watermark)?;
+    file.sync_all()?;
+
+    fs::rename(&tmp, path)
+}
+
+impl<T: Clock> IdGenerator for PersistentGenerator<T> {
+    type Id = SnowflakeId;
+    type Error = SnowflakeIdEGeneratorError;
+
+    fn generate(&self) -> Result<Self::Id, Self::Error> {
+        self.try_generate()
+    }
+}
+
+impl<T: Clock> Drop for PersistentGenerator<T> {
+    fn drop(&mut self) {
+        let _ = self.write(self.generator.snapshot());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::clock::Clock;
+    use crate::generator_policy::TickUnit;
+    use crate::persistent_generator::PersistentGenerator;
+    use crate::snow_flake_id::SnowflakeId;
+    use crate::snowflake_error::SnowflakeIdEGeneratorError;
+    use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+    use crate::THE_EPOCH_MILLIS;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::atomic::AtomicI64;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[derive(Clone)]
+    struct Fixture(Arc<AtomicI64>);
+
+    impl Clock for Fixture {
+        fn now_millis(&self) -> i64 {
+            self.0.load(Relaxed)
+        }
+    }
+
+    fn fixture(elapsed: i64) -> Fixture {
+        Fixture(Arc::new(AtomicI64::new(THE_EPOCH_MILLIS + elapsed)))
+    }
+
+    fn generator(clock: Fixture) -> SnowFlakeIdGenerator<Fixture> {
+        SnowFlakeIdGenerator::with_epoch_millis(clock, THE_EPOCH_MILLIS, 7, BitLayout::DEFAULT)
+            .unwrap()
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "lockfree_snowflake_{}_{}.watermark",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn read(path: &PathBuf) -> SnowflakeId {
+        fs::read_to_string(path).unwrap().parse().unwrap()
+    }
+
+    #[test]
+    fn watermark_test() {
+        let path = temp_path("watermark");
+        let clock = fixture(100);
+        let target =
+            PersistentGenerator::open(generator(clock.clone()), &path, Duration::from_millis(50))
+                .unwrap();
+        assert_eq!(target.path(), path.as_path());
+
+        let id = target.generate().unwrap();
+        assert_eq!(id.raw_timestamp(), 100);
+
+        let watermark = read(&path);
+        assert_eq!(watermark.raw_timestamp(), 150);
+        assert_eq!(watermark.machine_id(), 7);
+        assert_eq!(watermark.sequence(), BitLayout::DEFAULT.max_sequence());
+
+        clock.0.fetch_add(50, Relaxed);
+        target.generate().unwrap();
+        assert_eq!(read(&path), watermark);
+
+        clock.0.fetch_add(1, Relaxed);
+        target.generate().unwrap();
+        assert_eq!(read(&path).raw_timestamp(), 201);
+
+        drop(target);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn tick_unit_test() {
+        // 50ms is 5 ticks of 10ms; 1.5s rounds up to 2 ticks of a second.
+        for (unit, interval, elapsed, expected) in [
+            (TickUnit::TenMillis, 50, 100, (10, 15)),
+            (TickUnit::Seconds, 1_500, 100_000, (100, 102)),
+        ] {
+            let path = temp_path("tick_unit");
+            let target = PersistentGenerator::open(
+                generator(fixture(elapsed)).with_tick_unit(unit),
+                &path,
+                Duration::from_millis(interval),
+            )
+            .unwrap();
+
+            let id = target.generate().unwrap();
+            let layout = BitLayout::DEFAULT;
+            assert_eq!(layout.raw_timestamp(id), expected.0);
+            assert_eq!(layout.raw_timestamp(read(&path)), expected.1);
+
+            drop(target);
+            let _ = fs::remove_file(&path);
+        }
+    }
+
+    #[test]
+    fn crash_recovery_test() {
+        let path = temp_path("crash_recovery");
+        let clock = fixture(100);
+
+        let target =
+            PersistentGenerator::open(generator(clock.clone()), &path, Duration::from_millis(50))
+                .unwrap();
+        target.generate().unwrap();
+        std::mem::forget(target);
+
+        let restarted =
+            PersistentGenerator::open(generator(clock.clone()), &path, Duration::from_millis(50))
+                .unwrap();
+        assert_eq!(
+            restarted.try_generate(),
+            Err(SnowflakeIdEGeneratorError::ClockMovedBackwards)
+        );
+
+        clock.0.fetch_add(51, Relaxed);
+        let actual = restarted.generate().unwrap();
+        assert_eq!(actual.raw_timestamp(), 151);
+        assert_eq!(read(&path).raw_timestamp(), 201);
+
+        drop(restarted);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn graceful_restart_test() {
+        let path = temp_path("graceful_restart");
+        let clock = fixture(100);
+
+        let target =
+            PersistentGenerator::open(generator(clock.clone()), &path, Duration::from_millis(50))
+                .unwrap();
+        let last = target.generate().unwrap();
+        drop(target);
+        assert_eq!(read(&path), last);
+
+        let restarted =
+            PersistentGenerator::open(generator(clock.clone()), &path, Duration::from_millis(50))
+                .unwrap();
+        let actual = restarted.generate().unwrap();
+
+        assert_eq!(actual.raw_timestamp(), 100);
+        assert_eq!(actual.sequence(), last.sequence() + 1);
+        assert_eq!(read(&path).raw_timestamp(), 150);
+
+        drop(restarted);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn invalid_watermark_test() {
+        let path = temp_path("invalid_watermark");
+        fs::write(&path, "garbage").unwrap();
+
+        let actual = PersistentGenerator::open(generator(fixture(0)), &path, Duration::ZERO);
+        assert!(actual.is_err());
+
+        let _ = fs::remove_file(&path);
+    }
+}
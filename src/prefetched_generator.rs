@@ -0,0 +1,361 @@
+use crate::clock::Clock;
+use crate::persistent_generator::write_watermark;
+use crate::snow_flake_id::SnowflakeId;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+use crossbeam_queue::ArrayQueue;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::{Acquire, Release};
+use std::sync::Arc;
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const IDLE: Duration = Duration::from_millis(1);
+
+struct Shared<T: Clock> {
+    generator: SnowFlakeIdGenerator<T>,
+    queue: ArrayQueue<SnowflakeId>,
+    running: AtomicBool,
+}
+
+// Keeps a bounded lock-free queue topped up with IDs from a background thread, so the
+// request path is a single pop. When the current millisecond runs out of sequence numbers
+// the filler waits for the next one instead of spinning, and callers fall back to the
+// generator once the queue is drained.
+//
+// Prefetched IDs are unique but carry the time they were queued, not the time they were
+// handed out, and they don't interleave in order with fallback IDs.
+pub struct PrefetchedGenerator<T: Clock + Send + Sync + 'static> {
+    shared: Arc<Shared<T>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl<T: Clock + Send + Sync + 'static> PrefetchedGenerator<T> {
+    // Panics if `capacity` is zero.
+    pub fn new(generator: SnowFlakeIdGenerator<T>, capacity: usize) -> Self {
+        let shared = Arc::new(Shared {
+            generator,
+            queue: ArrayQueue::new(capacity),
+            running: AtomicBool::new(true),
+        });
+
+        let worker = {
+            let shared = shared.clone();
+            thread::spawn(move || fill(&shared))
+        };
+
+        PrefetchedGenerator {
+            shared,
+            worker: Some(worker),
+        }
+    }
+
+    pub fn generator(&self) -> &SnowFlakeIdGenerator<T> {
+        &self.shared.generator
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.shared.queue.capacity()
+    }
+
+    // IDs ready to be popped.
+    pub fn len(&self) -> usize {
+        self.shared.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shared.queue.is_empty()
+    }
+
+    // A prefetched ID, or `None` if the queue is drained.
+    pub fn pop(&self) -> Option<SnowflakeId> {
+        let id = self.shared.queue.pop();
+
+        if self.shared.queue.len() < self.shared.queue.capacity() / 2 {
+            if let Some(worker) = &self.worker {
+                worker.thread().unpark();
+            }
+        }
+
+        id
+    }
+
+    pub fn try_generate(&self) -> Result<SnowflakeId, SnowflakeIdEGeneratorError> {
+        match self.pop() {
+            Some(id) => Ok(id),
+            None => self.shared.generator.try_generate(),
+        }
+    }
+
+    pub fn generate(&self) -> Option<SnowflakeId> {
+        self.try_generate().ok()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.worker.is_some()
+    }
+
+    // Stops and joins the filler thread. The queue keeps what it holds and is still popped
+    // first; after that every call goes to the generator. Calling it again does nothing.
+    pub fn shutdown(&mut self) {
+        self.shared.running.store(false, Release);
+
+        if let Some(worker) = self.worker.take() {
+            worker.thread().unpark();
+            let _ = worker.join();
+        }
+    }
+
+    // Shuts down and hands back the generator together with the IDs still queued, oldest
+    // first.
+    pub fn drain(mut self) -> Drained<T> {
+        self.shutdown();
+
+        let mut unissued = Vec::with_capacity(self.shared.queue.len());
+        while let Some(id) = self.shared.queue.pop() {
+            unissued.push(id);
+        }
+
+        let shared = self.shared.clone();
+        drop(self);
+
+        match Arc::try_unwrap(shared) {
+            Ok(shared) => Drained {
+                generator: shared.generator,
+                unissued,
+            },
+            Err(_) => unreachable!("the filler thread has been joined"),
+        }
+    }
+}
+
+impl<T: Clock + Send + Sync + 'static> Drop for PrefetchedGenerator<T> {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+// What `drain` leaves behind. The generator's watermark lies at or past every unissued
+// ID, so persisting it before exit keeps a restart from replaying them; the file
+// `persist` writes is the one `PersistentGenerator::open` resumes from.
+pub struct Drained<T: Clock> {
+    pub generator: SnowFlakeIdGenerator<T>,
+    pub unissued: Vec<SnowflakeId>,
+}
+
+impl<T: Clock> Drained<T> {
+    pub fn watermark(&self) -> SnowflakeId {
+        self.generator.snapshot()
+    }
+
+    pub fn persist<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        write_watermark(path.as_ref(), self.watermark())
+    }
+}
+
+fn fill<T: Clock>(shared: &Shared<T>) {
+    while shared.running.load(Acquire) {
+        let free = shared.queue.capacity() - shared.queue.len();
+
+        if free == 0 {
+            thread::park_timeout(IDLE);
+            continue;
+        }
+
+        match shared.generator.reserve_block(free) {
+            Ok(block) => {
+                for id in block {
+                    if shared.queue.push(id).is_err() {
+                        break;
+                    }
+                }
+            }
+            // Out of sequence numbers (or the clock is misbehaving): back off until the
+            // next millisecond rather than burning a core.
+            Err(_) => thread::sleep(IDLE),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::clock::SystemClock;
+    use crate::persistent_generator::PersistentGenerator;
+    use crate::prefetched_generator::PrefetchedGenerator;
+    use crate::snowflake_error::SnowflakeIdEGeneratorError;
+    use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+    use crate::testing::ManualClock;
+    use crate::THE_EPOCH_MILLIS;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    fn wait_for_len<T: crate::clock::Clock + Send + Sync + 'static>(
+        target: &PrefetchedGenerator<T>,
+        len: usize,
+    ) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+
+        while target.len() < len {
+            assert!(Instant::now() < deadline, "queue never reached {}", len);
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn prefetch_test() {
+        let generator = SnowFlakeIdGenerator::with_epoch_millis(
+            SystemClock,
+            THE_EPOCH_MILLIS,
+            3,
+            BitLayout::DEFAULT,
+        )
+        .unwrap();
+        let target = PrefetchedGenerator::new(generator, 256);
+        assert_eq!(target.capacity(), 256);
+        assert_eq!(target.generator().machine_id(), 3);
+
+        wait_for_len(&target, 256);
+
+        let mut set = HashSet::new();
+        for _ in 0..1_000 {
+            let id = target.generate().unwrap();
+            assert_eq!(id.machine_id(), 3);
+            assert!(set.insert(id));
+        }
+    }
+
+    #[test]
+    fn backpressure_test() {
+        // Four IDs per millisecond and a frozen clock: the filler stalls after four.
+        let layout = BitLayout::new(42, 10, 2).unwrap();
+        let clock = ManualClock::new(THE_EPOCH_MILLIS + 5);
+        let generator =
+            SnowFlakeIdGenerator::with_epoch_millis(clock.clone(), THE_EPOCH_MILLIS, 1, layout)
+                .unwrap();
+        let target = PrefetchedGenerator::new(generator, 16);
+
+        wait_for_len(&target, 4);
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(target.len(), 4);
+
+        for sequence in 0..4 {
+            assert_eq!(layout.sequence(target.pop().unwrap()), sequence);
+        }
+        assert!(target.is_empty());
+        assert_eq!(
+            target.try_generate(),
+            Err(SnowflakeIdEGeneratorError::SequenceExhausted)
+        );
+
+        clock.advance(Duration::from_millis(1));
+        wait_for_len(&target, 4);
+        assert_eq!(layout.raw_timestamp(target.pop().unwrap()), 6);
+    }
+
+    #[test]
+    fn shutdown_test() {
+        let layout = BitLayout::new(42, 10, 2).unwrap();
+        let clock = ManualClock::new(THE_EPOCH_MILLIS + 5);
+        let generator =
+            SnowFlakeIdGenerator::with_epoch_millis(clock.clone(), THE_EPOCH_MILLIS, 1, layout)
+                .unwrap();
+        let mut target = PrefetchedGenerator::new(generator, 16);
+        assert!(target.is_running());
+
+        wait_for_len(&target, 4);
+        target.shutdown();
+        target.shutdown();
+        assert!(!target.is_running());
+
+        clock.advance(Duration::from_millis(1));
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(target.len(), 4);
+
+        for _ in 0..4 {
+            assert_eq!(layout.raw_timestamp(target.generate().unwrap()), 5);
+        }
+        assert_eq!(layout.raw_timestamp(target.generate().unwrap()), 6);
+        assert!(target.is_empty());
+    }
+
+    #[test]
+    fn drain_test() {
+        let layout = BitLayout::new(42, 10, 2).unwrap();
+        let clock = ManualClock::new(THE_EPOCH_MILLIS + 5);
+        let generator =
+            SnowFlakeIdGenerator::with_epoch_millis(clock.clone(), THE_EPOCH_MILLIS, 1, layout)
+                .unwrap();
+        let target = PrefetchedGenerator::new(generator, 16);
+
+        wait_for_len(&target, 4);
+        assert_eq!(layout.sequence(target.pop().unwrap()), 0);
+
+        let actual = target.drain();
+        let sequences: Vec<_> = actual
+            .unissued
+            .iter()
+            .map(|x| layout.sequence(*x))
+            .collect();
+        assert_eq!(sequences, vec![1, 2, 3]);
+        assert_eq!(actual.watermark(), *actual.unissued.last().unwrap());
+
+        let path = std::env::temp_dir().join(format!(
+            "lockfree_snowflake_drain_{}.watermark",
+            std::process::id()
+        ));
+        actual.persist(&path).unwrap();
+
+        clock.advance(Duration::from_millis(1));
+        let generator =
+            SnowFlakeIdGenerator::with_epoch_millis(clock, THE_EPOCH_MILLIS, 1, layout).unwrap();
+        let restarted =
+            PersistentGenerator::open(generator, &path, Duration::from_millis(50)).unwrap();
+        assert_eq!(restarted.generator().snapshot(), actual.watermark());
+        assert!(restarted.generate().unwrap() > actual.watermark());
+
+        drop(restarted);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn multi_thread_test() {
+        let generator = SnowFlakeIdGenerator::with_epoch_millis(
+            SystemClock,
+            THE_EPOCH_MILLIS,
+            1,
+            BitLayout::DEFAULT,
+        )
+        .unwrap();
+        let target = Arc::new(PrefetchedGenerator::new(generator, 1_024));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let target = target.clone();
+                thread::spawn(move || {
+                    let mut ids = Vec::with_capacity(2_000);
+                    while ids.len() < 2_000 {
+                        if let Some(id) = target.generate() {
+                            ids.push(id);
+                        }
+                    }
+                    ids
+                })
+            })
+            .collect();
+
+        let mut set = HashSet::new();
+        for handle in handles {
+            for id in handle.join().unwrap() {
+                assert!(set.insert(id));
+            }
+        }
+
+        assert_eq!(set.len(), 8_000);
+    }
+}
@@ -0,0 +1,202 @@
+// Python bindings. Build an importable module with
+// `cargo rustc --release --features python,pyo3/extension-module --crate-type cdylib`
+// and copy the library to `lockfree_snowflake.so` (`.pyd` on Windows).
+
+use crate::bit_layout::BitLayout;
+use crate::clock::SystemClock;
+use crate::generator_policy::OverflowPolicy;
+use crate::snow_flake_id::SnowflakeId;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+use crate::THE_EPOCH_MILLIS;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+
+fn runtime_error(e: SnowflakeIdEGeneratorError) -> PyErr {
+    PyRuntimeError::new_err(e.to_string())
+}
+
+#[pyclass(
+    name = "SnowflakeId",
+    module = "lockfree_snowflake",
+    frozen,
+    eq,
+    ord,
+    hash
+)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct PySnowflakeId(SnowflakeId);
+
+#[pymethods]
+impl PySnowflakeId {
+    #[new]
+    fn new(value: u64) -> Self {
+        PySnowflakeId(SnowflakeId::from(value))
+    }
+
+    #[staticmethod]
+    fn from_parts(timestamp: u64, machine_id: u16, sequence: u16) -> PyResult<Self> {
+        SnowflakeId::new(timestamp, machine_id, sequence)
+            .map(PySnowflakeId)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[getter]
+    fn raw_timestamp(&self) -> u64 {
+        self.0.raw_timestamp()
+    }
+
+    #[getter]
+    fn machine_id(&self) -> u16 {
+        self.0.machine_id()
+    }
+
+    #[getter]
+    fn sequence(&self) -> u16 {
+        self.0.sequence()
+    }
+
+    fn __int__(&self) -> u64 {
+        self.0.as_u64()
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("SnowflakeId({})", self.0)
+    }
+}
+
+// A generator on the system clock with the default layout. Generating releases the GIL,
+// so Python threads share the same lock-free path as Rust threads. The generator is boxed
+// because Python allocates the object itself and ignores the generator's cache-line
+// alignment.
+#[pyclass(name = "SnowflakeGenerator", module = "lockfree_snowflake", frozen)]
+pub struct PySnowflakeGenerator(Box<SnowFlakeIdGenerator<SystemClock>>);
+
+#[pymethods]
+impl PySnowflakeGenerator {
+    #[new]
+    #[pyo3(signature = (machine_id, epoch_millis = THE_EPOCH_MILLIS))]
+    fn new(machine_id: u16, epoch_millis: i64) -> PyResult<Self> {
+        SnowFlakeIdGenerator::with_epoch_millis(
+            SystemClock,
+            epoch_millis,
+            machine_id,
+            BitLayout::DEFAULT,
+        )
+        .map(|generator| {
+            PySnowflakeGenerator(Box::new(
+                generator.with_overflow_policy(OverflowPolicy::SpinUntilNextMillis),
+            ))
+        })
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[getter]
+    fn machine_id(&self) -> u16 {
+        self.0.machine_id()
+    }
+
+    #[getter]
+    fn epoch_millis(&self) -> i64 {
+        self.0.epoch_millis()
+    }
+
+    fn generate(&self, py: Python<'_>) -> PyResult<PySnowflakeId> {
+        py.allow_threads(|| self.0.try_generate())
+            .map(PySnowflakeId)
+            .map_err(runtime_error)
+    }
+
+    fn generate_batch(&self, py: Python<'_>, count: usize) -> PyResult<Vec<PySnowflakeId>> {
+        py.allow_threads(|| {
+            let mut ids = Vec::with_capacity(count);
+
+            while ids.len() < count {
+                ids.extend(self.0.reserve_block(count - ids.len())?.map(PySnowflakeId));
+            }
+
+            Ok(ids)
+        })
+        .map_err(runtime_error)
+    }
+}
+
+#[pymodule]
+fn lockfree_snowflake(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PySnowflakeId>()?;
+    m.add_class::<PySnowflakeGenerator>()?;
+    m.add("THE_EPOCH_MILLIS", THE_EPOCH_MILLIS)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::python::lockfree_snowflake;
+    use pyo3::ffi::c_str;
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+
+    fn run(script: &std::ffi::CStr) {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "lockfree_snowflake").unwrap();
+            lockfree_snowflake(&module).unwrap();
+
+            let globals = PyDict::new(py);
+            globals.set_item("sf", module).unwrap();
+            py.run(script, Some(&globals), None).unwrap();
+        });
+    }
+
+    #[test]
+    fn snowflake_id_test() {
+        run(c_str!(
+            r#"
+id = sf.SnowflakeId(175928847299678215)
+assert int(id) == 175928847299678215
+assert str(id) == "175928847299678215"
+assert repr(id) == "SnowflakeId(175928847299678215)"
+assert (id.raw_timestamp, id.machine_id, id.sequence) == (41944705796, 169, 7)
+
+assert sf.SnowflakeId.from_parts(41944705796, 169, 7) == id
+assert sf.SnowflakeId(1) < sf.SnowflakeId(2)
+assert len({sf.SnowflakeId(1), sf.SnowflakeId(1)}) == 1
+
+try:
+    sf.SnowflakeId.from_parts(0, 1024, 0)
+    assert False
+except ValueError:
+    pass
+"#
+        ));
+    }
+
+    #[test]
+    fn generator_test() {
+        run(c_str!(
+            r#"
+gen = sf.SnowflakeGenerator(42)
+assert gen.machine_id == 42
+assert gen.epoch_millis == sf.THE_EPOCH_MILLIS
+
+first = gen.generate()
+ids = gen.generate_batch(10000)
+assert len(ids) == 10000
+assert all(a < b for a, b in zip([first] + ids, ids))
+assert all(id.machine_id == 42 for id in ids)
+assert gen.generate_batch(0) == []
+
+try:
+    sf.SnowflakeGenerator(1024)
+    assert False
+except ValueError:
+    pass
+"#
+        ));
+    }
+}
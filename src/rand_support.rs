@@ -0,0 +1,189 @@
+use crate::bit_layout::BitLayout;
+use crate::snow_flake_id::{SnowflakeId, SnowflakeIdError};
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, TimeZone};
+use rand::distributions::{Distribution, Standard};
+use rand::Rng;
+
+// Every u64 is a valid ID, so this is uniform over the whole ID space.
+impl Distribution<SnowflakeId> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> SnowflakeId {
+        SnowflakeId::from(rng.gen::<u64>())
+    }
+}
+
+// Uniform over the IDs whose raw timestamp lies in `start..=end`, with any machine id and
+// sequence the layout allows. Load tests get IDs that decode to plausible times instead of
+// dates centuries away.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct TimeRange {
+    start: u64,
+    end: u64,
+    layout: BitLayout,
+}
+
+impl TimeRange {
+    pub fn new(start: u64, end: u64) -> Result<Self, SnowflakeIdError> {
+        Self::with_layout(start, end, BitLayout::DEFAULT)
+    }
+
+    pub fn with_layout(start: u64, end: u64, layout: BitLayout) -> Result<Self, SnowflakeIdError> {
+        if end > layout.max_timestamp() {
+            Err(SnowflakeIdError::Timestamp {
+                value: end,
+                max: layout.max_timestamp(),
+            })
+        } else if start > end {
+            Err(SnowflakeIdError::Timestamp {
+                value: start,
+                max: end,
+            })
+        } else {
+            Ok(TimeRange { start, end, layout })
+        }
+    }
+
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    pub fn layout(&self) -> &BitLayout {
+        &self.layout
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TimeRange {
+    // Both ends inclusive, to the millisecond.
+    pub fn between<Tz: TimeZone, TzEpoch: TimeZone>(
+        from: DateTime<Tz>,
+        to: DateTime<Tz>,
+        the_epoch: DateTime<TzEpoch>,
+    ) -> Result<Self, SnowflakeIdError> {
+        Self::between_with_layout(from, to, the_epoch, BitLayout::DEFAULT)
+    }
+
+    pub fn between_with_layout<Tz: TimeZone, TzEpoch: TimeZone>(
+        from: DateTime<Tz>,
+        to: DateTime<Tz>,
+        the_epoch: DateTime<TzEpoch>,
+        layout: BitLayout,
+    ) -> Result<Self, SnowflakeIdError> {
+        let start = SnowflakeId::lower_bound_for_with_layout(from, the_epoch.clone(), &layout)?;
+        let end = SnowflakeId::lower_bound_for_with_layout(to, the_epoch, &layout)?;
+
+        Self::with_layout(
+            layout.raw_timestamp(start),
+            layout.raw_timestamp(end),
+            layout,
+        )
+    }
+}
+
+impl Distribution<SnowflakeId> for TimeRange {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> SnowflakeId {
+        let timestamp = rng.gen_range(self.start..=self.end);
+        let machine_id = rng.gen_range(0..=self.layout.max_machine_id());
+        let sequence = rng.gen_range(0..=self.layout.max_sequence());
+
+        self.layout
+            .compose(timestamp, machine_id, sequence)
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::rand_support::TimeRange;
+    use crate::snow_flake_id::{SnowflakeId, SnowflakeIdError};
+    use rand::rngs::mock::StepRng;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn standard_test() {
+        let mut rng = StepRng::new(42, 1);
+
+        assert_eq!(rng.gen::<SnowflakeId>(), SnowflakeId::from(42u64));
+        assert_eq!(rng.gen::<SnowflakeId>(), SnowflakeId::from(43u64));
+    }
+
+    #[test]
+    fn time_range_test() {
+        let layout = BitLayout::new(39, 16, 8).unwrap();
+        let target = TimeRange::with_layout(1_000, 1_010, layout).unwrap();
+        assert_eq!(target.start(), 1_000);
+        assert_eq!(target.end(), 1_010);
+        assert_eq!(*target.layout(), layout);
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let ids: Vec<SnowflakeId> = (&mut rng).sample_iter(target).take(1_000).collect();
+
+        assert!(ids
+            .iter()
+            .all(|&id| (1_000..=1_010).contains(&layout.raw_timestamp(id))));
+        assert!(ids.iter().any(|&id| layout.raw_timestamp(id) == 1_000));
+        assert!(ids.iter().any(|&id| layout.raw_timestamp(id) == 1_010));
+        assert!(ids.iter().any(|&id| layout.machine_id(id) > u8::MAX as u16));
+    }
+
+    #[test]
+    fn single_millisecond_test() {
+        let target = TimeRange::new(5, 5).unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+
+        for _ in 0..100 {
+            assert_eq!(rng.sample(target).raw_timestamp(), 5);
+        }
+    }
+
+    #[test]
+    fn invalid_time_range_test() {
+        assert_eq!(
+            TimeRange::new(10, 9),
+            Err(SnowflakeIdError::Timestamp { value: 10, max: 9 })
+        );
+        assert_eq!(
+            TimeRange::new(0, 1 << 42),
+            Err(SnowflakeIdError::Timestamp {
+                value: 1 << 42,
+                max: (1 << 42) - 1
+            })
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn between_test() {
+        use chrono::Duration;
+
+        let from = *crate::THE_EPOCH + Duration::days(1);
+        let to = from + Duration::hours(1);
+
+        let target = TimeRange::between(from, to, *crate::THE_EPOCH).unwrap();
+        assert_eq!(target.start(), 86_400_000);
+        assert_eq!(target.end(), 86_400_000 + 3_600_000);
+
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let instant = rng
+                .sample(target)
+                .timestamp(*crate::THE_EPOCH, &chrono::Utc);
+            assert!(from <= instant && instant <= to);
+        }
+
+        assert_eq!(
+            TimeRange::between(
+                *crate::THE_EPOCH - Duration::seconds(1),
+                to,
+                *crate::THE_EPOCH
+            ),
+            Err(SnowflakeIdError::BeforeEpoch { millis: -1_000 })
+        );
+    }
+}
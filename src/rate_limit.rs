@@ -0,0 +1,130 @@
+use crate::sync::AtomicU64;
+use core::sync::atomic::Ordering::Relaxed;
+
+// How far ahead of schedule the limiter lets callers get. IDs only resolve milliseconds,
+// so finer smoothing would not be visible in them anyway.
+const TOLERANCE_NANOS: u64 = 1_000_000;
+
+// A lock-free leaky bucket (the generic cell rate algorithm). `next` is the instant, in
+// nanoseconds since the Unix epoch, at which the bucket has drained; every admitted ID
+// pushes it one interval further, and a caller is turned away while it lies more than the
+// tolerance ahead of the clock. Unused capacity does not accumulate, so an idle generator
+// can't be made to burst.
+pub(crate) struct RateLimiter {
+    max_per_second: u32,
+    interval_nanos: u64,
+    next: AtomicU64,
+}
+
+impl RateLimiter {
+    // `max_per_second` must not be zero.
+    pub(crate) fn new(max_per_second: u32) -> Self {
+        RateLimiter {
+            max_per_second,
+            interval_nanos: 1_000_000_000 / max_per_second as u64,
+            next: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn max_per_second(&self) -> u32 {
+        self.max_per_second
+    }
+
+    // Admits `count` IDs at `now_nanos`, or returns how many nanoseconds to wait before
+    // asking again.
+    pub(crate) fn acquire(&self, count: u64, now_nanos: u64) -> Result<(), u64> {
+        let cost = self.interval_nanos.saturating_mul(count);
+        let mut next = self.next.load(Relaxed);
+
+        loop {
+            let limit = now_nanos.saturating_add(TOLERANCE_NANOS);
+            if next > limit {
+                return Err(next - limit);
+            }
+
+            match self.next.compare_exchange_weak(
+                next,
+                next.max(now_nanos).saturating_add(cost),
+                Relaxed,
+                Relaxed,
+            ) {
+                Ok(_) => return Ok(()),
+                Err(actual) => next = actual,
+            }
+        }
+    }
+
+    // Hands back `count` IDs that were admitted but never issued.
+    pub(crate) fn release(&self, count: u64) {
+        let cost = self.interval_nanos.saturating_mul(count);
+        let _ = self
+            .next
+            .fetch_update(Relaxed, Relaxed, |next| Some(next.saturating_sub(cost)));
+    }
+}
+
+#[cfg(all(test, not(snowflake_loom)))]
+mod tests {
+    use crate::rate_limit::{RateLimiter, TOLERANCE_NANOS};
+
+    const START: u64 = 1_693_526_400_000_000_000;
+
+    #[test]
+    fn acquire_test() {
+        // One ID per 100ms.
+        let target = RateLimiter::new(10);
+        assert_eq!(target.max_per_second(), 10);
+
+        assert_eq!(target.acquire(1, START), Ok(()));
+        assert_eq!(target.acquire(1, START), Err(100_000_000 - TOLERANCE_NANOS));
+        assert_eq!(target.acquire(1, START + 99_000_000), Ok(()));
+        assert_eq!(target.acquire(1, START + 150_000_000), Err(49_000_000));
+        assert_eq!(target.acquire(1, START + 199_000_000), Ok(()));
+    }
+
+    #[test]
+    fn idle_test() {
+        let target = RateLimiter::new(10);
+        assert_eq!(target.acquire(1, START), Ok(()));
+
+        // A long pause does not bank capacity for a burst.
+        let later = START + 10_000_000_000;
+        assert_eq!(target.acquire(1, later), Ok(()));
+        assert!(target.acquire(1, later).is_err());
+    }
+
+    #[test]
+    fn weighted_test() {
+        let target = RateLimiter::new(1_000);
+
+        // A batch goes into debt and keeps the bucket closed until it is paid off.
+        assert_eq!(target.acquire(500, START), Ok(()));
+        assert_eq!(target.acquire(1, START + 1_000_000), Err(498_000_000));
+        assert_eq!(target.acquire(1, START + 499_000_000), Ok(()));
+    }
+
+    #[test]
+    fn release_test() {
+        let target = RateLimiter::new(1_000);
+
+        assert_eq!(target.acquire(500, START), Ok(()));
+        target.release(499);
+        assert_eq!(target.acquire(1, START), Ok(()));
+        assert_eq!(target.acquire(1, START), Err(1_000_000));
+
+        // Releasing more than was ever admitted leaves nothing owed rather than wrapping.
+        target.release(u64::MAX);
+        assert_eq!(target.acquire(1, START), Ok(()));
+    }
+
+    #[test]
+    fn tolerance_test() {
+        // 1ms of tolerance lets a millisecond's worth of IDs through at once.
+        let target = RateLimiter::new(1_000_000);
+        let admitted = (0..10_000)
+            .filter(|_| target.acquire(1, START).is_ok())
+            .count();
+
+        assert_eq!(admitted, 1_001);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::clock::Clock;
+use crate::snow_flake_id::SnowflakeId;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_error::SnowflakeIdEGeneratorError::SequenceExhausted;
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+use std::time::{Duration, Instant};
+
+const BURST: usize = 1_000;
+const PROBE_BUDGET: Duration = Duration::from_millis(100);
+
+// What `SnowFlakeIdGenerator::self_test` found. `clock_resolution` is the smallest step the
+// clock was seen to take, `None` if it never moved during the probe (a frozen or manual
+// clock). A clock coarser than one tick, like the 15.6ms default timer on Windows, still
+// yields unique IDs but crams every tick's worth into one step and exhausts sequences
+// early.
+#[derive(PartialEq, Eq, Debug)]
+pub struct SelfTestReport {
+    pub generated: usize,
+    pub monotonic: bool,
+    pub clock_resolution: Option<Duration>,
+    pub coarse_clock: bool,
+    pub error: Option<SnowflakeIdEGeneratorError>,
+}
+
+impl SelfTestReport {
+    pub fn is_healthy(&self) -> bool {
+        self.error.is_none()
+            && self.monotonic
+            && self.clock_resolution.is_some()
+            && !self.coarse_clock
+    }
+}
+
+impl<T: Clock> SnowFlakeIdGenerator<T> {
+    // A startup check that generates a short burst and measures the clock, so a service can
+    // refuse to start on a bad environment instead of issuing poor IDs later. It takes a
+    // few hundred milliseconds at most and uses up the IDs it generates.
+    pub fn self_test(&self) -> SelfTestReport {
+        let burst = BURST.min(self.layout().max_sequence() as usize + 1);
+        let deadline = Instant::now() + PROBE_BUDGET + self.tick_unit().duration() * 2;
+
+        let mut generated = 0;
+        let mut monotonic = true;
+        let mut error = None;
+        let mut previous: Option<SnowflakeId> = None;
+
+        while generated < burst {
+            match self.try_generate() {
+                Ok(id) => {
+                    monotonic &= previous.map_or(true, |x| x < id);
+                    previous = Some(id);
+                    generated += 1;
+                }
+                Err(SequenceExhausted) if Instant::now() < deadline => core::hint::spin_loop(),
+                Err(e) => {
+                    error = Some(e);
+                    break;
+                }
+            }
+        }
+
+        let clock_resolution = self.probe_resolution();
+
+        SelfTestReport {
+            generated,
+            monotonic,
+            clock_resolution,
+            coarse_clock: clock_resolution.is_some_and(|x| x > self.tick_unit().duration()),
+            error,
+        }
+    }
+
+    // Waits for the clock to step once to line up with an edge, then times the next step.
+    fn probe_resolution(&self) -> Option<Duration> {
+        let deadline = Instant::now() + PROBE_BUDGET;
+        let mut edges = Vec::with_capacity(2);
+        let mut last = self.clock().now_micros();
+
+        while edges.len() < 2 && Instant::now() < deadline {
+            let now = self.clock().now_micros();
+
+            if now != last {
+                edges.push(now);
+                last = now;
+            }
+        }
+
+        match edges[..] {
+            [first, second] => Some(Duration::from_micros(second.abs_diff(first))),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::clock::{Clock, SystemClock};
+    use crate::generator_policy::TickUnit;
+    use crate::self_test::SelfTestReport;
+    use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+    use crate::testing::ManualClock;
+    use crate::THE_EPOCH_MILLIS;
+    use std::time::{Duration, Instant};
+
+    // A wall clock that only moves in 16ms steps, like a default Windows timer.
+    struct CoarseClock(i64, Instant);
+
+    impl CoarseClock {
+        fn new() -> Self {
+            CoarseClock(SystemClock.now_millis(), Instant::now())
+        }
+    }
+
+    impl Clock for CoarseClock {
+        fn now_millis(&self) -> i64 {
+            let elapsed = self.1.elapsed().as_millis() as i64;
+            self.0 + elapsed / 16 * 16
+        }
+    }
+
+    fn generator<T: Clock>(clock: T) -> SnowFlakeIdGenerator<T> {
+        SnowFlakeIdGenerator::with_epoch_millis(clock, THE_EPOCH_MILLIS, 1, BitLayout::DEFAULT)
+            .unwrap()
+    }
+
+    #[test]
+    fn healthy_test() {
+        let target = generator(SystemClock);
+        let actual = target.self_test();
+
+        assert_eq!(actual.generated, 1_000);
+        assert!(actual.monotonic);
+        assert!(actual.clock_resolution.unwrap() <= Duration::from_millis(1));
+        assert!(!actual.coarse_clock);
+        assert_eq!(actual.error, None);
+        assert!(actual.is_healthy());
+        assert_eq!(target.stats().generated, 1_000);
+    }
+
+    #[test]
+    fn frozen_clock_test() {
+        let actual = generator(ManualClock::new(THE_EPOCH_MILLIS)).self_test();
+
+        assert_eq!(actual.generated, 1_000);
+        assert_eq!(actual.clock_resolution, None);
+        assert!(!actual.is_healthy());
+
+        // Two sequence bits run out within the frozen millisecond.
+        let target = SnowFlakeIdGenerator::with_epoch_millis(
+            ManualClock::new(THE_EPOCH_MILLIS + 5),
+            THE_EPOCH_MILLIS,
+            1,
+            BitLayout::new(42, 10, 2).unwrap(),
+        )
+        .unwrap();
+        target.generate().unwrap();
+
+        let actual = target.self_test();
+        assert_eq!(actual.generated, 3);
+        assert!(actual.error.is_some());
+    }
+
+    #[test]
+    fn coarse_clock_test() {
+        let actual = generator(CoarseClock::new()).self_test();
+
+        assert!(actual.clock_resolution.unwrap() >= Duration::from_millis(15));
+        assert!(actual.coarse_clock);
+        assert!(!actual.is_healthy());
+
+        // The same clock is fine for a generator ticking in seconds.
+        let actual = generator(CoarseClock::new())
+            .with_tick_unit(TickUnit::Seconds)
+            .self_test();
+        assert!(!actual.coarse_clock);
+    }
+
+    #[test]
+    fn report_test() {
+        let target = SelfTestReport {
+            generated: 1,
+            monotonic: false,
+            clock_resolution: Some(Duration::from_millis(1)),
+            coarse_clock: false,
+            error: None,
+        };
+
+        assert!(!target.is_healthy());
+    }
+}
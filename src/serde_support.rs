@@ -0,0 +1,317 @@
+use crate::snow_flake_id::SnowflakeId;
+use crate::typed_id::TypedId;
+use core::fmt::{Display, Formatter};
+use serde::de::{Error, Unexpected, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+struct SnowflakeIdVisitor;
+
+impl<'de> Visitor<'de> for SnowflakeIdVisitor {
+    type Value = SnowflakeId;
+
+    fn expecting(&self, formatter: &mut Formatter) -> core::fmt::Result {
+        formatter.write_str("a snowflake id as an unsigned integer or a decimal string")
+    }
+
+    fn visit_i64<E: Error>(self, v: i64) -> Result<Self::Value, E> {
+        if v < 0 {
+            Err(E::invalid_value(Unexpected::Signed(v), &self))
+        } else {
+            Ok(SnowflakeId::from(v as u64))
+        }
+    }
+
+    fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(SnowflakeId::from(v))
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse::<SnowflakeId>()
+            .map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
+    }
+}
+
+// Formats without type tags, like bincode, can't serve `deserialize_any`, so they are
+// asked for exactly what was serialized. Human-readable ones accept either form.
+fn deserialize_as<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    string: bool,
+) -> Result<SnowflakeId, D::Error> {
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(SnowflakeIdVisitor)
+    } else if string {
+        deserializer.deserialize_str(SnowflakeIdVisitor)
+    } else {
+        deserializer.deserialize_u64(SnowflakeIdVisitor)
+    }
+}
+
+impl Serialize for SnowflakeId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.as_u64())
+    }
+}
+
+impl<'de> Deserialize<'de> for SnowflakeId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_as(deserializer, false)
+    }
+}
+
+impl<T> Serialize for TypedId<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.id().serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for TypedId<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        SnowflakeId::deserialize(deserializer).map(TypedId::new)
+    }
+}
+
+// A `SnowflakeId` that always serializes as a decimal string, for payloads read by
+// JavaScript clients that would round a large number to the nearest double. Input is
+// accepted as either a string or a number.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub struct SnowflakeIdStr(pub SnowflakeId);
+
+impl SnowflakeIdStr {
+    pub const fn id(&self) -> SnowflakeId {
+        self.0
+    }
+}
+
+impl From<SnowflakeId> for SnowflakeIdStr {
+    fn from(value: SnowflakeId) -> Self {
+        SnowflakeIdStr(value)
+    }
+}
+
+impl From<SnowflakeIdStr> for SnowflakeId {
+    fn from(value: SnowflakeIdStr) -> Self {
+        value.0
+    }
+}
+
+impl Display for SnowflakeIdStr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Serialize for SnowflakeIdStr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        as_string::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SnowflakeIdStr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        as_string::deserialize(deserializer).map(SnowflakeIdStr)
+    }
+}
+
+pub mod as_string {
+    use super::deserialize_as;
+    use crate::snow_flake_id::SnowflakeId;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(id: &SnowflakeId, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(id)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<SnowflakeId, D::Error> {
+        deserialize_as(deserializer, true)
+    }
+}
+
+pub mod as_u64 {
+    use super::deserialize_as;
+    use crate::snow_flake_id::SnowflakeId;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(id: &SnowflakeId, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(id.as_u64())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<SnowflakeId, D::Error> {
+        deserialize_as(deserializer, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::serde_support::{as_string, as_u64, SnowflakeIdStr};
+    use crate::snow_flake_id::SnowflakeId;
+    use crate::typed_id::TypedId;
+    use serde::de::value::Error;
+    use serde::de::{Error as _, Visitor};
+    use serde::{forward_to_deserialize_any, Deserialize, Deserializer, Serialize};
+
+    const SAMPLE_SCR: u64 = 175_928_847_299_678_215;
+
+    // Stands in for bincode: no type tags, so only the requested type can be read.
+    enum Untagged {
+        U64(u64),
+        Str(&'static str),
+    }
+
+    impl<'de> Deserializer<'de> for Untagged {
+        type Error = Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+            Err(Error::custom("deserialize_any is not supported"))
+        }
+
+        fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self {
+                Untagged::U64(v) => visitor.visit_u64(v),
+                Untagged::Str(_) => Err(Error::custom("not a u64")),
+            }
+        }
+
+        fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self {
+                Untagged::Str(v) => visitor.visit_str(v),
+                Untagged::U64(_) => Err(Error::custom("not a str")),
+            }
+        }
+
+        fn is_human_readable(&self) -> bool {
+            false
+        }
+
+        forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u128 f32 f64 char string bytes byte_buf
+            option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum
+            identifier ignored_any
+        }
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Fixture {
+        #[serde(with = "crate::serde_support::as_string")]
+        string: SnowflakeId,
+        #[serde(with = "crate::serde_support::as_u64")]
+        number: SnowflakeId,
+        plain: SnowflakeId,
+    }
+
+    #[test]
+    fn serialize_test() {
+        let actual = serde_json::to_string(&SnowflakeId::from(SAMPLE_SCR)).unwrap();
+        assert_eq!(actual, "175928847299678215");
+    }
+
+    #[test]
+    fn deserialize_test() {
+        let actual: SnowflakeId = serde_json::from_str("175928847299678215").unwrap();
+        assert_eq!(actual, SnowflakeId::from(SAMPLE_SCR));
+
+        let actual: SnowflakeId = serde_json::from_str("\"175928847299678215\"").unwrap();
+        assert_eq!(actual, SnowflakeId::from(SAMPLE_SCR));
+    }
+
+    #[test]
+    fn invalid_deserialize_test() {
+        assert!(serde_json::from_str::<SnowflakeId>("-1").is_err());
+        assert!(serde_json::from_str::<SnowflakeId>("\"abc\"").is_err());
+        assert!(serde_json::from_str::<SnowflakeId>("\"18446744073709551616\"").is_err());
+        assert!(serde_json::from_str::<SnowflakeId>("1.5").is_err());
+    }
+
+    #[test]
+    fn typed_id_test() {
+        struct User;
+
+        let target = TypedId::<User>::new(SnowflakeId::from(SAMPLE_SCR));
+        let json = serde_json::to_string(&target).unwrap();
+        assert_eq!(json, "175928847299678215");
+
+        let actual: TypedId<User> = serde_json::from_str(&json).unwrap();
+        assert_eq!(actual, target);
+
+        let actual: TypedId<User> = serde_json::from_str("\"175928847299678215\"").unwrap();
+        assert_eq!(actual, target);
+        assert!(serde_json::from_str::<TypedId<User>>("-1").is_err());
+    }
+
+    #[test]
+    fn with_test() {
+        let fixture = Fixture {
+            string: SnowflakeId::from(SAMPLE_SCR),
+            number: SnowflakeId::from(SAMPLE_SCR),
+            plain: SnowflakeId::from(42u64),
+        };
+
+        let json = serde_json::to_string(&fixture).unwrap();
+        assert_eq!(
+            json,
+            r#"{"string":"175928847299678215","number":175928847299678215,"plain":42}"#
+        );
+
+        let actual: Fixture = serde_json::from_str(&json).unwrap();
+        assert_eq!(actual, fixture);
+    }
+
+    #[test]
+    fn snowflake_id_str_test() {
+        let target = SnowflakeIdStr::from(SnowflakeId::from(SAMPLE_SCR));
+
+        let json = serde_json::to_string(&target).unwrap();
+        assert_eq!(json, r#""175928847299678215""#);
+        assert_eq!(target.to_string(), "175928847299678215");
+
+        let actual: SnowflakeIdStr = serde_json::from_str(&json).unwrap();
+        assert_eq!(actual, target);
+
+        let actual: SnowflakeIdStr = serde_json::from_str("175928847299678215").unwrap();
+        assert_eq!(SnowflakeId::from(actual), SnowflakeId::from(SAMPLE_SCR));
+
+        let actual: Vec<SnowflakeIdStr> = serde_json::from_str(r#"[1, "2"]"#).unwrap();
+        assert_eq!(actual[0].id(), SnowflakeId::from(1u64));
+        assert_eq!(actual[1].id(), SnowflakeId::from(2u64));
+
+        assert!(serde_json::from_str::<SnowflakeIdStr>(r#""-1""#).is_err());
+    }
+
+    #[test]
+    fn untagged_deserialize_test() {
+        let expected = SnowflakeId::from(SAMPLE_SCR);
+
+        assert_eq!(
+            SnowflakeId::deserialize(Untagged::U64(SAMPLE_SCR)),
+            Ok(expected)
+        );
+        assert_eq!(as_u64::deserialize(Untagged::U64(SAMPLE_SCR)), Ok(expected));
+        assert_eq!(
+            as_string::deserialize(Untagged::Str("175928847299678215")),
+            Ok(expected)
+        );
+        assert_eq!(
+            SnowflakeIdStr::deserialize(Untagged::Str("175928847299678215")),
+            Ok(SnowflakeIdStr(expected))
+        );
+        assert!(as_string::deserialize(Untagged::Str("abc")).is_err());
+    }
+
+    #[test]
+    fn binary_round_trip_test() {
+        let fixture = Fixture {
+            string: SnowflakeId::from(SAMPLE_SCR),
+            number: SnowflakeId::from(SAMPLE_SCR),
+            plain: SnowflakeId::from(u64::MAX),
+        };
+
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&fixture, &mut bytes).unwrap();
+
+        let actual: Fixture = ciborium::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(actual, fixture);
+    }
+}
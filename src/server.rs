@@ -0,0 +1,151 @@
+use crate::clock::Clock;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+use proto::id_service_server::IdServiceServer;
+use proto::{GetBatchRequest, GetBatchResponse, GetIdRequest, GetIdResponse};
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+
+pub mod proto {
+    tonic::include_proto!("snowflake");
+}
+
+pub const MAX_BATCH: u32 = 65_536;
+
+fn to_status(e: SnowflakeIdEGeneratorError) -> Status {
+    Status::unavailable(e.to_string())
+}
+
+// Serves `snowflake.IdService` (see `proto/snowflake.proto`) from one shared generator, for
+// deployments that issue IDs from a central daemon. Mount it with `into_server`.
+pub struct GeneratorService<T: Clock> {
+    generator: Arc<SnowFlakeIdGenerator<T>>,
+}
+
+impl<T: Clock + Send + Sync + 'static> GeneratorService<T> {
+    pub fn new(generator: Arc<SnowFlakeIdGenerator<T>>) -> Self {
+        GeneratorService { generator }
+    }
+
+    pub fn generator(&self) -> &Arc<SnowFlakeIdGenerator<T>> {
+        &self.generator
+    }
+
+    pub fn into_server(self) -> IdServiceServer<Self> {
+        IdServiceServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl<T: Clock + Send + Sync + 'static> proto::id_service_server::IdService for GeneratorService<T> {
+    async fn get_id(&self, _: Request<GetIdRequest>) -> Result<Response<GetIdResponse>, Status> {
+        let id = self.generator.generate_async().await.map_err(to_status)?;
+        Ok(Response::new(GetIdResponse { id: id.as_u64() }))
+    }
+
+    async fn get_batch(
+        &self,
+        request: Request<GetBatchRequest>,
+    ) -> Result<Response<GetBatchResponse>, Status> {
+        let count = request.into_inner().count;
+
+        if count > MAX_BATCH {
+            return Err(Status::invalid_argument(format!(
+                "count must be at most {}",
+                MAX_BATCH
+            )));
+        }
+
+        let ids = self
+            .generator
+            .generate_many_async(count as usize)
+            .await
+            .map_err(to_status)?
+            .iter()
+            .map(|id| id.as_u64())
+            .collect();
+
+        Ok(Response::new(GetBatchResponse { ids }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::clock::{Clock, SystemClock};
+    use crate::server::proto::id_service_server::IdService;
+    use crate::server::proto::{GetBatchRequest, GetIdRequest};
+    use crate::server::{GeneratorService, MAX_BATCH};
+    use crate::snow_flake_id::SnowflakeId;
+    use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+    use crate::THE_EPOCH_MILLIS;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use tonic::{Code, Request};
+
+    struct Fixture(i64);
+
+    impl Clock for Fixture {
+        fn now_millis(&self) -> i64 {
+            self.0
+        }
+    }
+
+    fn service<T: Clock + Send + Sync + 'static>(clock: T) -> GeneratorService<T> {
+        let generator =
+            SnowFlakeIdGenerator::with_epoch_millis(clock, THE_EPOCH_MILLIS, 7, BitLayout::DEFAULT)
+                .unwrap();
+        GeneratorService::new(Arc::new(generator))
+    }
+
+    #[tokio::test]
+    async fn get_id_test() {
+        let target = service(SystemClock);
+
+        let first = target.get_id(Request::new(GetIdRequest {})).await.unwrap();
+        let second = target.get_id(Request::new(GetIdRequest {})).await.unwrap();
+
+        assert!(first.get_ref().id < second.get_ref().id);
+        assert_eq!(target.generator().generate().unwrap().machine_id(), 7);
+    }
+
+    #[tokio::test]
+    async fn get_batch_test() {
+        let target = service(SystemClock);
+
+        let actual = target
+            .get_batch(Request::new(GetBatchRequest { count: 10_000 }))
+            .await
+            .unwrap()
+            .into_inner()
+            .ids;
+
+        assert_eq!(actual.len(), 10_000);
+        assert!(actual.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(actual.iter().collect::<HashSet<_>>().len(), 10_000);
+
+        let actual = target
+            .get_batch(Request::new(GetBatchRequest { count: 0 }))
+            .await
+            .unwrap();
+        assert!(actual.get_ref().ids.is_empty());
+
+        let actual = target
+            .get_batch(Request::new(GetBatchRequest {
+                count: MAX_BATCH + 1,
+            }))
+            .await;
+        assert_eq!(actual.unwrap_err().code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn unavailable_test() {
+        let target = service(Fixture(THE_EPOCH_MILLIS + 5));
+        target
+            .generator()
+            .restore(SnowflakeId::new(6, 0, 0).unwrap());
+
+        let actual = target.get_id(Request::new(GetIdRequest {})).await;
+        assert_eq!(actual.unwrap_err().code(), Code::Unavailable);
+    }
+}
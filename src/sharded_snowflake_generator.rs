@@ -0,0 +1,395 @@
+use crate::bit_layout::BitLayout;
+use crate::clock::Clock;
+use crate::generator_metrics::GeneratorStats;
+use crate::generator_policy::{
+    ClockRegressionPolicy, MemoryOrderingPolicy, OverflowPolicy, TickUnit,
+};
+use crate::snow_flake_id::SnowflakeId;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_error::SnowflakeIdEGeneratorError::{
+    MachineIdOutOfRange, ShardBitsOutOfRange,
+};
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, TimeZone, Utc};
+use core::time::Duration;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::Relaxed;
+
+static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static SHARD_INDEX: usize = NEXT_SHARD.fetch_add(1, Relaxed);
+}
+
+// Each shard owns a disjoint slice of the sequence space: the top `shard_bits` of the
+// sequence field carry the shard index, so the IDs still decode to the configured
+// machine id under the parent layout. IDs are unique across shards, but IDs issued by
+// different shards within the same millisecond are not ordered by generation time.
+pub struct ShardedSnowflakeGenerator<T: Clock + Clone> {
+    shards: Vec<SnowFlakeIdGenerator<T>>,
+    machine_id: u16,
+    layout: BitLayout,
+    shard_bits: u32,
+}
+
+impl<T: Clock + Clone> ShardedSnowflakeGenerator<T> {
+    #[cfg(feature = "chrono")]
+    pub fn new<Tz: TimeZone>(
+        timestamp: T,
+        the_epoch: DateTime<Tz>,
+        machine_id: u16,
+        shard_bits: u32,
+    ) -> Result<Self, SnowflakeIdEGeneratorError> {
+        Self::with_layout(
+            timestamp,
+            the_epoch,
+            machine_id,
+            shard_bits,
+            BitLayout::DEFAULT,
+        )
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn with_layout<Tz: TimeZone>(
+        timestamp: T,
+        the_epoch: DateTime<Tz>,
+        machine_id: u16,
+        shard_bits: u32,
+        layout: BitLayout,
+    ) -> Result<Self, SnowflakeIdEGeneratorError> {
+        Self::with_epoch_millis(
+            timestamp,
+            the_epoch.timestamp_millis(),
+            machine_id,
+            shard_bits,
+            layout,
+        )
+    }
+
+    pub fn with_epoch_millis(
+        timestamp: T,
+        epoch_millis: i64,
+        machine_id: u16,
+        shard_bits: u32,
+        layout: BitLayout,
+    ) -> Result<Self, SnowflakeIdEGeneratorError> {
+        if machine_id > layout.max_machine_id() {
+            return Err(MachineIdOutOfRange {
+                value: machine_id as u64,
+                max: layout.max_machine_id() as u64,
+            });
+        }
+
+        // The widened machine field has to stay within 16 bits as well.
+        let max_bits = layout
+            .sequence_bits()
+            .min(u16::BITS - layout.machine_bits());
+        let out_of_range = ShardBitsOutOfRange {
+            value: shard_bits as u64,
+            max: max_bits as u64,
+        };
+
+        if shard_bits > max_bits {
+            return Err(out_of_range);
+        }
+
+        let shard_layout = BitLayout::new(
+            layout.timestamp_bits(),
+            layout.machine_bits() + shard_bits,
+            layout.sequence_bits() - shard_bits,
+        )
+        .map_err(|_| out_of_range)?;
+
+        let mut shards = Vec::with_capacity(1 << shard_bits);
+
+        for shard in 0..(1u16 << shard_bits) {
+            shards.push(SnowFlakeIdGenerator::with_epoch_millis(
+                timestamp.clone(),
+                epoch_millis,
+                (machine_id << shard_bits) | shard,
+                shard_layout,
+            )?);
+        }
+
+        Ok(ShardedSnowflakeGenerator {
+            shards,
+            machine_id,
+            layout,
+            shard_bits,
+        })
+    }
+
+    pub fn with_clock_regression_policy(self, policy: ClockRegressionPolicy) -> Self {
+        ShardedSnowflakeGenerator {
+            shards: self
+                .shards
+                .into_iter()
+                .map(|shard| shard.with_clock_regression_policy(policy))
+                .collect(),
+            ..self
+        }
+    }
+
+    pub fn with_overflow_policy(self, policy: OverflowPolicy) -> Self {
+        ShardedSnowflakeGenerator {
+            shards: self
+                .shards
+                .into_iter()
+                .map(|shard| shard.with_overflow_policy(policy))
+                .collect(),
+            ..self
+        }
+    }
+
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.shards[0].overflow_policy()
+    }
+
+    pub fn with_memory_ordering_policy(self, policy: MemoryOrderingPolicy) -> Self {
+        ShardedSnowflakeGenerator {
+            shards: self
+                .shards
+                .into_iter()
+                .map(|shard| shard.with_memory_ordering_policy(policy))
+                .collect(),
+            ..self
+        }
+    }
+
+    pub fn memory_ordering_policy(&self) -> MemoryOrderingPolicy {
+        self.shards[0].memory_ordering_policy()
+    }
+
+    pub fn with_max_backward_skew(self, skew: Duration) -> Self {
+        ShardedSnowflakeGenerator {
+            shards: self
+                .shards
+                .into_iter()
+                .map(|shard| shard.with_max_backward_skew(skew))
+                .collect(),
+            ..self
+        }
+    }
+
+    pub fn max_backward_skew(&self) -> Option<Duration> {
+        self.shards[0].max_backward_skew()
+    }
+
+    pub fn with_tick_unit(self, unit: TickUnit) -> Self {
+        ShardedSnowflakeGenerator {
+            shards: self
+                .shards
+                .into_iter()
+                .map(|shard| shard.with_tick_unit(unit))
+                .collect(),
+            ..self
+        }
+    }
+
+    pub fn tick_unit(&self) -> TickUnit {
+        self.shards[0].tick_unit()
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn the_epoch<Tz: TimeZone>(&self, time_zone: &Tz) -> DateTime<Tz> {
+        self.shards[0].the_epoch(time_zone)
+    }
+
+    pub fn epoch_millis(&self) -> i64 {
+        self.shards[0].epoch_millis()
+    }
+
+    pub fn stats(&self) -> GeneratorStats {
+        self.shards
+            .iter()
+            .fold(GeneratorStats::default(), |acc, shard| {
+                acc.merge(&shard.stats())
+            })
+    }
+
+    // Shards only borrow sequence bits, so they share the parent's timestamp field.
+    #[cfg(feature = "chrono")]
+    pub fn exhaustion_date(&self) -> Option<DateTime<Utc>> {
+        self.shards[0].exhaustion_date()
+    }
+
+    pub fn machine_id(&self) -> u16 {
+        self.machine_id
+    }
+
+    pub fn layout(&self) -> &BitLayout {
+        &self.layout
+    }
+
+    pub fn shard_bits(&self) -> u32 {
+        self.shard_bits
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    fn current_shard(&self) -> &SnowFlakeIdGenerator<T> {
+        let index = SHARD_INDEX.with(|index| *index) & (self.shards.len() - 1);
+        &self.shards[index]
+    }
+
+    pub fn try_generate(&self) -> Result<SnowflakeId, SnowflakeIdEGeneratorError> {
+        self.current_shard().try_generate()
+    }
+
+    pub fn generate(&self) -> Option<SnowflakeId> {
+        self.try_generate().ok()
+    }
+}
+
+#[cfg(all(test, feature = "chrono"))]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::generator_policy::{MemoryOrderingPolicy, OverflowPolicy, TickUnit};
+    use crate::sharded_snowflake_generator::ShardedSnowflakeGenerator;
+    use crate::snowflake_error::SnowflakeIdEGeneratorError;
+    use crate::timestamp::DefaultTimestamp;
+    use chrono::{DateTime, TimeZone, Utc};
+    use std::collections::HashSet;
+    use std::sync::{Arc, LazyLock};
+    use std::thread;
+
+    static THE_EPOCH: LazyLock<DateTime<Utc>> =
+        LazyLock::new(|| Utc::with_ymd_and_hms(&Utc, 2023, 9, 1, 0, 0, 0).unwrap());
+
+    #[test]
+    fn new_test() {
+        let target = ShardedSnowflakeGenerator::new(DefaultTimestamp, *THE_EPOCH, 42, 3).unwrap();
+
+        assert_eq!(target.shard_count(), 8);
+        assert_eq!(target.shard_bits(), 3);
+        assert_eq!(target.machine_id(), 42);
+        assert_eq!(*target.layout(), BitLayout::DEFAULT);
+        assert_eq!(target.the_epoch(&Utc), *THE_EPOCH);
+        assert_eq!(
+            target.exhaustion_date(),
+            DateTime::from_timestamp_millis(THE_EPOCH.timestamp_millis() + (1 << 42))
+        );
+
+        assert_eq!(target.overflow_policy(), OverflowPolicy::Error);
+
+        let target = target.with_overflow_policy(OverflowPolicy::BorrowFromFuture);
+        assert_eq!(target.overflow_policy(), OverflowPolicy::BorrowFromFuture);
+
+        assert_eq!(
+            target.memory_ordering_policy(),
+            MemoryOrderingPolicy::AcquireRelease
+        );
+
+        let target = target.with_memory_ordering_policy(MemoryOrderingPolicy::Relaxed);
+        assert_eq!(
+            target.memory_ordering_policy(),
+            MemoryOrderingPolicy::Relaxed
+        );
+
+        assert_eq!(target.max_backward_skew(), None);
+
+        let target = target.with_max_backward_skew(core::time::Duration::from_millis(20));
+        assert_eq!(
+            target.max_backward_skew(),
+            Some(core::time::Duration::from_millis(20))
+        );
+
+        assert_eq!(target.tick_unit(), TickUnit::Millis);
+        let target = target.with_tick_unit(TickUnit::TenMillis);
+        assert_eq!(target.tick_unit(), TickUnit::TenMillis);
+    }
+
+    #[test]
+    fn stats_test() {
+        let target = ShardedSnowflakeGenerator::new(DefaultTimestamp, *THE_EPOCH, 42, 2).unwrap();
+
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    let mut count = 0;
+                    while count < 1000 {
+                        count += target.generate().is_some() as usize;
+                    }
+                });
+            }
+        });
+
+        let actual = target.stats();
+        assert_eq!(actual.generated, 4000);
+        assert!(actual.max_timestamp > 0);
+    }
+
+    #[test]
+    fn invalid_new_test() {
+        let actual = ShardedSnowflakeGenerator::new(DefaultTimestamp, *THE_EPOCH, 1024, 3);
+        assert!(matches!(
+            actual,
+            Err(SnowflakeIdEGeneratorError::MachineIdOutOfRange {
+                value: 1024,
+                max: 1023
+            })
+        ));
+
+        let actual = ShardedSnowflakeGenerator::new(DefaultTimestamp, *THE_EPOCH, 42, 7);
+        assert!(matches!(
+            actual,
+            Err(SnowflakeIdEGeneratorError::ShardBitsOutOfRange { value: 7, max: 6 })
+        ));
+
+        let actual = ShardedSnowflakeGenerator::new(DefaultTimestamp, *THE_EPOCH, 42, 13);
+        assert!(matches!(
+            actual,
+            Err(SnowflakeIdEGeneratorError::ShardBitsOutOfRange { value: 13, max: 6 })
+        ));
+    }
+
+    #[test]
+    fn generate_keeps_machine_id_test() {
+        let target = ShardedSnowflakeGenerator::new(DefaultTimestamp, *THE_EPOCH, 42, 2).unwrap();
+
+        let actual = loop {
+            if let Some(id) = target.generate() {
+                break id;
+            }
+        };
+
+        assert_eq!(actual.machine_id(), 42);
+    }
+
+    #[test]
+    fn multi_thread_unique_test() {
+        let target =
+            Arc::new(ShardedSnowflakeGenerator::new(DefaultTimestamp, *THE_EPOCH, 42, 2).unwrap());
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let target = target.clone();
+                thread::spawn(move || {
+                    let mut ids = Vec::with_capacity(1000);
+
+                    while ids.len() < 1000 {
+                        if let Some(id) = target.generate() {
+                            ids.push(id);
+                        }
+                    }
+
+                    ids
+                })
+            })
+            .collect();
+
+        let mut set = HashSet::new();
+
+        for handle in handles {
+            for id in handle.join().unwrap() {
+                assert_eq!(id.machine_id(), 42);
+                assert!(set.insert(id));
+            }
+        }
+
+        assert_eq!(set.len(), 8000);
+    }
+}
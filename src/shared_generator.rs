@@ -0,0 +1,138 @@
+use crate::clock::Clock;
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+use alloc::sync::Arc;
+use core::ops::Deref;
+
+// A cheaply clonable handle to one generator. Clones share the same atomic state, so
+// handing one to every worker or request keeps the IDs unique and ordered exactly as if
+// they all called the same generator. The generator itself stays non-`Clone` on purpose:
+// two independent copies with the same machine id would issue duplicates.
+pub struct SharedGenerator<T: Clock>(Arc<SnowFlakeIdGenerator<T>>);
+
+impl<T: Clock> SharedGenerator<T> {
+    pub fn new(generator: SnowFlakeIdGenerator<T>) -> Self {
+        SharedGenerator(Arc::new(generator))
+    }
+
+    // Whether both handles point at the same generator.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    pub fn handle_count(&self) -> usize {
+        Arc::strong_count(&self.0)
+    }
+}
+
+impl<T: Clock> Clone for SharedGenerator<T> {
+    fn clone(&self) -> Self {
+        SharedGenerator(self.0.clone())
+    }
+}
+
+impl<T: Clock> Deref for SharedGenerator<T> {
+    type Target = SnowFlakeIdGenerator<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: Clock> From<SnowFlakeIdGenerator<T>> for SharedGenerator<T> {
+    fn from(value: SnowFlakeIdGenerator<T>) -> Self {
+        SharedGenerator::new(value)
+    }
+}
+
+impl<T: Clock> From<Arc<SnowFlakeIdGenerator<T>>> for SharedGenerator<T> {
+    fn from(value: Arc<SnowFlakeIdGenerator<T>>) -> Self {
+        SharedGenerator(value)
+    }
+}
+
+impl<T: Clock> SnowFlakeIdGenerator<T> {
+    pub fn into_shared(self) -> SharedGenerator<T> {
+        SharedGenerator::new(self)
+    }
+}
+
+// Compile-time checks that the generator and its handle can cross threads for any clock
+// that can. These only type-check, so they cost nothing at runtime.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+
+    #[allow(dead_code)]
+    const fn assert_all<T: Clock + Send + Sync>() {
+        assert_send_sync::<SnowFlakeIdGenerator<T>>();
+        assert_send_sync::<SharedGenerator<T>>();
+    }
+};
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::clock::SystemClock;
+    use crate::id_generator::IdGenerator;
+    use crate::shared_generator::SharedGenerator;
+    use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+    use crate::THE_EPOCH_MILLIS;
+    use std::collections::HashSet;
+    use std::thread;
+
+    fn generator() -> SnowFlakeIdGenerator<SystemClock> {
+        SnowFlakeIdGenerator::with_epoch_millis(
+            SystemClock,
+            THE_EPOCH_MILLIS,
+            5,
+            BitLayout::DEFAULT,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn clone_test() {
+        let target = generator().into_shared();
+        let cloned = target.clone();
+
+        assert!(target.ptr_eq(&cloned));
+        assert!(!target.ptr_eq(&SharedGenerator::from(generator())));
+        assert_eq!(target.handle_count(), 2);
+
+        // Clones share state: an ID from one moves the other's snapshot.
+        let id = cloned.generate().unwrap();
+        assert_eq!(target.snapshot(), id);
+        assert_eq!(IdGenerator::generate(&target).unwrap().machine_id(), 5);
+
+        drop(cloned);
+        assert_eq!(target.handle_count(), 1);
+    }
+
+    #[test]
+    fn multi_thread_test() {
+        let target = SharedGenerator::from(generator());
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let target = target.clone();
+                thread::spawn(move || {
+                    let mut ids = Vec::with_capacity(1_000);
+                    while ids.len() < 1_000 {
+                        if let Ok(id) = target.generate() {
+                            ids.push(id);
+                        }
+                    }
+                    ids
+                })
+            })
+            .collect();
+
+        let mut set = HashSet::new();
+        for handle in handles {
+            for id in handle.join().unwrap() {
+                assert!(set.insert(id));
+            }
+        }
+
+        assert_eq!(set.len(), 4_000);
+    }
+}
@@ -1,247 +1,2088 @@
-use crate::snow_flake_id::SnowflakeIdError::{Increment, MachineId, Timestamp};
+use crate::bit_layout::BitLayout;
+#[cfg(feature = "chrono")]
+use crate::epochs;
+#[cfg(feature = "chrono")]
+use crate::epochs::KnownEpoch;
+use crate::snow_flake_id::SnowflakeIdError::{
+    BeforeEpoch, InvalidBucket, MachineId, Sequence, SignBit, Timestamp,
+};
+#[cfg(feature = "chrono")]
 use chrono::{DateTime, Duration, TimeZone, Utc};
-use std::fmt::{Debug, Display, Formatter};
-use std::hash::{Hash, Hasher};
+use core::array::TryFromSliceError;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt::{Binary, Debug, Display, Formatter, LowerHex, Octal, UpperHex};
+use core::hash::{Hash, Hasher};
+use core::num::{IntErrorKind, ParseIntError, TryFromIntError};
+use core::str::FromStr;
 
-#[derive(PartialEq, Eq)]
-#[cfg_attr(test, derive(strum_macros::EnumIter))]
+// A field that doesn't fit the layout, or an instant the ID can't express. Values and
+// limits are in the units of the field (raw timestamp, machine id, sequence), so callers
+// can report or clamp without re-deriving the layout.
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[non_exhaustive]
 pub enum SnowflakeIdError {
-    Timestamp,
-    MachineId,
-    Increment,
+    Timestamp { value: u64, max: u64 },
+    MachineId { value: u64, max: u64 },
+    Sequence { value: u64, max: u64 },
+    // Milliseconds relative to the epoch; always negative.
+    BeforeEpoch { millis: i64 },
+    InvalidBucket { millis: i64 },
+    // The raw value, whose high bit makes it negative as an `i64`.
+    SignBit { value: u64 },
+}
+
+impl SnowflakeIdError {
+    // The old name, now the first sequence the default layout rejects. It only compares
+    // equal to that one value; match on `Sequence { .. }` instead.
+    #[deprecated(note = "use `SnowflakeIdError::Sequence` instead")]
+    #[allow(non_upper_case_globals)]
+    pub const Increment: SnowflakeIdError = Sequence {
+        value: BitLayout::DEFAULT.max_sequence() as u64 + 1,
+        max: BitLayout::DEFAULT.max_sequence() as u64,
+    };
+
+    fn format(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Timestamp { value, max } => write!(
+                f,
+                "SnowflakeIdError::Timestamp {{ value: {}, max: {} }}",
+                value, max
+            ),
+            MachineId { value, max } => write!(
+                f,
+                "SnowflakeIdError::MachineId {{ value: {}, max: {} }}",
+                value, max
+            ),
+            Sequence { value, max } => write!(
+                f,
+                "SnowflakeIdError::Sequence {{ value: {}, max: {} }}",
+                value, max
+            ),
+            BeforeEpoch { millis } => {
+                write!(f, "SnowflakeIdError::BeforeEpoch {{ millis: {} }}", millis)
+            }
+            InvalidBucket { millis } => {
+                write!(
+                    f,
+                    "SnowflakeIdError::InvalidBucket {{ millis: {} }}",
+                    millis
+                )
+            }
+            SignBit { value } => write!(f, "SnowflakeIdError::SignBit {{ value: {} }}", value),
+        }
+    }
+}
+
+impl Debug for SnowflakeIdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+impl Display for SnowflakeIdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SnowflakeIdError {}
+
+#[derive(PartialEq, Eq)]
+pub enum SnowflakeIdParseError {
+    Empty,
+    InvalidDigit,
+    Overflow,
+    NoPlausibleEpoch,
+}
+
+impl SnowflakeIdParseError {
+    fn format(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let str = match self {
+            SnowflakeIdParseError::Empty => "SnowflakeIdParseError::Empty",
+            SnowflakeIdParseError::InvalidDigit => "SnowflakeIdParseError::InvalidDigit",
+            SnowflakeIdParseError::Overflow => "SnowflakeIdParseError::Overflow",
+            SnowflakeIdParseError::NoPlausibleEpoch => "SnowflakeIdParseError::NoPlausibleEpoch",
+        };
+
+        write!(f, "{}", str)
+    }
+}
+
+impl Debug for SnowflakeIdParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+impl Display for SnowflakeIdParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SnowflakeIdParseError {}
+
+#[derive(PartialEq, Eq)]
+pub enum ValidationError {
+    BeforeEpoch,
+    InFuture,
+    ReservedBits,
+}
+
+impl ValidationError {
+    fn format(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let str = match self {
+            ValidationError::BeforeEpoch => "ValidationError::BeforeEpoch",
+            ValidationError::InFuture => "ValidationError::InFuture",
+            ValidationError::ReservedBits => "ValidationError::ReservedBits",
+        };
+
+        write!(f, "{}", str)
+    }
+}
+
+impl Debug for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationError {}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct SnowflakeId(u64);
+
+// An ID as its big-endian bytes, held rather than computed so it can be lent out as a
+// slice to key-value stores that take keys by reference. Byte-wise order is ID order, so
+// range scans over such keys run in creation order.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+#[repr(transparent)]
+pub struct SnowflakeKey([u8; 8]);
+
+// The classic Twitter split of the 10 machine bits: datacenter in the high 5, worker in the
+// low 5.
+pub const DATACENTER_BITS: u32 = 5;
+pub const WORKER_BITS: u32 = 5;
+pub const MAX_DATACENTER_ID: u16 = (1 << DATACENTER_BITS) - 1;
+pub const MAX_WORKER_ID: u16 = (1 << WORKER_BITS) - 1;
+
+// `u64::MAX` in decimal; a buffer this long fits any ID.
+pub const MAX_DECIMAL_LEN: usize = 20;
+
+pub const fn datacenter_machine_id(datacenter_id: u16, worker_id: u16) -> Option<u16> {
+    if datacenter_id > MAX_DATACENTER_ID || worker_id > MAX_WORKER_ID {
+        None
+    } else {
+        Some(datacenter_id << WORKER_BITS | worker_id)
+    }
+}
+
+#[cfg(feature = "chrono")]
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct SnowflakeIdParts {
+    pub timestamp: DateTime<Utc>,
+    pub raw_timestamp: u64,
+    pub machine_id: u16,
+    pub sequence: u16,
+}
+
+// An ID decoded under the platform epoch `parse_with_known_epochs` settled on.
+#[cfg(feature = "chrono")]
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct DetectedSnowflakeId {
+    pub id: SnowflakeId,
+    pub epoch: KnownEpoch,
+    pub parts: SnowflakeIdParts,
+}
+
+// Consecutive time buckets as inclusive `(lowest, highest)` ID pairs, for sharding a
+// scan with `WHERE id BETWEEN $1 AND $2`. Made by `SnowflakeId::iter_buckets`.
+#[cfg(feature = "chrono")]
+#[derive(Debug, Clone)]
+pub struct IdBuckets {
+    next: u64,
+    end: u64,
+    step: u64,
+    layout: BitLayout,
+}
+
+#[cfg(feature = "chrono")]
+impl Iterator for IdBuckets {
+    type Item = (SnowflakeId, SnowflakeId);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.end {
+            return None;
+        }
+
+        let start = self.next;
+        let last = start.saturating_add(self.step).min(self.end) - 1;
+        self.next = last + 1;
+
+        Some((
+            self.layout.compose(start, 0, 0).ok()?,
+            self.layout
+                .compose(
+                    last,
+                    self.layout.max_machine_id(),
+                    self.layout.max_sequence(),
+                )
+                .ok()?,
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.end.saturating_sub(self.next)).div_ceil(self.step) as usize;
+        (len, Some(len))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl ExactSizeIterator for IdBuckets {}
+
+impl From<u64> for SnowflakeId {
+    fn from(value: u64) -> Self {
+        SnowflakeId(value)
+    }
+}
+
+impl From<i64> for SnowflakeId {
+    fn from(value: i64) -> Self {
+        SnowflakeId(value as u64)
+    }
+}
+
+impl TryFrom<u128> for SnowflakeId {
+    type Error = TryFromIntError;
+
+    fn try_from(value: u128) -> Result<Self, Self::Error> {
+        u64::try_from(value).map(SnowflakeId)
+    }
+}
+
+// Decimal, or hexadecimal with a `0x` prefix.
+impl TryFrom<&str> for SnowflakeId {
+    type Error = SnowflakeIdParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if value.starts_with("0x") || value.starts_with("0X") {
+            SnowflakeId::from_hex_str(value)
+        } else {
+            value.parse()
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for SnowflakeId {
+    type Error = TryFromSliceError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        value.try_into().map(SnowflakeId::from_be_bytes)
+    }
+}
+
+impl From<SnowflakeId> for u64 {
+    fn from(value: SnowflakeId) -> Self {
+        value.as_u64()
+    }
+}
+
+impl From<SnowflakeId> for i64 {
+    fn from(value: SnowflakeId) -> Self {
+        value.as_i64()
+    }
+}
+
+impl From<SnowflakeId> for u128 {
+    fn from(value: SnowflakeId) -> Self {
+        value.as_u64() as u128
+    }
+}
+
+impl Clone for SnowflakeId {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl Copy for SnowflakeId {}
+
+impl SnowflakeId {
+    pub const fn new(
+        timestamp: u64,
+        machine_id: u16,
+        sequence: u16,
+    ) -> Result<Self, SnowflakeIdError> {
+        Self::with_layout(timestamp, machine_id, sequence, &BitLayout::DEFAULT)
+    }
+
+    // `Result::ok` is not const, so const contexts that only care about success use this.
+    pub const fn checked_new(timestamp: u64, machine_id: u16, sequence: u16) -> Option<Self> {
+        match Self::new(timestamp, machine_id, sequence) {
+            Ok(id) => Some(id),
+            Err(_) => None,
+        }
+    }
+
+    // `new` for consumers that read IDs as signed 64-bit integers: the timestamp is capped
+    // at 41 bits (`BitLayout::SIGN_SAFE`) so the sign bit can never be set. `new` itself
+    // keeps the 42-bit `DEFAULT` range: rejecting the sign bit there would refuse IDs a
+    // default generator issues once 2^41 ms have passed since its epoch (2039 for UNIX).
+    pub const fn new_positive(
+        timestamp: u64,
+        machine_id: u16,
+        sequence: u16,
+    ) -> Result<Self, SnowflakeIdError> {
+        Self::with_layout(timestamp, machine_id, sequence, &BitLayout::SIGN_SAFE)
+    }
+
+    pub const fn from_u64(value: u64) -> Self {
+        SnowflakeId(value)
+    }
+
+    pub const fn with_layout(
+        timestamp: u64,
+        machine_id: u16,
+        sequence: u16,
+        layout: &BitLayout,
+    ) -> Result<Self, SnowflakeIdError> {
+        layout.compose(timestamp, machine_id, sequence)
+    }
+
+    pub const fn machine_id(&self) -> u16 {
+        BitLayout::DEFAULT.machine_id(*self)
+    }
+
+    pub const fn sequence(&self) -> u16 {
+        BitLayout::DEFAULT.sequence(*self)
+    }
+
+    pub const fn datacenter_id(&self) -> u16 {
+        self.machine_id() >> WORKER_BITS
+    }
+
+    pub const fn worker_id(&self) -> u16 {
+        self.machine_id() & MAX_WORKER_ID
+    }
+
+    #[deprecated(note = "use `SnowflakeId::sequence` instead")]
+    pub const fn inclement(&self) -> u16 {
+        self.sequence()
+    }
+
+    pub const fn raw_timestamp(&self) -> u64 {
+        BitLayout::DEFAULT.raw_timestamp(*self)
+    }
+
+    // A partition in `0..num_partitions` for keyed streams (Kafka and the like), derived
+    // from the machine id and sequence only. Leaving the timestamp out keeps a busy
+    // millisecond from landing on one partition, and keeps the assignment of a
+    // (machine, sequence) pair stable forever. The mapping is part of the crate's output
+    // and will not change between releases. Panics if `num_partitions` is zero.
+    pub const fn partition_key(&self, num_partitions: u32) -> u32 {
+        self.partition_key_with_layout(num_partitions, &BitLayout::DEFAULT)
+    }
+
+    pub const fn partition_key_with_layout(&self, num_partitions: u32, layout: &BitLayout) -> u32 {
+        assert!(num_partitions > 0, "num_partitions must not be zero");
+
+        let key = (layout.machine_id(*self) as u64) << 16 | layout.sequence(*self) as u64;
+
+        // murmur3's 64-bit finalizer, then Lemire's multiply-shift range reduction.
+        let mut hash = key;
+        hash = (hash ^ (hash >> 33)).wrapping_mul(0xff51_afd7_ed55_8ccd);
+        hash = (hash ^ (hash >> 33)).wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+        hash ^= hash >> 33;
+
+        (((hash >> 32) * num_partitions as u64) >> 32) as u32
+    }
+
+    pub const fn as_u64(&self) -> u64 {
+        self.0
+    }
+
+    pub const fn as_i64(&self) -> i64 {
+        self.0 as i64
+    }
+
+    // `as_i64` for callers that cannot handle negative values.
+    pub const fn as_positive_i64(&self) -> Result<i64, SnowflakeIdError> {
+        if (self.0 as i64) < 0 {
+            Err(SignBit { value: self.0 })
+        } else {
+            Ok(self.0 as i64)
+        }
+    }
+
+    // Big-endian bytes sort like the IDs, which is what ordered byte-keyed stores such as
+    // RocksDB or LMDB need; little-endian is for formats that ask for it.
+    pub const fn to_be_bytes(&self) -> [u8; 8] {
+        self.0.to_be_bytes()
+    }
+
+    pub const fn to_le_bytes(&self) -> [u8; 8] {
+        self.0.to_le_bytes()
+    }
+
+    pub const fn from_be_bytes(bytes: [u8; 8]) -> Self {
+        SnowflakeId(u64::from_be_bytes(bytes))
+    }
+
+    pub const fn from_le_bytes(bytes: [u8; 8]) -> Self {
+        SnowflakeId(u64::from_le_bytes(bytes))
+    }
+
+    pub const fn to_key(&self) -> SnowflakeKey {
+        SnowflakeKey(self.to_be_bytes())
+    }
+
+    // Formats into the front of `buf` without allocating and returns the number of bytes
+    // written. Panics if `buf` is too short; `MAX_DECIMAL_LEN` bytes always suffice.
+    pub fn write_decimal(&self, buf: &mut [u8]) -> usize {
+        let mut tmp = [0u8; MAX_DECIMAL_LEN];
+        let mut pos = tmp.len();
+        let mut value = self.0;
+
+        loop {
+            pos -= 1;
+            tmp[pos] = b'0' + (value % 10) as u8;
+            value /= 10;
+
+            if value == 0 {
+                break;
+            }
+        }
+
+        let len = tmp.len() - pos;
+        buf[..len].copy_from_slice(&tmp[pos..]);
+        len
+    }
+
+    // The next raw value; a full sequence carries into the machine id and then the
+    // timestamp, so this is the smallest ID that sorts after `self`.
+    pub const fn successor(&self) -> Option<Self> {
+        match self.0.checked_add(1) {
+            Some(value) => Some(SnowflakeId(value)),
+            None => None,
+        }
+    }
+
+    pub const fn predecessor(&self) -> Option<Self> {
+        match self.0.checked_sub(1) {
+            Some(value) => Some(SnowflakeId(value)),
+            None => None,
+        }
+    }
+
+    pub const fn with_sequence(&self, sequence: u16) -> Result<Self, SnowflakeIdError> {
+        Self::new(self.raw_timestamp(), self.machine_id(), sequence)
+    }
+
+    pub const fn with_machine_id(&self, machine_id: u16) -> Result<Self, SnowflakeIdError> {
+        Self::new(self.raw_timestamp(), machine_id, self.sequence())
+    }
+
+    // The smallest ID of the same millisecond across all machines, i.e. the inclusive lower
+    // bound of a range scan over that millisecond.
+    pub const fn first_of_millisecond(&self) -> Self {
+        let shift = BitLayout::DEFAULT.machine_bits() + BitLayout::DEFAULT.sequence_bits();
+        SnowflakeId(self.0 >> shift << shift)
+    }
 }
 
-impl SnowflakeIdError {
-    fn format(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let str = match self {
-            Timestamp => "SnowflakeIdError::Timestamp",
-            MachineId => "SnowflakeIdError::MachineId",
-            Increment => "SnowflakeIdError::Increment",
+#[cfg(feature = "chrono")]
+impl SnowflakeId {
+    pub fn timestamp<TzIn: TimeZone, TzOut: TimeZone>(
+        &self,
+        the_epoch: DateTime<TzIn>,
+        time_zone: &TzOut,
+    ) -> DateTime<TzOut> {
+        let pivot = the_epoch.with_timezone(&Utc);
+
+        let dur = Duration::milliseconds(self.raw_timestamp() as i64);
+        (pivot + dur).with_timezone(time_zone)
+    }
+
+    fn timestamp_in(&self, the_epoch: DateTime<Utc>, layout: &BitLayout) -> DateTime<Utc> {
+        the_epoch + Duration::milliseconds(layout.raw_timestamp(*self) as i64)
+    }
+
+    pub fn into_parts<Tz: TimeZone>(self, the_epoch: DateTime<Tz>) -> SnowflakeIdParts {
+        self.into_parts_with_layout(the_epoch, &BitLayout::DEFAULT)
+    }
+
+    pub fn into_parts_with_layout<Tz: TimeZone>(
+        self,
+        the_epoch: DateTime<Tz>,
+        layout: &BitLayout,
+    ) -> SnowflakeIdParts {
+        SnowflakeIdParts {
+            timestamp: self.timestamp_in(the_epoch.with_timezone(&Utc), layout),
+            raw_timestamp: layout.raw_timestamp(self),
+            machine_id: layout.machine_id(self),
+            sequence: layout.sequence(self),
+        }
+    }
+
+    pub fn lower_bound_for<Tz: TimeZone, TzEpoch: TimeZone>(
+        instant: DateTime<Tz>,
+        the_epoch: DateTime<TzEpoch>,
+    ) -> Result<Self, SnowflakeIdError> {
+        Self::lower_bound_for_with_layout(instant, the_epoch, &BitLayout::DEFAULT)
+    }
+
+    pub fn upper_bound_for<Tz: TimeZone, TzEpoch: TimeZone>(
+        instant: DateTime<Tz>,
+        the_epoch: DateTime<TzEpoch>,
+    ) -> Result<Self, SnowflakeIdError> {
+        Self::upper_bound_for_with_layout(instant, the_epoch, &BitLayout::DEFAULT)
+    }
+
+    // The smallest ID any machine could issue within the millisecond of `instant`.
+    pub fn lower_bound_for_with_layout<Tz: TimeZone, TzEpoch: TimeZone>(
+        instant: DateTime<Tz>,
+        the_epoch: DateTime<TzEpoch>,
+        layout: &BitLayout,
+    ) -> Result<Self, SnowflakeIdError> {
+        let raw_timestamp = Self::raw_timestamp_for(instant, the_epoch)?;
+        layout.compose(raw_timestamp, 0, 0)
+    }
+
+    // The largest ID any machine could issue within the millisecond of `instant`.
+    pub fn upper_bound_for_with_layout<Tz: TimeZone, TzEpoch: TimeZone>(
+        instant: DateTime<Tz>,
+        the_epoch: DateTime<TzEpoch>,
+        layout: &BitLayout,
+    ) -> Result<Self, SnowflakeIdError> {
+        let raw_timestamp = Self::raw_timestamp_for(instant, the_epoch)?;
+        layout.compose(
+            raw_timestamp,
+            layout.max_machine_id(),
+            layout.max_sequence(),
+        )
+    }
+
+    // Composes an ID from a wall-clock instant instead of raw milliseconds since the epoch.
+    // An instant before the epoch is `SnowflakeIdError::BeforeEpoch`, carrying the (negative)
+    // milliseconds relative to it; one past the timestamp field is `Timestamp`.
+    pub fn from_datetime<Tz: TimeZone, TzEpoch: TimeZone>(
+        instant: DateTime<Tz>,
+        the_epoch: DateTime<TzEpoch>,
+        machine_id: u16,
+        sequence: u16,
+    ) -> Result<Self, SnowflakeIdError> {
+        Self::from_datetime_with_layout(
+            instant,
+            the_epoch,
+            machine_id,
+            sequence,
+            &BitLayout::DEFAULT,
+        )
+    }
+
+    pub fn from_datetime_with_layout<Tz: TimeZone, TzEpoch: TimeZone>(
+        instant: DateTime<Tz>,
+        the_epoch: DateTime<TzEpoch>,
+        machine_id: u16,
+        sequence: u16,
+        layout: &BitLayout,
+    ) -> Result<Self, SnowflakeIdError> {
+        let raw_timestamp = Self::raw_timestamp_for(instant, the_epoch)?;
+        layout.compose(raw_timestamp, machine_id, sequence)
+    }
+
+    // The smallest ID in the `bucket` containing this one, e.g. `Duration::hours(1)` for an
+    // hourly partition key. Buckets are aligned to the Unix epoch, so daily buckets start at
+    // UTC midnight whatever the snowflake epoch; a bucket that began before the snowflake
+    // epoch is clamped to it. A non-positive bucket is `SnowflakeIdError::InvalidBucket`.
+    pub fn truncate_to<Tz: TimeZone>(
+        &self,
+        bucket: Duration,
+        the_epoch: DateTime<Tz>,
+    ) -> Result<Self, SnowflakeIdError> {
+        self.truncate_to_with_layout(bucket, the_epoch, &BitLayout::DEFAULT)
+    }
+
+    pub fn truncate_to_with_layout<Tz: TimeZone>(
+        &self,
+        bucket: Duration,
+        the_epoch: DateTime<Tz>,
+        layout: &BitLayout,
+    ) -> Result<Self, SnowflakeIdError> {
+        let bucket_millis = bucket.num_milliseconds();
+
+        if bucket_millis <= 0 {
+            return Err(InvalidBucket {
+                millis: bucket_millis,
+            });
+        }
+
+        // In i128, since a timestamp field can be up to 64 bits wide. The bucket start lies
+        // between the epoch and this ID's timestamp, so the offset fits back in a `u64`.
+        let epoch_millis = the_epoch.timestamp_millis() as i128;
+        let millis = epoch_millis + layout.raw_timestamp(*self) as i128;
+        let start = (millis - millis.rem_euclid(bucket_millis as i128)).max(epoch_millis);
+
+        layout.compose((start - epoch_millis) as u64, 0, 0)
+    }
+
+    pub fn iter_buckets<Tz: TimeZone, TzEpoch: TimeZone>(
+        start: DateTime<Tz>,
+        end: DateTime<Tz>,
+        step: Duration,
+        the_epoch: DateTime<TzEpoch>,
+    ) -> Result<IdBuckets, SnowflakeIdError> {
+        Self::iter_buckets_with_layout(start, end, step, the_epoch, &BitLayout::DEFAULT)
+    }
+
+    // Splits `[start, end)` into buckets of `step`, counted from `start`; the last one is
+    // cut short at `end`. Every bucket covers an equal slice of time, which balances the
+    // work as far as the IDs are spread evenly over it. `end` past what the timestamp
+    // field holds is clamped to it. A non-positive `step` is `InvalidBucket`.
+    pub fn iter_buckets_with_layout<Tz: TimeZone, TzEpoch: TimeZone>(
+        start: DateTime<Tz>,
+        end: DateTime<Tz>,
+        step: Duration,
+        the_epoch: DateTime<TzEpoch>,
+        layout: &BitLayout,
+    ) -> Result<IdBuckets, SnowflakeIdError> {
+        let step_millis = step.num_milliseconds();
+
+        if step_millis <= 0 {
+            return Err(InvalidBucket {
+                millis: step_millis,
+            });
+        }
+
+        let next = Self::raw_timestamp_for(start, the_epoch.clone())?;
+        layout.compose(next, 0, 0)?;
+        let end = Self::raw_timestamp_for(end, the_epoch)?.min(layout.max_timestamp() + 1);
+
+        Ok(IdBuckets {
+            next,
+            end,
+            step: step_millis as u64,
+            layout: *layout,
+        })
+    }
+
+    fn raw_timestamp_for<Tz: TimeZone, TzEpoch: TimeZone>(
+        instant: DateTime<Tz>,
+        the_epoch: DateTime<TzEpoch>,
+    ) -> Result<u64, SnowflakeIdError> {
+        let elapsed = instant.timestamp_millis() - the_epoch.timestamp_millis();
+
+        if elapsed < 0 {
+            Err(BeforeEpoch { millis: elapsed })
+        } else {
+            Ok(elapsed as u64)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn validate<Tz: TimeZone>(
+        &self,
+        the_epoch: DateTime<Tz>,
+        layout: &BitLayout,
+    ) -> Result<(), ValidationError> {
+        self.validate_at(the_epoch, layout, Utc::now())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn parse_with_known_epochs(s: &str) -> Result<DetectedSnowflakeId, SnowflakeIdParseError> {
+        Self::parse_with_known_epochs_at(s, Utc::now())
+    }
+
+    // Parses an ID of unknown origin and picks the platform epoch under which it decodes to
+    // the latest valid instant no later than `now`, on the grounds that IDs being looked
+    // at are usually recent. The guess is only as good as the data allows: an old enough
+    // Twitter ID also passes for a Discord one, so prefer an explicit epoch whenever the
+    // source is known.
+    pub fn parse_with_known_epochs_at<Tz: TimeZone>(
+        s: &str,
+        now: DateTime<Tz>,
+    ) -> Result<DetectedSnowflakeId, SnowflakeIdParseError> {
+        let id: SnowflakeId = s.trim().parse()?;
+
+        KnownEpoch::ALL
+            .iter()
+            .filter(|x| id.validate_at(x.epoch(), &x.layout(), now.clone()).is_ok())
+            .map(|x| DetectedSnowflakeId {
+                id,
+                epoch: *x,
+                parts: id.into_parts_with_layout(x.epoch(), &x.layout()),
+            })
+            .max_by_key(|x| x.parts.timestamp)
+            .ok_or(SnowflakeIdParseError::NoPlausibleEpoch)
+    }
+
+    // Rejects values that no generator with this epoch and layout could have issued by
+    // `now`. Under a sign-safe layout a negative value (e.g. a signed BIGINT column) would
+    // decode before the epoch; layouts using all 64 bits set the high bit themselves once
+    // the timestamp gets large enough, so there it is just part of the timestamp.
+    pub fn validate_at<Tz: TimeZone, TzNow: TimeZone>(
+        &self,
+        the_epoch: DateTime<Tz>,
+        layout: &BitLayout,
+        now: DateTime<TzNow>,
+    ) -> Result<(), ValidationError> {
+        if layout.is_sign_safe() && self.as_i64() < 0 {
+            return Err(ValidationError::BeforeEpoch);
+        }
+
+        let total_bits = layout.timestamp_bits() + layout.machine_bits() + layout.sequence_bits();
+        if total_bits < u64::BITS && self.0 >> total_bits != 0 {
+            return Err(ValidationError::ReservedBits);
+        }
+
+        match i64::try_from(layout.raw_timestamp(*self))
+            .ok()
+            .and_then(|x| the_epoch.timestamp_millis().checked_add(x))
+        {
+            Some(millis) if millis <= now.timestamp_millis() => Ok(()),
+            _ => Err(ValidationError::InFuture),
+        }
+    }
+
+    // The timestamp under the crate's default epoch and layout, i.e. for IDs from a
+    // generator configured with `THE_EPOCH_MILLIS`.
+    pub fn timestamp_default(&self) -> DateTime<Utc> {
+        self.timestamp_in(epochs::DEFAULT, &BitLayout::DEFAULT)
+    }
+
+    pub fn timestamp_unix(&self) -> DateTime<Utc> {
+        self.timestamp_in(epochs::UNIX, &BitLayout::DEFAULT)
+    }
+
+    pub fn timestamp_twitter(&self) -> DateTime<Utc> {
+        self.timestamp_in(epochs::TWITTER, &BitLayout::TWITTER)
+    }
+
+    pub fn timestamp_discord(&self) -> DateTime<Utc> {
+        self.timestamp_in(epochs::DISCORD, &BitLayout::TWITTER)
+    }
+
+    pub fn timestamp_instagram(&self) -> DateTime<Utc> {
+        self.timestamp_in(epochs::INSTAGRAM, &BitLayout::INSTAGRAM)
+    }
+}
+
+// A multi-line breakdown for logs and support tickets. The raw bits are split into the
+// layout's fields with `|`, with any unused high bits first.
+#[cfg(all(feature = "std", feature = "chrono"))]
+impl SnowflakeId {
+    pub fn explain<Tz: TimeZone>(&self, the_epoch: DateTime<Tz>) -> String {
+        self.explain_with_layout(the_epoch, &BitLayout::DEFAULT)
+    }
+
+    pub fn explain_with_layout<Tz: TimeZone>(
+        &self,
+        the_epoch: DateTime<Tz>,
+        layout: &BitLayout,
+    ) -> String {
+        use chrono::{Local, SecondsFormat};
+        use std::fmt::Write;
+
+        let raw_timestamp = layout.raw_timestamp(*self);
+        let timestamp = i64::try_from(raw_timestamp).ok().and_then(|x| {
+            the_epoch
+                .with_timezone(&Utc)
+                .checked_add_signed(Duration::try_milliseconds(x)?)
+        });
+
+        let widths = [
+            (
+                "unused",
+                u64::BITS
+                    - layout.timestamp_bits()
+                    - layout.machine_bits()
+                    - layout.sequence_bits(),
+            ),
+            ("timestamp", layout.timestamp_bits()),
+            ("machine", layout.machine_bits()),
+            ("sequence", layout.sequence_bits()),
+        ];
+
+        let binary = format!("{:064b}", self.0);
+        let mut bits = Vec::new();
+        let mut fields = Vec::new();
+        let mut start = 0;
+
+        for (name, width) in widths.iter().filter(|x| x.1 > 0) {
+            let end = start + *width as usize;
+            bits.push(&binary[start..end]);
+            fields.push(format!("{}({})", name, width));
+            start = end;
+        }
+
+        let mut str = String::new();
+        let _ = writeln!(str, "id:            {}", self.0);
+
+        match timestamp {
+            Some(utc) => {
+                let _ = writeln!(
+                    str,
+                    "utc:           {}",
+                    utc.to_rfc3339_opts(SecondsFormat::Millis, true)
+                );
+                let _ = writeln!(
+                    str,
+                    "local:         {}",
+                    utc.with_timezone(&Local)
+                        .to_rfc3339_opts(SecondsFormat::Millis, false)
+                );
+            }
+            None => {
+                let _ = writeln!(str, "utc:           out of range");
+                let _ = writeln!(str, "local:         out of range");
+            }
+        }
+
+        let _ = writeln!(str, "raw_timestamp: {}", raw_timestamp);
+        let _ = writeln!(str, "machine_id:    {}", layout.machine_id(*self));
+        let _ = writeln!(str, "sequence:      {}", layout.sequence(*self));
+        let _ = writeln!(str, "bits:          {}", bits.join("|"));
+        let _ = write!(str, "fields:        {}", fields.join("|"));
+
+        str
+    }
+}
+
+impl SnowflakeKey {
+    pub const fn id(&self) -> SnowflakeId {
+        SnowflakeId::from_be_bytes(self.0)
+    }
+
+    pub const fn as_bytes(&self) -> &[u8; 8] {
+        &self.0
+    }
+}
+
+impl From<SnowflakeId> for SnowflakeKey {
+    fn from(value: SnowflakeId) -> Self {
+        value.to_key()
+    }
+}
+
+impl From<SnowflakeKey> for SnowflakeId {
+    fn from(value: SnowflakeKey) -> Self {
+        value.id()
+    }
+}
+
+impl From<SnowflakeKey> for [u8; 8] {
+    fn from(value: SnowflakeKey) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<&[u8]> for SnowflakeKey {
+    type Error = TryFromSliceError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        value.try_into().map(SnowflakeKey)
+    }
+}
+
+impl AsRef<[u8; 8]> for SnowflakeKey {
+    fn as_ref(&self) -> &[u8; 8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for SnowflakeKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+// Hash and order agree with the slice's, so maps keyed by `SnowflakeKey` can be looked up
+// with raw bytes read back from a store.
+impl Borrow<[u8]> for SnowflakeKey {
+    fn borrow(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Display for SnowflakeId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+// The radix formats pass the formatter through, so width, fill, zero padding and the `#`
+// prefix behave exactly as they do for the raw u64.
+impl LowerHex for SnowflakeId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl UpperHex for SnowflakeId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        UpperHex::fmt(&self.0, f)
+    }
+}
+
+impl Octal for SnowflakeId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Octal::fmt(&self.0, f)
+    }
+}
+
+impl Binary for SnowflakeId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Binary::fmt(&self.0, f)
+    }
+}
+
+fn parse_error(e: ParseIntError) -> SnowflakeIdParseError {
+    match e.kind() {
+        IntErrorKind::Empty => SnowflakeIdParseError::Empty,
+        IntErrorKind::PosOverflow => SnowflakeIdParseError::Overflow,
+        _ => SnowflakeIdParseError::InvalidDigit,
+    }
+}
+
+impl FromStr for SnowflakeId {
+    type Err = SnowflakeIdParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<u64>().map(SnowflakeId).map_err(parse_error)
+    }
+}
+
+impl SnowflakeId {
+    // Parses the output of `{:x}`, `{:X}` or `{:#x}`; the `0x` prefix is optional.
+    pub fn from_hex_str(s: &str) -> Result<Self, SnowflakeIdParseError> {
+        let digits = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
+
+        u64::from_str_radix(digits, 16)
+            .map(SnowflakeId)
+            .map_err(parse_error)
+    }
+}
+
+// IDs compare by their raw value. Since the timestamp occupies the most significant
+// bits, followed by the machine id and the sequence, this equals generation order for
+// IDs sharing the same layout and epoch (k-sortable across machines).
+impl PartialOrd for SnowflakeId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SnowflakeId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl Hash for SnowflakeId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+#[cfg(all(test, feature = "chrono"))]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::snow_flake_id::SnowflakeIdError::Timestamp;
+    use crate::snow_flake_id::{
+        datacenter_machine_id, SnowflakeId, SnowflakeIdError, SnowflakeIdParseError,
+        SnowflakeIdParts, SnowflakeKey, ValidationError, MAX_DECIMAL_LEN,
+    };
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::BTreeMap;
+    use std::hash::{Hash, Hasher};
+
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use proptest::prelude::*;
+    use std::sync::LazyLock;
+
+    const MAX_TIMESTAMP: u64 = BitLayout::DEFAULT.max_timestamp();
+    const MAX_MACHINE_ID: u16 = BitLayout::DEFAULT.max_machine_id();
+    const MAX_SEQUENCE: u16 = BitLayout::DEFAULT.max_sequence();
+
+    const SAMPLE_SCR: u64 = 175_928_847_299_678_215;
+    const EXPECTED_MACHINE_ID: u16 = 169;
+    const EXPECTED_SEQUENCE: u16 = 7;
+    const EXPECTED_RAW_TIMESTAMP: u64 = 41_944_705_796;
+    static SNOWFLAKE_EXPECTED_TIMESTAMP: LazyLock<DateTime<Utc>> = LazyLock::new(|| {
+        Utc::with_ymd_and_hms(&Utc, 2016, 4, 30, 11, 18, 25)
+            .unwrap()
+            .checked_add_signed(Duration::milliseconds(796))
+            .unwrap()
+    });
+
+    #[test]
+    fn debug_display_test() {}
+
+    static THE_EPOCH: LazyLock<DateTime<Utc>> =
+        LazyLock::new(|| Utc::with_ymd_and_hms(&Utc, 2015, 1, 1, 0, 0, 0).unwrap());
+
+    fn fixture() -> SnowflakeId {
+        SnowflakeId(SAMPLE_SCR)
+    }
+
+    #[test]
+    fn snowflake_id_error_debug_test() {
+        let targets = [
+            SnowflakeIdError::Timestamp { value: 5, max: 4 },
+            SnowflakeIdError::MachineId {
+                value: 1024,
+                max: 1023,
+            },
+            SnowflakeIdError::Sequence {
+                value: 4096,
+                max: 4095,
+            },
+            SnowflakeIdError::BeforeEpoch { millis: -1 },
+            SnowflakeIdError::InvalidBucket { millis: 0 },
+            SnowflakeIdError::SignBit { value: 1 << 63 },
+        ];
+        let expected = [
+            "SnowflakeIdError::Timestamp { value: 5, max: 4 }",
+            "SnowflakeIdError::MachineId { value: 1024, max: 1023 }",
+            "SnowflakeIdError::Sequence { value: 4096, max: 4095 }",
+            "SnowflakeIdError::BeforeEpoch { millis: -1 }",
+            "SnowflakeIdError::InvalidBucket { millis: 0 }",
+            "SnowflakeIdError::SignBit { value: 9223372036854775808 }",
+        ];
+
+        for elem in targets.iter().zip(expected) {
+            assert_eq!(format!("{}", elem.0), elem.1);
+            assert_eq!(format!("{:?}", elem.0), elem.1);
+        }
+    }
+
+    #[test]
+    fn snowflake_id_parse_error_debug_test() {
+        let targets = [
+            SnowflakeIdParseError::Empty,
+            SnowflakeIdParseError::InvalidDigit,
+            SnowflakeIdParseError::Overflow,
+            SnowflakeIdParseError::NoPlausibleEpoch,
+        ];
+        let expected = [
+            "SnowflakeIdParseError::Empty",
+            "SnowflakeIdParseError::InvalidDigit",
+            "SnowflakeIdParseError::Overflow",
+            "SnowflakeIdParseError::NoPlausibleEpoch",
+        ];
+
+        for elem in targets.iter().zip(expected) {
+            assert_eq!(format!("{}", elem.0), elem.1);
+            assert_eq!(format!("{:?}", elem.0), elem.1);
+        }
+    }
+
+    #[test]
+    fn validation_error_debug_test() {
+        let targets = [
+            ValidationError::BeforeEpoch,
+            ValidationError::InFuture,
+            ValidationError::ReservedBits,
+        ];
+        let expected = [
+            "ValidationError::BeforeEpoch",
+            "ValidationError::InFuture",
+            "ValidationError::ReservedBits",
+        ];
+
+        for elem in targets.iter().zip(expected) {
+            assert_eq!(format!("{}", elem.0), elem.1);
+            assert_eq!(format!("{:?}", elem.0), elem.1);
+        }
+    }
+
+    #[test]
+    fn display_test() {
+        assert_eq!(format!("{}", fixture()), "175928847299678215");
+        assert_eq!(
+            format!("{:>20}", SnowflakeId::from(42u64)),
+            "                  42"
+        );
+        assert_eq!(fixture().to_string(), SAMPLE_SCR.to_string());
+    }
+
+    #[test]
+    fn write_decimal_test() {
+        let mut buf = [0u8; MAX_DECIMAL_LEN];
+
+        for value in [0, 9, 10, SAMPLE_SCR, u64::MAX] {
+            let len = SnowflakeId::from(value).write_decimal(&mut buf);
+            assert_eq!(&buf[..len], value.to_string().as_bytes());
+        }
+
+        let mut buf = [b'x'; 4];
+        assert_eq!(SnowflakeId::from(42u64).write_decimal(&mut buf), 2);
+        assert_eq!(&buf, b"42xx");
+    }
+
+    #[test]
+    #[should_panic]
+    fn write_decimal_short_buffer_test() {
+        let mut buf = [0u8; 17];
+        fixture().write_decimal(&mut buf);
+    }
+
+    #[test]
+    fn from_str_test() {
+        let actual: SnowflakeId = "175928847299678215".parse().unwrap();
+        assert_eq!(actual, fixture());
+
+        let actual: SnowflakeId = u64::MAX.to_string().parse().unwrap();
+        assert_eq!(actual.as_u64(), u64::MAX);
+
+        let actual: SnowflakeId = fixture().to_string().parse().unwrap();
+        assert_eq!(actual, fixture());
+    }
+
+    #[test]
+    fn radix_format_test() {
+        let id = SnowflakeId::from(0x2a_u64);
+
+        assert_eq!(format!("{:x}", fixture()), format!("{:x}", SAMPLE_SCR));
+        assert_eq!(format!("{:X}", fixture()), format!("{:X}", SAMPLE_SCR));
+        assert_eq!(format!("{:o}", fixture()), format!("{:o}", SAMPLE_SCR));
+        assert_eq!(format!("{:b}", fixture()), format!("{:b}", SAMPLE_SCR));
+
+        assert_eq!(format!("{:x}", id), "2a");
+        assert_eq!(format!("{:#X}", id), "0x2A");
+        assert_eq!(format!("{:016x}", id), "000000000000002a");
+        assert_eq!(format!("{:#010x}", id), "0x0000002a");
+        assert_eq!(format!("{:>6o}", id), "    52");
+        assert_eq!(format!("{:#b}", id), "0b101010");
+    }
+
+    #[test]
+    fn from_hex_str_test() {
+        for str in [
+            format!("{:x}", fixture()),
+            format!("{:X}", fixture()),
+            format!("{:#x}", fixture()),
+            format!("{:#018X}", fixture()),
+        ] {
+            assert_eq!(SnowflakeId::from_hex_str(&str), Ok(fixture()));
+        }
+
+        assert_eq!(
+            SnowflakeId::from_hex_str("ffffffffffffffff").map(|id| id.as_u64()),
+            Ok(u64::MAX)
+        );
+        assert_eq!(
+            SnowflakeId::from_hex_str(""),
+            Err(SnowflakeIdParseError::Empty)
+        );
+        assert_eq!(
+            SnowflakeId::from_hex_str("0x"),
+            Err(SnowflakeIdParseError::Empty)
+        );
+        assert_eq!(
+            SnowflakeId::from_hex_str("0xg1"),
+            Err(SnowflakeIdParseError::InvalidDigit)
+        );
+        assert_eq!(
+            SnowflakeId::from_hex_str("10000000000000000"),
+            Err(SnowflakeIdParseError::Overflow)
+        );
+    }
+
+    #[test]
+    fn try_from_str_test() {
+        assert_eq!(SnowflakeId::try_from("175928847299678215"), Ok(fixture()));
+        assert_eq!(SnowflakeId::try_from("0x271065ac10a9007"), Ok(fixture()));
+        assert_eq!(SnowflakeId::try_from("0X271065AC10A9007"), Ok(fixture()));
+
+        assert_eq!(SnowflakeId::try_from(""), Err(SnowflakeIdParseError::Empty));
+        assert_eq!(
+            SnowflakeId::try_from("271065ac10a9007"),
+            Err(SnowflakeIdParseError::InvalidDigit)
+        );
+        assert_eq!(
+            SnowflakeId::try_from("0x10000000000000000"),
+            Err(SnowflakeIdParseError::Overflow)
+        );
+    }
+
+    #[test]
+    fn try_from_u128_test() {
+        assert_eq!(SnowflakeId::try_from(SAMPLE_SCR as u128), Ok(fixture()));
+        assert_eq!(
+            SnowflakeId::try_from(u64::MAX as u128).map(|id| id.as_u64()),
+            Ok(u64::MAX)
+        );
+        assert!(SnowflakeId::try_from(u64::MAX as u128 + 1).is_err());
+    }
+
+    #[test]
+    fn into_integer_test() {
+        assert_eq!(u64::from(fixture()), SAMPLE_SCR);
+        assert_eq!(i64::from(fixture()), SAMPLE_SCR as i64);
+        assert_eq!(u128::from(fixture()), SAMPLE_SCR as u128);
+
+        let max = SnowflakeId::from(u64::MAX);
+        assert_eq!(i64::from(max), -1);
+        assert_eq!(u128::from(max), u64::MAX as u128);
+    }
+
+    #[test]
+    fn invalid_from_str_test() {
+        assert_eq!("".parse::<SnowflakeId>(), Err(SnowflakeIdParseError::Empty));
+        assert_eq!(
+            "12a".parse::<SnowflakeId>(),
+            Err(SnowflakeIdParseError::InvalidDigit)
+        );
+        assert_eq!(
+            "-1".parse::<SnowflakeId>(),
+            Err(SnowflakeIdParseError::InvalidDigit)
+        );
+        assert_eq!(
+            "18446744073709551616".parse::<SnowflakeId>(),
+            Err(SnowflakeIdParseError::Overflow)
+        );
+    }
+
+    #[test]
+    fn new_test() {
+        let fixture = SnowflakeId::new(
+            EXPECTED_RAW_TIMESTAMP,
+            EXPECTED_MACHINE_ID,
+            EXPECTED_SEQUENCE,
+        )
+        .unwrap();
+        assert_eq!(fixture.0, SAMPLE_SCR);
+    }
+
+    #[test]
+    fn const_test() {
+        const ID: Option<SnowflakeId> = SnowflakeId::checked_new(
+            EXPECTED_RAW_TIMESTAMP,
+            EXPECTED_MACHINE_ID,
+            EXPECTED_SEQUENCE,
+        );
+        const INVALID: Option<SnowflakeId> =
+            SnowflakeId::checked_new(MAX_TIMESTAMP + 1, EXPECTED_MACHINE_ID, EXPECTED_SEQUENCE);
+        const PARTS: (u64, u16, u16, u64) = match ID {
+            Some(id) => (
+                id.raw_timestamp(),
+                id.machine_id(),
+                id.sequence(),
+                id.as_u64(),
+            ),
+            None => panic!(),
+        };
+
+        assert_eq!(
+            PARTS,
+            (
+                EXPECTED_RAW_TIMESTAMP,
+                EXPECTED_MACHINE_ID,
+                EXPECTED_SEQUENCE,
+                SAMPLE_SCR
+            )
+        );
+        assert!(INVALID.is_none());
+
+        const FROM_RAW: SnowflakeId = SnowflakeId::from_u64(SAMPLE_SCR);
+        assert_eq!(ID, Some(FROM_RAW));
+    }
+
+    #[test]
+    fn limit_new_test() {
+        let fixture = SnowflakeId::new(MAX_TIMESTAMP, MAX_MACHINE_ID, MAX_SEQUENCE).unwrap();
+        assert_eq!(fixture.as_u64(), u64::MAX)
+    }
+
+    #[test]
+    fn our_of_range_new_test() {
+        fn assert(actual: Result<SnowflakeId, SnowflakeIdError>, expected: SnowflakeIdError) {
+            match actual {
+                Ok(_) => unreachable!(),
+                Err(e) => assert_eq!(e, expected),
+            }
+        }
+
+        let fixture = SnowflakeId::new(MAX_TIMESTAMP + 1, MAX_MACHINE_ID, MAX_SEQUENCE);
+        assert(
+            fixture,
+            Timestamp {
+                value: MAX_TIMESTAMP + 1,
+                max: MAX_TIMESTAMP,
+            },
+        );
+
+        let fixture = SnowflakeId::new(MAX_TIMESTAMP, MAX_MACHINE_ID + 1, MAX_SEQUENCE);
+        assert(
+            fixture,
+            SnowflakeIdError::MachineId {
+                value: MAX_MACHINE_ID as u64 + 1,
+                max: MAX_MACHINE_ID as u64,
+            },
+        );
+
+        let fixture = SnowflakeId::new(MAX_TIMESTAMP, MAX_MACHINE_ID, MAX_SEQUENCE + 1);
+        assert(
+            fixture,
+            SnowflakeIdError::Sequence {
+                value: MAX_SEQUENCE as u64 + 1,
+                max: MAX_SEQUENCE as u64,
+            },
+        );
+    }
+
+    #[test]
+    fn with_layout_test() {
+        let layout = BitLayout::new(39, 16, 8).unwrap();
+        let actual = SnowflakeId::with_layout(1, 2, 3, &layout).unwrap();
+        assert_eq!(actual.as_u64(), (1 << 24) | (2 << 8) | 3);
+
+        let actual = SnowflakeId::with_layout(1 << 39, 2, 3, &layout);
+        assert_eq!(
+            actual,
+            Err(SnowflakeIdError::Timestamp {
+                value: 1 << 39,
+                max: layout.max_timestamp()
+            })
+        );
+    }
+
+    #[test]
+    fn from_u64_test() {
+        let actual = SnowflakeId::from(42u64);
+        assert_eq!(actual.0, 42u64);
+    }
+
+    #[test]
+    fn from_i64_test() {
+        let actual = SnowflakeId::from(42i64);
+        assert_eq!(actual.0, 42u64);
+    }
+
+    #[test]
+    fn timestamp_test() {
+        let actual = fixture();
+        assert_eq!(
+            actual.timestamp(*THE_EPOCH, &Utc),
+            *SNOWFLAKE_EXPECTED_TIMESTAMP
+        );
+    }
+
+    #[test]
+    fn timestamp_default_test() {
+        let actual = fixture();
+        assert_eq!(
+            actual.timestamp_default(),
+            actual.timestamp(
+                DateTime::from_timestamp_millis(crate::THE_EPOCH_MILLIS).unwrap(),
+                &Utc
+            )
+        );
+    }
+
+    #[test]
+    fn timestamp_discord_test() {
+        assert_eq!(fixture().timestamp_discord(), *SNOWFLAKE_EXPECTED_TIMESTAMP);
+    }
+
+    #[test]
+    fn timestamp_twitter_test() {
+        let expected = Utc::with_ymd_and_hms(&Utc, 2019, 12, 31, 19, 26, 16)
+            .unwrap()
+            .checked_add_signed(Duration::milliseconds(771))
+            .unwrap();
+
+        let actual = SnowflakeId::from(1_212_092_628_029_698_048u64);
+        assert_eq!(actual.timestamp_twitter(), expected);
+    }
+
+    #[test]
+    fn timestamp_instagram_test() {
+        let layout = BitLayout::INSTAGRAM;
+        let actual = SnowflakeId::with_layout(1_000, 5001, 1, &layout).unwrap();
+
+        assert_eq!(
+            actual.timestamp_instagram(),
+            crate::epochs::INSTAGRAM + Duration::milliseconds(1_000)
+        );
+    }
+
+    #[test]
+    fn timestamp_unix_test() {
+        let actual = SnowflakeId::new(1_000, 1, 1).unwrap();
+        assert_eq!(
+            actual.timestamp_unix(),
+            Utc::with_ymd_and_hms(&Utc, 1970, 1, 1, 0, 0, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn into_parts_test() {
+        let expected = SnowflakeIdParts {
+            timestamp: *SNOWFLAKE_EXPECTED_TIMESTAMP,
+            raw_timestamp: EXPECTED_RAW_TIMESTAMP,
+            machine_id: EXPECTED_MACHINE_ID,
+            sequence: EXPECTED_SEQUENCE,
+        };
+
+        assert_eq!(fixture().into_parts(*THE_EPOCH), expected);
+    }
+
+    #[test]
+    fn into_parts_with_layout_test() {
+        let layout = BitLayout::new(39, 16, 8).unwrap();
+        let actual = SnowflakeId::with_layout(1_500, 0xbeef, 0x42, &layout)
+            .unwrap()
+            .into_parts_with_layout(*THE_EPOCH, &layout);
+
+        assert_eq!(
+            actual,
+            SnowflakeIdParts {
+                timestamp: *THE_EPOCH + Duration::milliseconds(1_500),
+                raw_timestamp: 1_500,
+                machine_id: 0xbeef,
+                sequence: 0x42,
+            }
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn explain_test() {
+        let epoch = Utc.with_ymd_and_hms(2015, 1, 1, 0, 0, 0).unwrap();
+        let actual = SnowflakeId::new(1_500, 169, 7).unwrap().explain(epoch);
+        let lines: Vec<_> = actual.lines().collect();
+
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[0], "id:            6292148231");
+        assert_eq!(lines[1], "utc:           2015-01-01T00:00:01.500Z");
+        assert!(lines[2].starts_with("local:         2015-01-01T"));
+        assert_eq!(lines[3], "raw_timestamp: 1500");
+        assert_eq!(lines[4], "machine_id:    169");
+        assert_eq!(lines[5], "sequence:      7");
+        assert_eq!(
+            lines[6],
+            "bits:          000000000000000000000000000000010111011100|0010101001|000000000111"
+        );
+        assert_eq!(
+            lines[7],
+            "fields:        timestamp(42)|machine(10)|sequence(12)"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn explain_with_layout_test() {
+        let actual =
+            SnowflakeId::from(i64::MAX).explain_with_layout(*THE_EPOCH, &BitLayout::TWITTER);
+        assert!(actual.ends_with("fields:        unused(1)|timestamp(41)|machine(10)|sequence(12)"));
+        assert!(actual.contains("bits:          0|1"));
+
+        let layout = BitLayout::new(64, 0, 0).unwrap();
+        let actual = SnowflakeId::from(u64::MAX)
+            .explain_with_layout(Utc.timestamp_opt(0, 0).unwrap(), &layout);
+
+        assert!(actual.contains("utc:           out of range\n"));
+        assert!(actual.ends_with(&format!(
+            "bits:          {}\nfields:        timestamp(64)",
+            "1".repeat(64)
+        )));
+    }
+
+    #[test]
+    fn bound_for_test() {
+        let lower =
+            SnowflakeId::lower_bound_for(*SNOWFLAKE_EXPECTED_TIMESTAMP, *THE_EPOCH).unwrap();
+        let upper =
+            SnowflakeId::upper_bound_for(*SNOWFLAKE_EXPECTED_TIMESTAMP, *THE_EPOCH).unwrap();
+
+        assert_eq!(lower.raw_timestamp(), EXPECTED_RAW_TIMESTAMP);
+        assert_eq!(lower.machine_id(), 0);
+        assert_eq!(lower.sequence(), 0);
+
+        assert_eq!(upper.raw_timestamp(), EXPECTED_RAW_TIMESTAMP);
+        assert_eq!(upper.machine_id(), MAX_MACHINE_ID);
+        assert_eq!(upper.sequence(), MAX_SEQUENCE);
+
+        assert!(lower <= fixture() && fixture() <= upper);
+        assert_eq!(
+            SnowflakeId::lower_bound_for(
+                *SNOWFLAKE_EXPECTED_TIMESTAMP + Duration::milliseconds(1),
+                *THE_EPOCH
+            )
+            .unwrap()
+            .as_u64(),
+            upper.as_u64() + 1
+        );
+    }
+
+    #[test]
+    fn bound_for_with_layout_test() {
+        let layout = BitLayout::new(39, 16, 8).unwrap();
+        let instant = *THE_EPOCH + Duration::milliseconds(5);
+
+        let lower = SnowflakeId::lower_bound_for_with_layout(instant, *THE_EPOCH, &layout).unwrap();
+        let upper = SnowflakeId::upper_bound_for_with_layout(instant, *THE_EPOCH, &layout).unwrap();
+
+        assert_eq!(lower.as_u64(), 5 << 24);
+        assert_eq!(upper.as_u64(), (6 << 24) - 1);
+    }
+
+    #[test]
+    fn invalid_bound_for_test() {
+        let before = *THE_EPOCH - Duration::milliseconds(1);
+        assert_eq!(
+            SnowflakeId::lower_bound_for(before, *THE_EPOCH),
+            Err(SnowflakeIdError::BeforeEpoch { millis: -1 })
+        );
+        assert_eq!(
+            SnowflakeId::upper_bound_for(before, *THE_EPOCH),
+            Err(SnowflakeIdError::BeforeEpoch { millis: -1 })
+        );
+
+        let layout = BitLayout::new(39, 16, 8).unwrap();
+        let after = *THE_EPOCH + Duration::milliseconds(1 << 39);
+        assert_eq!(
+            SnowflakeId::upper_bound_for_with_layout(after, *THE_EPOCH, &layout),
+            Err(SnowflakeIdError::Timestamp {
+                value: 1 << 39,
+                max: (1 << 39) - 1
+            })
+        );
+    }
+
+    #[test]
+    fn from_datetime_test() {
+        let actual = SnowflakeId::from_datetime(
+            *SNOWFLAKE_EXPECTED_TIMESTAMP,
+            *THE_EPOCH,
+            EXPECTED_MACHINE_ID,
+            EXPECTED_SEQUENCE,
+        );
+        assert_eq!(actual, Ok(fixture()));
+
+        let layout = BitLayout::new(39, 16, 8).unwrap();
+        let actual = SnowflakeId::from_datetime_with_layout(
+            *THE_EPOCH + Duration::milliseconds(5),
+            *THE_EPOCH,
+            0xbeef,
+            0xff,
+            &layout,
+        )
+        .unwrap();
+        assert_eq!(layout.raw_timestamp(actual), 5);
+        assert_eq!(layout.machine_id(actual), 0xbeef);
+        assert_eq!(layout.sequence(actual), 0xff);
+
+        assert_eq!(
+            SnowflakeId::from_datetime(*THE_EPOCH - Duration::milliseconds(1), *THE_EPOCH, 1, 0),
+            Err(SnowflakeIdError::BeforeEpoch { millis: -1 })
+        );
+        assert_eq!(
+            SnowflakeId::from_datetime_with_layout(
+                *THE_EPOCH - Duration::days(1),
+                *THE_EPOCH,
+                0,
+                0,
+                &layout
+            ),
+            Err(SnowflakeIdError::BeforeEpoch {
+                millis: -86_400_000
+            })
+        );
+        assert_eq!(
+            SnowflakeId::from_datetime(
+                *THE_EPOCH + Duration::milliseconds(1 << 42),
+                *THE_EPOCH,
+                1,
+                0
+            ),
+            Err(SnowflakeIdError::Timestamp {
+                value: 1 << 42,
+                max: MAX_TIMESTAMP
+            })
+        );
+        assert_eq!(
+            SnowflakeId::from_datetime(*THE_EPOCH, *THE_EPOCH, 1024, 0),
+            Err(SnowflakeIdError::MachineId {
+                value: 1024,
+                max: 1023
+            })
+        );
+    }
+
+    #[test]
+    fn iter_buckets_test() {
+        let start = Utc.with_ymd_and_hms(2024, 3, 5, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 3, 5, 2, 30, 0).unwrap();
+        let target = SnowflakeId::iter_buckets(start, end, Duration::hours(1), *THE_EPOCH).unwrap();
+        assert_eq!(target.len(), 3);
+
+        let actual: Vec<_> = target.collect();
+        let hour = |h: u32, m: u32| Utc.with_ymd_and_hms(2024, 3, 5, h, m, 0).unwrap();
+        let last_before = |instant: DateTime<Utc>| {
+            SnowflakeId::upper_bound_for(instant - Duration::milliseconds(1), *THE_EPOCH).unwrap()
         };
+        let expected = vec![
+            (
+                SnowflakeId::lower_bound_for(hour(0, 0), *THE_EPOCH).unwrap(),
+                last_before(hour(1, 0)),
+            ),
+            (
+                SnowflakeId::lower_bound_for(hour(1, 0), *THE_EPOCH).unwrap(),
+                last_before(hour(2, 0)),
+            ),
+            (
+                SnowflakeId::lower_bound_for(hour(2, 0), *THE_EPOCH).unwrap(),
+                last_before(hour(2, 30)),
+            ),
+        ];
+        assert_eq!(actual, expected);
 
-        write!(f, "{}", str)
+        for pair in actual.windows(2) {
+            assert_eq!(pair[0].1.as_u64() + 1, pair[1].0.as_u64());
+        }
     }
-}
 
-impl Debug for SnowflakeIdError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        self.format(f)
-    }
-}
+    #[test]
+    fn iter_buckets_edge_test() {
+        let start = Utc.with_ymd_and_hms(2024, 3, 5, 0, 0, 0).unwrap();
 
-impl Display for SnowflakeIdError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        self.format(f)
+        assert_eq!(
+            SnowflakeId::iter_buckets(start, start, Duration::hours(1), *THE_EPOCH)
+                .unwrap()
+                .count(),
+            0
+        );
+        assert_eq!(
+            SnowflakeId::iter_buckets(start, start, Duration::zero(), *THE_EPOCH).unwrap_err(),
+            SnowflakeIdError::InvalidBucket { millis: 0 }
+        );
+        assert!(matches!(
+            SnowflakeId::iter_buckets(
+                *THE_EPOCH - Duration::days(1),
+                start,
+                Duration::hours(1),
+                *THE_EPOCH
+            ),
+            Err(SnowflakeIdError::BeforeEpoch { .. })
+        ));
+
+        // Four timestamp bits: the field ends 16ms after the epoch.
+        let layout = BitLayout::new(4, 10, 12).unwrap();
+        let actual: Vec<_> = SnowflakeId::iter_buckets_with_layout(
+            *THE_EPOCH,
+            *THE_EPOCH + Duration::days(1),
+            Duration::milliseconds(10),
+            *THE_EPOCH,
+            &layout,
+        )
+        .unwrap()
+        .map(|(low, high)| (layout.raw_timestamp(low), layout.raw_timestamp(high)))
+        .collect();
+        assert_eq!(actual, vec![(0, 9), (10, 15)]);
     }
-}
 
-impl std::error::Error for SnowflakeIdError {}
+    #[test]
+    fn truncate_to_test() {
+        let at = |instant| SnowflakeId::from_datetime(instant, *THE_EPOCH, 42, 7).unwrap();
+        let id = at(Utc.with_ymd_and_hms(2024, 3, 5, 13, 47, 12).unwrap());
 
-const MAX_TIMESTAMP: u64 = 0x03_ff_ff_ff_ff_ff;
-const MAX_MACHINE_ID: u16 = 0x03_ff;
-const MAX_INCLEMENT_ID: u16 = 0x0f_ff;
+        assert_eq!(
+            id.truncate_to(Duration::minutes(1), *THE_EPOCH),
+            SnowflakeId::lower_bound_for(
+                Utc.with_ymd_and_hms(2024, 3, 5, 13, 47, 0).unwrap(),
+                *THE_EPOCH
+            )
+        );
+        assert_eq!(
+            id.truncate_to(Duration::hours(1), *THE_EPOCH),
+            SnowflakeId::lower_bound_for(
+                Utc.with_ymd_and_hms(2024, 3, 5, 13, 0, 0).unwrap(),
+                *THE_EPOCH
+            )
+        );
+        assert_eq!(
+            id.truncate_to(Duration::days(1), *THE_EPOCH),
+            SnowflakeId::lower_bound_for(
+                Utc.with_ymd_and_hms(2024, 3, 5, 0, 0, 0).unwrap(),
+                *THE_EPOCH
+            )
+        );
 
-#[derive(PartialEq, Eq, Debug)]
-pub struct SnowflakeId(u64);
+        // Unix-aligned weeks start on Thursdays; the one holding the default epoch (a Friday)
+        // began before it and is clamped to ID 0.
+        let first_day = at(*THE_EPOCH + Duration::hours(5));
+        assert_eq!(
+            first_day.truncate_to(Duration::days(7), *THE_EPOCH),
+            Ok(SnowflakeId::from(0u64))
+        );
 
-impl From<u64> for SnowflakeId {
-    fn from(value: u64) -> Self {
-        SnowflakeId(value)
+        assert_eq!(
+            id.truncate_to(Duration::zero(), *THE_EPOCH),
+            Err(SnowflakeIdError::InvalidBucket { millis: 0 })
+        );
     }
-}
 
-impl From<i64> for SnowflakeId {
-    fn from(value: i64) -> Self {
-        SnowflakeId(value as u64)
-    }
-}
+    #[test]
+    fn truncate_to_wide_layout_test() {
+        let layout = BitLayout::new(64, 0, 0).unwrap();
+        let second = Duration::seconds(1);
 
-impl Clone for SnowflakeId {
-    fn clone(&self) -> Self {
-        *self
+        for (raw, expected) in [
+            (i64::MAX as u64 - 5, 9_223_372_036_854_775_000u64),
+            (u64::MAX, 18_446_744_073_709_551_000u64),
+        ] {
+            assert_eq!(
+                SnowflakeId::from(raw).truncate_to_with_layout(second, *THE_EPOCH, &layout),
+                Ok(SnowflakeId::from(expected))
+            );
+        }
     }
-}
-
-impl Copy for SnowflakeId {}
 
-impl SnowflakeId {
-    pub fn new(timestamp: u64, machine_id: u16, inclement: u16) -> Result<Self, SnowflakeIdError> {
-        if timestamp > MAX_TIMESTAMP {
-            Err(Timestamp)
-        } else if machine_id > MAX_MACHINE_ID {
-            return Err(MachineId);
-        } else if inclement > MAX_INCLEMENT_ID {
-            return Err(Increment);
-        } else {
-            let mut tmp = timestamp << 22;
-            tmp |= (machine_id as u64) << 12;
-            tmp |= inclement as u64;
+    #[test]
+    fn validate_test() {
+        let now = *SNOWFLAKE_EXPECTED_TIMESTAMP;
 
-            return Ok(SnowflakeId::from(tmp));
-        }
+        assert_eq!(
+            fixture().validate_at(*THE_EPOCH, &BitLayout::DEFAULT, now),
+            Ok(())
+        );
+        assert_eq!(
+            fixture().validate_at(
+                *THE_EPOCH,
+                &BitLayout::DEFAULT,
+                now - Duration::milliseconds(1)
+            ),
+            Err(ValidationError::InFuture)
+        );
     }
 
-    pub fn timestamp<TzIn: TimeZone, TzOut: TimeZone>(
-        &self,
-        the_epoch: DateTime<TzIn>,
-        time_zone: &TzOut,
-    ) -> DateTime<TzOut> {
-        let pivot = the_epoch.with_timezone(&Utc);
+    #[test]
+    #[cfg(feature = "std")]
+    fn validate_now_test() {
+        assert_eq!(fixture().validate(*THE_EPOCH, &BitLayout::DEFAULT), Ok(()));
 
-        let dur = Duration::milliseconds(self.raw_timestamp() as i64);
-        (pivot + dur).with_timezone(&time_zone)
+        let id = SnowflakeId::new(MAX_TIMESTAMP >> 1, 0, 0).unwrap();
+        assert_eq!(
+            id.validate(*THE_EPOCH, &BitLayout::DEFAULT),
+            Err(ValidationError::InFuture)
+        );
     }
 
-    pub fn machine_id(&self) -> u16 {
-        ((self.0 & 0x3F_F0_00_u64) >> 12) as u16
-    }
+    #[test]
+    fn parse_with_known_epochs_test() {
+        use crate::epochs::KnownEpoch;
 
-    pub fn inclement(&self) -> u16 {
-        (self.0 & 0x0F_FF_u64) as u16
-    }
+        let now = Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap();
+        let millis_at = |epoch: KnownEpoch, year: i32| {
+            (Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0)
+                .unwrap()
+                .timestamp_millis()
+                - epoch.millis()) as u64
+        };
 
-    pub fn raw_timestamp(&self) -> u64 {
-        self.0 >> 22
-    }
+        let actual = SnowflakeId::parse_with_known_epochs_at(" 175928847299678215\n", now).unwrap();
+        assert_eq!(actual.id, fixture());
+        assert_eq!(actual.epoch, KnownEpoch::Discord);
+        assert_eq!(actual.parts.timestamp, *SNOWFLAKE_EXPECTED_TIMESTAMP);
+        assert_eq!(actual.parts.machine_id, EXPECTED_MACHINE_ID);
 
-    pub fn as_u64(&self) -> u64 {
-        self.0
-    }
+        // Too recent for the Discord epoch, which would put it in 2028.
+        let twitter = SnowflakeId::with_layout(
+            millis_at(KnownEpoch::Twitter, 2024),
+            5,
+            1,
+            &BitLayout::TWITTER,
+        )
+        .unwrap();
+        let actual = SnowflakeId::parse_with_known_epochs_at(&twitter.to_string(), now).unwrap();
+        assert_eq!(actual.epoch, KnownEpoch::Twitter);
+        assert_eq!(
+            actual.parts.timestamp,
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        );
 
-    pub fn as_i64(&self) -> i64 {
-        self.0 as i64
-    }
-}
+        let instagram = SnowflakeId::with_layout(
+            millis_at(KnownEpoch::Instagram, 2020),
+            4_000,
+            9,
+            &BitLayout::INSTAGRAM,
+        )
+        .unwrap();
+        let actual = SnowflakeId::parse_with_known_epochs_at(&instagram.to_string(), now).unwrap();
+        assert_eq!(actual.epoch, KnownEpoch::Instagram);
+        assert_eq!(actual.parts.machine_id, 4_000);
+        assert_eq!(actual.parts.sequence, 9);
 
-impl Hash for SnowflakeId {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.0.hash(state)
+        assert_eq!(
+            SnowflakeId::parse_with_known_epochs_at(&u64::MAX.to_string(), now),
+            Err(SnowflakeIdParseError::NoPlausibleEpoch)
+        );
+        assert_eq!(
+            SnowflakeId::parse_with_known_epochs_at("abc", now),
+            Err(SnowflakeIdParseError::InvalidDigit)
+        );
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::snow_flake_id::SnowflakeIdError::Timestamp;
-    use crate::snow_flake_id::{
-        SnowflakeId, SnowflakeIdError, MAX_INCLEMENT_ID, MAX_MACHINE_ID, MAX_TIMESTAMP,
-    };
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
 
-    use chrono::{DateTime, Duration, TimeZone, Utc};
-    use std::sync::LazyLock;
-    use strum::IntoEnumIterator;
+    #[test]
+    fn validate_high_bit_test() {
+        // Past 2^41 ms after the Unix epoch (September 2039) DEFAULT IDs have the top bit set.
+        let minted = Utc.with_ymd_and_hms(2040, 1, 1, 0, 0, 0).unwrap();
+        let id = SnowflakeId::from_datetime(minted, crate::epochs::UNIX, 42, 7).unwrap();
+        assert!(id.as_i64() < 0);
 
-    const SAMPLE_SCR: u64 = 175_928_847_299_678_215;
-    const EXPECTED_MACHINE_ID: u16 = 169;
-    const EXPECTED_INCLEMENT: u16 = 7;
-    const EXPECTED_RAW_TIMESTAMP: u64 = 41_944_705_796;
-    static SNOWFLAKE_EXPECTED_TIMESTAMP: LazyLock<DateTime<Utc>> = LazyLock::new(|| {
-        Utc::with_ymd_and_hms(&Utc, 2016, 4, 30, 11, 18, 25)
-            .unwrap()
-            .checked_add_signed(Duration::milliseconds(796))
-            .unwrap()
-    });
+        assert_eq!(
+            id.validate_at(crate::epochs::UNIX, &BitLayout::DEFAULT, minted),
+            Ok(())
+        );
+        assert_eq!(
+            id.validate_at(
+                crate::epochs::UNIX,
+                &BitLayout::DEFAULT,
+                minted - Duration::milliseconds(1)
+            ),
+            Err(ValidationError::InFuture)
+        );
+    }
 
     #[test]
-    fn debug_display_test() {}
+    fn invalid_validate_test() {
+        let now = *SNOWFLAKE_EXPECTED_TIMESTAMP;
 
-    static THE_EPOCH: LazyLock<DateTime<Utc>> =
-        LazyLock::new(|| Utc::with_ymd_and_hms(&Utc, 2015, 1, 1, 0, 0, 0).unwrap());
+        // DEFAULT uses all 64 bits, so -1 is just the last timestamp the field holds.
+        assert_eq!(
+            SnowflakeId::from(-1i64).validate_at(*THE_EPOCH, &BitLayout::DEFAULT, now),
+            Err(ValidationError::InFuture)
+        );
 
-    fn fixture() -> SnowflakeId {
-        SnowflakeId(SAMPLE_SCR)
+        let layout = BitLayout::new(41, 10, 12).unwrap();
+        assert_eq!(
+            SnowflakeId::from(1u64 << 63).validate_at(*THE_EPOCH, &layout, now),
+            Err(ValidationError::BeforeEpoch)
+        );
+        assert_eq!(
+            SnowflakeId::from(1u64 << 62).validate_at(
+                *THE_EPOCH,
+                &BitLayout::new(40, 10, 12).unwrap(),
+                now
+            ),
+            Err(ValidationError::ReservedBits)
+        );
     }
 
     #[test]
-    fn snowflake_id_error_debug_test() {
-        let expected = [
-            "SnowflakeIdError::Timestamp",
-            "SnowflakeIdError::MachineId",
-            "SnowflakeIdError::Increment",
-        ];
+    fn machine_id_test() {
+        assert_eq!(fixture().machine_id(), EXPECTED_MACHINE_ID);
+    }
 
-        for elem in SnowflakeIdError::iter().zip(expected) {
-            assert_eq!(format!("{}", elem.0), elem.1);
-            assert_eq!(format!("{:?}", elem.0), elem.1);
-        }
+    #[test]
+    fn sequence_test() {
+        assert_eq!(fixture().sequence(), EXPECTED_SEQUENCE);
     }
 
     #[test]
-    fn new_test() {
-        let fixture = SnowflakeId::new(
-            EXPECTED_RAW_TIMESTAMP,
-            EXPECTED_MACHINE_ID,
-            EXPECTED_INCLEMENT,
-        )
-        .unwrap();
-        assert_eq!(fixture.0, SAMPLE_SCR);
+    fn datacenter_worker_test() {
+        let id = SnowflakeId::new(5, datacenter_machine_id(21, 9).unwrap(), 1).unwrap();
+        assert_eq!(id.machine_id(), 21 << 5 | 9);
+        assert_eq!(id.datacenter_id(), 21);
+        assert_eq!(id.worker_id(), 9);
+
+        let id = SnowflakeId::new(5, 1023, 1).unwrap();
+        assert_eq!(id.datacenter_id(), 31);
+        assert_eq!(id.worker_id(), 31);
+
+        assert_eq!(datacenter_machine_id(0, 0), Some(0));
+        assert_eq!(datacenter_machine_id(32, 0), None);
+        assert_eq!(datacenter_machine_id(0, 32), None);
     }
 
     #[test]
-    fn limit_new_test() {
-        let fixture = SnowflakeId::new(MAX_TIMESTAMP, MAX_MACHINE_ID, MAX_INCLEMENT_ID).unwrap();
-        assert_eq!(fixture.as_u64(), u64::MAX)
+    #[allow(deprecated)]
+    fn deprecated_inclement_test() {
+        assert_eq!(fixture().inclement(), EXPECTED_SEQUENCE);
+        assert_eq!(
+            SnowflakeId::new(0, 0, 4096),
+            Err(SnowflakeIdError::Increment)
+        );
     }
 
     #[test]
-    fn our_of_range_new_test() {
-        fn assert(actual: Result<SnowflakeId, SnowflakeIdError>, expected: SnowflakeIdError) {
-            match actual {
-                Ok(_) => unreachable!(),
-                Err(e) => assert_eq!(e, expected),
-            }
-        }
+    fn navigation_test() {
+        let id = SnowflakeId::new(5, 42, 7).unwrap();
 
-        let fixture = SnowflakeId::new(MAX_TIMESTAMP + 1, MAX_MACHINE_ID, MAX_INCLEMENT_ID);
-        assert(fixture, Timestamp);
+        assert_eq!(id.successor(), SnowflakeId::new(5, 42, 8).ok());
+        assert_eq!(id.predecessor(), SnowflakeId::new(5, 42, 6).ok());
+        assert_eq!(
+            SnowflakeId::new(5, 42, MAX_SEQUENCE).unwrap().successor(),
+            SnowflakeId::new(5, 43, 0).ok()
+        );
+        assert_eq!(
+            SnowflakeId::new(5, 0, 0).unwrap().predecessor(),
+            SnowflakeId::new(4, MAX_MACHINE_ID, MAX_SEQUENCE).ok()
+        );
+        assert_eq!(SnowflakeId::from(u64::MAX).successor(), None);
+        assert_eq!(SnowflakeId::from(0u64).predecessor(), None);
 
-        let fixture = SnowflakeId::new(MAX_TIMESTAMP, MAX_MACHINE_ID + 1, MAX_INCLEMENT_ID);
-        assert(fixture, SnowflakeIdError::MachineId);
+        assert_eq!(id.with_sequence(0), SnowflakeId::new(5, 42, 0));
+        assert_eq!(id.with_machine_id(1), SnowflakeId::new(5, 1, 7));
+        assert_eq!(
+            id.with_sequence(4096),
+            Err(SnowflakeIdError::Sequence {
+                value: 4096,
+                max: 4095
+            })
+        );
+        assert_eq!(
+            id.with_machine_id(1024),
+            Err(SnowflakeIdError::MachineId {
+                value: 1024,
+                max: 1023
+            })
+        );
 
-        let fixture = SnowflakeId::new(MAX_TIMESTAMP, MAX_MACHINE_ID, MAX_INCLEMENT_ID + 1);
-        assert(fixture, SnowflakeIdError::Increment);
+        assert_eq!(
+            id.first_of_millisecond(),
+            SnowflakeId::new(5, 0, 0).unwrap()
+        );
+        assert_eq!(
+            fixture().first_of_millisecond(),
+            SnowflakeId::new(EXPECTED_RAW_TIMESTAMP, 0, 0).unwrap()
+        );
     }
 
     #[test]
-    fn from_u64_test() {
-        let actual = SnowflakeId::from(42u64);
-        assert_eq!(actual.0, 42u64);
+    fn raw_timestamp_test() {
+        assert_eq!(fixture().raw_timestamp(), EXPECTED_RAW_TIMESTAMP);
     }
 
     #[test]
-    fn from_i64_test() {
-        let actual = SnowflakeId::from(42i64);
-        assert_eq!(actual.0, 42u64);
+    fn as_u64_test() {
+        assert_eq!(fixture().as_u64(), SAMPLE_SCR);
     }
 
     #[test]
-    fn timestamp_test() {
-        let actual = fixture();
+    fn bytes_test() {
+        let target = fixture();
+
+        assert_eq!(target.to_be_bytes(), SAMPLE_SCR.to_be_bytes());
+        assert_eq!(target.to_le_bytes(), SAMPLE_SCR.to_le_bytes());
+        assert_eq!(SnowflakeId::from_be_bytes(target.to_be_bytes()), target);
+        assert_eq!(SnowflakeId::from_le_bytes(target.to_le_bytes()), target);
         assert_eq!(
-            actual.timestamp(*THE_EPOCH, &Utc),
-            *SNOWFLAKE_EXPECTED_TIMESTAMP
+            SnowflakeId::try_from(&target.to_be_bytes()[..]).unwrap(),
+            target
         );
+        assert!(SnowflakeId::try_from(&[0u8; 7][..]).is_err());
     }
 
     #[test]
-    fn machine_id_test() {
-        assert_eq!(fixture().machine_id(), EXPECTED_MACHINE_ID);
+    fn key_test() {
+        let target = fixture().to_key();
+
+        assert_eq!(target.as_bytes(), &SAMPLE_SCR.to_be_bytes());
+        assert_eq!(
+            AsRef::<[u8]>::as_ref(&target),
+            &SAMPLE_SCR.to_be_bytes()[..]
+        );
+        assert_eq!(target.id(), fixture());
+        assert_eq!(SnowflakeId::from(target), fixture());
+        assert_eq!(SnowflakeKey::from(fixture()), target);
+        assert_eq!(<[u8; 8]>::from(target), SAMPLE_SCR.to_be_bytes());
+        assert_eq!(
+            SnowflakeKey::try_from(&SAMPLE_SCR.to_be_bytes()[..]).unwrap(),
+            target
+        );
+        assert!(SnowflakeKey::try_from(&[0u8; 9][..]).is_err());
+
+        let map = std::collections::HashMap::from([(target, "value")]);
+        assert_eq!(map.get(&SAMPLE_SCR.to_be_bytes()[..]), Some(&"value"));
     }
 
     #[test]
-    fn inclement_test_test() {
-        assert_eq!(fixture().inclement(), EXPECTED_INCLEMENT);
+    fn key_order_test() {
+        let mut ids: Vec<_> = [0u64, 1, 255, 256, 65_535, 1 << 40, SAMPLE_SCR, u64::MAX - 1]
+            .into_iter()
+            .map(SnowflakeId::from)
+            .collect();
+        ids.reverse();
+
+        let mut keys: Vec<_> = ids.iter().map(|x| x.to_be_bytes()).collect();
+        keys.sort();
+        ids.sort();
+
+        assert_eq!(
+            keys.into_iter()
+                .map(SnowflakeId::from_be_bytes)
+                .collect::<Vec<_>>(),
+            ids
+        );
     }
 
     #[test]
-    fn raw_timestamp_test() {
-        assert_eq!(fixture().raw_timestamp(), EXPECTED_RAW_TIMESTAMP);
+    fn partition_key_test() {
+        let id = |timestamp, machine_id, sequence| {
+            SnowflakeId::with_layout(timestamp, machine_id, sequence, &BitLayout::DEFAULT).unwrap()
+        };
+
+        // Pinned: a change here would reshuffle every keyed stream.
+        let actual: Vec<_> = (0..8).map(|seq| id(1, 42, seq).partition_key(12)).collect();
+        assert_eq!(actual, [2, 6, 0, 0, 2, 6, 8, 10]);
+
+        assert_eq!(
+            id(1, 42, 7).partition_key(12),
+            id(99_999, 42, 7).partition_key(12)
+        );
+        assert!((0..4096).all(|seq| id(1, 42, seq).partition_key(3) < 3));
+        assert!((0..4096).all(|seq| id(1, 42, seq).partition_key(1) == 0));
+
+        let layout = BitLayout::INSTAGRAM;
+        let target = SnowflakeId::with_layout(5, 8191, 1023, &layout).unwrap();
+        assert_eq!(
+            target.partition_key_with_layout(64, &layout),
+            SnowflakeId::with_layout(6, 8191, 1023, &layout)
+                .unwrap()
+                .partition_key_with_layout(64, &layout)
+        );
     }
 
     #[test]
-    fn as_u64_test() {
-        assert_eq!(fixture().as_u64(), SAMPLE_SCR);
+    fn partition_key_distribution_test() {
+        let mut counts = [0u32; 16];
+        for machine_id in 0..64 {
+            for sequence in 0..256 {
+                let id =
+                    SnowflakeId::with_layout(1, machine_id, sequence, &BitLayout::DEFAULT).unwrap();
+                counts[id.partition_key(16) as usize] += 1;
+            }
+        }
+
+        // 1024 per partition if perfectly even.
+        assert!(
+            counts.iter().all(|&x| (900..1150).contains(&x)),
+            "{:?}",
+            counts
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "num_partitions must not be zero")]
+    fn partition_key_zero_test() {
+        SnowflakeId::from(42u64).partition_key(0);
     }
 
     #[test]
@@ -250,6 +2091,41 @@ mod tests {
     }
 
     #[test]
+    fn as_positive_i64_test() {
+        assert_eq!(fixture().as_positive_i64(), Ok(SAMPLE_SCR as i64));
+        assert_eq!(SnowflakeId::from(i64::MAX).as_positive_i64(), Ok(i64::MAX));
+        assert_eq!(
+            SnowflakeId::from(u64::MAX).as_positive_i64(),
+            Err(SnowflakeIdError::SignBit { value: u64::MAX })
+        );
+    }
+
+    #[test]
+    fn new_positive_test() {
+        let max = BitLayout::SIGN_SAFE.max_timestamp();
+        let actual = SnowflakeId::new_positive(max, 1023, 4095).unwrap();
+
+        assert_eq!(actual.as_positive_i64(), Ok(i64::MAX));
+        assert_eq!(actual, SnowflakeId::new(max, 1023, 4095).unwrap());
+        assert_eq!(actual.raw_timestamp(), max);
+        assert_eq!(actual.machine_id(), 1023);
+        assert_eq!(actual.sequence(), 4095);
+
+        assert_eq!(
+            SnowflakeId::new_positive(max + 1, 0, 0),
+            Err(SnowflakeIdError::Timestamp {
+                value: max + 1,
+                max
+            })
+        );
+        assert!(SnowflakeId::new(max + 1, 0, 0)
+            .unwrap()
+            .as_positive_i64()
+            .is_err());
+    }
+
+    #[test]
+    #[allow(clippy::clone_on_copy)]
     fn clone_test() {
         let fixture = fixture();
         let cloned = fixture.clone();
@@ -282,4 +2158,76 @@ mod tests {
         SnowflakeId::from(52u64).hash(&mut hb);
         assert_ne!(ha.finish(), hb.finish());
     }
+
+    #[test]
+    fn ord_test() {
+        let older = SnowflakeId::new(EXPECTED_RAW_TIMESTAMP, 1023, 4095).unwrap();
+        let newer = SnowflakeId::new(EXPECTED_RAW_TIMESTAMP + 1, 0, 0).unwrap();
+        assert!(older < newer);
+
+        let a = SnowflakeId::new(EXPECTED_RAW_TIMESTAMP, 1, 5).unwrap();
+        let b = SnowflakeId::new(EXPECTED_RAW_TIMESTAMP, 1, 6).unwrap();
+        assert!(a < b);
+        assert_eq!(a.cmp(&a), std::cmp::Ordering::Equal);
+
+        let mut actual = vec![newer, b, older, a];
+        actual.sort();
+        assert_eq!(actual, vec![a, b, older, newer]);
+    }
+
+    #[test]
+    fn btree_map_key_test() {
+        let mut map = BTreeMap::new();
+        map.insert(SnowflakeId::from(30u64), "c");
+        map.insert(SnowflakeId::from(10u64), "a");
+        map.insert(SnowflakeId::from(20u64), "b");
+
+        let actual: Vec<_> = map.values().copied().collect();
+        assert_eq!(actual, vec!["a", "b", "c"]);
+    }
+
+    proptest! {
+        #[test]
+        fn new_round_trip_proptest(
+            timestamp in 0..=MAX_TIMESTAMP,
+            machine_id in 0..=MAX_MACHINE_ID,
+            sequence in 0..=MAX_SEQUENCE,
+        ) {
+            let actual = SnowflakeId::new(timestamp, machine_id, sequence).unwrap();
+
+            prop_assert_eq!(actual.raw_timestamp(), timestamp);
+            prop_assert_eq!(actual.machine_id(), machine_id);
+            prop_assert_eq!(actual.sequence(), sequence);
+            prop_assert_eq!(SnowflakeId::from(actual.as_u64()), actual);
+            prop_assert_eq!(actual.to_string().parse::<SnowflakeId>().unwrap(), actual);
+        }
+
+        #[test]
+        fn with_layout_round_trip_proptest(
+            machine_bits in 0..=16u32,
+            sequence_bits in 0..=16u32,
+            raw in any::<u64>(),
+        ) {
+            let timestamp_bits = 63 - machine_bits - sequence_bits;
+            let layout = BitLayout::new(timestamp_bits, machine_bits, sequence_bits).unwrap();
+
+            let timestamp = raw & layout.max_timestamp();
+            let machine_id = (raw as u16) & layout.max_machine_id();
+            let sequence = ((raw >> 16) as u16) & layout.max_sequence();
+
+            let actual = SnowflakeId::with_layout(timestamp, machine_id, sequence, &layout).unwrap();
+
+            prop_assert_eq!(layout.raw_timestamp(actual), timestamp);
+            prop_assert_eq!(layout.machine_id(actual), machine_id);
+            prop_assert_eq!(layout.sequence(actual), sequence);
+        }
+
+        #[test]
+        fn ord_matches_parts_proptest(a in any::<u64>(), b in any::<u64>()) {
+            let (a, b) = (SnowflakeId::from(a >> 1), SnowflakeId::from(b >> 1));
+            let parts = |id: SnowflakeId| (id.raw_timestamp(), id.machine_id(), id.sequence());
+
+            prop_assert_eq!(a.cmp(&b), parts(a).cmp(&parts(b)));
+        }
+    }
 }
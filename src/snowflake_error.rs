@@ -1,52 +1,192 @@
-use std::error::Error;
-use std::fmt::{Debug, Display, Formatter};
+use crate::snow_flake_id::SnowflakeIdError;
+use core::fmt::{Debug, Display, Formatter};
 
+// The error of every generator in the crate. Out-of-range settings and timestamps carry
+// the offending value and the allowed maximum, in the units of the setting (a machine id,
+// a bit count, raw timestamp ticks). Failures composing an ID surface as `Id`, through
+// the `From<SnowflakeIdError>` impl.
+#[derive(PartialEq, Eq)]
+#[non_exhaustive]
 pub enum SnowflakeIdEGeneratorError {
-	MachineIdOutOfRange,
+	MachineIdOutOfRange { value: u64, max: u64 },
+	ClockMovedBackwards,
+	SequenceExhausted,
+	Contended,
+	ShardBitsOutOfRange { value: u64, max: u64 },
+	SlotsExhausted,
+	PersistenceFailed,
+	EpochInFuture,
+	TimestampOverflow { value: u64, max: u64 },
+	DomainBitsOutOfRange { value: u64, max: u64 },
+	UnknownDomain,
+	RateLimited,
+	Forked,
+	Id(SnowflakeIdError),
+}
+
+fn write_range(f: &mut Formatter<'_>, name: &str, value: u64, max: u64) -> core::fmt::Result {
+	write!(
+		f,
+		"SnowflakeIdEGeneratorError::{} {{ value: {}, max: {} }}",
+		name, value, max
+	)
 }
 
 #[allow(unreachable_patterns)]
-fn format(this: &SnowflakeIdEGeneratorError, f: &mut Formatter<'_>) -> std::fmt::Result {
+fn format(this: &SnowflakeIdEGeneratorError, f: &mut Formatter<'_>) -> core::fmt::Result {
 	let tmp = match this {
-		SnowflakeIdEGeneratorError::MachineIdOutOfRange => "MachineIdOutOfRange",
+		SnowflakeIdEGeneratorError::MachineIdOutOfRange { value, max } => {
+			return write_range(f, "MachineIdOutOfRange", *value, *max);
+		}
+		SnowflakeIdEGeneratorError::ShardBitsOutOfRange { value, max } => {
+			return write_range(f, "ShardBitsOutOfRange", *value, *max);
+		}
+		SnowflakeIdEGeneratorError::TimestampOverflow { value, max } => {
+			return write_range(f, "TimestampOverflow", *value, *max);
+		}
+		SnowflakeIdEGeneratorError::DomainBitsOutOfRange { value, max } => {
+			return write_range(f, "DomainBitsOutOfRange", *value, *max);
+		}
+		SnowflakeIdEGeneratorError::ClockMovedBackwards => "ClockMovedBackwards",
+		SnowflakeIdEGeneratorError::SequenceExhausted => "SequenceExhausted",
+		SnowflakeIdEGeneratorError::Contended => "Contended",
+		SnowflakeIdEGeneratorError::SlotsExhausted => "SlotsExhausted",
+		SnowflakeIdEGeneratorError::PersistenceFailed => "PersistenceFailed",
+		SnowflakeIdEGeneratorError::EpochInFuture => "EpochInFuture",
+		SnowflakeIdEGeneratorError::UnknownDomain => "UnknownDomain",
+		SnowflakeIdEGeneratorError::RateLimited => "RateLimited",
+		SnowflakeIdEGeneratorError::Forked => "Forked",
+		SnowflakeIdEGeneratorError::Id(e) => {
+			return write!(f, "SnowflakeIdEGeneratorError::Id({})", e);
+		}
 		_ => unreachable!(),
 	};
 	write!(f, "SnowflakeIdEGeneratorError::{}", tmp)
 }
 
 impl Debug for SnowflakeIdEGeneratorError {
-	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
 		format(self, f)
 	}
 }
 
 impl Display for SnowflakeIdEGeneratorError {
-	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
 		format(self, f)
 	}
 }
 
-impl Error for SnowflakeIdEGeneratorError {}
+#[cfg(feature = "std")]
+impl std::error::Error for SnowflakeIdEGeneratorError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			SnowflakeIdEGeneratorError::Id(e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
+impl From<SnowflakeIdError> for SnowflakeIdEGeneratorError {
+	fn from(value: SnowflakeIdError) -> Self {
+		SnowflakeIdEGeneratorError::Id(value)
+	}
+}
 
 #[cfg(test)]
 mod tests {
+	use crate::snow_flake_id::SnowflakeIdError;
 	use crate::snowflake_error::SnowflakeIdEGeneratorError;
 
 	#[test]
 	fn debug_test() {
-		let target = SnowflakeIdEGeneratorError::MachineIdOutOfRange;
+		let target = SnowflakeIdEGeneratorError::MachineIdOutOfRange {
+			value: 1024,
+			max: 1023,
+		};
 		assert_eq!(
-			"SnowflakeIdEGeneratorError::MachineIdOutOfRange",
+			"SnowflakeIdEGeneratorError::MachineIdOutOfRange { value: 1024, max: 1023 }",
 			format!("{:?}", target)
 		)
 	}
 
 	#[test]
 	fn display_test() {
-		let target = SnowflakeIdEGeneratorError::MachineIdOutOfRange;
+		let target = SnowflakeIdEGeneratorError::MachineIdOutOfRange {
+			value: 1024,
+			max: 1023,
+		};
 		assert_eq!(
-			"SnowflakeIdEGeneratorError::MachineIdOutOfRange",
+			"SnowflakeIdEGeneratorError::MachineIdOutOfRange { value: 1024, max: 1023 }",
 			format!("{}", target)
 		)
 	}
+
+	#[test]
+	fn all_variants_format_test() {
+		let targets = [
+			SnowflakeIdEGeneratorError::MachineIdOutOfRange {
+				value: 1024,
+				max: 1023,
+			},
+			SnowflakeIdEGeneratorError::ClockMovedBackwards,
+			SnowflakeIdEGeneratorError::SequenceExhausted,
+			SnowflakeIdEGeneratorError::Contended,
+			SnowflakeIdEGeneratorError::ShardBitsOutOfRange { value: 13, max: 12 },
+			SnowflakeIdEGeneratorError::SlotsExhausted,
+			SnowflakeIdEGeneratorError::PersistenceFailed,
+			SnowflakeIdEGeneratorError::EpochInFuture,
+			SnowflakeIdEGeneratorError::TimestampOverflow {
+				value: 4_398_046_511_104,
+				max: 4_398_046_511_103,
+			},
+			SnowflakeIdEGeneratorError::DomainBitsOutOfRange { value: 11, max: 10 },
+			SnowflakeIdEGeneratorError::UnknownDomain,
+			SnowflakeIdEGeneratorError::RateLimited,
+			SnowflakeIdEGeneratorError::Forked,
+			SnowflakeIdEGeneratorError::Id(SnowflakeIdError::Sequence {
+				value: 4096,
+				max: 4095,
+			}),
+		];
+		let expected = [
+			"SnowflakeIdEGeneratorError::MachineIdOutOfRange { value: 1024, max: 1023 }",
+			"SnowflakeIdEGeneratorError::ClockMovedBackwards",
+			"SnowflakeIdEGeneratorError::SequenceExhausted",
+			"SnowflakeIdEGeneratorError::Contended",
+			"SnowflakeIdEGeneratorError::ShardBitsOutOfRange { value: 13, max: 12 }",
+			"SnowflakeIdEGeneratorError::SlotsExhausted",
+			"SnowflakeIdEGeneratorError::PersistenceFailed",
+			"SnowflakeIdEGeneratorError::EpochInFuture",
+			"SnowflakeIdEGeneratorError::TimestampOverflow { value: 4398046511104, max: 4398046511103 }",
+			"SnowflakeIdEGeneratorError::DomainBitsOutOfRange { value: 11, max: 10 }",
+			"SnowflakeIdEGeneratorError::UnknownDomain",
+			"SnowflakeIdEGeneratorError::RateLimited",
+			"SnowflakeIdEGeneratorError::Forked",
+			"SnowflakeIdEGeneratorError::Id(SnowflakeIdError::Sequence { value: 4096, max: 4095 })",
+		];
+
+		for elem in targets.iter().zip(expected) {
+			assert_eq!(format!("{}", elem.0), elem.1);
+			assert_eq!(format!("{:?}", elem.0), elem.1);
+		}
+	}
+
+	#[cfg(feature = "std")]
+	#[test]
+	fn source_test() {
+		use std::error::Error;
+
+		let inner = SnowflakeIdError::MachineId {
+			value: 1024,
+			max: 1023,
+		};
+		let target = SnowflakeIdEGeneratorError::from(inner);
+		assert_eq!(target, SnowflakeIdEGeneratorError::Id(inner));
+		assert_eq!(
+			target.source().unwrap().to_string(),
+			"SnowflakeIdError::MachineId { value: 1024, max: 1023 }"
+		);
+
+		assert!(SnowflakeIdEGeneratorError::Contended.source().is_none());
+	}
 }
@@ -0,0 +1,402 @@
+use crate::bit_layout::BitLayout;
+use crate::clock::Clock;
+use crate::generator_policy::{
+    ClockRegressionPolicy, MemoryOrderingPolicy, OverflowPolicy, TickUnit,
+};
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_generator_builder::SnowflakeGeneratorBuilderError::{
+    EpochInFuture, MachineIdOutOfRange, MissingMachineId,
+};
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+#[cfg(all(feature = "std", feature = "chrono"))]
+use crate::timestamp::{CachedTimestamp, DefaultTimestamp, HybridClock};
+use crate::THE_EPOCH_MILLIS;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, TimeZone};
+use core::fmt::{Debug, Display, Formatter};
+use core::time::Duration;
+
+#[derive(PartialEq, Eq)]
+pub enum SnowflakeGeneratorBuilderError {
+    MissingMachineId,
+    MachineIdOutOfRange,
+    EpochInFuture,
+}
+
+impl SnowflakeGeneratorBuilderError {
+    fn format(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let str = match self {
+            MissingMachineId => "SnowflakeGeneratorBuilderError::MissingMachineId",
+            MachineIdOutOfRange => "SnowflakeGeneratorBuilderError::MachineIdOutOfRange",
+            EpochInFuture => "SnowflakeGeneratorBuilderError::EpochInFuture",
+        };
+
+        write!(f, "{}", str)
+    }
+}
+
+impl Debug for SnowflakeGeneratorBuilderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+impl Display for SnowflakeGeneratorBuilderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SnowflakeGeneratorBuilderError {}
+
+pub struct SnowflakeGeneratorBuilder<T: Clock> {
+    clock: T,
+    epoch_millis: i64,
+    machine_id: Option<u16>,
+    layout: BitLayout,
+    tick_unit: TickUnit,
+    clock_regression_policy: ClockRegressionPolicy,
+    overflow_policy: OverflowPolicy,
+    memory_ordering_policy: MemoryOrderingPolicy,
+    max_backward_skew: Option<Duration>,
+    max_ids_per_second: u32,
+}
+
+#[cfg(all(feature = "std", feature = "chrono"))]
+impl Default for SnowflakeGeneratorBuilder<DefaultTimestamp> {
+    fn default() -> Self {
+        SnowflakeGeneratorBuilder::new()
+    }
+}
+
+#[cfg(all(feature = "std", feature = "chrono"))]
+impl SnowflakeGeneratorBuilder<DefaultTimestamp> {
+    pub fn new() -> Self {
+        SnowflakeGeneratorBuilder::with_clock(DefaultTimestamp)
+    }
+}
+
+impl<T: Clock> SnowflakeGeneratorBuilder<T> {
+    pub fn with_clock(clock: T) -> Self {
+        SnowflakeGeneratorBuilder {
+            clock,
+            epoch_millis: THE_EPOCH_MILLIS,
+            machine_id: None,
+            layout: BitLayout::DEFAULT,
+            tick_unit: TickUnit::default(),
+            clock_regression_policy: ClockRegressionPolicy::default(),
+            overflow_policy: OverflowPolicy::default(),
+            memory_ordering_policy: MemoryOrderingPolicy::default(),
+            max_backward_skew: None,
+            max_ids_per_second: 0,
+        }
+    }
+
+    pub fn clock<U: Clock>(self, clock: U) -> SnowflakeGeneratorBuilder<U> {
+        SnowflakeGeneratorBuilder {
+            clock,
+            epoch_millis: self.epoch_millis,
+            machine_id: self.machine_id,
+            layout: self.layout,
+            tick_unit: self.tick_unit,
+            clock_regression_policy: self.clock_regression_policy,
+            overflow_policy: self.overflow_policy,
+            memory_ordering_policy: self.memory_ordering_policy,
+            max_backward_skew: self.max_backward_skew,
+            max_ids_per_second: self.max_ids_per_second,
+        }
+    }
+
+    // Swaps in a `HybridClock` anchored now, for deployments where NTP steps the wall clock.
+    #[cfg(all(feature = "std", feature = "chrono"))]
+    pub fn hybrid_clock(self) -> SnowflakeGeneratorBuilder<HybridClock> {
+        self.clock(HybridClock::new())
+    }
+
+    // Swaps in a `CachedTimestamp` that re-reads the wall clock every `refresh`.
+    #[cfg(all(feature = "std", feature = "chrono"))]
+    pub fn cached_clock(self, refresh: Duration) -> SnowflakeGeneratorBuilder<CachedTimestamp> {
+        self.clock(CachedTimestamp::new(refresh))
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn epoch<Tz: TimeZone>(mut self, the_epoch: DateTime<Tz>) -> Self {
+        self.epoch_millis = the_epoch.timestamp_millis();
+        self
+    }
+
+    pub fn epoch_millis(mut self, epoch_millis: i64) -> Self {
+        self.epoch_millis = epoch_millis;
+        self
+    }
+
+    pub fn machine_id(mut self, machine_id: u16) -> Self {
+        self.machine_id = Some(machine_id);
+        self
+    }
+
+    pub fn layout(mut self, layout: BitLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    pub fn tick_unit(mut self, unit: TickUnit) -> Self {
+        self.tick_unit = unit;
+        self
+    }
+
+    pub fn clock_regression_policy(mut self, policy: ClockRegressionPolicy) -> Self {
+        self.clock_regression_policy = policy;
+        self
+    }
+
+    pub fn overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    pub fn memory_ordering_policy(mut self, policy: MemoryOrderingPolicy) -> Self {
+        self.memory_ordering_policy = policy;
+        self
+    }
+
+    pub fn max_backward_skew(mut self, skew: Duration) -> Self {
+        self.max_backward_skew = Some(skew);
+        self
+    }
+
+    pub fn max_ids_per_second(mut self, max_ids_per_second: u32) -> Self {
+        self.max_ids_per_second = max_ids_per_second;
+        self
+    }
+
+    pub fn build(self) -> Result<SnowFlakeIdGenerator<T>, SnowflakeGeneratorBuilderError> {
+        let machine_id = self.machine_id.ok_or(MissingMachineId)?;
+
+        if machine_id > self.layout.max_machine_id() {
+            return Err(MachineIdOutOfRange);
+        }
+
+        let generator = SnowFlakeIdGenerator::with_epoch_millis(
+            self.clock,
+            self.epoch_millis,
+            machine_id,
+            self.layout,
+        )
+        .map_err(|e| match e {
+            SnowflakeIdEGeneratorError::EpochInFuture => EpochInFuture,
+            _ => MachineIdOutOfRange,
+        })?;
+
+        let generator = generator
+            .with_tick_unit(self.tick_unit)
+            .with_clock_regression_policy(self.clock_regression_policy)
+            .with_overflow_policy(self.overflow_policy)
+            .with_memory_ordering_policy(self.memory_ordering_policy)
+            .with_max_ids_per_second(self.max_ids_per_second);
+
+        Ok(match self.max_backward_skew {
+            Some(skew) => generator.with_max_backward_skew(skew),
+            None => generator,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "chrono"))]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::generator_policy::{
+        ClockRegressionPolicy, MemoryOrderingPolicy, OverflowPolicy, TickUnit,
+    };
+    use crate::snowflake_generator_builder::{
+        SnowflakeGeneratorBuilder, SnowflakeGeneratorBuilderError,
+    };
+    use crate::timestamp::Timestamp;
+    use crate::THE_EPOCH;
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use mockall::mock;
+    use std::sync::LazyLock;
+
+    static DISCORD_EPOCH: LazyLock<DateTime<Utc>> =
+        LazyLock::new(|| Utc::with_ymd_and_hms(&Utc, 2015, 1, 1, 0, 0, 0).unwrap());
+
+    mock! {
+    Fixture{}
+    impl Timestamp for Fixture{
+        fn timestamp(&self) -> DateTime<Utc>;
+    }
+    }
+
+    #[test]
+    fn snowflake_generator_builder_error_debug_test() {
+        let targets = [
+            SnowflakeGeneratorBuilderError::MissingMachineId,
+            SnowflakeGeneratorBuilderError::MachineIdOutOfRange,
+            SnowflakeGeneratorBuilderError::EpochInFuture,
+        ];
+        let expected = [
+            "SnowflakeGeneratorBuilderError::MissingMachineId",
+            "SnowflakeGeneratorBuilderError::MachineIdOutOfRange",
+            "SnowflakeGeneratorBuilderError::EpochInFuture",
+        ];
+
+        for elem in targets.iter().zip(expected) {
+            assert_eq!(format!("{}", elem.0), elem.1);
+            assert_eq!(format!("{:?}", elem.0), elem.1);
+        }
+    }
+
+    #[test]
+    fn default_build_test() {
+        let actual = SnowflakeGeneratorBuilder::new()
+            .machine_id(42)
+            .build()
+            .unwrap();
+
+        assert_eq!(actual.machine_id(), 42);
+        assert_eq!(actual.the_epoch(&Utc), *THE_EPOCH);
+        assert_eq!(*actual.layout(), BitLayout::DEFAULT);
+        assert_eq!(actual.tick_unit(), TickUnit::Millis);
+        assert_eq!(
+            actual.clock_regression_policy(),
+            ClockRegressionPolicy::Reject
+        );
+        assert_eq!(actual.overflow_policy(), OverflowPolicy::Error);
+        assert_eq!(
+            actual.memory_ordering_policy(),
+            MemoryOrderingPolicy::AcquireRelease
+        );
+        assert_eq!(actual.max_backward_skew(), None);
+    }
+
+    #[test]
+    fn build_test() {
+        let layout = BitLayout::new(39, 16, 8).unwrap();
+        let mut mock = MockFixture::new();
+        mock.expect_timestamp()
+            .returning(|| *DISCORD_EPOCH + Duration::milliseconds(5));
+
+        let actual = SnowflakeGeneratorBuilder::default()
+            .clock(mock)
+            .epoch(*DISCORD_EPOCH)
+            .machine_id(0xbeef)
+            .layout(layout)
+            .clock_regression_policy(ClockRegressionPolicy::UseLastTimestamp)
+            .overflow_policy(OverflowPolicy::SpinUntilNextMillis)
+            .memory_ordering_policy(MemoryOrderingPolicy::Relaxed)
+            .max_backward_skew(core::time::Duration::from_millis(50))
+            .build()
+            .unwrap();
+
+        assert_eq!(actual.machine_id(), 0xbeef);
+        assert_eq!(actual.the_epoch(&Utc), *DISCORD_EPOCH);
+        assert_eq!(*actual.layout(), layout);
+        assert_eq!(
+            actual.clock_regression_policy(),
+            ClockRegressionPolicy::UseLastTimestamp
+        );
+        assert_eq!(
+            actual.overflow_policy(),
+            OverflowPolicy::SpinUntilNextMillis
+        );
+        assert_eq!(
+            actual.memory_ordering_policy(),
+            MemoryOrderingPolicy::Relaxed
+        );
+        assert_eq!(
+            actual.max_backward_skew(),
+            Some(core::time::Duration::from_millis(50))
+        );
+
+        let id = actual.generate().unwrap();
+        assert_eq!(layout.raw_timestamp(id), 5);
+
+        let actual = SnowflakeGeneratorBuilder::new()
+            .tick_unit(TickUnit::Seconds)
+            .hybrid_clock()
+            .machine_id(1)
+            .build()
+            .unwrap();
+        assert_eq!(actual.tick_unit(), TickUnit::Seconds);
+        assert_eq!(layout.machine_id(id), 0xbeef);
+    }
+
+    #[test]
+    fn epoch_millis_test() {
+        let mut mock = MockFixture::new();
+        mock.expect_timestamp()
+            .returning(|| *DISCORD_EPOCH + Duration::milliseconds(5));
+
+        let actual = SnowflakeGeneratorBuilder::with_clock(mock)
+            .epoch_millis(DISCORD_EPOCH.timestamp_millis())
+            .machine_id(1)
+            .build()
+            .unwrap();
+
+        assert_eq!(actual.epoch_millis(), DISCORD_EPOCH.timestamp_millis());
+        assert_eq!(actual.the_epoch(&Utc), *DISCORD_EPOCH);
+    }
+
+    #[test]
+    fn hybrid_clock_build_test() {
+        let actual = SnowflakeGeneratorBuilder::new()
+            .machine_id(7)
+            .hybrid_clock()
+            .build()
+            .unwrap();
+
+        let first = actual.generate().unwrap();
+        let second = actual.generate().unwrap();
+        assert!(first < second);
+        assert_eq!(second.machine_id(), 7);
+    }
+
+    #[test]
+    fn cached_clock_build_test() {
+        let actual = SnowflakeGeneratorBuilder::new()
+            .machine_id(7)
+            .cached_clock(core::time::Duration::from_millis(100))
+            .build()
+            .unwrap();
+
+        let first = actual.generate().unwrap();
+        let second = actual.generate().unwrap();
+        assert!(first < second);
+        assert!(
+            (first.timestamp(*THE_EPOCH, &Utc) - Utc::now())
+                .num_milliseconds()
+                .abs()
+                < 1_000
+        );
+    }
+
+    #[test]
+    fn invalid_build_test() {
+        let actual = SnowflakeGeneratorBuilder::new().build();
+        assert!(matches!(
+            actual,
+            Err(SnowflakeGeneratorBuilderError::MissingMachineId)
+        ));
+
+        let actual = SnowflakeGeneratorBuilder::new().machine_id(1024).build();
+        assert!(matches!(
+            actual,
+            Err(SnowflakeGeneratorBuilderError::MachineIdOutOfRange)
+        ));
+
+        let mut mock = MockFixture::new();
+        mock.expect_timestamp()
+            .returning(|| *DISCORD_EPOCH - Duration::milliseconds(1));
+
+        let actual = SnowflakeGeneratorBuilder::new()
+            .clock(mock)
+            .epoch(*DISCORD_EPOCH)
+            .machine_id(1)
+            .build();
+        assert!(matches!(
+            actual,
+            Err(SnowflakeGeneratorBuilderError::EpochInFuture)
+        ));
+    }
+}
@@ -0,0 +1,216 @@
+use crate::bit_layout::BitLayout;
+use crate::snow_flake_id::{SnowflakeId, SnowflakeIdParseError};
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use core::cmp::Ordering;
+use core::fmt::{Display, Formatter};
+use core::hash::{Hash, Hasher};
+use core::num::IntErrorKind;
+use core::str::FromStr;
+
+const MACHINE_SHIFT: u32 = u32::BITS;
+const TIMESTAMP_SHIFT: u32 = u32::BITS * 2;
+
+// The 128-bit sibling of `SnowflakeId`: a 64-bit microsecond timestamp, a 32-bit machine
+// id and a 32-bit sequence, most significant first. Every field is full width, so any
+// combination of parts is valid and the timestamp outlives any realistic epoch.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct SnowflakeId128(u128);
+
+impl From<u128> for SnowflakeId128 {
+    fn from(value: u128) -> Self {
+        SnowflakeId128(value)
+    }
+}
+
+impl SnowflakeId128 {
+    pub const fn new(timestamp_micros: u64, machine_id: u32, sequence: u32) -> Self {
+        SnowflakeId128(
+            (timestamp_micros as u128) << TIMESTAMP_SHIFT
+                | (machine_id as u128) << MACHINE_SHIFT
+                | sequence as u128,
+        )
+    }
+
+    // Widens a 64-bit ID decoded with `layout`, keeping its epoch. The millisecond
+    // timestamp becomes the first microsecond of that millisecond, so widened IDs still
+    // sort in the same order as the originals.
+    pub fn from_snowflake(id: SnowflakeId, layout: &BitLayout) -> Option<Self> {
+        let timestamp_micros = layout.raw_timestamp(id).checked_mul(1000)?;
+
+        Some(SnowflakeId128::new(
+            timestamp_micros,
+            layout.machine_id(id) as u32,
+            layout.sequence(id) as u32,
+        ))
+    }
+
+    pub const fn raw_timestamp(&self) -> u64 {
+        (self.0 >> TIMESTAMP_SHIFT) as u64
+    }
+
+    pub const fn machine_id(&self) -> u32 {
+        (self.0 >> MACHINE_SHIFT) as u32
+    }
+
+    pub const fn sequence(&self) -> u32 {
+        self.0 as u32
+    }
+
+    pub const fn as_u128(&self) -> u128 {
+        self.0
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl SnowflakeId128 {
+    // `None` if the timestamp is beyond what chrono can represent.
+    pub fn timestamp<TzIn: TimeZone, TzOut: TimeZone>(
+        &self,
+        the_epoch: DateTime<TzIn>,
+        time_zone: &TzOut,
+    ) -> Option<DateTime<TzOut>> {
+        let micros = i64::try_from(self.raw_timestamp()).ok()?;
+
+        the_epoch
+            .with_timezone(&Utc)
+            .checked_add_signed(Duration::microseconds(micros))
+            .map(|x| x.with_timezone(time_zone))
+    }
+}
+
+impl Display for SnowflakeId128 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl FromStr for SnowflakeId128 {
+    type Err = SnowflakeIdParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<u128>() {
+            Ok(value) => Ok(SnowflakeId128(value)),
+            Err(e) => match e.kind() {
+                IntErrorKind::Empty => Err(SnowflakeIdParseError::Empty),
+                IntErrorKind::PosOverflow => Err(SnowflakeIdParseError::Overflow),
+                _ => Err(SnowflakeIdParseError::InvalidDigit),
+            },
+        }
+    }
+}
+
+impl PartialOrd for SnowflakeId128 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SnowflakeId128 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl Hash for SnowflakeId128 {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::snow_flake_id::{SnowflakeId, SnowflakeIdParseError};
+    use crate::snowflake_id_128::SnowflakeId128;
+
+    const SAMPLE_SCR: u64 = 175_928_847_299_678_215;
+
+    #[test]
+    fn new_test() {
+        let target = SnowflakeId128::new(0x0123_4567_89ab_cdef, 0xdead_beef, 0xcafe_babe);
+
+        assert_eq!(target.as_u128(), 0x0123_4567_89ab_cdef_dead_beef_cafe_babe);
+        assert_eq!(target.raw_timestamp(), 0x0123_4567_89ab_cdef);
+        assert_eq!(target.machine_id(), 0xdead_beef);
+        assert_eq!(target.sequence(), 0xcafe_babe);
+
+        let target = SnowflakeId128::new(u64::MAX, u32::MAX, u32::MAX);
+        assert_eq!(target.as_u128(), u128::MAX);
+    }
+
+    #[test]
+    fn from_snowflake_test() {
+        let actual =
+            SnowflakeId128::from_snowflake(SnowflakeId::from(SAMPLE_SCR), &BitLayout::DEFAULT)
+                .unwrap();
+
+        assert_eq!(actual.raw_timestamp(), 41_944_705_796_000);
+        assert_eq!(actual.machine_id(), 169);
+        assert_eq!(actual.sequence(), 7);
+
+        let older = SnowflakeId::new(5, 1023, 4095).unwrap();
+        let newer = SnowflakeId::new(6, 0, 0).unwrap();
+        assert!(
+            SnowflakeId128::from_snowflake(older, &BitLayout::DEFAULT)
+                < SnowflakeId128::from_snowflake(newer, &BitLayout::DEFAULT)
+        );
+
+        let layout = BitLayout::new(64, 0, 0).unwrap();
+        assert_eq!(
+            SnowflakeId128::from_snowflake(SnowflakeId::from(u64::MAX), &layout),
+            None
+        );
+    }
+
+    #[test]
+    fn display_from_str_test() {
+        let target = SnowflakeId128::new(u64::MAX, 1, 2);
+        let str = target.to_string();
+
+        assert_eq!(str, "340282366920938463444927863362353627138");
+        assert_eq!(str.parse::<SnowflakeId128>().unwrap(), target);
+
+        assert_eq!(
+            "".parse::<SnowflakeId128>(),
+            Err(SnowflakeIdParseError::Empty)
+        );
+        assert_eq!(
+            "12a".parse::<SnowflakeId128>(),
+            Err(SnowflakeIdParseError::InvalidDigit)
+        );
+        assert_eq!(
+            "340282366920938463463374607431768211456".parse::<SnowflakeId128>(),
+            Err(SnowflakeIdParseError::Overflow)
+        );
+    }
+
+    #[test]
+    fn ord_test() {
+        let a = SnowflakeId128::new(1, u32::MAX, u32::MAX);
+        let b = SnowflakeId128::new(2, 0, 0);
+        let c = SnowflakeId128::new(2, 0, 1);
+
+        let mut actual = vec![c, b, a];
+        actual.sort();
+        assert_eq!(actual, vec![a, b, c]);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn timestamp_test() {
+        use chrono::{Duration, TimeZone, Utc};
+
+        let epoch = Utc.with_ymd_and_hms(2015, 1, 1, 0, 0, 0).unwrap();
+        let target = SnowflakeId128::new(1_500_250, 0, 0);
+
+        assert_eq!(
+            target.timestamp(epoch, &Utc),
+            Some(epoch + Duration::microseconds(1_500_250))
+        );
+        assert_eq!(
+            SnowflakeId128::new(u64::MAX, 0, 0).timestamp(epoch, &Utc),
+            None
+        );
+    }
+}
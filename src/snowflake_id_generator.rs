@@ -1,94 +1,838 @@
+use crate::bit_layout::BitLayout;
+use crate::bound_id::BoundId;
+#[cfg(feature = "std")]
+use crate::clock::SystemClock;
+use crate::clock::{Clock, DynClock};
+#[cfg(feature = "std")]
+use crate::config::{Config, ConfigError};
+use crate::decoder::Decoder;
+use crate::generator_metrics;
+use crate::generator_metrics::{GeneratorStats, StatsCounters};
+use crate::generator_policy::{
+    ClockRegressionPolicy, MemoryOrderingPolicy, OverflowPolicy, TickUnit,
+};
+use crate::rate_limit::RateLimiter;
 use crate::snow_flake_id::SnowflakeId;
+#[cfg(feature = "chrono")]
+use crate::snow_flake_id::{datacenter_machine_id, MAX_DATACENTER_ID, MAX_WORKER_ID};
 use crate::snowflake_error::SnowflakeIdEGeneratorError;
-use crate::snowflake_error::SnowflakeIdEGeneratorError::MachineIdOutOfRange;
-use crate::timestamp::Timestamp;
+use crate::snowflake_error::SnowflakeIdEGeneratorError::{
+    ClockMovedBackwards, Contended, EpochInFuture, MachineIdOutOfRange, RateLimited,
+    SequenceExhausted, TimestampOverflow,
+};
+use crate::sync::{AtomicU64, CachePadded};
+use alloc::boxed::Box;
+#[cfg(feature = "chrono")]
 use chrono::{DateTime, TimeZone, Utc};
-use std::sync::atomic::AtomicU64;
-use std::sync::atomic::Ordering::Relaxed;
+use core::fmt::{Debug, Formatter};
+use core::ops::Range;
+use core::time::Duration;
 
-const MAX_MACHINE_ID: u16 = 1023;
-const MAX_INCLEMENT_NUMBER: u16 = 4095;
+// The clock can still end up behind the epoch after construction (e.g. a manually
+// adjusted system clock), and a long-lived generator can outrun the timestamp field.
+pub(crate) fn elapsed_millis(
+    now_millis: i64,
+    epoch_millis: i64,
+    layout: &BitLayout,
+) -> Result<u64, SnowflakeIdEGeneratorError> {
+    let elapsed = now_millis as i128 - epoch_millis as i128;
 
-pub struct SnowFlakeIdGenerator<T: Timestamp> {
+    if elapsed < 0 {
+        Err(EpochInFuture)
+    } else {
+        within_timestamp(elapsed as u128, layout)
+    }
+}
+
+// Like `elapsed_millis`, for generators ticking in some other unit.
+pub(crate) fn elapsed_ticks(
+    now_micros: i64,
+    epoch_millis: i64,
+    unit: TickUnit,
+    layout: &BitLayout,
+) -> Result<u64, SnowflakeIdEGeneratorError> {
+    let elapsed = now_micros as i128 - epoch_millis as i128 * 1_000;
+
+    if elapsed < 0 {
+        Err(EpochInFuture)
+    } else {
+        within_timestamp(elapsed as u128 / unit.as_micros() as u128, layout)
+    }
+}
+
+fn within_timestamp(ticks: u128, layout: &BitLayout) -> Result<u64, SnowflakeIdEGeneratorError> {
+    let max = layout.max_timestamp();
+
+    if ticks > max as u128 {
+        Err(TimestampOverflow {
+            value: u64::try_from(ticks).unwrap_or(u64::MAX),
+            max,
+        })
+    } else {
+        Ok(ticks as u64)
+    }
+}
+
+// A generator whose clock type is erased, for keeping generators over different clocks
+// in one collection or behind one signature. Costs a virtual call per clock read.
+pub type DynGenerator = SnowFlakeIdGenerator<DynClock>;
+
+pub struct SnowFlakeIdGenerator<T: Clock> {
     timestamp: T,
-    the_epoch: DateTime<Utc>,
+    epoch_millis: i64,
     machine_id: u16,
-    recent: AtomicU64,
+    layout: BitLayout,
+    tick_unit: TickUnit,
+    clock_regression_policy: ClockRegressionPolicy,
+    overflow_policy: OverflowPolicy,
+    memory_ordering_policy: MemoryOrderingPolicy,
+    max_backward_skew: Option<u64>,
+    rate_limiter: Option<CachePadded<RateLimiter>>,
+    // Both are written on every ID; padding keeps them off the line holding the
+    // configuration above, and off each other's.
+    recent: CachePadded<AtomicU64>,
+    stats: CachePadded<StatsCounters>,
 }
 
-impl<T: Timestamp> SnowFlakeIdGenerator<T> {
+impl<T: Clock> SnowFlakeIdGenerator<T> {
+    #[cfg(feature = "chrono")]
     pub fn new<Tz: TimeZone>(
         timestamp: T,
         the_epoch: DateTime<Tz>,
         machine_id: u16,
     ) -> Result<Self, SnowflakeIdEGeneratorError> {
-        if machine_id > MAX_MACHINE_ID {
-            Err(MachineIdOutOfRange)
+        Self::with_layout(timestamp, the_epoch, machine_id, BitLayout::DEFAULT)
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn with_layout<Tz: TimeZone>(
+        timestamp: T,
+        the_epoch: DateTime<Tz>,
+        machine_id: u16,
+        layout: BitLayout,
+    ) -> Result<Self, SnowflakeIdEGeneratorError> {
+        Self::with_epoch_millis(timestamp, the_epoch.timestamp_millis(), machine_id, layout)
+    }
+
+    // Twitter-style construction with the machine field split into a 5-bit datacenter and a
+    // 5-bit worker id; decode them with `SnowflakeId::datacenter_id`/`worker_id`.
+    #[cfg(feature = "chrono")]
+    pub fn with_datacenter<Tz: TimeZone>(
+        timestamp: T,
+        the_epoch: DateTime<Tz>,
+        datacenter_id: u16,
+        worker_id: u16,
+    ) -> Result<Self, SnowflakeIdEGeneratorError> {
+        let machine_id = datacenter_machine_id(datacenter_id, worker_id).ok_or(
+            if datacenter_id > MAX_DATACENTER_ID {
+                MachineIdOutOfRange {
+                    value: datacenter_id as u64,
+                    max: MAX_DATACENTER_ID as u64,
+                }
+            } else {
+                MachineIdOutOfRange {
+                    value: worker_id as u64,
+                    max: MAX_WORKER_ID as u64,
+                }
+            },
+        )?;
+        Self::new(timestamp, the_epoch, machine_id)
+    }
+
+    pub fn with_epoch_millis(
+        timestamp: T,
+        epoch_millis: i64,
+        machine_id: u16,
+        layout: BitLayout,
+    ) -> Result<Self, SnowflakeIdEGeneratorError> {
+        if machine_id > layout.max_machine_id() {
+            Err(MachineIdOutOfRange {
+                value: machine_id as u64,
+                max: layout.max_machine_id() as u64,
+            })
+        } else if timestamp.now_millis() < epoch_millis {
+            Err(EpochInFuture)
         } else {
             Ok(SnowFlakeIdGenerator::<T> {
                 timestamp,
-                the_epoch: the_epoch.with_timezone(&Utc),
+                epoch_millis,
                 machine_id,
-                recent: AtomicU64::new(0),
+                layout,
+                tick_unit: TickUnit::default(),
+                clock_regression_policy: ClockRegressionPolicy::default(),
+                overflow_policy: OverflowPolicy::default(),
+                memory_ordering_policy: MemoryOrderingPolicy::default(),
+                max_backward_skew: None,
+                rate_limiter: None,
+                recent: CachePadded::new(AtomicU64::new(0)),
+                stats: CachePadded::default(),
             })
         }
     }
 
+    // Boxes the clock, keeping the settings and the state issued so far.
+    pub fn into_dyn(self) -> DynGenerator
+    where
+        T: Send + Sync + 'static,
+    {
+        SnowFlakeIdGenerator::<DynClock> {
+            timestamp: Box::new(self.timestamp),
+            epoch_millis: self.epoch_millis,
+            machine_id: self.machine_id,
+            layout: self.layout,
+            tick_unit: self.tick_unit,
+            clock_regression_policy: self.clock_regression_policy,
+            overflow_policy: self.overflow_policy,
+            memory_ordering_policy: self.memory_ordering_policy,
+            max_backward_skew: self.max_backward_skew,
+            rate_limiter: self.rate_limiter,
+            recent: self.recent,
+            stats: self.stats,
+        }
+    }
+
+    // The same clock and settings under another machine ID, carrying on from this
+    // generator's last ID so the timestamps never step back. Stats start over.
+    #[cfg(all(feature = "fork", unix))]
+    pub(crate) fn rebind(&self, machine_id: u16) -> Result<Self, SnowflakeIdEGeneratorError>
+    where
+        T: Clone,
+    {
+        if machine_id > self.layout.max_machine_id() {
+            return Err(MachineIdOutOfRange {
+                value: machine_id as u64,
+                max: self.layout.max_machine_id() as u64,
+            });
+        }
+
+        Ok(SnowFlakeIdGenerator::<T> {
+            timestamp: self.timestamp.clone(),
+            epoch_millis: self.epoch_millis,
+            machine_id,
+            layout: self.layout,
+            tick_unit: self.tick_unit,
+            clock_regression_policy: self.clock_regression_policy,
+            overflow_policy: self.overflow_policy,
+            memory_ordering_policy: self.memory_ordering_policy,
+            max_backward_skew: self.max_backward_skew,
+            rate_limiter: self
+                .rate_limiter
+                .as_ref()
+                .map(|x| CachePadded::new(RateLimiter::new(x.max_per_second()))),
+            recent: CachePadded::new(AtomicU64::new(self.snapshot().as_u64())),
+            stats: CachePadded::default(),
+        })
+    }
+
+    #[cfg(feature = "chrono")]
     pub fn the_epoch<Tz: TimeZone>(&self, time_zone: &Tz) -> DateTime<Tz> {
-        self.the_epoch.with_timezone(time_zone)
+        DateTime::<Utc>::from_timestamp_millis(self.epoch_millis)
+            .unwrap()
+            .with_timezone(time_zone)
+    }
+
+    pub fn epoch_millis(&self) -> i64 {
+        self.epoch_millis
+    }
+
+    // The first tick the timestamp field can no longer represent; from then on generating
+    // fails with `TimestampOverflow`. `None` if that lies beyond what chrono can represent,
+    // which only happens for layouts with very wide timestamp fields or coarse ticks.
+    #[cfg(feature = "chrono")]
+    pub fn exhaustion_date(&self) -> Option<DateTime<Utc>> {
+        let remaining = (self.layout.max_timestamp() as i128 + 1)
+            .checked_mul(self.tick_unit.as_micros() as i128)?;
+        let micros = i64::try_from(self.epoch_millis as i128 * 1_000 + remaining).ok()?;
+
+        DateTime::<Utc>::from_timestamp_micros(micros)
+    }
+
+    pub fn clock(&self) -> &T {
+        &self.timestamp
     }
 
     pub fn machine_id(&self) -> u16 {
         self.machine_id
     }
 
-    fn calc_timestamp(&self, scr: DateTime<Utc>) -> u64 {
-        let diff = scr - self.the_epoch;
-        diff.num_milliseconds() as u64
+    pub fn layout(&self) -> &BitLayout {
+        &self.layout
+    }
+
+    // Sets what one step of the timestamp field stands for; see `TickUnit`. IDs from
+    // generators with different units must not be mixed.
+    pub fn with_tick_unit(mut self, unit: TickUnit) -> Self {
+        self.tick_unit = unit;
+        self
+    }
+
+    pub fn tick_unit(&self) -> TickUnit {
+        self.tick_unit
+    }
+
+    pub fn with_clock_regression_policy(mut self, policy: ClockRegressionPolicy) -> Self {
+        self.clock_regression_policy = policy;
+        self
+    }
+
+    pub fn clock_regression_policy(&self) -> ClockRegressionPolicy {
+        self.clock_regression_policy
+    }
+
+    pub fn with_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.overflow_policy
+    }
+
+    pub fn with_memory_ordering_policy(mut self, policy: MemoryOrderingPolicy) -> Self {
+        self.memory_ordering_policy = policy;
+        self
+    }
+
+    pub fn memory_ordering_policy(&self) -> MemoryOrderingPolicy {
+        self.memory_ordering_policy
+    }
+
+    // Bounds how far back the clock may step before the generator gives up. A regression
+    // within the window is handled by the clock regression policy (e.g. waiting out an NTP
+    // slew); a larger one fails with `ClockMovedBackwards` whatever the policy. Unbounded
+    // by default. The window is tracked in whole milliseconds.
+    pub fn with_max_backward_skew(mut self, skew: Duration) -> Self {
+        self.max_backward_skew = Some(u64::try_from(skew.as_millis()).unwrap_or(u64::MAX));
+        self
+    }
+
+    pub fn max_backward_skew(&self) -> Option<Duration> {
+        self.max_backward_skew.map(Duration::from_millis)
+    }
+
+    // Caps issuance at `max_ids_per_second`, spread evenly: past the rate, generating fails
+    // with `RateLimited` (`generate_async` waits instead), and idle time does not build up
+    // credit for a later burst. Batches and blocks are charged for every ID asked for.
+    // Zero removes the limit.
+    pub fn with_max_ids_per_second(mut self, max_ids_per_second: u32) -> Self {
+        self.rate_limiter = (max_ids_per_second > 0)
+            .then(|| CachePadded::new(RateLimiter::new(max_ids_per_second)));
+        self
+    }
+
+    pub fn max_ids_per_second(&self) -> Option<u32> {
+        self.rate_limiter.as_ref().map(|x| x.max_per_second())
+    }
+
+    // Ok if `count` IDs may be issued now, otherwise the nanoseconds to wait.
+    fn admit(&self, count: usize) -> Result<(), u64> {
+        match &self.rate_limiter {
+            None => Ok(()),
+            Some(limiter) => {
+                let now_nanos = self.timestamp.now_micros().max(0) as u64 * 1_000;
+                limiter.acquire(count as u64, now_nanos)
+            }
+        }
+    }
+
+    // Gives back admitted IDs that were never issued: a failed reservation, a block cut
+    // short at the end of a millisecond, or a collection that stopped early.
+    fn refund(&self, count: usize) {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.release(count as u64);
+        }
+    }
+
+    fn take_admitted(&self, count: usize) -> Result<IdBlock, SnowflakeIdEGeneratorError> {
+        let block = self.take_block(count);
+        self.refund(count - block.as_ref().map_or(0, |x| x.remaining()));
+        block
+    }
+
+    pub fn config(&self) -> GeneratorConfig {
+        GeneratorConfig {
+            epoch_millis: self.epoch_millis,
+            machine_id: self.machine_id,
+            layout: self.layout,
+            tick_unit: self.tick_unit,
+            clock_regression_policy: self.clock_regression_policy,
+            overflow_policy: self.overflow_policy,
+            memory_ordering_policy: self.memory_ordering_policy,
+            max_backward_skew_millis: self.max_backward_skew,
+            max_ids_per_second: self.max_ids_per_second(),
+        }
+    }
+
+    // A decoder matching this generator's epoch, layout and tick unit.
+    pub fn decoder(&self) -> Decoder {
+        Decoder::new(self.epoch_millis, self.layout).with_tick_unit(self.tick_unit)
+    }
+
+    // The last issued ID. Feeding it to `restore` on a fresh generator keeps a restarted
+    // process from reissuing IDs within the same millisecond or after a clock regression.
+    pub fn snapshot(&self) -> SnowflakeId {
+        SnowflakeId::from(self.recent.load(self.memory_ordering_policy.load()))
+    }
+
+    pub fn restore(&self, snapshot: SnowflakeId) {
+        self.recent.fetch_max(
+            snapshot.as_u64(),
+            self.memory_ordering_policy.read_modify_write(),
+        );
+    }
+
+    // `max_timestamp` is the raw timestamp field, in ticks since the epoch.
+    pub fn stats(&self) -> GeneratorStats {
+        self.stats.snapshot()
+    }
+
+    fn calc_timestamp(&self, now_millis: i64) -> Result<u64, SnowflakeIdEGeneratorError> {
+        elapsed_millis(now_millis, self.epoch_millis, &self.layout)
+    }
+
+    fn now_ticks(&self) -> Result<u64, SnowflakeIdEGeneratorError> {
+        match self.tick_unit {
+            TickUnit::Millis => self.calc_timestamp(self.timestamp.now_millis()),
+            unit => elapsed_ticks(
+                self.timestamp.now_micros(),
+                self.epoch_millis,
+                unit,
+                &self.layout,
+            ),
+        }
+    }
+
+    fn ticks_to_micros(&self, ticks: u64) -> u64 {
+        ticks.saturating_mul(self.tick_unit.as_micros())
+    }
+
+    fn current_timestamp(&self, last: u64) -> Result<u64, SnowflakeIdEGeneratorError> {
+        loop {
+            let now = self.now_ticks()?;
+
+            if now >= last {
+                return Ok(now);
+            }
+
+            if self
+                .max_backward_skew
+                .is_some_and(|skew| self.ticks_to_micros(last - now) > skew.saturating_mul(1_000))
+            {
+                generator_metrics::record_clock_regression();
+                self.stats.record_clock_regression();
+                return Err(ClockMovedBackwards);
+            }
+
+            if self.overflow_policy == OverflowPolicy::BorrowFromFuture {
+                return Ok(last);
+            }
+
+            generator_metrics::record_clock_regression();
+            self.stats.record_clock_regression();
+
+            match self.clock_regression_policy {
+                ClockRegressionPolicy::Reject => return Err(ClockMovedBackwards),
+                ClockRegressionPolicy::UseLastTimestamp => return Ok(last),
+                #[cfg(feature = "std")]
+                ClockRegressionPolicy::WaitUntilCaughtUp => std::thread::sleep(
+                    std::time::Duration::from_micros(self.ticks_to_micros(last - now)),
+                ),
+                #[cfg(not(feature = "std"))]
+                ClockRegressionPolicy::WaitUntilCaughtUp => core::hint::spin_loop(),
+            }
+        }
+    }
+
+    fn next_timestamp(&self, last: u64) -> Result<u64, SnowflakeIdEGeneratorError> {
+        loop {
+            let now = self.current_timestamp(last)?;
+
+            if now > last {
+                return Ok(now);
+            }
+
+            core::hint::spin_loop();
+        }
     }
 
-    fn try_inclement(scr: u16) -> Option<u16> {
-        if scr >= MAX_INCLEMENT_NUMBER {
+    fn next_sequence(&self, scr: u16) -> Option<u16> {
+        if scr >= self.layout.max_sequence() {
             None
         } else {
             Some(scr + 1)
         }
     }
 
-    pub fn generate(&self) -> Option<SnowflakeId> {
-        let pivot = SnowflakeId::from(self.recent.load(Relaxed));
-        let now = self.calc_timestamp(self.timestamp.timestamp());
+    fn reserve(
+        &self,
+        count: usize,
+    ) -> Result<(SnowflakeId, SnowflakeId), SnowflakeIdEGeneratorError> {
+        let ordering = self.memory_ordering_policy;
+        let pivot = SnowflakeId::from(self.recent.load(ordering.load()));
+        let last_timestamp = self.layout.raw_timestamp(pivot);
+        let now = self.current_timestamp(last_timestamp)?;
+
+        let (now, first) = if last_timestamp == now {
+            match self.next_sequence(self.layout.sequence(pivot)) {
+                Some(sequence) => (now, sequence),
+                None => {
+                    generator_metrics::record_sequence_exhausted();
+                    self.stats.record_sequence_exhausted();
 
-        let inclement = if pivot.raw_timestamp() == now {
-            Self::try_inclement(pivot.inclement())?
+                    match self.overflow_policy {
+                        OverflowPolicy::Error => return Err(SequenceExhausted),
+                        OverflowPolicy::SpinUntilNextMillis => (self.next_timestamp(now)?, 0),
+                        OverflowPolicy::BorrowFromFuture if now < self.layout.max_timestamp() => {
+                            (now + 1, 0)
+                        }
+                        OverflowPolicy::BorrowFromFuture => {
+                            return Err(TimestampOverflow {
+                                value: now + 1,
+                                max: self.layout.max_timestamp(),
+                            })
+                        }
+                    }
+                }
+            }
         } else {
-            0
+            (now, 0)
         };
 
-        let candidate = SnowflakeId::new(now, self.machine_id, inclement).unwrap();
+        let available = (self.layout.max_sequence() - first) as usize + 1;
+        let last = first + (count.min(available) - 1) as u16;
+
+        let candidate = SnowflakeId::with_layout(now, self.machine_id, last, &self.layout).unwrap();
 
         match self.recent.compare_exchange_weak(
             pivot.as_u64(),
             candidate.as_u64(),
-            Relaxed,
-            Relaxed,
+            ordering.read_modify_write(),
+            ordering.load(),
         ) {
-            Ok(_) => Some(candidate),
-            Err(_) => None,
+            Ok(_) => {
+                generator_metrics::record_generated((last - first) as u64 + 1);
+                self.stats.record_generated((last - first) as u64 + 1, now);
+
+                Ok((
+                    SnowflakeId::with_layout(now, self.machine_id, first, &self.layout).unwrap(),
+                    candidate,
+                ))
+            }
+            Err(_) => {
+                generator_metrics::record_cas_retry();
+                self.stats.record_cas_failure();
+                Err(Contended)
+            }
+        }
+    }
+
+    // A lost race is retried here, so callers never see `Contended`.
+    pub fn try_generate(&self) -> Result<SnowflakeId, SnowflakeIdEGeneratorError> {
+        if self.rate_limiter.is_none() {
+            return self.take_block(1).map(|mut block| block.next().unwrap());
+        }
+
+        self.admit(1).map_err(|_| RateLimited)?;
+
+        // The slot is paid for, and a failure hands it back.
+        self.take_admitted(1).map(|mut block| block.next().unwrap())
+    }
+
+    pub fn generate(&self) -> Option<SnowflakeId> {
+        self.try_generate().ok()
+    }
+
+    pub fn try_generate_bound(&self) -> Result<BoundId, SnowflakeIdEGeneratorError> {
+        self.try_generate()
+            .map(|id| BoundId::new(id, self.decoder()))
+    }
+
+    pub fn generate_bound(&self) -> Option<BoundId> {
+        self.try_generate_bound().ok()
+    }
+
+    #[cfg(feature = "tokio")]
+    pub async fn generate_async(&self) -> Result<SnowflakeId, SnowflakeIdEGeneratorError> {
+        self.admit_async(1).await;
+
+        loop {
+            match self.take_block(1) {
+                Ok(mut block) => return Ok(block.next().unwrap()),
+                Err(SequenceExhausted) => {
+                    tokio::time::sleep(std::time::Duration::from_millis(1)).await
+                }
+                Err(e) => {
+                    self.refund(1);
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    async fn admit_async(&self, count: usize) {
+        while let Err(wait) = self.admit(count) {
+            tokio::time::sleep(std::time::Duration::from_nanos(wait)).await
+        }
+    }
+
+    // Collects `count` IDs, waiting out exhausted milliseconds. A count larger than what is
+    // left in the current millisecond spills into the following ones, so the IDs ascend but
+    // are not necessarily contiguous.
+    #[cfg(feature = "tokio")]
+    pub async fn generate_many_async(
+        &self,
+        count: usize,
+    ) -> Result<Vec<SnowflakeId>, SnowflakeIdEGeneratorError> {
+        self.admit_async(count).await;
+        let mut ids = Vec::with_capacity(count);
+
+        while ids.len() < count {
+            match self.take_block(count - ids.len()) {
+                Ok(block) => ids.extend(block),
+                Err(SequenceExhausted) => {
+                    tokio::time::sleep(std::time::Duration::from_millis(1)).await
+                }
+                Err(e) => {
+                    self.refund(count - ids.len());
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(ids)
+    }
+
+    // Collects up to `count` IDs like `generate_many_async`, but busy-waits for the next
+    // millisecond and gives up at `deadline`, returning the IDs gathered so far. A clock
+    // regression is waited out as well; any other error ends the collection early. With a
+    // rate limit the whole count is admitted up front, and nothing is returned if the
+    // limiter's wait runs past the deadline; whatever is not collected is handed back.
+    #[cfg(feature = "std")]
+    pub fn generate_until(&self, count: usize, deadline: std::time::Instant) -> Vec<SnowflakeId> {
+        let mut ids = Vec::new();
+
+        while let Err(wait) = self.admit(count) {
+            let wait = std::time::Duration::from_nanos(wait);
+            if std::time::Instant::now() + wait >= deadline {
+                return ids;
+            }
+            std::thread::sleep(wait);
+        }
+
+        ids.reserve(count);
+
+        while ids.len() < count && std::time::Instant::now() < deadline {
+            match self.take_block(count - ids.len()) {
+                Ok(block) => ids.extend(block),
+                Err(SequenceExhausted | ClockMovedBackwards) => core::hint::spin_loop(),
+                Err(_) => break,
+            }
+        }
+
+        self.refund(count - ids.len());
+        ids
+    }
+
+    // Reserves up to `count` consecutive IDs in one CAS. Like `reserve_block`, the batch
+    // never spans milliseconds, so it is cut short at the end of the current one; check
+    // its `len` and ask again for the rest. A lost race is retried, not reported.
+    pub fn generate_batch(&self, count: usize) -> Option<SnowflakeIdBatch> {
+        if count == 0 {
+            return Some(SnowflakeIdBatch { range: 0..0 });
+        }
+
+        self.admit(count).ok()?;
+
+        let (first, last) = self.take_admitted(count).ok()?.bounds()?;
+        let batch = SnowflakeIdBatch {
+            range: first.as_u64()..last.as_u64() + 1,
+        };
+
+        generator_metrics::record_batch_size(batch.len());
+        Some(batch)
+    }
+
+    // Reserves up to `size` consecutive IDs in one CAS and hands them out as an `IdBlock`.
+    // A block never spans milliseconds, so it holds at most the sequence numbers left in
+    // the current one; ask again for the rest.
+    pub fn reserve_block(&self, size: usize) -> Result<IdBlock, SnowflakeIdEGeneratorError> {
+        if size == 0 {
+            return Ok(IdBlock::default());
+        }
+
+        self.admit(size).map_err(|_| RateLimited)?;
+        self.take_admitted(size)
+    }
+
+    fn take_block(&self, size: usize) -> Result<IdBlock, SnowflakeIdEGeneratorError> {
+        loop {
+            match self.reserve(size) {
+                Ok((first, last)) => return Ok(IdBlock::new(first, last)),
+                Err(Contended) => core::hint::spin_loop(),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+// The clock is left out, so generators over clocks without a `Debug` impl still print.
+impl<T: Clock> Debug for SnowFlakeIdGenerator<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SnowFlakeIdGenerator")
+            .field("config", &self.config())
+            .field("last_id", &self.snapshot())
+            .finish()
+    }
+}
+
+// Everything that shapes the IDs a generator issues, for logging at startup and diffing
+// across deployments.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GeneratorConfig {
+    pub epoch_millis: i64,
+    pub machine_id: u16,
+    pub layout: BitLayout,
+    pub tick_unit: TickUnit,
+    pub clock_regression_policy: ClockRegressionPolicy,
+    pub overflow_policy: OverflowPolicy,
+    pub memory_ordering_policy: MemoryOrderingPolicy,
+    pub max_backward_skew_millis: Option<u64>,
+    pub max_ids_per_second: Option<u32>,
+}
+
+#[cfg(feature = "std")]
+impl SnowFlakeIdGenerator<SystemClock> {
+    // Configures the generator from `SNOWFLAKE_MACHINE_ID` and `SNOWFLAKE_EPOCH`; see
+    // `Config::from_env`.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Config::from_env()?.build(SystemClock)
+    }
+}
+
+// IDs reserved from a generator, handed out locally without touching its shared state.
+// The block is a plain pair of integers, so it can be moved to a worker thread or sent to
+// another service (via `bounds` and `IdBlock::from_bounds`) and drained offline.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct IdBlock {
+    next: u64,
+    // A count rather than an end, so a block ending at `u64::MAX` still fits.
+    remaining: u64,
+}
+
+impl IdBlock {
+    // `first` and `last` come from one `reserve`, so they share a tick and a machine ID.
+    pub(crate) fn new(first: SnowflakeId, last: SnowflakeId) -> Self {
+        IdBlock {
+            next: first.as_u64(),
+            remaining: last.as_u64() - first.as_u64() + 1,
+        }
+    }
+
+    // Rebuilds a block from its `bounds`, e.g. on the service it was sent to. `None` unless
+    // both IDs share a tick and a machine ID under `layout` and `first` is not past `last`.
+    pub fn from_bounds(first: SnowflakeId, last: SnowflakeId, layout: &BitLayout) -> Option<Self> {
+        if first > last
+            || layout.raw_timestamp(first) != layout.raw_timestamp(last)
+            || layout.machine_id(first) != layout.machine_id(last)
+        {
+            return None;
         }
+
+        Some(IdBlock::new(first, last))
+    }
+
+    // The first and last ID left in the block.
+    pub fn bounds(&self) -> Option<(SnowflakeId, SnowflakeId)> {
+        (self.remaining > 0).then(|| {
+            (
+                SnowflakeId::from(self.next),
+                SnowflakeId::from(self.next + (self.remaining - 1)),
+            )
+        })
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.remaining as usize
+    }
+}
+
+impl Iterator for IdBlock {
+    type Item = SnowflakeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let id = SnowflakeId::from(self.next);
+        self.remaining -= 1;
+        // Only wraps past the last ID of a block ending at `u64::MAX`.
+        self.next = self.next.wrapping_add(1);
+        Some(id)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining(), Some(self.remaining()))
+    }
+}
+
+impl ExactSizeIterator for IdBlock {}
+
+pub struct SnowflakeIdBatch {
+    range: Range<u64>,
+}
+
+impl Iterator for SnowflakeIdBatch {
+    type Item = SnowflakeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.next().map(SnowflakeId::from)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.range.end - self.range.start) as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for SnowflakeIdBatch {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.range.next_back().map(SnowflakeId::from)
     }
 }
 
-#[cfg(test)]
+impl ExactSizeIterator for SnowflakeIdBatch {}
+
+#[cfg(all(test, not(snowflake_loom), feature = "std", feature = "chrono"))]
 mod tests {
     use super::super::timestamp::Timestamp;
+    use crate::bit_layout::BitLayout;
+    use crate::clock::SystemClock;
+    use crate::decoder::Decoder;
+    use crate::generator_metrics::GeneratorStats;
+    use crate::generator_policy::{
+        ClockRegressionPolicy, MemoryOrderingPolicy, OverflowPolicy, TickUnit,
+    };
+    use crate::snow_flake_id::SnowflakeId;
     use crate::snowflake_error::SnowflakeIdEGeneratorError;
-    use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+    use crate::snowflake_error::SnowflakeIdEGeneratorError::{
+        ClockMovedBackwards, RateLimited, SequenceExhausted,
+    };
+    use crate::snowflake_id_generator::{
+        elapsed_ticks, DynGenerator, GeneratorConfig, IdBlock, SnowFlakeIdGenerator,
+    };
+    use crate::testing::ManualClock;
     use crate::timestamp::DefaultTimestamp;
     use chrono::{DateTime, Duration, FixedOffset, TimeZone, Utc};
     use mockall::mock;
     use std::ops::AddAssign;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::Relaxed;
     use std::sync::LazyLock;
+    use std::thread;
+    use std::time::Instant;
 
     const EXPECTED_RAW_TIMESTAMP: u64 = 41_944_705_796;
 
@@ -103,7 +847,7 @@ mod tests {
         LazyLock::new(|| Utc::with_ymd_and_hms(&Utc, 2015, 1, 1, 0, 0, 0).unwrap());
 
     static THE_EPOCH: LazyLock<DateTime<Utc>> =
-        LazyLock::new(|| Utc::with_ymd_and_hms(&Utc, 1970, 01, 01, 0, 0, 0).unwrap());
+        LazyLock::new(|| Utc::with_ymd_and_hms(&Utc, 1970, 1, 1, 0, 0, 0).unwrap());
 
     mock! {
     Fixture{}
@@ -116,10 +860,37 @@ mod tests {
 
     #[test]
     fn calc_timestamp_test() {
-        let fixture = MockGen::new(MockFixture::new(), *DISCORD_EPOCH, 1).unwrap();
+        let mut mock = MockFixture::new();
+        mock.expect_timestamp().returning(|| *EXPECTED_TIMESTAMP);
+        let fixture = MockGen::new(mock, *DISCORD_EPOCH, 1).unwrap();
+
+        let actual = fixture.calc_timestamp(EXPECTED_TIMESTAMP.timestamp_millis());
+        assert_eq!(actual, Ok(EXPECTED_RAW_TIMESTAMP));
+
+        let actual = fixture.calc_timestamp(DISCORD_EPOCH.timestamp_millis() - 1);
+        assert_eq!(actual, Err(SnowflakeIdEGeneratorError::EpochInFuture));
+
+        let max = BitLayout::DEFAULT.max_timestamp() as i64;
+        let actual = fixture.calc_timestamp(DISCORD_EPOCH.timestamp_millis() + max);
+        assert_eq!(actual, Ok(max as u64));
+
+        let actual = fixture.calc_timestamp(DISCORD_EPOCH.timestamp_millis() + max + 1);
+        assert_eq!(
+            actual,
+            Err(SnowflakeIdEGeneratorError::TimestampOverflow {
+                value: max as u64 + 1,
+                max: max as u64
+            })
+        );
+
+        let actual = fixture.calc_timestamp(i64::MIN);
+        assert_eq!(actual, Err(SnowflakeIdEGeneratorError::EpochInFuture));
 
-        let actual = fixture.calc_timestamp(*EXPECTED_TIMESTAMP);
-        assert_eq!(actual, EXPECTED_RAW_TIMESTAMP);
+        let actual = fixture.calc_timestamp(i64::MAX);
+        assert!(matches!(
+            actual,
+            Err(SnowflakeIdEGeneratorError::TimestampOverflow { .. })
+        ));
     }
 
     #[test]
@@ -148,68 +919,267 @@ mod tests {
     }
 
     #[test]
-    fn machine_id_test() {
-        let target =
-            SnowFlakeIdGenerator::<DefaultTimestamp>::new(DefaultTimestamp, *THE_EPOCH, 42)
-                .unwrap();
-        assert_eq!(target.machine_id(), 42);
+    fn with_epoch_millis_test() {
+        let target = SnowFlakeIdGenerator::with_epoch_millis(
+            SystemClock,
+            DISCORD_EPOCH.timestamp_millis(),
+            42,
+            BitLayout::DEFAULT,
+        )
+        .unwrap();
+
+        assert_eq!(target.epoch_millis(), DISCORD_EPOCH.timestamp_millis());
+        assert_eq!(target.the_epoch(&Utc), *DISCORD_EPOCH);
+
+        let id = target.generate().unwrap();
+        assert_eq!(id.machine_id(), 42);
     }
 
     #[test]
-    fn invalid_machine_id_test() {
-        let target =
-            SnowFlakeIdGenerator::<DefaultTimestamp>::new(DefaultTimestamp, *THE_EPOCH, 1024);
+    fn snapshot_restore_test() {
+        let mut mock = MockFixture::new();
+        mock.expect_timestamp().returning(|| *EXPECTED_TIMESTAMP);
 
-        match target {
-            Ok(_) => unreachable!(),
-            Err(e) => match e {
-                SnowflakeIdEGeneratorError::MachineIdOutOfRange => assert!(true),
-            },
-        }
+        let gen = SnowFlakeIdGenerator::new(mock, *DISCORD_EPOCH, 1).unwrap();
+        assert_eq!(gen.snapshot().as_u64(), 0);
+
+        gen.generate().unwrap();
+        let last = gen.generate().unwrap();
+        assert_eq!(gen.snapshot(), last);
+
+        let mut mock = MockFixture::new();
+        mock.expect_timestamp().returning(|| *EXPECTED_TIMESTAMP);
+
+        let restored = SnowFlakeIdGenerator::new(mock, *DISCORD_EPOCH, 1).unwrap();
+        restored.restore(gen.snapshot());
+        restored.restore(SnowflakeId::from(0u64));
+
+        let actual = restored.generate().unwrap();
+        assert_eq!(actual.raw_timestamp(), EXPECTED_RAW_TIMESTAMP);
+        assert_eq!(actual.sequence(), last.sequence() + 1);
     }
 
     #[test]
-    fn generate_test() {
-        let mut mock = MockFixture::new();
+    fn cache_padding_test() {
+        // Generators stored side by side must not share a cache line.
+        assert!(core::mem::align_of::<SnowFlakeIdGenerator<SystemClock>>() >= 64);
+        assert_eq!(
+            core::mem::size_of::<SnowFlakeIdGenerator<SystemClock>>() % 64,
+            0
+        );
+    }
 
-        mock.expect_timestamp()
-            .times(4097)
-            .returning(|| *EXPECTED_TIMESTAMP);
+    #[test]
+    fn stats_test() {
+        let mut mock = MockFixture::new();
+        mock.expect_timestamp().returning(|| *EXPECTED_TIMESTAMP);
 
         let gen = SnowFlakeIdGenerator::new(mock, *DISCORD_EPOCH, 1).unwrap();
+        assert_eq!(gen.stats(), GeneratorStats::default());
 
-        for i in 0..4096u16 {
-            let actual = gen.generate().unwrap();
-            assert_eq!(actual.inclement(), i);
-            assert_eq!(actual.raw_timestamp(), EXPECTED_RAW_TIMESTAMP);
+        assert_eq!(gen.generate_batch(4000).unwrap().len(), 4000);
+        for _ in 0..96 {
+            gen.generate().unwrap();
         }
+        assert_eq!(gen.try_generate(), Err(SequenceExhausted));
 
-        match gen.generate() {
-            None => assert!(true),
-            Some(_) => unreachable!(),
-        }
+        gen.restore(SnowflakeId::new(EXPECTED_RAW_TIMESTAMP + 1, 0, 0).unwrap());
+        assert_eq!(gen.try_generate(), Err(ClockMovedBackwards));
+
+        assert_eq!(
+            gen.stats(),
+            GeneratorStats {
+                generated: 4096,
+                cas_failures: 0,
+                sequence_exhausted: 1,
+                clock_regressions: 1,
+                max_timestamp: EXPECTED_RAW_TIMESTAMP,
+            }
+        );
     }
 
     #[test]
-    fn complex_gen_test() {
-        let mut mock = MockFixture::new();
-        let mut time = *THE_EPOCH;
+    fn cross_thread_visibility_test() {
+        for _ in 0..100 {
+            let gen = SnowFlakeIdGenerator::with_epoch_millis(
+                SystemClock,
+                crate::THE_EPOCH_MILLIS,
+                1,
+                BitLayout::DEFAULT,
+            )
+            .unwrap();
+            let payload = AtomicUsize::new(0);
 
-        time.add_assign(Duration::milliseconds(1));
-        let tmp = time;
-        mock.expect_timestamp().times(4097).returning(move || tmp);
+            thread::scope(|scope| {
+                scope.spawn(|| {
+                    payload.store(42, Relaxed);
+                    gen.generate().unwrap();
+                });
 
-        time.add_assign(Duration::milliseconds(1));
-        let tmp = time;
+                while gen.snapshot().as_u64() == 0 {
+                    std::hint::spin_loop();
+                }
 
-        mock.expect_timestamp().times(1).returning(move || tmp);
+                assert_eq!(payload.load(Relaxed), 42);
+            });
+        }
+    }
 
-        let fixture = SnowFlakeIdGenerator::new(mock, *THE_EPOCH, 42).unwrap();
+    #[test]
+    fn memory_ordering_policy_test() {
+        let gen = SnowFlakeIdGenerator::new(DefaultTimestamp, *DISCORD_EPOCH, 1).unwrap();
+        assert_eq!(
+            gen.memory_ordering_policy(),
+            MemoryOrderingPolicy::AcquireRelease
+        );
+
+        let gen = gen.with_memory_ordering_policy(MemoryOrderingPolicy::Relaxed);
+        assert_eq!(gen.memory_ordering_policy(), MemoryOrderingPolicy::Relaxed);
+        assert!(gen.generate().is_some());
+    }
+
+    #[test]
+    fn restore_ahead_of_clock_test() {
+        let mut mock = MockFixture::new();
+        mock.expect_timestamp().returning(|| *EXPECTED_TIMESTAMP);
+
+        let gen = SnowFlakeIdGenerator::new(mock, *DISCORD_EPOCH, 1).unwrap();
+        gen.restore(SnowflakeId::new(EXPECTED_RAW_TIMESTAMP + 1, 1, 0).unwrap());
+
+        assert_eq!(
+            gen.try_generate(),
+            Err(SnowflakeIdEGeneratorError::ClockMovedBackwards)
+        );
+    }
+
+    #[test]
+    fn machine_id_test() {
+        let target =
+            SnowFlakeIdGenerator::<DefaultTimestamp>::new(DefaultTimestamp, *THE_EPOCH, 42)
+                .unwrap();
+        assert_eq!(target.machine_id(), 42);
+    }
+
+    #[test]
+    fn invalid_machine_id_test() {
+        let target =
+            SnowFlakeIdGenerator::<DefaultTimestamp>::new(DefaultTimestamp, *THE_EPOCH, 1024);
+
+        assert!(matches!(
+            target,
+            Err(SnowflakeIdEGeneratorError::MachineIdOutOfRange {
+                value: 1024,
+                max: 1023
+            })
+        ));
+    }
+
+    #[test]
+    fn with_datacenter_test() {
+        let target =
+            SnowFlakeIdGenerator::with_datacenter(DefaultTimestamp, *THE_EPOCH, 3, 17).unwrap();
+        assert_eq!(target.machine_id(), 3 << 5 | 17);
+
+        let id = target.generate().unwrap();
+        assert_eq!(id.datacenter_id(), 3);
+        assert_eq!(id.worker_id(), 17);
+
+        for (datacenter_id, worker_id) in [(32, 0), (0, 32)] {
+            assert!(matches!(
+                SnowFlakeIdGenerator::with_datacenter(
+                    DefaultTimestamp,
+                    *THE_EPOCH,
+                    datacenter_id,
+                    worker_id
+                ),
+                Err(SnowflakeIdEGeneratorError::MachineIdOutOfRange { value: 32, max: 31 })
+            ));
+        }
+    }
+
+    #[test]
+    fn layout_test() {
+        let target =
+            SnowFlakeIdGenerator::<DefaultTimestamp>::new(DefaultTimestamp, *THE_EPOCH, 42)
+                .unwrap();
+        assert_eq!(*target.layout(), BitLayout::DEFAULT);
+    }
+
+    #[test]
+    fn invalid_machine_id_with_layout_test() {
+        let layout = BitLayout::new(41, 5, 12).unwrap();
+
+        let target =
+            SnowFlakeIdGenerator::with_layout(DefaultTimestamp, *THE_EPOCH, 31, layout).unwrap();
+        assert_eq!(target.machine_id(), 31);
+
+        let target = SnowFlakeIdGenerator::with_layout(DefaultTimestamp, *THE_EPOCH, 32, layout);
+        assert!(matches!(
+            target,
+            Err(SnowflakeIdEGeneratorError::MachineIdOutOfRange { value: 32, max: 31 })
+        ));
+    }
+
+    #[test]
+    fn generate_with_layout_test() {
+        let layout = BitLayout::new(39, 16, 8).unwrap();
+        let mut mock = MockFixture::new();
+
+        mock.expect_timestamp()
+            .times(258)
+            .returning(|| *EXPECTED_TIMESTAMP);
+
+        let gen = SnowFlakeIdGenerator::with_layout(mock, *DISCORD_EPOCH, 0xbeef, layout).unwrap();
+
+        for i in 0..256u16 {
+            let actual = gen.generate().unwrap();
+            assert_eq!(layout.sequence(actual), i);
+            assert_eq!(layout.machine_id(actual), 0xbeef);
+            assert_eq!(layout.raw_timestamp(actual), EXPECTED_RAW_TIMESTAMP);
+        }
+
+        assert!(gen.generate().is_none());
+    }
+
+    #[test]
+    fn generate_test() {
+        let mut mock = MockFixture::new();
+
+        mock.expect_timestamp()
+            .times(4098)
+            .returning(|| *EXPECTED_TIMESTAMP);
+
+        let gen = SnowFlakeIdGenerator::new(mock, *DISCORD_EPOCH, 1).unwrap();
+
+        for i in 0..4096u16 {
+            let actual = gen.generate().unwrap();
+            assert_eq!(actual.sequence(), i);
+            assert_eq!(actual.raw_timestamp(), EXPECTED_RAW_TIMESTAMP);
+        }
+
+        assert!(gen.generate().is_none());
+    }
+
+    #[test]
+    fn complex_gen_test() {
+        let mut mock = MockFixture::new();
+        let mut time = *THE_EPOCH;
+
+        time.add_assign(Duration::milliseconds(1));
+        let tmp = time;
+        mock.expect_timestamp().times(4098).returning(move || tmp);
+
+        time.add_assign(Duration::milliseconds(1));
+        let tmp = time;
+
+        mock.expect_timestamp().times(1).returning(move || tmp);
+
+        let fixture = SnowFlakeIdGenerator::new(mock, *THE_EPOCH, 42).unwrap();
 
         for i in 0..0x1000u16 {
             let actual = fixture.generate().unwrap();
             assert_eq!(1, actual.raw_timestamp());
-            assert_eq!(i, actual.inclement());
+            assert_eq!(i, actual.sequence());
             assert_eq!(42, actual.machine_id());
         }
 
@@ -219,6 +1189,934 @@ mod tests {
 
         assert_eq!(2, actual.raw_timestamp());
         assert_eq!(42, actual.machine_id());
-        assert_eq!(0, actual.inclement());
+        assert_eq!(0, actual.sequence());
+    }
+
+    #[test]
+    fn generate_batch_test() {
+        let mut mock = MockFixture::new();
+
+        mock.expect_timestamp()
+            .times(5)
+            .returning(|| *EXPECTED_TIMESTAMP);
+
+        let gen = SnowFlakeIdGenerator::new(mock, *DISCORD_EPOCH, 1).unwrap();
+
+        let batch = gen.generate_batch(1000).unwrap();
+        assert_eq!(batch.len(), 1000);
+
+        for (i, actual) in batch.enumerate() {
+            assert_eq!(actual.sequence(), i as u16);
+            assert_eq!(actual.machine_id(), 1);
+            assert_eq!(actual.raw_timestamp(), EXPECTED_RAW_TIMESTAMP);
+        }
+
+        let actual = gen.generate().unwrap();
+        assert_eq!(actual.sequence(), 1000);
+
+        let batch = gen.generate_batch(10_000).unwrap();
+        assert_eq!(batch.len(), 4096 - 1001);
+
+        let actual: Vec<_> = batch.rev().map(|id| id.sequence()).collect();
+        assert_eq!(actual.first(), Some(&4095));
+        assert_eq!(actual.last(), Some(&1001));
+
+        assert!(gen.generate_batch(1).is_none());
+    }
+
+    #[test]
+    fn exhaustion_date_test() {
+        let target =
+            SnowFlakeIdGenerator::<DefaultTimestamp>::new(DefaultTimestamp, *THE_EPOCH, 42)
+                .unwrap();
+        assert_eq!(
+            target.exhaustion_date(),
+            Utc.with_ymd_and_hms(2109, 5, 15, 7, 35, 11)
+                .single()
+                .map(|x| x + Duration::milliseconds(104))
+        );
+
+        let layout = BitLayout::new(41, 10, 12).unwrap();
+        let target =
+            SnowFlakeIdGenerator::with_layout(DefaultTimestamp, *DISCORD_EPOCH, 42, layout)
+                .unwrap();
+        assert_eq!(
+            target.exhaustion_date(),
+            Utc.with_ymd_and_hms(2084, 9, 6, 15, 47, 35)
+                .single()
+                .map(|x| x + Duration::milliseconds(552))
+        );
+
+        let layout = BitLayout::new(63, 0, 0).unwrap();
+        let target =
+            SnowFlakeIdGenerator::with_layout(DefaultTimestamp, *THE_EPOCH, 0, layout).unwrap();
+        assert_eq!(target.exhaustion_date(), None);
+    }
+
+    #[test]
+    fn epoch_in_future_test() {
+        let actual =
+            SnowFlakeIdGenerator::with_epoch_millis(SystemClock, i64::MAX, 42, BitLayout::DEFAULT);
+        assert!(matches!(
+            actual,
+            Err(SnowflakeIdEGeneratorError::EpochInFuture)
+        ));
+
+        let mut mock = MockFixture::new();
+        let mut seq = mockall::Sequence::new();
+        mock.expect_timestamp()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|| *EXPECTED_TIMESTAMP);
+        mock.expect_timestamp()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|| *DISCORD_EPOCH - Duration::milliseconds(1));
+
+        let gen = SnowFlakeIdGenerator::new(mock, *DISCORD_EPOCH, 1).unwrap();
+        assert_eq!(
+            gen.try_generate(),
+            Err(SnowflakeIdEGeneratorError::EpochInFuture)
+        );
+    }
+
+    #[test]
+    fn timestamp_overflow_test() {
+        let layout = BitLayout::new(4, 10, 1).unwrap();
+        let mut mock = MockFixture::new();
+        let mut seq = mockall::Sequence::new();
+        mock.expect_timestamp()
+            .times(3)
+            .in_sequence(&mut seq)
+            .returning(|| *THE_EPOCH + Duration::milliseconds(15));
+        mock.expect_timestamp()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|| *THE_EPOCH + Duration::milliseconds(16));
+
+        let gen = SnowFlakeIdGenerator::with_layout(mock, *THE_EPOCH, 1, layout)
+            .unwrap()
+            .with_overflow_policy(OverflowPolicy::BorrowFromFuture);
+
+        assert_eq!(gen.generate_batch(2).unwrap().len(), 2);
+        assert_eq!(
+            gen.try_generate(),
+            Err(SnowflakeIdEGeneratorError::TimestampOverflow { value: 16, max: 15 })
+        );
+        assert_eq!(
+            gen.try_generate(),
+            Err(SnowflakeIdEGeneratorError::TimestampOverflow { value: 16, max: 15 })
+        );
+    }
+
+    #[test]
+    fn reserve_block_test() {
+        let mut mock = MockFixture::new();
+        mock.expect_timestamp()
+            .times(5)
+            .returning(|| *EXPECTED_TIMESTAMP);
+
+        let gen = SnowFlakeIdGenerator::new(mock, *DISCORD_EPOCH, 1).unwrap();
+
+        let mut block = gen.reserve_block(100).unwrap();
+        assert_eq!(block.remaining(), 100);
+        let (first, last) = block.bounds().unwrap();
+        assert_eq!(first.sequence(), 0);
+        assert_eq!(last.sequence(), 99);
+
+        // The generator carries on after the block, which is drained elsewhere.
+        let handle = thread::spawn(move || {
+            let first = block.next().unwrap();
+            let rest: Vec<_> = block.by_ref().collect();
+            (first, rest, block)
+        });
+        assert_eq!(gen.generate().unwrap().sequence(), 100);
+
+        let (first, rest, block) = handle.join().unwrap();
+        assert_eq!(first.sequence(), 0);
+        assert_eq!(first.machine_id(), 1);
+        assert_eq!(rest.len(), 99);
+        assert_eq!(rest.last().unwrap().sequence(), 99);
+        assert_eq!(block.remaining(), 0);
+        assert_eq!(block.bounds(), None);
+
+        let block = gen.reserve_block(10_000).unwrap();
+        assert_eq!(block.remaining(), 4096 - 101);
+
+        assert_eq!(
+            gen.reserve_block(1),
+            Err(SnowflakeIdEGeneratorError::SequenceExhausted)
+        );
+        assert_eq!(gen.reserve_block(0).unwrap().remaining(), 0);
+    }
+
+    #[test]
+    fn id_block_from_bounds_test() {
+        let layout = BitLayout::DEFAULT;
+        let first = SnowflakeId::new(EXPECTED_RAW_TIMESTAMP, 1, 10).unwrap();
+        let last = SnowflakeId::new(EXPECTED_RAW_TIMESTAMP, 1, 12).unwrap();
+
+        let block = IdBlock::from_bounds(first, last, &layout).unwrap();
+        assert_eq!(block.bounds(), Some((first, last)));
+        let actual: Vec<_> = block.map(|x| x.sequence()).collect();
+        assert_eq!(actual, [10, 11, 12]);
+
+        let other_tick = SnowflakeId::new(EXPECTED_RAW_TIMESTAMP + 1, 1, 0).unwrap();
+        let other_machine = SnowflakeId::new(EXPECTED_RAW_TIMESTAMP, 2, 12).unwrap();
+        assert_eq!(IdBlock::from_bounds(last, first, &layout), None);
+        assert_eq!(IdBlock::from_bounds(first, other_tick, &layout), None);
+        assert_eq!(IdBlock::from_bounds(first, other_machine, &layout), None);
+
+        let first = SnowflakeId::from(u64::MAX - 1);
+        let last = SnowflakeId::from(u64::MAX);
+        let mut block = IdBlock::from_bounds(first, last, &layout).unwrap();
+        assert_eq!(block.remaining(), 2);
+        assert_eq!(block.bounds(), Some((first, last)));
+        assert_eq!(block.next(), Some(first));
+        assert_eq!(block.next(), Some(last));
+        assert_eq!(block.next(), None);
+        assert_eq!(block.bounds(), None);
+    }
+
+    #[test]
+    fn empty_generate_batch_test() {
+        let mut mock = MockFixture::new();
+        mock.expect_timestamp().returning(|| *EXPECTED_TIMESTAMP);
+        let gen = SnowFlakeIdGenerator::new(mock, *DISCORD_EPOCH, 1).unwrap();
+
+        let batch = gen.generate_batch(0).unwrap();
+        assert_eq!(batch.len(), 0);
+    }
+
+    fn regressing_mock(after_regression: usize) -> MockFixture {
+        let mut mock = MockFixture::new();
+        let mut seq = mockall::Sequence::new();
+        let mut time = *THE_EPOCH;
+
+        // Construction and the first generate.
+        time.add_assign(Duration::milliseconds(10));
+        let tmp = time;
+        mock.expect_timestamp()
+            .times(2)
+            .in_sequence(&mut seq)
+            .returning(move || tmp);
+
+        time.add_assign(Duration::milliseconds(-5));
+        let tmp = time;
+        mock.expect_timestamp()
+            .times(after_regression)
+            .in_sequence(&mut seq)
+            .returning(move || tmp);
+
+        mock
+    }
+
+    #[test]
+    fn clock_regression_policy_test() {
+        let target =
+            SnowFlakeIdGenerator::<DefaultTimestamp>::new(DefaultTimestamp, *THE_EPOCH, 42)
+                .unwrap();
+        assert_eq!(
+            target.clock_regression_policy(),
+            ClockRegressionPolicy::Reject
+        );
+
+        let target = target.with_clock_regression_policy(ClockRegressionPolicy::UseLastTimestamp);
+        assert_eq!(
+            target.clock_regression_policy(),
+            ClockRegressionPolicy::UseLastTimestamp
+        );
+    }
+
+    #[test]
+    fn reject_clock_regression_test() {
+        let fixture = SnowFlakeIdGenerator::new(regressing_mock(2), *THE_EPOCH, 42).unwrap();
+
+        assert_eq!(fixture.generate().unwrap().raw_timestamp(), 10);
+        assert_eq!(
+            fixture.try_generate(),
+            Err(SnowflakeIdEGeneratorError::ClockMovedBackwards)
+        );
+        assert!(fixture.generate().is_none());
+    }
+
+    #[test]
+    fn use_last_timestamp_clock_regression_test() {
+        let fixture = SnowFlakeIdGenerator::new(regressing_mock(2), *THE_EPOCH, 42)
+            .unwrap()
+            .with_clock_regression_policy(ClockRegressionPolicy::UseLastTimestamp);
+
+        let first = fixture.generate().unwrap();
+        assert_eq!(first.raw_timestamp(), 10);
+        assert_eq!(first.sequence(), 0);
+
+        for i in 1..3u16 {
+            let actual = fixture.generate().unwrap();
+            assert_eq!(actual.raw_timestamp(), 10);
+            assert_eq!(actual.sequence(), i);
+        }
+    }
+
+    #[test]
+    fn wait_until_caught_up_clock_regression_test() {
+        let mut mock = regressing_mock(1);
+        let mut time = *THE_EPOCH;
+        time.add_assign(Duration::milliseconds(11));
+        mock.expect_timestamp().times(1).returning(move || time);
+
+        let fixture = SnowFlakeIdGenerator::new(mock, *THE_EPOCH, 42)
+            .unwrap()
+            .with_clock_regression_policy(ClockRegressionPolicy::WaitUntilCaughtUp);
+
+        assert_eq!(fixture.generate().unwrap().raw_timestamp(), 10);
+
+        let actual = fixture.generate().unwrap();
+        assert_eq!(actual.raw_timestamp(), 11);
+        assert_eq!(actual.sequence(), 0);
+    }
+
+    #[test]
+    fn max_backward_skew_test() {
+        let target =
+            SnowFlakeIdGenerator::<DefaultTimestamp>::new(DefaultTimestamp, *THE_EPOCH, 42)
+                .unwrap();
+        assert_eq!(target.max_backward_skew(), None);
+
+        let target = target.with_max_backward_skew(core::time::Duration::from_micros(2500));
+        assert_eq!(
+            target.max_backward_skew(),
+            Some(core::time::Duration::from_millis(2))
+        );
+    }
+
+    #[test]
+    fn rate_limit_test() {
+        let clock = ManualClock::new(crate::THE_EPOCH_MILLIS + 10);
+        let target = SnowFlakeIdGenerator::with_epoch_millis(
+            clock.clone(),
+            crate::THE_EPOCH_MILLIS,
+            42,
+            BitLayout::DEFAULT,
+        )
+        .unwrap();
+        assert_eq!(target.max_ids_per_second(), None);
+
+        // One ID every 10ms, plus the limiter's 1ms of tolerance.
+        let target = target.with_max_ids_per_second(100);
+        assert_eq!(target.max_ids_per_second(), Some(100));
+        assert_eq!(target.config().max_ids_per_second, Some(100));
+
+        assert!(target.try_generate().is_ok());
+        assert_eq!(target.try_generate(), Err(RateLimited));
+        assert_eq!(target.generate(), None);
+
+        clock.advance(core::time::Duration::from_millis(9));
+        assert!(target.try_generate().is_ok());
+        assert_eq!(target.try_generate(), Err(RateLimited));
+
+        // Batches and blocks are charged for the whole request.
+        clock.advance(core::time::Duration::from_millis(10));
+        assert_eq!(target.reserve_block(5).unwrap().remaining(), 5);
+        clock.advance(core::time::Duration::from_millis(10));
+        assert_eq!(target.reserve_block(1), Err(RateLimited));
+        clock.advance(core::time::Duration::from_millis(40));
+        assert!(target.generate_batch(2).is_some());
+        assert!(target.generate_batch(2).is_none());
+
+        let target = target.with_max_ids_per_second(0);
+        assert_eq!(target.max_ids_per_second(), None);
+        assert!((0..100).all(|_| target.generate().is_some()));
+    }
+
+    #[test]
+    fn rate_limit_refund_test() {
+        let clock = ManualClock::new(crate::THE_EPOCH_MILLIS + 10);
+        let layout = BitLayout::new(41, 10, 1).unwrap();
+        let target = SnowFlakeIdGenerator::with_epoch_millis(
+            clock.clone(),
+            crate::THE_EPOCH_MILLIS,
+            42,
+            layout,
+        )
+        .unwrap()
+        .with_max_ids_per_second(1_000);
+
+        // Two sequence numbers per millisecond: the other two are handed back.
+        assert_eq!(target.reserve_block(4).unwrap().remaining(), 2);
+        clock.advance(core::time::Duration::from_millis(1));
+        assert!(target.try_generate().is_ok());
+
+        // A failed reservation costs nothing, so the limiter never gets to say no.
+        clock.advance(core::time::Duration::from_millis(10));
+        target.restore(SnowflakeId::with_layout(100, 42, 0, &layout).unwrap());
+        for _ in 0..10 {
+            assert_eq!(target.try_generate(), Err(ClockMovedBackwards));
+        }
+    }
+
+    #[test]
+    fn config_test() {
+        let target = SnowFlakeIdGenerator::with_epoch_millis(
+            ManualClock::new(crate::THE_EPOCH_MILLIS + 10),
+            crate::THE_EPOCH_MILLIS,
+            42,
+            BitLayout::TWITTER,
+        )
+        .unwrap()
+        .with_overflow_policy(OverflowPolicy::SpinUntilNextMillis)
+        .with_max_backward_skew(core::time::Duration::from_millis(5));
+
+        let expected = GeneratorConfig {
+            epoch_millis: crate::THE_EPOCH_MILLIS,
+            machine_id: 42,
+            layout: BitLayout::TWITTER,
+            tick_unit: TickUnit::Millis,
+            clock_regression_policy: ClockRegressionPolicy::Reject,
+            overflow_policy: OverflowPolicy::SpinUntilNextMillis,
+            memory_ordering_policy: MemoryOrderingPolicy::AcquireRelease,
+            max_backward_skew_millis: Some(5),
+            max_ids_per_second: None,
+        };
+        assert_eq!(target.config(), expected);
+
+        let actual = format!("{:?}", target);
+        assert!(actual.starts_with("SnowFlakeIdGenerator { config: GeneratorConfig {"));
+        assert!(actual.contains("machine_id: 42"));
+        assert!(actual.contains("overflow_policy: SpinUntilNextMillis"));
+        assert!(actual.ends_with("last_id: SnowflakeId(0) }"));
+    }
+
+    #[test]
+    fn generate_until_test() {
+        let target = SnowFlakeIdGenerator::with_epoch_millis(
+            SystemClock,
+            crate::THE_EPOCH_MILLIS,
+            1,
+            BitLayout::new(42, 10, 4).unwrap(),
+        )
+        .unwrap();
+
+        // 16 IDs per millisecond: 100 of them span several.
+        let actual =
+            target.generate_until(100, Instant::now() + core::time::Duration::from_secs(5));
+        assert_eq!(actual.len(), 100);
+        assert!(actual.windows(2).all(|x| x[0] < x[1]));
+
+        assert!(target.generate_until(10, Instant::now()).is_empty());
+        assert!(target
+            .generate_until(0, Instant::now() + core::time::Duration::from_secs(1))
+            .is_empty());
+    }
+
+    #[test]
+    fn generate_until_deadline_test() {
+        // A frozen clock and four IDs per millisecond: the rest never comes.
+        let layout = BitLayout::new(42, 10, 2).unwrap();
+        let target = SnowFlakeIdGenerator::with_epoch_millis(
+            ManualClock::new(crate::THE_EPOCH_MILLIS + 5),
+            crate::THE_EPOCH_MILLIS,
+            1,
+            layout,
+        )
+        .unwrap();
+
+        let deadline = Instant::now() + core::time::Duration::from_millis(20);
+        let actual = target.generate_until(10, deadline);
+
+        assert!(Instant::now() >= deadline);
+        assert_eq!(actual.len(), 4);
+        assert!(actual.iter().all(|x| layout.raw_timestamp(*x) == 5));
+    }
+
+    #[test]
+    fn dyn_generator_test() {
+        let clock = ManualClock::new(crate::THE_EPOCH_MILLIS + 5);
+        let typed = SnowFlakeIdGenerator::with_epoch_millis(
+            clock.clone(),
+            crate::THE_EPOCH_MILLIS,
+            1,
+            BitLayout::DEFAULT,
+        )
+        .unwrap()
+        .with_tick_unit(TickUnit::TenMillis);
+        let issued = typed.generate().unwrap();
+
+        let targets: Vec<DynGenerator> = vec![
+            typed.into_dyn(),
+            DynGenerator::with_epoch_millis(
+                Box::new(SystemClock),
+                crate::THE_EPOCH_MILLIS,
+                2,
+                BitLayout::DEFAULT,
+            )
+            .unwrap(),
+        ];
+
+        assert_eq!(targets[0].tick_unit(), TickUnit::TenMillis);
+        assert_eq!(targets[0].snapshot(), issued);
+        assert_eq!(targets[0].stats().generated, 1);
+
+        clock.advance(core::time::Duration::from_millis(20));
+        let actual = targets[0].generate().unwrap();
+        assert_eq!(actual.raw_timestamp(), 2);
+        assert_eq!(actual.machine_id(), 1);
+
+        assert_eq!(targets[1].generate().unwrap().machine_id(), 2);
+    }
+
+    #[test]
+    fn generate_bound_test() {
+        let target = SnowFlakeIdGenerator::with_epoch_millis(
+            ManualClock::new(crate::THE_EPOCH_MILLIS + 20_000),
+            crate::THE_EPOCH_MILLIS,
+            42,
+            BitLayout::DEFAULT,
+        )
+        .unwrap()
+        .with_tick_unit(TickUnit::Seconds);
+
+        assert_eq!(
+            target.decoder(),
+            Decoder::new(crate::THE_EPOCH_MILLIS, BitLayout::DEFAULT)
+                .with_tick_unit(TickUnit::Seconds)
+        );
+
+        let actual = target.generate_bound().unwrap();
+        assert_eq!(actual.decoder(), &target.decoder());
+        assert_eq!(actual.raw_timestamp(), 20);
+        assert_eq!(actual.machine_id(), 42);
+        assert_eq!(actual.unix_millis(), Some(crate::THE_EPOCH_MILLIS + 20_000));
+
+        let next = target.try_generate_bound().unwrap();
+        assert!(actual < next);
+        assert_eq!(next.sequence(), actual.sequence() + 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_serialize_test() {
+        let target = SnowFlakeIdGenerator::with_epoch_millis(
+            ManualClock::new(crate::THE_EPOCH_MILLIS),
+            crate::THE_EPOCH_MILLIS,
+            7,
+            BitLayout::DEFAULT,
+        )
+        .unwrap();
+
+        let actual = serde_json::to_value(target.config()).unwrap();
+        let expected = serde_json::json!({
+            "epoch_millis": crate::THE_EPOCH_MILLIS,
+            "machine_id": 7,
+            "layout": {"timestamp_bits": 42, "machine_bits": 10, "sequence_bits": 12},
+            "tick_unit": "Millis",
+            "clock_regression_policy": "Reject",
+            "overflow_policy": "Error",
+            "memory_ordering_policy": "AcquireRelease",
+            "max_backward_skew_millis": null,
+            "max_ids_per_second": null,
+        });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn tick_unit_test() {
+        let clock = ManualClock::new(crate::THE_EPOCH_MILLIS + 12_345);
+        let generator = |unit| {
+            SnowFlakeIdGenerator::with_epoch_millis(
+                clock.clone(),
+                crate::THE_EPOCH_MILLIS,
+                42,
+                BitLayout::DEFAULT,
+            )
+            .unwrap()
+            .with_tick_unit(unit)
+        };
+
+        let cases = [
+            (TickUnit::Micros, 12_345_000),
+            (TickUnit::Millis, 12_345),
+            (TickUnit::TenMillis, 1_234),
+            (TickUnit::Seconds, 12),
+        ];
+
+        for (unit, expected) in cases {
+            let target = generator(unit);
+            assert_eq!(target.tick_unit(), unit);
+
+            let id = target.generate().unwrap();
+            assert_eq!(id.raw_timestamp(), expected);
+            assert_eq!(target.generate().unwrap().sequence(), 1);
+        }
+
+        // A coarse tick keeps the sequence going for longer.
+        let target = generator(TickUnit::Seconds);
+        target.generate().unwrap();
+        clock.advance(core::time::Duration::from_millis(999 - 345));
+        assert_eq!(target.generate().unwrap().sequence(), 1);
+        clock.advance(core::time::Duration::from_millis(1));
+        assert_eq!(target.generate().unwrap().raw_timestamp(), 13);
+
+        let target = generator(TickUnit::Seconds);
+        assert_eq!(
+            target.exhaustion_date(),
+            DateTime::from_timestamp_millis(crate::THE_EPOCH_MILLIS)
+                .map(|x| x + Duration::seconds(1 << 42))
+        );
+    }
+
+    #[test]
+    fn elapsed_ticks_test() {
+        let layout = BitLayout::new(10, 0, 0).unwrap();
+
+        assert_eq!(
+            elapsed_ticks(6_500_000, 1_000, TickUnit::Seconds, &layout),
+            Ok(5)
+        );
+        assert_eq!(
+            elapsed_ticks(999_999, 1_000, TickUnit::Micros, &layout),
+            Err(SnowflakeIdEGeneratorError::EpochInFuture)
+        );
+        assert_eq!(
+            elapsed_ticks(1_000_000 + 1_024, 1_000, TickUnit::Micros, &layout),
+            Err(SnowflakeIdEGeneratorError::TimestampOverflow {
+                value: 1_024,
+                max: 1_023
+            })
+        );
+        assert_eq!(
+            elapsed_ticks(0, i64::MAX, TickUnit::Millis, &layout),
+            Err(SnowflakeIdEGeneratorError::EpochInFuture)
+        );
+        assert_eq!(
+            elapsed_ticks(i64::MAX, i64::MIN, TickUnit::Micros, &layout),
+            Err(SnowflakeIdEGeneratorError::TimestampOverflow {
+                value: u64::MAX,
+                max: 1_023
+            })
+        );
+    }
+
+    #[test]
+    fn regression_within_max_backward_skew_test() {
+        let fixture = SnowFlakeIdGenerator::new(regressing_mock(1), *THE_EPOCH, 42)
+            .unwrap()
+            .with_clock_regression_policy(ClockRegressionPolicy::UseLastTimestamp)
+            .with_max_backward_skew(core::time::Duration::from_millis(5));
+
+        assert_eq!(fixture.generate().unwrap().raw_timestamp(), 10);
+
+        let actual = fixture.generate().unwrap();
+        assert_eq!(actual.raw_timestamp(), 10);
+        assert_eq!(actual.sequence(), 1);
+    }
+
+    #[test]
+    fn regression_beyond_max_backward_skew_test() {
+        let fixture = SnowFlakeIdGenerator::new(regressing_mock(1), *THE_EPOCH, 42)
+            .unwrap()
+            .with_clock_regression_policy(ClockRegressionPolicy::UseLastTimestamp)
+            .with_max_backward_skew(core::time::Duration::from_millis(4));
+
+        assert_eq!(fixture.generate().unwrap().raw_timestamp(), 10);
+        assert_eq!(
+            fixture.try_generate(),
+            Err(SnowflakeIdEGeneratorError::ClockMovedBackwards)
+        );
+        assert_eq!(fixture.stats().clock_regressions, 1);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn metrics_test() {
+        use crate::generator_metrics::{self, tests::Fixture};
+
+        let recorder = Fixture::default();
+
+        metrics::with_local_recorder(&recorder, || {
+            let mut mock = MockFixture::new();
+            mock.expect_timestamp().returning(|| *EXPECTED_TIMESTAMP);
+
+            let gen = SnowFlakeIdGenerator::new(mock, *DISCORD_EPOCH, 1).unwrap();
+            gen.generate().unwrap();
+            gen.generate_batch(4095).unwrap();
+            assert!(gen.generate().is_none());
+
+            let fixture = SnowFlakeIdGenerator::new(regressing_mock(1), *THE_EPOCH, 42).unwrap();
+            fixture.generate().unwrap();
+            assert!(fixture.generate().is_none());
+        });
+
+        assert_eq!(recorder.get(generator_metrics::IDS_GENERATED), 4097);
+        assert_eq!(recorder.get(generator_metrics::BATCH_SIZE), 4095);
+        assert_eq!(recorder.get(generator_metrics::SEQUENCE_EXHAUSTED), 1);
+        assert_eq!(recorder.get(generator_metrics::CLOCK_REGRESSIONS), 1);
+        assert_eq!(recorder.get(generator_metrics::CAS_RETRIES), 0);
+    }
+
+    #[test]
+    fn sequence_exhausted_error_test() {
+        let mut mock = MockFixture::new();
+        mock.expect_timestamp()
+            .times(4098)
+            .returning(|| *EXPECTED_TIMESTAMP);
+
+        let gen = SnowFlakeIdGenerator::new(mock, *DISCORD_EPOCH, 1).unwrap();
+        gen.generate_batch(4096).unwrap();
+
+        for _ in 0..4096 {
+            assert_eq!(
+                gen.try_generate(),
+                Err(SnowflakeIdEGeneratorError::SequenceExhausted)
+            );
+        }
+    }
+
+    #[test]
+    fn overflow_policy_test() {
+        let target =
+            SnowFlakeIdGenerator::<DefaultTimestamp>::new(DefaultTimestamp, *THE_EPOCH, 42)
+                .unwrap();
+        assert_eq!(target.overflow_policy(), OverflowPolicy::Error);
+
+        let target = target.with_overflow_policy(OverflowPolicy::BorrowFromFuture);
+        assert_eq!(target.overflow_policy(), OverflowPolicy::BorrowFromFuture);
+    }
+
+    #[test]
+    fn spin_until_next_millis_overflow_test() {
+        let calls = AtomicUsize::new(0);
+        let mut mock = MockFixture::new();
+        mock.expect_timestamp().returning(move || {
+            if calls.fetch_add(1, Relaxed) < 4099 {
+                *EXPECTED_TIMESTAMP
+            } else {
+                *EXPECTED_TIMESTAMP + Duration::milliseconds(1)
+            }
+        });
+
+        let gen = SnowFlakeIdGenerator::new(mock, *DISCORD_EPOCH, 1)
+            .unwrap()
+            .with_overflow_policy(OverflowPolicy::SpinUntilNextMillis);
+        gen.generate_batch(4096).unwrap();
+
+        let actual = gen.try_generate().unwrap();
+        assert_eq!(actual.raw_timestamp(), EXPECTED_RAW_TIMESTAMP + 1);
+        assert_eq!(actual.sequence(), 0);
+    }
+
+    #[test]
+    fn borrow_from_future_overflow_test() {
+        let mut mock = MockFixture::new();
+        mock.expect_timestamp().returning(|| *EXPECTED_TIMESTAMP);
+
+        let gen = SnowFlakeIdGenerator::new(mock, *DISCORD_EPOCH, 1)
+            .unwrap()
+            .with_overflow_policy(OverflowPolicy::BorrowFromFuture);
+        gen.generate_batch(4096).unwrap();
+
+        let actual = gen.try_generate().unwrap();
+        assert_eq!(actual.raw_timestamp(), EXPECTED_RAW_TIMESTAMP + 1);
+        assert_eq!(actual.sequence(), 0);
+
+        let actual = gen.try_generate().unwrap();
+        assert_eq!(actual.raw_timestamp(), EXPECTED_RAW_TIMESTAMP + 1);
+        assert_eq!(actual.sequence(), 1);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn generate_async_test() {
+        let mut mock = MockFixture::new();
+        let mut seq = mockall::Sequence::new();
+        let mut time = *THE_EPOCH;
+
+        time.add_assign(Duration::milliseconds(1));
+        let tmp = time;
+        mock.expect_timestamp()
+            .times(4098)
+            .in_sequence(&mut seq)
+            .returning(move || tmp);
+
+        time.add_assign(Duration::milliseconds(1));
+        let tmp = time;
+        mock.expect_timestamp()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(move || tmp);
+
+        let fixture = SnowFlakeIdGenerator::new(mock, *THE_EPOCH, 42).unwrap();
+
+        for i in 0..0x1000u16 {
+            let actual = fixture.generate_async().await.unwrap();
+            assert_eq!(1, actual.raw_timestamp());
+            assert_eq!(i, actual.sequence());
+        }
+
+        let actual = fixture.generate_async().await.unwrap();
+        assert_eq!(2, actual.raw_timestamp());
+        assert_eq!(0, actual.sequence());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn generate_async_error_test() {
+        let fixture = SnowFlakeIdGenerator::new(regressing_mock(1), *THE_EPOCH, 42).unwrap();
+
+        assert!(fixture.generate_async().await.is_ok());
+        assert_eq!(
+            fixture.generate_async().await,
+            Err(SnowflakeIdEGeneratorError::ClockMovedBackwards)
+        );
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn generate_many_async_test() {
+        let fixture = SnowFlakeIdGenerator::with_epoch_millis(
+            SystemClock,
+            THE_EPOCH.timestamp_millis(),
+            42,
+            BitLayout::DEFAULT,
+        )
+        .unwrap();
+
+        let actual = fixture.generate_many_async(10_000).await.unwrap();
+        assert_eq!(actual.len(), 10_000);
+        assert!(actual.windows(2).all(|pair| pair[0] < pair[1]));
+
+        assert!(fixture.generate_many_async(0).await.unwrap().is_empty());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn generate_async_rate_limit_test() {
+        let fixture = SnowFlakeIdGenerator::with_epoch_millis(
+            SystemClock,
+            THE_EPOCH.timestamp_millis(),
+            42,
+            BitLayout::DEFAULT,
+        )
+        .unwrap()
+        .with_max_ids_per_second(200);
+
+        // One ID every 5ms: the tenth is due 45ms after the first, less the 1ms tolerance.
+        let started = std::time::Instant::now();
+        for _ in 0..10 {
+            fixture.generate_async().await.unwrap();
+        }
+        assert!(started.elapsed() >= std::time::Duration::from_millis(40));
+
+        // The batch is paid for up front, so it comes back whole.
+        let actual = fixture.generate_many_async(20).await.unwrap();
+        assert_eq!(actual.len(), 20);
+        assert!(started.elapsed() >= std::time::Duration::from_millis(44));
+    }
+}
+
+// Run with `RUSTFLAGS="--cfg snowflake_loom" cargo test --release --lib loom_tests`.
+#[cfg(all(test, snowflake_loom))]
+mod loom_tests {
+    use crate::bit_layout::BitLayout;
+    use crate::clock::Clock;
+    use crate::generator_policy::{MemoryOrderingPolicy, OverflowPolicy};
+    use crate::snow_flake_id::SnowflakeId;
+    use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+    use loom::sync::atomic::AtomicUsize;
+    use loom::sync::atomic::Ordering::Relaxed;
+    use loom::sync::Arc;
+    use loom::thread;
+
+    struct Fixture(i64);
+
+    impl Clock for Fixture {
+        fn now_millis(&self) -> i64 {
+            self.0
+        }
+    }
+
+    fn generate(target: &SnowFlakeIdGenerator<Fixture>, count: usize) -> Vec<SnowflakeId> {
+        (0..count).map(|_| target.try_generate().unwrap()).collect()
+    }
+
+    fn verify(per_thread: Vec<Vec<SnowflakeId>>, expected: usize) {
+        let mut all = Vec::new();
+
+        for ids in per_thread {
+            assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+            all.extend(ids);
+        }
+
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), expected);
+    }
+
+    fn run(layout: BitLayout, overflow_policy: OverflowPolicy) {
+        loom::model(move || {
+            let target = Arc::new(
+                SnowFlakeIdGenerator::with_epoch_millis(Fixture(5), 0, 42, layout)
+                    .unwrap()
+                    .with_overflow_policy(overflow_policy),
+            );
+
+            let other = target.clone();
+            let handle = thread::spawn(move || generate(&other, 2));
+            let local = generate(&target, 2);
+
+            verify(vec![local, handle.join().unwrap()], 4);
+        });
+    }
+
+    #[test]
+    fn unique_and_monotonic_test() {
+        run(BitLayout::DEFAULT, OverflowPolicy::Error);
+    }
+
+    #[test]
+    fn borrow_from_future_test() {
+        run(
+            BitLayout::new(52, 10, 1).unwrap(),
+            OverflowPolicy::BorrowFromFuture,
+        );
+    }
+
+    #[test]
+    fn contended_batch_test() {
+        loom::model(|| {
+            let target = Arc::new(
+                SnowFlakeIdGenerator::with_epoch_millis(Fixture(5), 0, 42, BitLayout::DEFAULT)
+                    .unwrap(),
+            );
+
+            let other = target.clone();
+            let handle = thread::spawn(move || other.generate_batch(2).unwrap().collect());
+            let local = target.generate_batch(2).unwrap().collect();
+
+            verify(vec![local, handle.join().unwrap()], 4);
+        });
+    }
+
+    #[test]
+    fn acquire_release_publishes_test() {
+        loom::model(|| {
+            let target = Arc::new(
+                SnowFlakeIdGenerator::with_epoch_millis(Fixture(5), 0, 42, BitLayout::DEFAULT)
+                    .unwrap()
+                    .with_memory_ordering_policy(MemoryOrderingPolicy::AcquireRelease),
+            );
+            let payload = Arc::new(AtomicUsize::new(0));
+
+            let (other, written) = (target.clone(), payload.clone());
+            let handle = thread::spawn(move || {
+                written.store(42, Relaxed);
+                generate(&other, 1);
+            });
+
+            if target.snapshot().as_u64() != 0 {
+                assert_eq!(payload.load(Relaxed), 42);
+            }
+
+            handle.join().unwrap();
+        });
     }
 }
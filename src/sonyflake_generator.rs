@@ -0,0 +1,365 @@
+use crate::clock::Clock;
+use crate::epochs::SONYFLAKE_MILLIS;
+use crate::generator_policy::ClockRegressionPolicy;
+use crate::id_generator::IdGenerator;
+use crate::snow_flake_id::SnowflakeIdError;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_error::SnowflakeIdEGeneratorError::{
+    ClockMovedBackwards, Contended, EpochInFuture, SequenceExhausted, TimestampOverflow,
+};
+use crate::sync::AtomicU64;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, TimeZone};
+use core::fmt::{Display, Formatter};
+use core::sync::atomic::Ordering::Relaxed;
+
+pub const TIME_UNIT_MILLIS: i64 = 10;
+
+const TIMESTAMP_BITS: u32 = 39;
+const SEQUENCE_BITS: u32 = 8;
+const MACHINE_BITS: u32 = 16;
+
+const MAX_ELAPSED_TIME: u64 = (1 << TIMESTAMP_BITS) - 1;
+
+// Same field widths and order as github.com/sony/sonyflake:
+// 39 bits of 10 ms ticks | 8 bits of sequence | 16 bits of machine id.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub struct SonyflakeId(u64);
+
+impl From<u64> for SonyflakeId {
+    fn from(value: u64) -> Self {
+        SonyflakeId(value)
+    }
+}
+
+impl Display for SonyflakeId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl SonyflakeId {
+    pub fn new(elapsed_time: u64, sequence: u8, machine_id: u16) -> Result<Self, SnowflakeIdError> {
+        if elapsed_time > MAX_ELAPSED_TIME {
+            return Err(SnowflakeIdError::Timestamp {
+                value: elapsed_time,
+                max: MAX_ELAPSED_TIME,
+            });
+        }
+
+        let mut tmp = elapsed_time << (SEQUENCE_BITS + MACHINE_BITS);
+        tmp |= (sequence as u64) << MACHINE_BITS;
+        tmp |= machine_id as u64;
+
+        Ok(SonyflakeId(tmp))
+    }
+
+    pub fn elapsed_time(&self) -> u64 {
+        self.0 >> (SEQUENCE_BITS + MACHINE_BITS)
+    }
+
+    pub fn sequence(&self) -> u8 {
+        (self.0 >> MACHINE_BITS) as u8
+    }
+
+    pub fn machine_id(&self) -> u16 {
+        self.0 as u16
+    }
+
+    pub fn timestamp_millis(&self, epoch_millis: i64) -> i64 {
+        epoch_millis + self.elapsed_time() as i64 * TIME_UNIT_MILLIS
+    }
+
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+pub struct SonyflakeGenerator<T: Clock> {
+    clock: T,
+    epoch_millis: i64,
+    machine_id: u16,
+    clock_regression_policy: ClockRegressionPolicy,
+    recent: AtomicU64,
+}
+
+impl<T: Clock> SonyflakeGenerator<T> {
+    pub fn new(clock: T, machine_id: u16) -> Self {
+        Self::with_epoch_millis(clock, SONYFLAKE_MILLIS, machine_id)
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn with_epoch<Tz: TimeZone>(clock: T, the_epoch: DateTime<Tz>, machine_id: u16) -> Self {
+        Self::with_epoch_millis(clock, the_epoch.timestamp_millis(), machine_id)
+    }
+
+    pub fn with_epoch_millis(clock: T, epoch_millis: i64, machine_id: u16) -> Self {
+        SonyflakeGenerator {
+            clock,
+            epoch_millis,
+            machine_id,
+            clock_regression_policy: ClockRegressionPolicy::default(),
+            recent: AtomicU64::new(0),
+        }
+    }
+
+    pub fn with_clock_regression_policy(mut self, policy: ClockRegressionPolicy) -> Self {
+        self.clock_regression_policy = policy;
+        self
+    }
+
+    pub fn clock_regression_policy(&self) -> ClockRegressionPolicy {
+        self.clock_regression_policy
+    }
+
+    pub fn epoch_millis(&self) -> i64 {
+        self.epoch_millis
+    }
+
+    pub fn machine_id(&self) -> u16 {
+        self.machine_id
+    }
+
+    // Checked like the snowflake generator's `elapsed_millis`: a clock behind the epoch or
+    // past the 39-bit horizon is an error, not a wrapped tick count.
+    fn elapsed_time(&self) -> Result<u64, SnowflakeIdEGeneratorError> {
+        let elapsed = self.clock.now_millis() as i128 - self.epoch_millis as i128;
+
+        if elapsed < 0 {
+            return Err(EpochInFuture);
+        }
+
+        let ticks = elapsed as u128 / TIME_UNIT_MILLIS as u128;
+        if ticks > MAX_ELAPSED_TIME as u128 {
+            Err(TimestampOverflow {
+                value: u64::try_from(ticks).unwrap_or(u64::MAX),
+                max: MAX_ELAPSED_TIME,
+            })
+        } else {
+            Ok(ticks as u64)
+        }
+    }
+
+    fn current_elapsed_time(&self, last: u64) -> Result<u64, SnowflakeIdEGeneratorError> {
+        loop {
+            let now = self.elapsed_time()?;
+
+            if now >= last {
+                return Ok(now);
+            }
+
+            match self.clock_regression_policy {
+                ClockRegressionPolicy::Reject => return Err(ClockMovedBackwards),
+                ClockRegressionPolicy::UseLastTimestamp => return Ok(last),
+                #[cfg(feature = "std")]
+                ClockRegressionPolicy::WaitUntilCaughtUp => std::thread::sleep(
+                    std::time::Duration::from_millis((last - now) * TIME_UNIT_MILLIS as u64),
+                ),
+                #[cfg(not(feature = "std"))]
+                ClockRegressionPolicy::WaitUntilCaughtUp => core::hint::spin_loop(),
+            }
+        }
+    }
+
+    pub fn try_generate(&self) -> Result<SonyflakeId, SnowflakeIdEGeneratorError> {
+        let pivot = SonyflakeId(self.recent.load(Relaxed));
+        let last = pivot.elapsed_time();
+        let now = self.current_elapsed_time(last)?;
+
+        let sequence = if last == now {
+            pivot.sequence().checked_add(1).ok_or(SequenceExhausted)?
+        } else {
+            0
+        };
+
+        let candidate = SonyflakeId::new(now, sequence, self.machine_id)?;
+
+        match self.recent.compare_exchange_weak(
+            pivot.as_u64(),
+            candidate.as_u64(),
+            Relaxed,
+            Relaxed,
+        ) {
+            Ok(_) => Ok(candidate),
+            Err(_) => Err(Contended),
+        }
+    }
+
+    pub fn generate(&self) -> Option<SonyflakeId> {
+        self.try_generate().ok()
+    }
+}
+
+impl<T: Clock> IdGenerator for SonyflakeGenerator<T> {
+    type Id = SonyflakeId;
+    type Error = SnowflakeIdEGeneratorError;
+
+    fn generate(&self) -> Result<Self::Id, Self::Error> {
+        self.try_generate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::Clock;
+    use crate::epochs::SONYFLAKE_MILLIS;
+    use crate::generator_policy::ClockRegressionPolicy;
+    use crate::id_generator::IdGenerator;
+    use crate::snow_flake_id::SnowflakeIdError;
+    use crate::snowflake_error::SnowflakeIdEGeneratorError;
+    use crate::sonyflake_generator::{SonyflakeGenerator, SonyflakeId};
+    use std::sync::atomic::AtomicI64;
+    use std::sync::atomic::Ordering::Relaxed;
+
+    struct Fixture(AtomicI64);
+
+    impl Fixture {
+        fn at(elapsed_millis: i64) -> Self {
+            Fixture(AtomicI64::new(SONYFLAKE_MILLIS + elapsed_millis))
+        }
+
+        fn advance(&self, millis: i64) {
+            self.0.fetch_add(millis, Relaxed);
+        }
+    }
+
+    impl Clock for &Fixture {
+        fn now_millis(&self) -> i64 {
+            self.0.load(Relaxed)
+        }
+    }
+
+    #[test]
+    fn id_test() {
+        let actual = SonyflakeId::new(5, 2, 0xbeef).unwrap();
+
+        assert_eq!(actual.as_u64(), (5 << 24) | (2 << 16) | 0xbeef);
+        assert_eq!(actual.elapsed_time(), 5);
+        assert_eq!(actual.sequence(), 2);
+        assert_eq!(actual.machine_id(), 0xbeef);
+        assert_eq!(
+            actual.timestamp_millis(SONYFLAKE_MILLIS),
+            SONYFLAKE_MILLIS + 50
+        );
+        assert_eq!(actual.to_string(), actual.as_u64().to_string());
+    }
+
+    #[test]
+    fn invalid_id_test() {
+        assert_eq!(
+            SonyflakeId::new(1 << 39, 0, 0),
+            Err(SnowflakeIdError::Timestamp {
+                value: 1 << 39,
+                max: (1 << 39) - 1
+            })
+        );
+
+        let max = SonyflakeId::new((1 << 39) - 1, u8::MAX, u16::MAX).unwrap();
+        assert_eq!(max.as_u64(), i64::MAX as u64);
+    }
+
+    #[test]
+    fn generate_test() {
+        let clock = Fixture::at(25);
+        let target = SonyflakeGenerator::new(&clock, 42);
+
+        assert_eq!(target.epoch_millis(), SONYFLAKE_MILLIS);
+        assert_eq!(target.machine_id(), 42);
+
+        for i in 0..3u8 {
+            let actual = target.generate().unwrap();
+            assert_eq!(actual.elapsed_time(), 2);
+            assert_eq!(actual.sequence(), i);
+            assert_eq!(actual.machine_id(), 42);
+        }
+
+        clock.advance(10);
+        let actual = target.generate().unwrap();
+        assert_eq!(actual.elapsed_time(), 3);
+        assert_eq!(actual.sequence(), 0);
+    }
+
+    #[test]
+    fn sequence_exhausted_test() {
+        let clock = Fixture::at(10);
+        let target = SonyflakeGenerator::new(&clock, 1);
+
+        for _ in 0..256 {
+            target.try_generate().unwrap();
+        }
+
+        assert_eq!(
+            target.try_generate(),
+            Err(SnowflakeIdEGeneratorError::SequenceExhausted)
+        );
+
+        clock.advance(10);
+        assert_eq!(target.try_generate().unwrap().sequence(), 0);
+    }
+
+    #[test]
+    fn clock_regression_test() {
+        let clock = Fixture::at(100);
+        let target = SonyflakeGenerator::new(&clock, 1);
+        target.generate().unwrap();
+
+        clock.advance(-20);
+        assert_eq!(
+            target.try_generate(),
+            Err(SnowflakeIdEGeneratorError::ClockMovedBackwards)
+        );
+
+        let target = target.with_clock_regression_policy(ClockRegressionPolicy::UseLastTimestamp);
+        let actual = target.try_generate().unwrap();
+        assert_eq!(actual.elapsed_time(), 10);
+        assert_eq!(actual.sequence(), 1);
+    }
+
+    #[test]
+    fn epoch_in_future_test() {
+        let clock = Fixture::at(0);
+        let target = SonyflakeGenerator::with_epoch_millis(&clock, SONYFLAKE_MILLIS + 60_000, 1);
+
+        assert_eq!(
+            target.try_generate(),
+            Err(SnowflakeIdEGeneratorError::EpochInFuture)
+        );
+    }
+
+    #[test]
+    fn timestamp_overflow_test() {
+        // The last 10 ms tick the 39-bit field holds, then the first one past it.
+        let clock = Fixture::at(((1 << 39) - 1) * 10);
+        let target = SonyflakeGenerator::new(&clock, 1);
+        assert_eq!(target.generate().unwrap().elapsed_time(), (1 << 39) - 1);
+
+        clock.advance(10);
+        assert_eq!(
+            target.try_generate(),
+            Err(SnowflakeIdEGeneratorError::TimestampOverflow {
+                value: 1 << 39,
+                max: (1 << 39) - 1
+            })
+        );
+
+        let clock = Fixture(AtomicI64::new(i64::MAX));
+        let target = SonyflakeGenerator::with_epoch_millis(&clock, i64::MIN, 1);
+        assert_eq!(
+            target.try_generate(),
+            Err(SnowflakeIdEGeneratorError::TimestampOverflow {
+                value: u64::MAX / 10,
+                max: (1 << 39) - 1
+            })
+        );
+    }
+
+    #[test]
+    fn id_generator_test() {
+        let clock = Fixture::at(0);
+        let target = SonyflakeGenerator::with_epoch_millis(&clock, SONYFLAKE_MILLIS - 1_000, 7);
+
+        let actual = IdGenerator::generate(&target).unwrap();
+        assert_eq!(actual.elapsed_time(), 100);
+        assert_eq!(actual.machine_id(), 7);
+    }
+}
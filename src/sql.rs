@@ -0,0 +1,252 @@
+use crate::bit_layout::BitLayout;
+use crate::decoder::Decoder;
+use alloc::format;
+use alloc::string::String;
+
+// Emits PostgreSQL that decodes IDs the way `Decoder` does, so a database can derive
+// `created_at` (or index by machine) without the epoch and layout being copied by hand
+// into a migration. Regenerate the SQL whenever the generator's configuration changes.
+//
+// IDs are stored as `bigint`, which is signed: the shifts are arithmetic, so every field
+// is masked after shifting to read IDs with the top bit set correctly. Layouts with a
+// 64-bit timestamp field have no room for that and are not supported.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct PgSql {
+    decoder: Decoder,
+    prefix: String,
+}
+
+fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+impl PgSql {
+    pub fn new(decoder: Decoder) -> Self {
+        PgSql {
+            decoder,
+            prefix: String::from("snowflake"),
+        }
+    }
+
+    // Function names are `<prefix>_created_at` and so on; `snowflake` by default. The
+    // prefix may be schema qualified (`ids.snowflake`) and is emitted as is.
+    pub fn with_function_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = String::from(prefix);
+        self
+    }
+
+    pub fn decoder(&self) -> &Decoder {
+        &self.decoder
+    }
+
+    pub fn function_prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    fn layout(&self) -> &BitLayout {
+        self.decoder.layout()
+    }
+
+    fn field(&self, id: &str, shift: u32, mask: u64) -> String {
+        match shift {
+            0 => format!("({} & {})", id, mask),
+            _ => format!("(({} >> {}) & {})", id, shift, mask),
+        }
+    }
+
+    fn raw_timestamp_expr(&self, id: &str) -> String {
+        let layout = self.layout();
+        self.field(
+            id,
+            layout.sequence_bits() + layout.machine_bits(),
+            layout.max_timestamp(),
+        )
+    }
+
+    fn created_at(&self, id: &str) -> String {
+        format!(
+            "to_timestamp(({} * {} + {}) / 1000000.0)",
+            self.raw_timestamp_expr(id),
+            self.decoder.tick_unit().as_micros(),
+            self.decoder.epoch_millis() as i128 * 1_000
+        )
+    }
+
+    fn machine_id(&self, id: &str) -> String {
+        let layout = self.layout();
+        self.field(id, layout.sequence_bits(), layout.max_machine_id() as u64)
+    }
+
+    fn sequence(&self, id: &str) -> String {
+        self.field(id, 0, self.layout().max_sequence() as u64)
+    }
+
+    // A `timestamptz` expression over the `bigint` column `id_column`.
+    pub fn created_at_expr(&self, id_column: &str) -> String {
+        self.created_at(&quote_ident(id_column))
+    }
+
+    pub fn machine_id_expr(&self, id_column: &str) -> String {
+        self.machine_id(&quote_ident(id_column))
+    }
+
+    pub fn sequence_expr(&self, id_column: &str) -> String {
+        self.sequence(&quote_ident(id_column))
+    }
+
+    // A column definition for `CREATE TABLE` or `ALTER TABLE ... ADD COLUMN`.
+    pub fn generated_column(&self, id_column: &str, column: &str) -> String {
+        format!(
+            "{} timestamptz GENERATED ALWAYS AS ({}) STORED",
+            quote_ident(column),
+            self.created_at_expr(id_column)
+        )
+    }
+
+    // `CREATE OR REPLACE FUNCTION` statements for `<prefix>_created_at(bigint)`,
+    // `<prefix>_machine_id(bigint)`, `<prefix>_sequence(bigint)` and
+    // `<prefix>_min_id(timestamptz)`, the smallest ID issued at or after an instant (for
+    // range scans on the primary key). The header comment records the configuration, so
+    // two deployments can be compared by diffing their migrations.
+    pub fn functions(&self) -> String {
+        let layout = self.layout();
+        let prefix = &self.prefix;
+        let function = |name: &str, arg: &str, returns: &str, body: String| {
+            format!(
+                "CREATE OR REPLACE FUNCTION {prefix}_{name}({arg}) RETURNS {returns}\n\
+                 LANGUAGE plpgsql IMMUTABLE STRICT PARALLEL SAFE AS $$\n\
+                 BEGIN\n    RETURN {body};\nEND;\n$$;\n"
+            )
+        };
+
+        let min_id = format!(
+            "greatest(0, ceil((extract(epoch FROM ts) * 1000000 - {}) / {}))::bigint << {}",
+            self.decoder.epoch_millis() as i128 * 1_000,
+            self.decoder.tick_unit().as_micros(),
+            layout.sequence_bits() + layout.machine_bits()
+        );
+
+        format!(
+            "-- epoch_millis={}, layout={}/{}/{}, tick_unit={:?}\n{}\n{}\n{}\n{}",
+            self.decoder.epoch_millis(),
+            layout.timestamp_bits(),
+            layout.machine_bits(),
+            layout.sequence_bits(),
+            self.decoder.tick_unit(),
+            function(
+                "created_at",
+                "id bigint",
+                "timestamptz",
+                self.created_at("id")
+            ),
+            function(
+                "machine_id",
+                "id bigint",
+                "integer",
+                format!("{}::integer", self.machine_id("id"))
+            ),
+            function(
+                "sequence",
+                "id bigint",
+                "integer",
+                format!("{}::integer", self.sequence("id"))
+            ),
+            function("min_id", "ts timestamptz", "bigint", min_id),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::decoder::Decoder;
+    use crate::epochs::TWITTER_MILLIS;
+    use crate::generator_policy::TickUnit;
+    use crate::sql::PgSql;
+    use crate::THE_EPOCH_MILLIS;
+
+    #[test]
+    fn expr_test() {
+        let target = PgSql::new(Decoder::default());
+
+        assert_eq!(
+            target.created_at_expr("id"),
+            "to_timestamp((((\"id\" >> 22) & 4398046511103) * 1000 + 1693526400000000) / 1000000.0)"
+        );
+        assert_eq!(target.machine_id_expr("id"), "((\"id\" >> 12) & 1023)");
+        assert_eq!(target.sequence_expr("id"), "(\"id\" & 4095)");
+    }
+
+    #[test]
+    fn layout_and_tick_test() {
+        let decoder =
+            Decoder::new(TWITTER_MILLIS, BitLayout::INSTAGRAM).with_tick_unit(TickUnit::TenMillis);
+        let target = PgSql::new(decoder);
+
+        assert_eq!(
+            target.created_at_expr("pk"),
+            "to_timestamp((((\"pk\" >> 23) & 2199023255551) * 10000 + 1288834974657000) / 1000000.0)"
+        );
+        assert_eq!(target.machine_id_expr("pk"), "((\"pk\" >> 10) & 8191)");
+        assert_eq!(target.sequence_expr("pk"), "(\"pk\" & 1023)");
+    }
+
+    #[test]
+    fn quote_test() {
+        let target = PgSql::new(Decoder::default());
+        assert_eq!(
+            target.sequence_expr("odd\"name"),
+            "(\"odd\"\"name\" & 4095)"
+        );
+    }
+
+    #[test]
+    fn generated_column_test() {
+        let target = PgSql::new(Decoder::new(0, BitLayout::DEFAULT));
+
+        assert_eq!(
+            target.generated_column("id", "created_at"),
+            "\"created_at\" timestamptz GENERATED ALWAYS AS \
+             (to_timestamp((((\"id\" >> 22) & 4398046511103) * 1000 + 0) / 1000000.0)) STORED"
+        );
+    }
+
+    #[test]
+    fn functions_test() {
+        let target = PgSql::new(Decoder::default()).with_function_prefix("ids.sf");
+        assert_eq!(target.function_prefix(), "ids.sf");
+        assert_eq!(target.decoder().epoch_millis(), THE_EPOCH_MILLIS);
+
+        let expected = "\
+-- epoch_millis=1693526400000, layout=42/10/12, tick_unit=Millis
+CREATE OR REPLACE FUNCTION ids.sf_created_at(id bigint) RETURNS timestamptz
+LANGUAGE plpgsql IMMUTABLE STRICT PARALLEL SAFE AS $$
+BEGIN
+    RETURN to_timestamp((((id >> 22) & 4398046511103) * 1000 + 1693526400000000) / 1000000.0);
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION ids.sf_machine_id(id bigint) RETURNS integer
+LANGUAGE plpgsql IMMUTABLE STRICT PARALLEL SAFE AS $$
+BEGIN
+    RETURN ((id >> 12) & 1023)::integer;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION ids.sf_sequence(id bigint) RETURNS integer
+LANGUAGE plpgsql IMMUTABLE STRICT PARALLEL SAFE AS $$
+BEGIN
+    RETURN (id & 4095)::integer;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION ids.sf_min_id(ts timestamptz) RETURNS bigint
+LANGUAGE plpgsql IMMUTABLE STRICT PARALLEL SAFE AS $$
+BEGIN
+    RETURN greatest(0, ceil((extract(epoch FROM ts) * 1000000 - 1693526400000000) / 1000))::bigint << 22;
+END;
+$$;
+";
+        assert_eq!(target.functions(), expected);
+    }
+}
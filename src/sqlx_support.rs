@@ -0,0 +1,71 @@
+use crate::snow_flake_id::SnowflakeId;
+use sqlx::database::{HasArguments, HasValueRef};
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::{Database, Decode, Encode, Type};
+
+// IDs are stored as BIGINT, i.e. whatever the driver maps `i64` to. The default layout
+// leaves the sign bit to the timestamp, so values stay positive until the epoch is
+// roughly 69 years old.
+impl<DB: Database> Type<DB> for SnowflakeId
+where
+    i64: Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <i64 as Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <i64 as Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'q, DB: Database> Encode<'q, DB> for SnowflakeId
+where
+    i64: Encode<'q, DB>,
+{
+    fn encode_by_ref(&self, buf: &mut <DB as HasArguments<'q>>::ArgumentBuffer) -> IsNull {
+        self.as_i64().encode_by_ref(buf)
+    }
+}
+
+impl<'r, DB: Database> Decode<'r, DB> for SnowflakeId
+where
+    i64: Decode<'r, DB>,
+{
+    fn decode(value: <DB as HasValueRef<'r>>::ValueRef) -> Result<Self, BoxDynError> {
+        <i64 as Decode<DB>>::decode(value).map(SnowflakeId::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::snow_flake_id::SnowflakeId;
+    use sqlx::postgres::{PgArgumentBuffer, PgTypeInfo};
+    use sqlx::{Encode, Postgres, Type};
+
+    const SAMPLE_SCR: u64 = 175_928_847_299_678_215;
+
+    #[test]
+    fn type_info_test() {
+        assert_eq!(
+            <SnowflakeId as Type<Postgres>>::type_info(),
+            <i64 as Type<Postgres>>::type_info()
+        );
+        assert!(<SnowflakeId as Type<Postgres>>::compatible(
+            &PgTypeInfo::with_name("INT8")
+        ));
+    }
+
+    #[test]
+    fn encode_test() {
+        let mut actual = PgArgumentBuffer::default();
+        let mut expected = PgArgumentBuffer::default();
+
+        let _ = Encode::<Postgres>::encode_by_ref(&SnowflakeId::from(SAMPLE_SCR), &mut actual);
+        let _ = Encode::<Postgres>::encode_by_ref(&(SAMPLE_SCR as i64), &mut expected);
+
+        assert_eq!(&actual[..], &expected[..]);
+        assert_eq!(&actual[..], &SAMPLE_SCR.to_be_bytes());
+    }
+}
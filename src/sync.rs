@@ -0,0 +1,36 @@
+// The generator's state lives behind this alias so that
+// `RUSTFLAGS="--cfg snowflake_loom"` swaps in loom's model-checked atomic.
+//
+// wasm32 has 64-bit atomics; without the `atomics` target feature (the default for
+// `wasm32-unknown-unknown`) they lower to plain loads and stores, which is all a
+// single-threaded module needs.
+#[cfg(not(snowflake_loom))]
+pub(crate) use core::sync::atomic::AtomicU64;
+#[cfg(snowflake_loom)]
+pub(crate) use loom::sync::atomic::AtomicU64;
+
+// Gives a hot atomic a cache line of its own, so CAS traffic on it neither invalidates
+// the read-mostly fields next to it nor the state of a neighbouring generator when
+// generators sit in an array. x86_64 prefetches lines in pairs and Apple's aarch64 cores
+// use 128-byte lines, hence the larger alignment there.
+#[cfg_attr(any(target_arch = "x86_64", target_arch = "aarch64"), repr(align(128)))]
+#[cfg_attr(
+    not(any(target_arch = "x86_64", target_arch = "aarch64")),
+    repr(align(64))
+)]
+#[derive(Default)]
+pub(crate) struct CachePadded<T>(T);
+
+impl<T> CachePadded<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        CachePadded(value)
+    }
+}
+
+impl<T> core::ops::Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
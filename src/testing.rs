@@ -0,0 +1,799 @@
+use crate::bit_layout::BitLayout;
+use crate::clock::Clock;
+use crate::id_generator::IdGenerator;
+use crate::snow_flake_id::SnowflakeId;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+#[cfg(feature = "chrono")]
+use crate::timestamp::Timestamp;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+use std::sync::atomic::{AtomicI64, AtomicU64};
+use std::sync::Arc;
+#[cfg(feature = "chrono")]
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// A clock that only moves when told to. Clones share the same time, so a test can keep
+// one handle and move the clock while a generator owns another.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    millis: Arc<AtomicI64>,
+}
+
+impl ManualClock {
+    pub fn new(millis: i64) -> Self {
+        ManualClock {
+            millis: Arc::new(AtomicI64::new(millis)),
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn at(instant: DateTime<Utc>) -> Self {
+        ManualClock::new(instant.timestamp_millis())
+    }
+
+    pub fn millis(&self) -> i64 {
+        self.millis.load(Acquire)
+    }
+
+    pub fn set_millis(&self, millis: i64) {
+        self.millis.store(millis, Release)
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.millis.fetch_add(duration.as_millis() as i64, Release);
+    }
+
+    pub fn rewind(&self, duration: Duration) {
+        self.millis.fetch_sub(duration.as_millis() as i64, Release);
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for ManualClock {
+    fn timestamp(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(self.millis()).unwrap()
+    }
+}
+
+#[cfg(not(feature = "chrono"))]
+impl Clock for ManualClock {
+    fn now_millis(&self) -> i64 {
+        self.millis()
+    }
+}
+
+// A timestamp that only moves when told to. Clones share the same instant, so a test can
+// hand one to a generator and keep another to drive it, including backwards with a
+// negative `advance` to exercise clock regression.
+#[cfg(feature = "chrono")]
+#[derive(Clone, Debug)]
+pub struct ManualTimestamp(Arc<Mutex<DateTime<Utc>>>);
+
+#[cfg(feature = "chrono")]
+impl ManualTimestamp {
+    pub fn new(instant: DateTime<Utc>) -> Self {
+        ManualTimestamp(Arc::new(Mutex::new(instant)))
+    }
+
+    pub fn set(&self, instant: DateTime<Utc>) {
+        *self.0.lock().unwrap() = instant;
+    }
+
+    pub fn advance(&self, duration: chrono::Duration) {
+        *self.0.lock().unwrap() += duration;
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for ManualTimestamp {
+    fn timestamp(&self) -> DateTime<Utc> {
+        *self.0.lock().unwrap()
+    }
+}
+
+// splitmix64: tiny, good enough to spread steps, and stable across platforms and releases.
+fn next_random(state: &AtomicU64) -> u64 {
+    let mut z = state
+        .fetch_add(0x9e37_79b9_7f4a_7c15, Relaxed)
+        .wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce5_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+// Produces the same stream of IDs for the same seed and starting clock on every run, so
+// tests can snapshot IDs. Before each ID the virtual clock moves forward by a seeded step
+// of `0..=max_step_millis`, which exercises both sequence increments and new
+// milliseconds. The machine id is derived from the seed as well.
+pub struct ReplayGenerator {
+    generator: SnowFlakeIdGenerator<ManualClock>,
+    clock: ManualClock,
+    max_step_millis: u64,
+    state: AtomicU64,
+}
+
+impl ReplayGenerator {
+    pub fn new(seed: u64, clock: ManualClock) -> Self {
+        ReplayGenerator::with_layout(seed, clock, BitLayout::DEFAULT)
+    }
+
+    pub fn with_layout(seed: u64, clock: ManualClock, layout: BitLayout) -> Self {
+        let state = AtomicU64::new(seed);
+        let machine_id = (next_random(&state) & layout.max_machine_id() as u64) as u16;
+        let epoch_millis = clock.millis();
+
+        ReplayGenerator {
+            generator: SnowFlakeIdGenerator::with_epoch_millis(
+                clock.clone(),
+                epoch_millis,
+                machine_id,
+                layout,
+            )
+            .unwrap(),
+            clock,
+            max_step_millis: 1,
+            state,
+        }
+    }
+
+    pub fn with_max_step(mut self, max_step: Duration) -> Self {
+        self.max_step_millis = max_step.as_millis() as u64;
+        self
+    }
+
+    pub fn clock(&self) -> &ManualClock {
+        &self.clock
+    }
+
+    pub fn machine_id(&self) -> u16 {
+        self.generator.machine_id()
+    }
+
+    pub fn epoch_millis(&self) -> i64 {
+        self.generator.epoch_millis()
+    }
+
+    pub fn try_generate(&self) -> Result<SnowflakeId, SnowflakeIdEGeneratorError> {
+        let step = next_random(&self.state) % (self.max_step_millis + 1);
+        self.clock.advance(Duration::from_millis(step));
+
+        match self.generator.try_generate() {
+            Err(SnowflakeIdEGeneratorError::SequenceExhausted) => {
+                self.clock.advance(Duration::from_millis(1));
+                self.generator.try_generate()
+            }
+            other => other,
+        }
+    }
+
+    pub fn generate(&self) -> Option<SnowflakeId> {
+        self.try_generate().ok()
+    }
+}
+
+impl Iterator for &ReplayGenerator {
+    type Item = SnowflakeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.generate()
+    }
+}
+
+// Wraps another clock and misbehaves on purpose: each reading is off by up to `jitter`
+// either way, now and then the clock freezes for a while (a stalled VM, a descheduled
+// thread), and now and then it steps back (an NTP correction). Steps back are permanent,
+// as they would be on a real machine. The faults are drawn from a seeded stream, so a
+// failing run can be replayed, and counted, so a test can check that they happened.
+#[derive(Debug)]
+pub struct JitterClock<C: Clock> {
+    inner: C,
+    state: AtomicU64,
+    jitter_millis: i64,
+    stall_probability: f64,
+    stall_millis: i64,
+    backward_probability: f64,
+    backward_millis: i64,
+    offset: AtomicI64,
+    stalled_until: AtomicI64,
+    stalled_at: AtomicI64,
+    stalls: AtomicU64,
+    backward_jumps: AtomicU64,
+}
+
+impl<C: Clock> JitterClock<C> {
+    // Starts out well-behaved; enable faults with the `with_*` setters.
+    pub fn new(inner: C, seed: u64) -> Self {
+        JitterClock {
+            inner,
+            state: AtomicU64::new(seed),
+            jitter_millis: 0,
+            stall_probability: 0.0,
+            stall_millis: 0,
+            backward_probability: 0.0,
+            backward_millis: 0,
+            offset: AtomicI64::new(0),
+            stalled_until: AtomicI64::new(i64::MIN),
+            stalled_at: AtomicI64::new(0),
+            stalls: AtomicU64::new(0),
+            backward_jumps: AtomicU64::new(0),
+        }
+    }
+
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter_millis = jitter.as_millis() as i64;
+        self
+    }
+
+    // Each reading freezes the clock for `duration` (of the inner clock's time) with the
+    // given probability.
+    pub fn with_stalls(mut self, probability: f64, duration: Duration) -> Self {
+        self.stall_probability = probability;
+        self.stall_millis = duration.as_millis() as i64;
+        self
+    }
+
+    // Each reading steps the clock back by `jump` with the given probability.
+    pub fn with_backward_jumps(mut self, probability: f64, jump: Duration) -> Self {
+        self.backward_probability = probability;
+        self.backward_millis = jump.as_millis() as i64;
+        self
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn stalls(&self) -> u64 {
+        self.stalls.load(Relaxed)
+    }
+
+    pub fn backward_jumps(&self) -> u64 {
+        self.backward_jumps.load(Relaxed)
+    }
+
+    fn chance(&self, probability: f64) -> bool {
+        // The top 53 bits as a uniform double in [0, 1).
+        if probability <= 0.0 {
+            return false;
+        }
+
+        let sample = (next_random(&self.state) >> 11) as f64 / (1u64 << 53) as f64;
+        sample < probability
+    }
+
+    fn read(&self) -> i64 {
+        let now = self.inner.now_millis();
+
+        if now < self.stalled_until.load(Relaxed) {
+            return self.stalled_at.load(Relaxed);
+        }
+
+        if self.chance(self.stall_probability) {
+            let reading = now + self.offset.load(Relaxed);
+            self.stalled_at.store(reading, Relaxed);
+            self.stalled_until.store(now + self.stall_millis, Relaxed);
+            self.stalls.fetch_add(1, Relaxed);
+            return reading;
+        }
+
+        if self.chance(self.backward_probability) {
+            self.offset.fetch_sub(self.backward_millis, Relaxed);
+            self.backward_jumps.fetch_add(1, Relaxed);
+        }
+
+        let jitter = match self.jitter_millis {
+            0 => 0,
+            max => (next_random(&self.state) % (2 * max as u64 + 1)) as i64 - max,
+        };
+
+        now + self.offset.load(Relaxed) + jitter
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<C: Clock> Timestamp for JitterClock<C> {
+    fn timestamp(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(self.read()).unwrap_or_default()
+    }
+}
+
+#[cfg(not(feature = "chrono"))]
+impl<C: Clock> Clock for JitterClock<C> {
+    fn now_millis(&self) -> i64 {
+        self.read()
+    }
+}
+
+// Hammers one or more generators from several threads for a while and reports any ID that
+// came out twice, and any thread that saw a generator go backwards. Threads are spread
+// round-robin over the generators, so pass generators with distinct machine ids to check
+// that they never collide with each other. Generation errors (e.g. an exhausted
+// millisecond) are counted and retried.
+#[derive(Clone, Debug)]
+pub struct CollisionHarness {
+    threads: usize,
+    duration: Duration,
+    limit_per_thread: Option<usize>,
+    check_ordering: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct CollisionReport<I> {
+    pub generated: usize,
+    pub errors: usize,
+    // Every extra occurrence of an ID, so an ID issued three times shows up twice.
+    pub duplicates: Vec<I>,
+    // Pairs of consecutive IDs a thread got from the same generator, later one second.
+    pub ordering_violations: Vec<(I, I)>,
+    pub elapsed: Duration,
+}
+
+impl<I: Debug> CollisionReport<I> {
+    pub fn is_clean(&self) -> bool {
+        self.duplicates.is_empty() && self.ordering_violations.is_empty()
+    }
+
+    pub fn assert_clean(&self) {
+        assert!(
+            self.is_clean(),
+            "{} duplicate(s) and {} ordering violation(s) among {} IDs; first duplicates: {:?}, first violations: {:?}",
+            self.duplicates.len(),
+            self.ordering_violations.len(),
+            self.generated,
+            &self.duplicates[..self.duplicates.len().min(5)],
+            &self.ordering_violations[..self.ordering_violations.len().min(5)],
+        );
+    }
+}
+
+impl CollisionHarness {
+    pub fn new(threads: usize, duration: Duration) -> Self {
+        CollisionHarness {
+            threads: threads.max(1),
+            duration,
+            limit_per_thread: None,
+            check_ordering: true,
+        }
+    }
+
+    // Stops each thread after this many IDs even if time is left, to bound memory.
+    pub fn with_limit_per_thread(mut self, limit: usize) -> Self {
+        self.limit_per_thread = Some(limit);
+        self
+    }
+
+    // Generators that hop between internal shards (e.g. `ShardedSnowflakeGenerator`) are
+    // not monotonic per thread; turn the check off for those.
+    pub fn with_ordering_check(mut self, check: bool) -> Self {
+        self.check_ordering = check;
+        self
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn run<G>(&self, generator: &G) -> CollisionReport<G::Id>
+    where
+        G: IdGenerator + Sync,
+        G::Id: Ord + Hash + Copy + Send,
+    {
+        self.run_many(&[generator])
+    }
+
+    // Panics if `generators` is empty.
+    pub fn run_many<G>(&self, generators: &[&G]) -> CollisionReport<G::Id>
+    where
+        G: IdGenerator + Sync,
+        G::Id: Ord + Hash + Copy + Send,
+    {
+        assert!(!generators.is_empty(), "no generator to test");
+
+        let started = Instant::now();
+        let deadline = started + self.duration;
+        let limit = self.limit_per_thread.unwrap_or(usize::MAX);
+
+        let results: Vec<(Vec<G::Id>, usize)> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..self.threads)
+                .map(|i| {
+                    let generator = generators[i % generators.len()];
+                    scope.spawn(move || {
+                        let mut ids = Vec::new();
+                        let mut errors = 0;
+
+                        while ids.len() < limit && Instant::now() < deadline {
+                            match generator.generate() {
+                                Ok(id) => ids.push(id),
+                                Err(_) => errors += 1,
+                            }
+                        }
+
+                        (ids, errors)
+                    })
+                })
+                .collect();
+
+            handles.into_iter().map(|x| x.join().unwrap()).collect()
+        });
+
+        let elapsed = started.elapsed();
+        let mut seen = HashSet::new();
+        let mut report = CollisionReport {
+            generated: 0,
+            errors: 0,
+            duplicates: Vec::new(),
+            ordering_violations: Vec::new(),
+            elapsed,
+        };
+
+        for (ids, errors) in results {
+            report.generated += ids.len();
+            report.errors += errors;
+
+            if self.check_ordering {
+                report.ordering_violations.extend(
+                    ids.windows(2)
+                        .filter(|pair| pair[0] >= pair[1])
+                        .map(|pair| (pair[0], pair[1])),
+                );
+            }
+
+            report
+                .duplicates
+                .extend(ids.into_iter().filter(|id| !seen.insert(*id)));
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::clock::Clock;
+    use crate::clock::SystemClock;
+    use crate::id_generator::IdGenerator;
+    use crate::snowflake_error::SnowflakeIdEGeneratorError;
+    use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+    use crate::testing::{
+        CollisionHarness, CollisionReport, JitterClock, ManualClock, ReplayGenerator,
+    };
+    use crate::THE_EPOCH_MILLIS;
+    use std::collections::HashSet;
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::time::Duration;
+
+    #[test]
+    fn manual_clock_test() {
+        let target = ManualClock::new(THE_EPOCH_MILLIS);
+        let shared = target.clone();
+        assert_eq!(target.now_millis(), THE_EPOCH_MILLIS);
+
+        shared.advance(Duration::from_millis(1500));
+        assert_eq!(target.now_millis(), THE_EPOCH_MILLIS + 1500);
+
+        shared.rewind(Duration::from_secs(1));
+        assert_eq!(target.now_millis(), THE_EPOCH_MILLIS + 500);
+
+        target.set_millis(42);
+        assert_eq!(shared.millis(), 42);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn manual_clock_timestamp_test() {
+        use crate::timestamp::Timestamp;
+
+        let target = ManualClock::at(*crate::THE_EPOCH);
+        assert_eq!(target.timestamp(), *crate::THE_EPOCH);
+
+        target.advance(Duration::from_millis(5));
+        assert_eq!(
+            target.timestamp(),
+            *crate::THE_EPOCH + chrono::Duration::milliseconds(5)
+        );
+    }
+
+    #[test]
+    fn replay_test() {
+        let run = |seed| {
+            let target = ReplayGenerator::new(seed, ManualClock::new(THE_EPOCH_MILLIS));
+            target.take(1000).collect::<Vec<_>>()
+        };
+
+        let first = run(42);
+        assert_eq!(first, run(42));
+        assert_ne!(first, run(43));
+
+        assert!(first.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(first
+            .iter()
+            .all(|id| id.machine_id() == first[0].machine_id()));
+        assert!(first.iter().any(|id| id.sequence() > 0));
+    }
+
+    #[test]
+    fn replay_snapshot_test() {
+        let target = ReplayGenerator::new(7, ManualClock::new(THE_EPOCH_MILLIS))
+            .with_max_step(Duration::from_millis(3));
+
+        let actual: Vec<_> = target
+            .take(4)
+            .map(|id| (id.raw_timestamp(), id.machine_id(), id.sequence()))
+            .collect();
+
+        // Pinned so that a change to the stream shows up as a test failure.
+        assert_eq!(actual, [(3, 323, 0), (6, 323, 0), (6, 323, 1), (8, 323, 0)]);
+        assert_eq!(target.clock().millis(), THE_EPOCH_MILLIS + 8);
+        assert_eq!(target.epoch_millis(), THE_EPOCH_MILLIS);
+    }
+
+    #[test]
+    fn replay_exhaustion_test() {
+        let layout = BitLayout::new(41, 10, 1).unwrap();
+        let target = ReplayGenerator::with_layout(1, ManualClock::new(THE_EPOCH_MILLIS), layout)
+            .with_max_step(Duration::ZERO);
+
+        let actual: Vec<_> = target
+            .take(5)
+            .map(|id| (layout.raw_timestamp(id), layout.sequence(id)))
+            .collect();
+
+        // The generator treats the epoch millisecond's first slot as taken, so the first
+        // ID starts at sequence 1; a full millisecond pushes the clock forward.
+        assert_eq!(actual, [(0, 1), (1, 0), (1, 1), (2, 0), (2, 1)]);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn manual_timestamp_test() {
+        use crate::testing::ManualTimestamp;
+        use crate::timestamp::Timestamp;
+
+        let target = ManualTimestamp::new(*crate::THE_EPOCH);
+        let shared = target.clone();
+        assert_eq!(target.timestamp(), *crate::THE_EPOCH);
+
+        shared.advance(chrono::Duration::milliseconds(10));
+        assert_eq!(
+            target.timestamp(),
+            *crate::THE_EPOCH + chrono::Duration::milliseconds(10)
+        );
+
+        shared.advance(chrono::Duration::milliseconds(-3));
+        assert_eq!(
+            target.timestamp(),
+            *crate::THE_EPOCH + chrono::Duration::milliseconds(7)
+        );
+
+        target.set(*crate::THE_EPOCH + chrono::Duration::days(1));
+        assert_eq!(
+            shared.timestamp(),
+            *crate::THE_EPOCH + chrono::Duration::days(1)
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn manual_timestamp_generator_test() {
+        use crate::snowflake_error::SnowflakeIdEGeneratorError;
+        use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+        use crate::testing::ManualTimestamp;
+
+        let clock = ManualTimestamp::new(*crate::THE_EPOCH + chrono::Duration::milliseconds(5));
+        let target = SnowFlakeIdGenerator::with_epoch_millis(
+            clock.clone(),
+            THE_EPOCH_MILLIS,
+            1,
+            BitLayout::DEFAULT,
+        )
+        .unwrap();
+
+        assert_eq!(target.generate().unwrap().raw_timestamp(), 5);
+
+        clock.advance(chrono::Duration::milliseconds(-2));
+        assert_eq!(
+            target.try_generate(),
+            Err(SnowflakeIdEGeneratorError::ClockMovedBackwards)
+        );
+
+        clock.advance(chrono::Duration::milliseconds(4));
+        assert_eq!(target.generate().unwrap().raw_timestamp(), 7);
+    }
+
+    #[test]
+    fn collision_harness_test() {
+        let target = SnowFlakeIdGenerator::with_epoch_millis(
+            SystemClock,
+            THE_EPOCH_MILLIS,
+            1,
+            BitLayout::DEFAULT,
+        )
+        .unwrap();
+
+        let harness = CollisionHarness::new(4, Duration::from_millis(50));
+        let report = harness.run(&target);
+
+        report.assert_clean();
+        assert!(report.generated > 0);
+        assert!(report.elapsed >= harness.duration());
+    }
+
+    #[test]
+    fn collision_harness_many_test() {
+        let generator = |machine_id| {
+            SnowFlakeIdGenerator::with_epoch_millis(
+                SystemClock,
+                THE_EPOCH_MILLIS,
+                machine_id,
+                BitLayout::DEFAULT,
+            )
+            .unwrap()
+        };
+        let (first, second) = (generator(1), generator(2));
+
+        let report = CollisionHarness::new(4, Duration::from_secs(1))
+            .with_limit_per_thread(10_000)
+            .run_many(&[&first, &second]);
+
+        report.assert_clean();
+        assert_eq!(report.generated, 40_000);
+    }
+
+    #[test]
+    fn collision_harness_detects_duplicates_test() {
+        // Two generators sharing a machine id and a frozen clock issue the same 4096 IDs.
+        let clock = ManualClock::new(THE_EPOCH_MILLIS + 1);
+        let generator = || {
+            SnowFlakeIdGenerator::with_epoch_millis(
+                clock.clone(),
+                THE_EPOCH_MILLIS,
+                7,
+                BitLayout::DEFAULT,
+            )
+            .unwrap()
+        };
+        let (first, second) = (generator(), generator());
+
+        let report =
+            CollisionHarness::new(2, Duration::from_millis(20)).run_many(&[&first, &second]);
+
+        assert!(!report.is_clean());
+        assert_eq!(report.generated, 8_192);
+        assert_eq!(report.duplicates.len(), 4_096);
+        assert!(report.ordering_violations.is_empty());
+        assert!(report.errors > 0);
+    }
+
+    struct Wrapping(AtomicU64);
+
+    impl IdGenerator for Wrapping {
+        type Id = u64;
+        type Error = ();
+
+        fn generate(&self) -> Result<u64, ()> {
+            Ok(self.0.fetch_add(1, Relaxed) % 10)
+        }
+    }
+
+    #[test]
+    fn collision_harness_detects_ordering_test() {
+        let target = Wrapping(AtomicU64::new(0));
+        let harness = CollisionHarness::new(1, Duration::from_secs(1)).with_limit_per_thread(25);
+
+        let report = harness.run(&target);
+        assert_eq!(report.duplicates.len(), 15);
+        assert_eq!(report.ordering_violations, [(9, 0), (9, 0)]);
+
+        let report = harness.with_ordering_check(false).run(&target);
+        assert!(report.ordering_violations.is_empty());
+        assert_eq!(report.duplicates.len(), 15);
+    }
+
+    #[test]
+    #[should_panic(expected = "1 duplicate(s)")]
+    fn collision_report_assert_test() {
+        CollisionReport {
+            generated: 2,
+            errors: 0,
+            duplicates: vec![1],
+            ordering_violations: Vec::new(),
+            elapsed: Duration::ZERO,
+        }
+        .assert_clean();
+    }
+
+    #[test]
+    fn jitter_clock_passthrough_test() {
+        let inner = ManualClock::new(THE_EPOCH_MILLIS);
+        let target = JitterClock::new(inner.clone(), 1);
+
+        assert_eq!(target.now_millis(), THE_EPOCH_MILLIS);
+        inner.advance(Duration::from_millis(3));
+        assert_eq!(target.now_millis(), THE_EPOCH_MILLIS + 3);
+        assert_eq!(target.inner().millis(), THE_EPOCH_MILLIS + 3);
+        assert_eq!((target.stalls(), target.backward_jumps()), (0, 0));
+    }
+
+    #[test]
+    fn jitter_clock_jitter_test() {
+        let readings = |seed| {
+            let target = JitterClock::new(ManualClock::new(THE_EPOCH_MILLIS), seed)
+                .with_jitter(Duration::from_millis(5));
+            (0..1_000).map(|_| target.now_millis()).collect::<Vec<_>>()
+        };
+
+        let actual = readings(7);
+        assert_eq!(actual, readings(7));
+        assert_ne!(actual, readings(8));
+
+        let offsets: HashSet<_> = actual.iter().map(|x| x - THE_EPOCH_MILLIS).collect();
+        assert_eq!(offsets, (-5..=5).collect());
+    }
+
+    #[test]
+    fn jitter_clock_stall_test() {
+        let inner = ManualClock::new(THE_EPOCH_MILLIS);
+        let target = JitterClock::new(inner.clone(), 1).with_stalls(1.0, Duration::from_millis(5));
+
+        assert_eq!(target.now_millis(), THE_EPOCH_MILLIS);
+        inner.advance(Duration::from_millis(4));
+        assert_eq!(target.now_millis(), THE_EPOCH_MILLIS);
+        assert_eq!(target.stalls(), 1);
+
+        // The stall is over; with a certain stall the clock freezes again at once.
+        inner.advance(Duration::from_millis(2));
+        assert_eq!(target.now_millis(), THE_EPOCH_MILLIS + 6);
+        assert_eq!(target.stalls(), 2);
+    }
+
+    #[test]
+    fn jitter_clock_backward_test() {
+        let target = JitterClock::new(ManualClock::new(THE_EPOCH_MILLIS), 1)
+            .with_backward_jumps(1.0, Duration::from_millis(10));
+
+        assert_eq!(target.now_millis(), THE_EPOCH_MILLIS - 10);
+        assert_eq!(target.now_millis(), THE_EPOCH_MILLIS - 20);
+        assert_eq!(target.backward_jumps(), 2);
+
+        let target = JitterClock::new(ManualClock::new(THE_EPOCH_MILLIS), 3)
+            .with_backward_jumps(0.1, Duration::from_millis(1));
+        let readings: Vec<_> = (0..1_000).map(|_| target.now_millis()).collect();
+        assert!((50..150).contains(&target.backward_jumps()));
+        assert_eq!(
+            *readings.last().unwrap(),
+            THE_EPOCH_MILLIS - target.backward_jumps() as i64
+        );
+    }
+
+    #[test]
+    fn jitter_clock_generator_test() {
+        let inner = ManualClock::new(THE_EPOCH_MILLIS + 100);
+        let target = SnowFlakeIdGenerator::with_epoch_millis(
+            JitterClock::new(inner.clone(), 1).with_backward_jumps(1.0, Duration::from_millis(20)),
+            THE_EPOCH_MILLIS,
+            1,
+            BitLayout::DEFAULT,
+        )
+        .unwrap();
+
+        assert!(target.try_generate().is_ok());
+        assert_eq!(
+            target.try_generate(),
+            Err(SnowflakeIdEGeneratorError::ClockMovedBackwards)
+        );
+        assert!(target.clock().backward_jumps() >= 2);
+    }
+}
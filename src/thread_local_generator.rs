@@ -0,0 +1,479 @@
+use crate::bit_layout::BitLayout;
+use crate::clock::Clock;
+use crate::generator_policy::ClockRegressionPolicy;
+use crate::id_generator::IdGenerator;
+use crate::snow_flake_id::SnowflakeId;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_error::SnowflakeIdEGeneratorError::{
+    ClockMovedBackwards, EpochInFuture, MachineIdOutOfRange, SequenceExhausted,
+    ShardBitsOutOfRange, SlotsExhausted,
+};
+use crate::snowflake_id_generator::elapsed_millis;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, TimeZone};
+use std::cell::Cell;
+use std::sync::Mutex;
+
+// Like the sharded generator, the top `thread_bits` of the sequence field are handed to
+// slots, but a slot is owned by a single `LocalGenerator` at a time, so generating is a
+// plain read-modify-write on a `Cell` with no atomics at all. Released slots keep their
+// last issued ID, so a later owner continues from there instead of repeating IDs.
+pub struct ThreadLocalGenerator<T: Clock + Clone> {
+    clock: T,
+    epoch_millis: i64,
+    machine_id: u16,
+    layout: BitLayout,
+    thread_bits: u32,
+    slot_layout: BitLayout,
+    clock_regression_policy: ClockRegressionPolicy,
+    free_slots: Mutex<Vec<(u16, u64)>>,
+}
+
+impl<T: Clock + Clone> ThreadLocalGenerator<T> {
+    #[cfg(feature = "chrono")]
+    pub fn new<Tz: TimeZone>(
+        clock: T,
+        the_epoch: DateTime<Tz>,
+        machine_id: u16,
+        thread_bits: u32,
+    ) -> Result<Self, SnowflakeIdEGeneratorError> {
+        Self::with_layout(
+            clock,
+            the_epoch,
+            machine_id,
+            thread_bits,
+            BitLayout::DEFAULT,
+        )
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn with_layout<Tz: TimeZone>(
+        clock: T,
+        the_epoch: DateTime<Tz>,
+        machine_id: u16,
+        thread_bits: u32,
+        layout: BitLayout,
+    ) -> Result<Self, SnowflakeIdEGeneratorError> {
+        Self::with_epoch_millis(
+            clock,
+            the_epoch.timestamp_millis(),
+            machine_id,
+            thread_bits,
+            layout,
+        )
+    }
+
+    pub fn with_epoch_millis(
+        clock: T,
+        epoch_millis: i64,
+        machine_id: u16,
+        thread_bits: u32,
+        layout: BitLayout,
+    ) -> Result<Self, SnowflakeIdEGeneratorError> {
+        if machine_id > layout.max_machine_id() {
+            return Err(MachineIdOutOfRange {
+                value: machine_id as u64,
+                max: layout.max_machine_id() as u64,
+            });
+        }
+
+        // The widened machine field has to stay within 16 bits as well.
+        let max_bits = layout
+            .sequence_bits()
+            .min(u16::BITS - layout.machine_bits());
+        let out_of_range = ShardBitsOutOfRange {
+            value: thread_bits as u64,
+            max: max_bits as u64,
+        };
+
+        if thread_bits > max_bits {
+            return Err(out_of_range);
+        }
+
+        if clock.now_millis() < epoch_millis {
+            return Err(EpochInFuture);
+        }
+
+        let slot_layout = BitLayout::new(
+            layout.timestamp_bits(),
+            layout.machine_bits() + thread_bits,
+            layout.sequence_bits() - thread_bits,
+        )
+        .map_err(|_| out_of_range)?;
+
+        let free_slots = (0..(1u16 << thread_bits))
+            .rev()
+            .map(|slot| (slot, 0))
+            .collect();
+
+        Ok(ThreadLocalGenerator {
+            clock,
+            epoch_millis,
+            machine_id,
+            layout,
+            thread_bits,
+            slot_layout,
+            clock_regression_policy: ClockRegressionPolicy::default(),
+            free_slots: Mutex::new(free_slots),
+        })
+    }
+
+    pub fn with_clock_regression_policy(mut self, policy: ClockRegressionPolicy) -> Self {
+        self.clock_regression_policy = policy;
+        self
+    }
+
+    pub fn clock_regression_policy(&self) -> ClockRegressionPolicy {
+        self.clock_regression_policy
+    }
+
+    pub fn epoch_millis(&self) -> i64 {
+        self.epoch_millis
+    }
+
+    pub fn machine_id(&self) -> u16 {
+        self.machine_id
+    }
+
+    pub fn layout(&self) -> &BitLayout {
+        &self.layout
+    }
+
+    pub fn thread_bits(&self) -> u32 {
+        self.thread_bits
+    }
+
+    pub fn available_slots(&self) -> usize {
+        self.free_slots.lock().unwrap().len()
+    }
+
+    pub fn local(&self) -> Result<LocalGenerator<'_, T>, SnowflakeIdEGeneratorError> {
+        let (slot, recent) = self
+            .free_slots
+            .lock()
+            .unwrap()
+            .pop()
+            .ok_or(SlotsExhausted)?;
+
+        Ok(LocalGenerator {
+            parent: self,
+            machine_id: (self.machine_id << self.thread_bits) | slot,
+            slot,
+            recent: Cell::new(recent),
+        })
+    }
+
+    fn current_timestamp(&self, last: u64) -> Result<u64, SnowflakeIdEGeneratorError> {
+        loop {
+            let now = elapsed_millis(
+                self.clock.now_millis(),
+                self.epoch_millis,
+                &self.slot_layout,
+            )?;
+
+            if now >= last {
+                return Ok(now);
+            }
+
+            match self.clock_regression_policy {
+                ClockRegressionPolicy::Reject => return Err(ClockMovedBackwards),
+                ClockRegressionPolicy::UseLastTimestamp => return Ok(last),
+                ClockRegressionPolicy::WaitUntilCaughtUp => {
+                    std::thread::sleep(std::time::Duration::from_millis(last - now))
+                }
+            }
+        }
+    }
+}
+
+// A slot of a `ThreadLocalGenerator`. It is `Send` but not `Sync`: move it into the
+// thread that uses it; dropping it gives the slot back to the parent.
+pub struct LocalGenerator<'a, T: Clock + Clone> {
+    parent: &'a ThreadLocalGenerator<T>,
+    machine_id: u16,
+    slot: u16,
+    recent: Cell<u64>,
+}
+
+impl<T: Clock + Clone> LocalGenerator<'_, T> {
+    pub fn slot(&self) -> u16 {
+        self.slot
+    }
+
+    pub fn try_generate(&self) -> Result<SnowflakeId, SnowflakeIdEGeneratorError> {
+        let layout = &self.parent.slot_layout;
+
+        let pivot = SnowflakeId::from(self.recent.get());
+        let last_timestamp = layout.raw_timestamp(pivot);
+        let now = self.parent.current_timestamp(last_timestamp)?;
+
+        let sequence = if last_timestamp == now {
+            let sequence = layout.sequence(pivot);
+            if sequence >= layout.max_sequence() {
+                return Err(SequenceExhausted);
+            }
+            sequence + 1
+        } else {
+            0
+        };
+
+        let id = SnowflakeId::with_layout(now, self.machine_id, sequence, layout).unwrap();
+        self.recent.set(id.as_u64());
+
+        Ok(id)
+    }
+
+    pub fn generate(&self) -> Option<SnowflakeId> {
+        self.try_generate().ok()
+    }
+}
+
+impl<T: Clock + Clone> IdGenerator for LocalGenerator<'_, T> {
+    type Id = SnowflakeId;
+    type Error = SnowflakeIdEGeneratorError;
+
+    fn generate(&self) -> Result<Self::Id, Self::Error> {
+        self.try_generate()
+    }
+}
+
+impl<T: Clock + Clone> Drop for LocalGenerator<'_, T> {
+    fn drop(&mut self) {
+        if let Ok(mut free_slots) = self.parent.free_slots.lock() {
+            free_slots.push((self.slot, self.recent.get()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::clock::{Clock, SystemClock};
+    use crate::snowflake_error::SnowflakeIdEGeneratorError;
+    use crate::thread_local_generator::ThreadLocalGenerator;
+    use crate::THE_EPOCH_MILLIS;
+    use std::collections::HashSet;
+    use std::sync::atomic::AtomicI64;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[derive(Clone)]
+    struct Fixture(Arc<AtomicI64>);
+
+    impl Clock for Fixture {
+        fn now_millis(&self) -> i64 {
+            self.0.load(Relaxed)
+        }
+    }
+
+    fn fixture(elapsed: i64) -> Fixture {
+        Fixture(Arc::new(AtomicI64::new(THE_EPOCH_MILLIS + elapsed)))
+    }
+
+    #[test]
+    fn new_test() {
+        let target = ThreadLocalGenerator::with_epoch_millis(
+            SystemClock,
+            THE_EPOCH_MILLIS,
+            42,
+            3,
+            BitLayout::DEFAULT,
+        )
+        .unwrap();
+
+        assert_eq!(target.machine_id(), 42);
+        assert_eq!(target.thread_bits(), 3);
+        assert_eq!(target.epoch_millis(), THE_EPOCH_MILLIS);
+        assert_eq!(*target.layout(), BitLayout::DEFAULT);
+        assert_eq!(target.available_slots(), 8);
+    }
+
+    #[test]
+    fn invalid_new_test() {
+        let actual = ThreadLocalGenerator::with_epoch_millis(
+            SystemClock,
+            THE_EPOCH_MILLIS,
+            1024,
+            3,
+            BitLayout::DEFAULT,
+        );
+        assert!(matches!(
+            actual,
+            Err(SnowflakeIdEGeneratorError::MachineIdOutOfRange {
+                value: 1024,
+                max: 1023
+            })
+        ));
+
+        let actual = ThreadLocalGenerator::with_epoch_millis(
+            SystemClock,
+            THE_EPOCH_MILLIS,
+            42,
+            13,
+            BitLayout::DEFAULT,
+        );
+        assert!(matches!(
+            actual,
+            Err(SnowflakeIdEGeneratorError::ShardBitsOutOfRange { value: 13, max: 6 })
+        ));
+    }
+
+    #[test]
+    fn epoch_in_future_test() {
+        let actual = ThreadLocalGenerator::with_epoch_millis(
+            fixture(-1),
+            THE_EPOCH_MILLIS,
+            42,
+            1,
+            BitLayout::DEFAULT,
+        );
+        assert!(matches!(
+            actual,
+            Err(SnowflakeIdEGeneratorError::EpochInFuture)
+        ));
+
+        let clock = fixture(5);
+        let target = ThreadLocalGenerator::with_epoch_millis(
+            clock.clone(),
+            THE_EPOCH_MILLIS,
+            42,
+            1,
+            BitLayout::DEFAULT,
+        )
+        .unwrap();
+
+        clock.0.store(THE_EPOCH_MILLIS - 1, Relaxed);
+        assert_eq!(
+            target.local().unwrap().try_generate(),
+            Err(SnowflakeIdEGeneratorError::EpochInFuture)
+        );
+    }
+
+    #[test]
+    fn local_generate_test() {
+        let clock = fixture(5);
+        let target = ThreadLocalGenerator::with_epoch_millis(
+            clock,
+            THE_EPOCH_MILLIS,
+            42,
+            2,
+            BitLayout::DEFAULT,
+        )
+        .unwrap();
+
+        let local = target.local().unwrap();
+        assert_eq!(local.slot(), 0);
+
+        for i in 0..1024u16 {
+            let actual = local.try_generate().unwrap();
+            assert_eq!(actual.raw_timestamp(), 5);
+            assert_eq!(actual.machine_id(), 42);
+            assert_eq!(actual.sequence(), i);
+        }
+
+        assert_eq!(
+            local.try_generate(),
+            Err(SnowflakeIdEGeneratorError::SequenceExhausted)
+        );
+
+        let other = target.local().unwrap();
+        assert_eq!(other.slot(), 1);
+        assert_eq!(other.try_generate().unwrap().sequence(), 1024);
+    }
+
+    #[test]
+    fn slot_reuse_test() {
+        let clock = fixture(5);
+        let target = ThreadLocalGenerator::with_epoch_millis(
+            clock,
+            THE_EPOCH_MILLIS,
+            42,
+            1,
+            BitLayout::DEFAULT,
+        )
+        .unwrap();
+
+        let first = target.local().unwrap();
+        let second = target.local().unwrap();
+        assert!(matches!(
+            target.local(),
+            Err(SnowflakeIdEGeneratorError::SlotsExhausted)
+        ));
+
+        let last = first.try_generate().unwrap();
+        drop(second);
+        drop(first);
+        assert_eq!(target.available_slots(), 2);
+
+        let reused = target.local().unwrap();
+        assert_eq!(reused.slot(), 0);
+        assert!(reused.try_generate().unwrap() > last);
+    }
+
+    #[test]
+    fn clock_regression_test() {
+        let clock = fixture(5);
+        let target = ThreadLocalGenerator::with_epoch_millis(
+            clock.clone(),
+            THE_EPOCH_MILLIS,
+            42,
+            1,
+            BitLayout::DEFAULT,
+        )
+        .unwrap();
+
+        let local = target.local().unwrap();
+        local.try_generate().unwrap();
+
+        clock.0.fetch_sub(1, Relaxed);
+        assert_eq!(
+            local.try_generate(),
+            Err(SnowflakeIdEGeneratorError::ClockMovedBackwards)
+        );
+    }
+
+    #[test]
+    fn multi_thread_unique_test() {
+        let target = Arc::new(
+            ThreadLocalGenerator::with_epoch_millis(
+                SystemClock,
+                THE_EPOCH_MILLIS,
+                42,
+                3,
+                BitLayout::DEFAULT,
+            )
+            .unwrap(),
+        );
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let target = target.clone();
+                thread::spawn(move || {
+                    let local = target.local().unwrap();
+                    let mut ids = Vec::with_capacity(1000);
+
+                    while ids.len() < 1000 {
+                        if let Some(id) = local.generate() {
+                            ids.push(id);
+                        }
+                    }
+
+                    ids
+                })
+            })
+            .collect();
+
+        let mut set = HashSet::new();
+
+        for handle in handles {
+            for id in handle.join().unwrap() {
+                assert_eq!(id.machine_id(), 42);
+                assert!(set.insert(id));
+            }
+        }
+
+        assert_eq!(set.len(), 8000);
+        assert_eq!(target.available_slots(), 8);
+    }
+}
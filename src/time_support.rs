@@ -0,0 +1,146 @@
+use crate::bit_layout::BitLayout;
+use crate::clock::Clock;
+use crate::decoder::DecodedId;
+use crate::snow_flake_id::SnowflakeId;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+use time::{Duration, OffsetDateTime};
+
+// The `time` counterpart of `Timestamp`, for projects that have standardized on `time`
+// and build without chrono. A blanket `Clock` impl would overlap the chrono one, so wrap
+// the source in `TimeClock` to drive a generator with it.
+pub trait OffsetTimestamp {
+    fn timestamp(&self) -> OffsetDateTime;
+}
+
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Default)]
+pub struct DefaultOffsetTimestamp;
+
+#[cfg(feature = "std")]
+impl OffsetTimestamp for DefaultOffsetTimestamp {
+    fn timestamp(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc()
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct TimeClock<T: OffsetTimestamp>(pub T);
+
+impl<T: OffsetTimestamp> Clock for TimeClock<T> {
+    fn now_millis(&self) -> i64 {
+        (self.0.timestamp().unix_timestamp_nanos() / 1_000_000) as i64
+    }
+
+    fn now_micros(&self) -> i64 {
+        (self.0.timestamp().unix_timestamp_nanos() / 1_000) as i64
+    }
+}
+
+fn unix_millis(instant: OffsetDateTime) -> i64 {
+    (instant.unix_timestamp_nanos() / 1_000_000) as i64
+}
+
+impl<T: Clock> SnowFlakeIdGenerator<T> {
+    pub fn with_offset_epoch(
+        timestamp: T,
+        the_epoch: OffsetDateTime,
+        machine_id: u16,
+        layout: BitLayout,
+    ) -> Result<Self, SnowflakeIdEGeneratorError> {
+        Self::with_epoch_millis(timestamp, unix_millis(the_epoch), machine_id, layout)
+    }
+
+    pub fn offset_epoch(&self) -> OffsetDateTime {
+        OffsetDateTime::UNIX_EPOCH + Duration::milliseconds(self.epoch_millis())
+    }
+}
+
+impl SnowflakeId {
+    // The result carries the offset of `the_epoch`; convert with `to_offset` as needed.
+    pub fn offset_timestamp(&self, the_epoch: OffsetDateTime) -> OffsetDateTime {
+        self.offset_timestamp_with_layout(the_epoch, &BitLayout::DEFAULT)
+    }
+
+    pub fn offset_timestamp_with_layout(
+        &self,
+        the_epoch: OffsetDateTime,
+        layout: &BitLayout,
+    ) -> OffsetDateTime {
+        the_epoch + Duration::milliseconds(layout.raw_timestamp(*self) as i64)
+    }
+}
+
+impl DecodedId {
+    pub fn offset_timestamp(&self) -> Option<OffsetDateTime> {
+        OffsetDateTime::from_unix_timestamp_nanos(self.unix_millis? as i128 * 1_000_000).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::Decoder;
+    use crate::THE_EPOCH_MILLIS;
+    use time::macros::datetime;
+
+    struct Fixture;
+
+    impl OffsetTimestamp for Fixture {
+        fn timestamp(&self) -> OffsetDateTime {
+            datetime!(2023-09-01 00:00:00.123456 UTC)
+        }
+    }
+
+    fn the_epoch() -> OffsetDateTime {
+        OffsetDateTime::UNIX_EPOCH + Duration::milliseconds(THE_EPOCH_MILLIS)
+    }
+
+    #[test]
+    fn time_clock_test() {
+        let clock = TimeClock(Fixture);
+        assert_eq!(clock.now_millis(), 1_693_526_400_123);
+        assert_eq!(clock.now_micros(), 1_693_526_400_123_456);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn default_offset_timestamp_test() {
+        let expected = crate::clock::SystemClock.now_millis();
+        let actual = TimeClock(DefaultOffsetTimestamp).now_millis();
+        assert!((actual - expected).abs() < 1_000);
+    }
+
+    #[test]
+    fn generator_test() {
+        let gen = SnowFlakeIdGenerator::with_offset_epoch(
+            TimeClock(Fixture),
+            the_epoch(),
+            42,
+            BitLayout::DEFAULT,
+        )
+        .unwrap();
+
+        assert_eq!(gen.offset_epoch(), the_epoch());
+
+        let id = gen.generate().unwrap();
+        assert_eq!(id.machine_id(), 42);
+        assert_eq!(
+            id.offset_timestamp(the_epoch()),
+            datetime!(2023-09-01 00:00:00.123 UTC)
+        );
+    }
+
+    #[test]
+    fn offset_timestamp_test() {
+        let id = SnowflakeId::with_layout(1_000, 1, 2, &BitLayout::TWITTER).unwrap();
+        let epoch = datetime!(2020-01-01 09:00 +9);
+
+        let actual = id.offset_timestamp_with_layout(epoch, &BitLayout::TWITTER);
+        assert_eq!(actual, datetime!(2020-01-01 09:00:01 +9));
+        assert_eq!(actual.offset(), epoch.offset());
+
+        let decoded = Decoder::new(unix_millis(epoch), BitLayout::TWITTER).decode(id);
+        assert_eq!(decoded.offset_timestamp(), Some(actual));
+    }
+}
@@ -1,19 +1,372 @@
 use chrono::{DateTime, Utc};
+#[cfg(feature = "std")]
+use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed, Release};
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicI64, AtomicU64};
+#[cfg(feature = "std")]
+use std::time::Instant;
 
 pub trait Timestamp {
 	fn timestamp(&self) -> DateTime<Utc>;
+
+	// `resolution` and `is_monotonic` describe the clock for callers that want to know; the
+	// generator itself neither reads them nor trusts them, and `self_test` measures the
+	// clock instead.
+	//
+	// The smallest step between two distinct readings. IDs only carry milliseconds, so
+	// anything finer is fine; a coarser clock repeats timestamps and burns sequence space.
+	fn resolution(&self) -> core::time::Duration {
+		core::time::Duration::from_millis(1)
+	}
+
+	// Whether readings never go backwards. Clocks that promise this let the generator's
+	// clock regression policy stay a formality.
+	fn is_monotonic(&self) -> bool {
+		false
+	}
 }
 
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Default)]
 pub struct DefaultTimestamp;
 
+#[cfg(feature = "std")]
 impl Timestamp for DefaultTimestamp {
 	fn timestamp(&self) -> DateTime<Utc> {
 		Utc::now()
 	}
 }
 
+#[cfg(feature = "std")]
 impl DefaultTimestamp {
 	pub fn new() -> Self {
 		DefaultTimestamp
 	}
 }
+
+// `Date.now()` from the JavaScript host, for browsers and edge workers where
+// `std::time::SystemTime` is unavailable on `wasm32-unknown-unknown`.
+#[cfg(feature = "wasm")]
+#[derive(Clone, Copy, Default)]
+pub struct JsTimestamp;
+
+#[cfg(feature = "wasm")]
+impl Timestamp for JsTimestamp {
+	fn timestamp(&self) -> DateTime<Utc> {
+		DateTime::from_timestamp_millis(js_sys::Date::now() as i64).unwrap()
+	}
+}
+
+// Reads the wall clock once and then advances by the monotonic `Instant` delta, so an NTP
+// step can't push timestamps backwards. The price is drift: a long-running clock no longer
+// follows corrections to the system time.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug)]
+pub struct HybridClock {
+	anchor: DateTime<Utc>,
+	started: Instant,
+}
+
+#[cfg(feature = "std")]
+impl Default for HybridClock {
+	fn default() -> Self {
+		HybridClock::new()
+	}
+}
+
+#[cfg(feature = "std")]
+impl HybridClock {
+	pub fn new() -> Self {
+		HybridClock {
+			anchor: Utc::now(),
+			started: Instant::now(),
+		}
+	}
+
+	pub fn anchor(&self) -> DateTime<Utc> {
+		self.anchor
+	}
+}
+
+#[cfg(feature = "std")]
+impl Timestamp for HybridClock {
+	fn timestamp(&self) -> DateTime<Utc> {
+		self.anchor + self.started.elapsed()
+	}
+
+	fn is_monotonic(&self) -> bool {
+		true
+	}
+}
+
+// Reads the wall clock once per `refresh` interval and in between adds the monotonic
+// elapsed time to that anchor, so the hot path skips the wall clock entirely. Unlike
+// `HybridClock` it keeps following NTP corrections, at the cost that a re-anchor can step
+// backwards; the generator's clock regression policy handles that.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct CachedTimestamp {
+	started: Instant,
+	refresh_millis: u64,
+	// Wall-clock millis minus millis elapsed since `started`, as of the last anchor.
+	offset_millis: AtomicI64,
+	next_refresh: AtomicU64,
+}
+
+#[cfg(feature = "std")]
+impl Default for CachedTimestamp {
+	fn default() -> Self {
+		CachedTimestamp::new(core::time::Duration::from_secs(1))
+	}
+}
+
+#[cfg(feature = "std")]
+impl CachedTimestamp {
+	pub fn new(refresh: core::time::Duration) -> Self {
+		let refresh_millis = u64::try_from(refresh.as_millis()).unwrap_or(u64::MAX);
+
+		CachedTimestamp {
+			started: Instant::now(),
+			refresh_millis,
+			offset_millis: AtomicI64::new(Utc::now().timestamp_millis()),
+			next_refresh: AtomicU64::new(refresh_millis),
+		}
+	}
+
+	pub fn refresh_interval(&self) -> core::time::Duration {
+		core::time::Duration::from_millis(self.refresh_millis)
+	}
+}
+
+#[cfg(feature = "std")]
+impl Timestamp for CachedTimestamp {
+	fn timestamp(&self) -> DateTime<Utc> {
+		let elapsed = self.started.elapsed().as_millis() as u64;
+		let next = self.next_refresh.load(Relaxed);
+
+		// One caller per interval wins the CAS and re-anchors; the rest keep the old offset.
+		if elapsed >= next
+			&& self
+				.next_refresh
+				.compare_exchange(
+					next,
+					elapsed.saturating_add(self.refresh_millis),
+					Relaxed,
+					Relaxed,
+				)
+				.is_ok()
+		{
+			self.offset_millis
+				.store(Utc::now().timestamp_millis() - elapsed as i64, Relaxed);
+		}
+
+		DateTime::from_timestamp_millis(self.offset_millis.load(Relaxed) + elapsed as i64).unwrap()
+	}
+}
+
+// Follows a wall clock (typically PTP- or NTP-disciplined) but never reports an earlier
+// time than it already has. While the source is behind, e.g. after a leap-second step
+// or when a smear is undone, readings advance at half the monotonic rate, so the gap
+// closes gradually and IDs keep their order instead of failing with a clock regression.
+//
+// Lock-free like `CachedTimestamp`: the last reading advances by CAS. The two atomics can
+// be read a moment apart under contention, which at worst speeds the catch-up slightly;
+// readings stay monotonic either way.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct SmearedClock<T: Timestamp> {
+	source: T,
+	anchor: DateTime<Utc>,
+	started: Instant,
+	// The last reading, in nanoseconds since `anchor`.
+	last_nanos: AtomicI64,
+	// When it was taken, in nanoseconds since `started`.
+	at_nanos: AtomicI64,
+}
+
+#[cfg(feature = "std")]
+impl<T: Timestamp> SmearedClock<T> {
+	pub fn new(source: T) -> Self {
+		let anchor = source.timestamp();
+
+		SmearedClock {
+			source,
+			anchor,
+			started: Instant::now(),
+			last_nanos: AtomicI64::new(0),
+			at_nanos: AtomicI64::new(0),
+		}
+	}
+
+	pub fn source(&self) -> &T {
+		&self.source
+	}
+}
+
+#[cfg(feature = "std")]
+impl<T: Timestamp> Timestamp for SmearedClock<T> {
+	fn timestamp(&self) -> DateTime<Utc> {
+		let source = (self.source.timestamp() - self.anchor)
+			.num_nanoseconds()
+			.unwrap_or(i64::MIN);
+		let now = i64::try_from(self.started.elapsed().as_nanos()).unwrap_or(i64::MAX);
+		let mut last = self.last_nanos.load(Acquire);
+
+		loop {
+			let at = self.at_nanos.load(Acquire);
+			let next = source.max(last.saturating_add(now.saturating_sub(at).max(0) / 2));
+
+			match self
+				.last_nanos
+				.compare_exchange_weak(last, next, AcqRel, Acquire)
+			{
+				Ok(_) => {
+					self.at_nanos.fetch_max(now, Release);
+					return self.anchor + chrono::Duration::nanoseconds(next);
+				}
+				Err(actual) => last = actual,
+			}
+		}
+	}
+
+	fn resolution(&self) -> core::time::Duration {
+		self.source.resolution()
+	}
+
+	fn is_monotonic(&self) -> bool {
+		true
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use crate::bit_layout::BitLayout;
+	use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+	use crate::testing::ManualTimestamp;
+	use crate::timestamp::{
+		CachedTimestamp, DefaultTimestamp, HybridClock, SmearedClock, Timestamp,
+	};
+	use crate::{THE_EPOCH, THE_EPOCH_MILLIS};
+	use chrono::{Duration, Utc};
+	use std::thread;
+
+	#[test]
+	fn hybrid_clock_test() {
+		let target = HybridClock::new();
+		assert!((Utc::now() - target.anchor()).num_milliseconds().abs() < 1_000);
+
+		let first = target.timestamp();
+		thread::sleep(std::time::Duration::from_millis(5));
+		let second = target.timestamp();
+
+		assert!(first >= target.anchor());
+		assert!(second - first >= Duration::milliseconds(5));
+	}
+
+	#[test]
+	fn cached_timestamp_test() {
+		let target = CachedTimestamp::new(std::time::Duration::from_secs(60));
+		assert_eq!(
+			target.refresh_interval(),
+			std::time::Duration::from_secs(60)
+		);
+
+		let first = target.timestamp();
+		assert!((Utc::now() - first).num_milliseconds().abs() < 1_000);
+
+		thread::sleep(std::time::Duration::from_millis(5));
+		let second = target.timestamp();
+		assert!(second - first >= Duration::milliseconds(5));
+
+		assert_eq!(
+			CachedTimestamp::default().refresh_interval(),
+			std::time::Duration::from_secs(1)
+		);
+	}
+
+	#[test]
+	fn cached_timestamp_refresh_test() {
+		// A zero interval re-anchors on every read, so it tracks the wall clock exactly.
+		let target = CachedTimestamp::new(std::time::Duration::ZERO);
+
+		for _ in 0..10 {
+			let before = Utc::now().timestamp_millis();
+			let actual = target.timestamp().timestamp_millis();
+			let after = Utc::now().timestamp_millis();
+
+			assert!(before <= actual && actual <= after);
+		}
+	}
+
+	#[test]
+	fn contract_test() {
+		assert_eq!(
+			DefaultTimestamp.resolution(),
+			std::time::Duration::from_millis(1)
+		);
+		assert!(!DefaultTimestamp.is_monotonic());
+		assert!(!ManualTimestamp::new(*THE_EPOCH).is_monotonic());
+		assert!(HybridClock::new().is_monotonic());
+		assert!(SmearedClock::new(DefaultTimestamp).is_monotonic());
+	}
+
+	#[test]
+	fn smeared_clock_test() {
+		let source = ManualTimestamp::new(*THE_EPOCH + Duration::seconds(1));
+		let target = SmearedClock::new(source.clone());
+		assert_eq!(
+			target.source().timestamp(),
+			*THE_EPOCH + Duration::seconds(1)
+		);
+
+		// A leap-second step: the source jumps back, the smeared clock holds its ground.
+		source.advance(Duration::seconds(-1));
+		let held = target.timestamp();
+		assert!(held >= *THE_EPOCH + Duration::seconds(1));
+		assert!(held - (*THE_EPOCH + Duration::seconds(1)) < Duration::milliseconds(500));
+
+		thread::sleep(std::time::Duration::from_millis(10));
+		assert!(target.timestamp() > held);
+
+		// Once the source is ahead again it is followed exactly.
+		source.advance(Duration::seconds(5));
+		assert_eq!(target.timestamp(), *THE_EPOCH + Duration::seconds(5));
+	}
+
+	#[test]
+	fn smeared_clock_concurrent_test() {
+		let source = ManualTimestamp::new(*THE_EPOCH + Duration::seconds(1));
+		let target = SmearedClock::new(source.clone());
+
+		thread::scope(|scope| {
+			for _ in 0..4 {
+				scope.spawn(|| {
+					let readings: Vec<_> = (0..1_000).map(|_| target.timestamp()).collect();
+					assert!(readings.windows(2).all(|pair| pair[0] <= pair[1]));
+				});
+			}
+
+			for _ in 0..100 {
+				source.advance(Duration::milliseconds(-1));
+			}
+		});
+
+		assert!(target.timestamp() >= *THE_EPOCH + Duration::seconds(1));
+	}
+
+	#[test]
+	fn smeared_clock_generator_test() {
+		let clock = ManualTimestamp::new(*THE_EPOCH + Duration::milliseconds(5));
+		let target = SnowFlakeIdGenerator::with_epoch_millis(
+			SmearedClock::new(clock.clone()),
+			THE_EPOCH_MILLIS,
+			1,
+			BitLayout::DEFAULT,
+		)
+		.unwrap();
+
+		let first = target.generate().unwrap();
+		clock.advance(Duration::milliseconds(-2));
+		assert!(target.generate().unwrap() > first);
+	}
+}
@@ -0,0 +1,178 @@
+use crate::id_generator::IdGenerator;
+use crate::snow_flake_id::{SnowflakeId, SnowflakeIdParseError};
+use core::cmp::Ordering;
+use core::fmt::{Debug, Display, Formatter};
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::str::FromStr;
+
+// A `SnowflakeId` tagged with the kind of thing it identifies, so a `TypedId<User>` can't
+// be passed where a `TypedId<Order>` is expected. The marker only exists at compile time:
+// the layout, ordering, text and serde forms are those of the plain ID. The traits are
+// implemented by hand so the marker type needs none of them.
+pub struct TypedId<T> {
+    id: SnowflakeId,
+    // `fn() -> T` keeps the ID `Send + Sync` whatever the marker is.
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> TypedId<T> {
+    pub const fn new(id: SnowflakeId) -> Self {
+        TypedId {
+            id,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn generate<G: IdGenerator<Id = SnowflakeId>>(generator: &G) -> Result<Self, G::Error> {
+        generator.generate().map(TypedId::new)
+    }
+
+    pub const fn id(&self) -> SnowflakeId {
+        self.id
+    }
+
+    pub const fn as_u64(&self) -> u64 {
+        self.id.as_u64()
+    }
+
+    // Moves the ID to another domain, for the rare place that legitimately needs it (e.g.
+    // an entity reusing its parent's ID).
+    pub const fn cast<U>(self) -> TypedId<U> {
+        TypedId::new(self.id)
+    }
+}
+
+impl<T> From<SnowflakeId> for TypedId<T> {
+    fn from(value: SnowflakeId) -> Self {
+        TypedId::new(value)
+    }
+}
+
+impl<T> From<TypedId<T>> for SnowflakeId {
+    fn from(value: TypedId<T>) -> Self {
+        value.id
+    }
+}
+
+impl<T> Clone for TypedId<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for TypedId<T> {}
+
+impl<T> PartialEq for TypedId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for TypedId<T> {}
+
+impl<T> PartialOrd for TypedId<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for TypedId<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+impl<T> Hash for TypedId<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state)
+    }
+}
+
+impl<T> Debug for TypedId<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "TypedId<{}>({})", core::any::type_name::<T>(), self.id)
+    }
+}
+
+impl<T> Display for TypedId<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.id, f)
+    }
+}
+
+impl<T> FromStr for TypedId<T> {
+    type Err = SnowflakeIdParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(TypedId::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::snow_flake_id::SnowflakeId;
+    use crate::typed_id::TypedId;
+
+    struct User;
+    struct Order;
+
+    const SAMPLE: u64 = 175_928_847_299_678_215;
+
+    #[test]
+    fn conversion_test() {
+        let target = TypedId::<User>::new(SnowflakeId::from(SAMPLE));
+        assert_eq!(target.id(), SnowflakeId::from(SAMPLE));
+        assert_eq!(target.as_u64(), SAMPLE);
+        assert_eq!(SnowflakeId::from(target), SnowflakeId::from(SAMPLE));
+        assert_eq!(TypedId::<User>::from(SnowflakeId::from(SAMPLE)), target);
+
+        let order: TypedId<Order> = target.cast();
+        assert_eq!(order.id(), target.id());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn generate_test() {
+        use crate::bit_layout::BitLayout;
+        use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+        use crate::testing::ManualClock;
+        use crate::THE_EPOCH_MILLIS;
+        use std::collections::HashSet;
+
+        let generator = SnowFlakeIdGenerator::with_epoch_millis(
+            ManualClock::new(THE_EPOCH_MILLIS + 5),
+            THE_EPOCH_MILLIS,
+            42,
+            BitLayout::DEFAULT,
+        )
+        .unwrap();
+
+        let first = TypedId::<User>::generate(&generator).unwrap();
+        let second = TypedId::<User>::generate(&generator).unwrap();
+
+        assert!(first < second);
+        assert_eq!(first.id().machine_id(), 42);
+        assert_eq!(HashSet::from([first, second, first]).len(), 2);
+    }
+
+    #[test]
+    fn text_test() {
+        let target = TypedId::<User>::new(SnowflakeId::from(SAMPLE));
+        assert_eq!(target.to_string(), SAMPLE.to_string());
+        assert_eq!(SAMPLE.to_string().parse::<TypedId<User>>(), Ok(target));
+        assert!("user".parse::<TypedId<User>>().is_err());
+
+        let actual = format!("{:?}", target);
+        assert!(actual.starts_with("TypedId<"));
+        assert!(actual.ends_with("User>(175928847299678215)"));
+    }
+
+    #[test]
+    fn send_sync_test() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        // A marker that is neither `Send` nor `Sync` must not leak into the ID.
+        assert_send_sync::<TypedId<std::rc::Rc<User>>>();
+    }
+}
@@ -0,0 +1,442 @@
+use crate::bit_layout::BitLayout;
+use crate::clock::Clock;
+use crate::id_generator::IdGenerator;
+use crate::snow_flake_id::{SnowflakeId, SnowflakeIdError};
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_error::SnowflakeIdEGeneratorError::{
+    ClockMovedBackwards, Contended, SequenceExhausted,
+};
+use crate::ulid::UlidParseError::{Empty, InvalidCharacter, InvalidLength, Overflow};
+use core::fmt::{Debug, Display, Formatter};
+use core::str::FromStr;
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering::Relaxed;
+
+const CROCKFORD_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const ENCODED_LEN: usize = 26;
+
+const TIMESTAMP_BITS: u32 = 48;
+const RANDOM_BITS: u32 = 80;
+const COUNTER_BITS: u32 = 16;
+
+const MAX_TIMESTAMP: u64 = (1 << TIMESTAMP_BITS) - 1;
+const MAX_COUNTER: u64 = (1 << COUNTER_BITS) - 1;
+
+#[derive(PartialEq, Eq)]
+pub enum UlidParseError {
+    Empty,
+    InvalidLength,
+    InvalidCharacter,
+    Overflow,
+}
+
+impl UlidParseError {
+    fn format(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let str = match self {
+            Empty => "UlidParseError::Empty",
+            InvalidLength => "UlidParseError::InvalidLength",
+            InvalidCharacter => "UlidParseError::InvalidCharacter",
+            Overflow => "UlidParseError::Overflow",
+        };
+
+        write!(f, "{}", str)
+    }
+}
+
+impl Debug for UlidParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+impl Display for UlidParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UlidParseError {}
+
+// 48 bits of Unix milliseconds followed by 80 bits of randomness, as in the ULID spec.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub struct Ulid(u128);
+
+impl From<u128> for Ulid {
+    fn from(value: u128) -> Self {
+        Ulid(value)
+    }
+}
+
+impl Ulid {
+    pub fn new(timestamp_millis: u64, random: u128) -> Result<Self, SnowflakeIdError> {
+        if timestamp_millis > MAX_TIMESTAMP {
+            Err(SnowflakeIdError::Timestamp {
+                value: timestamp_millis,
+                max: MAX_TIMESTAMP,
+            })
+        } else {
+            let random = random & ((1 << RANDOM_BITS) - 1);
+            Ok(Ulid(((timestamp_millis as u128) << RANDOM_BITS) | random))
+        }
+    }
+
+    // The machine id and sequence bits of the snowflake end up in the low bits of the
+    // random part, so the conversion can be reversed with `to_snowflake`.
+    pub fn from_snowflake(
+        id: SnowflakeId,
+        epoch_millis: i64,
+        layout: &BitLayout,
+    ) -> Result<Self, SnowflakeIdError> {
+        let timestamp = epoch_millis + layout.raw_timestamp(id) as i64;
+        let random = ((layout.machine_id(id) as u128) << layout.sequence_bits())
+            | layout.sequence(id) as u128;
+
+        if timestamp < 0 {
+            Err(SnowflakeIdError::BeforeEpoch { millis: timestamp })
+        } else {
+            Ulid::new(timestamp as u64, random)
+        }
+    }
+
+    pub fn to_snowflake(
+        &self,
+        epoch_millis: i64,
+        layout: &BitLayout,
+    ) -> Result<SnowflakeId, SnowflakeIdError> {
+        let raw_timestamp = self.timestamp_millis() as i64 - epoch_millis;
+        if raw_timestamp < 0 {
+            return Err(SnowflakeIdError::BeforeEpoch {
+                millis: raw_timestamp,
+            });
+        }
+
+        let random = self.random();
+        let sequence = random & layout.max_sequence() as u128;
+        let machine_id = random >> layout.sequence_bits();
+
+        if machine_id > layout.max_machine_id() as u128 {
+            return Err(SnowflakeIdError::MachineId {
+                value: u64::try_from(machine_id).unwrap_or(u64::MAX),
+                max: layout.max_machine_id() as u64,
+            });
+        }
+
+        layout.compose(raw_timestamp as u64, machine_id as u16, sequence as u16)
+    }
+
+    pub fn timestamp_millis(&self) -> u64 {
+        (self.0 >> RANDOM_BITS) as u64
+    }
+
+    pub fn random(&self) -> u128 {
+        self.0 & ((1 << RANDOM_BITS) - 1)
+    }
+
+    pub fn as_u128(&self) -> u128 {
+        self.0
+    }
+}
+
+impl Display for Ulid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let mut buf = [0u8; ENCODED_LEN];
+
+        for (i, elem) in buf.iter_mut().enumerate() {
+            let shift = (ENCODED_LEN - 1 - i) * 5;
+            *elem = CROCKFORD_ALPHABET[((self.0 >> shift) & 0x1f) as usize];
+        }
+
+        f.write_str(core::str::from_utf8(&buf).unwrap())
+    }
+}
+
+impl FromStr for Ulid {
+    type Err = UlidParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(Empty);
+        }
+
+        if s.len() != ENCODED_LEN {
+            return Err(InvalidLength);
+        }
+
+        let mut value = 0u128;
+
+        for (i, c) in s.bytes().enumerate() {
+            let digit = CROCKFORD_ALPHABET
+                .iter()
+                .position(|x| *x == c.to_ascii_uppercase())
+                .ok_or(InvalidCharacter)?;
+
+            // 26 characters carry 130 bits, so the leading one may only use three of them.
+            if i == 0 && digit > 7 {
+                return Err(Overflow);
+            }
+
+            value = (value << 5) | digit as u128;
+        }
+
+        Ok(Ulid(value))
+    }
+}
+
+impl SnowflakeId {
+    pub fn to_ulid_timestamp(&self, epoch_millis: i64) -> u64 {
+        (epoch_millis + self.raw_timestamp() as i64) as u64
+    }
+}
+
+// Monotonic ULIDs: the random part is a per-generator entropy word followed by a 16-bit
+// counter, and the (timestamp, counter) pair is advanced with the same CAS loop as the
+// snowflake generator.
+pub struct UlidGenerator<T: Clock> {
+    clock: T,
+    entropy: u64,
+    recent: AtomicU64,
+}
+
+impl<T: Clock> UlidGenerator<T> {
+    pub fn new(clock: T, entropy: u64) -> Self {
+        UlidGenerator {
+            clock,
+            entropy,
+            recent: AtomicU64::new(0),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn with_random_entropy(clock: T) -> Self {
+        use std::collections::hash_map::RandomState;
+        use std::hash::{BuildHasher, Hasher};
+
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_i64(clock.now_millis());
+        let entropy = hasher.finish();
+
+        Self::new(clock, entropy)
+    }
+
+    pub fn entropy(&self) -> u64 {
+        self.entropy
+    }
+
+    pub fn try_generate(&self) -> Result<Ulid, SnowflakeIdEGeneratorError> {
+        let pivot = self.recent.load(Relaxed);
+        let last = pivot >> COUNTER_BITS;
+        let now = self.clock.now_millis() as u64 & MAX_TIMESTAMP;
+
+        let (timestamp, counter) = if now > last {
+            (now, 0)
+        } else if now == last {
+            let counter = pivot & MAX_COUNTER;
+            if counter == MAX_COUNTER {
+                return Err(SequenceExhausted);
+            }
+            (now, counter + 1)
+        } else {
+            return Err(ClockMovedBackwards);
+        };
+
+        let candidate = (timestamp << COUNTER_BITS) | counter;
+
+        match self
+            .recent
+            .compare_exchange_weak(pivot, candidate, Relaxed, Relaxed)
+        {
+            Ok(_) => {
+                let random = ((self.entropy as u128) << COUNTER_BITS) | counter as u128;
+                Ok(Ulid::new(timestamp, random).unwrap())
+            }
+            Err(_) => Err(Contended),
+        }
+    }
+
+    pub fn generate(&self) -> Option<Ulid> {
+        self.try_generate().ok()
+    }
+}
+
+impl<T: Clock> IdGenerator for UlidGenerator<T> {
+    type Id = Ulid;
+    type Error = SnowflakeIdEGeneratorError;
+
+    fn generate(&self) -> Result<Self::Id, Self::Error> {
+        self.try_generate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::clock::Clock;
+    use crate::id_generator::IdGenerator;
+    use crate::snow_flake_id::{SnowflakeId, SnowflakeIdError};
+    use crate::snowflake_error::SnowflakeIdEGeneratorError;
+    use crate::ulid::{Ulid, UlidGenerator, UlidParseError};
+    use std::sync::atomic::AtomicI64;
+    use std::sync::atomic::Ordering::Relaxed;
+
+    const SAMPLE: &str = "01ARZ3NDEKTSV4RRFFQ69G5FAV";
+    const SAMPLE_TIMESTAMP: u64 = 1_469_922_850_259;
+    const SAMPLE_RANDOM: u128 = 0xd676_4c61_efb9_9302_bd5b;
+    const DISCORD_EPOCH_MILLIS: i64 = 1_420_070_400_000;
+
+    struct Fixture(AtomicI64);
+
+    impl Clock for &Fixture {
+        fn now_millis(&self) -> i64 {
+            self.0.load(Relaxed)
+        }
+    }
+
+    #[test]
+    fn ulid_parse_error_debug_test() {
+        let targets = [
+            UlidParseError::Empty,
+            UlidParseError::InvalidLength,
+            UlidParseError::InvalidCharacter,
+            UlidParseError::Overflow,
+        ];
+        let expected = [
+            "UlidParseError::Empty",
+            "UlidParseError::InvalidLength",
+            "UlidParseError::InvalidCharacter",
+            "UlidParseError::Overflow",
+        ];
+
+        for elem in targets.iter().zip(expected) {
+            assert_eq!(format!("{}", elem.0), elem.1);
+            assert_eq!(format!("{:?}", elem.0), elem.1);
+        }
+    }
+
+    #[test]
+    fn new_test() {
+        let actual = Ulid::new(SAMPLE_TIMESTAMP, SAMPLE_RANDOM).unwrap();
+
+        assert_eq!(actual.timestamp_millis(), SAMPLE_TIMESTAMP);
+        assert_eq!(actual.random(), SAMPLE_RANDOM);
+        assert_eq!(actual.to_string(), SAMPLE);
+
+        assert_eq!(
+            Ulid::new(1 << 48, 0),
+            Err(SnowflakeIdError::Timestamp {
+                value: 1 << 48,
+                max: (1 << 48) - 1
+            })
+        );
+    }
+
+    #[test]
+    fn parse_test() {
+        let actual: Ulid = SAMPLE.parse().unwrap();
+        assert_eq!(actual, Ulid::new(SAMPLE_TIMESTAMP, SAMPLE_RANDOM).unwrap());
+        assert_eq!(SAMPLE.to_lowercase().parse::<Ulid>(), Ok(actual));
+
+        let max = Ulid::from(u128::MAX);
+        assert_eq!(max.to_string(), "7ZZZZZZZZZZZZZZZZZZZZZZZZZ");
+        assert_eq!(max.to_string().parse::<Ulid>(), Ok(max));
+    }
+
+    #[test]
+    fn invalid_parse_test() {
+        assert_eq!("".parse::<Ulid>(), Err(UlidParseError::Empty));
+        assert_eq!("01ARZ3".parse::<Ulid>(), Err(UlidParseError::InvalidLength));
+        assert_eq!(
+            "01ARZ3NDEKTSV4RRFFQ69G5FAU".parse::<Ulid>(),
+            Err(UlidParseError::InvalidCharacter)
+        );
+        assert_eq!(
+            "81ARZ3NDEKTSV4RRFFQ69G5FAV".parse::<Ulid>(),
+            Err(UlidParseError::Overflow)
+        );
+    }
+
+    #[test]
+    fn snowflake_conversion_test() {
+        let layout = BitLayout::DEFAULT;
+        let id = SnowflakeId::new(1_000, 0x3ff, 0xabc).unwrap();
+
+        assert_eq!(
+            id.to_ulid_timestamp(DISCORD_EPOCH_MILLIS),
+            DISCORD_EPOCH_MILLIS as u64 + 1_000
+        );
+
+        let actual = Ulid::from_snowflake(id, DISCORD_EPOCH_MILLIS, &layout).unwrap();
+        assert_eq!(
+            actual.timestamp_millis(),
+            DISCORD_EPOCH_MILLIS as u64 + 1_000
+        );
+        assert_eq!(actual.random(), 0x3f_fabc);
+        assert_eq!(actual.to_snowflake(DISCORD_EPOCH_MILLIS, &layout), Ok(id));
+
+        assert_eq!(
+            actual.to_snowflake(DISCORD_EPOCH_MILLIS + 1_001, &layout),
+            Err(SnowflakeIdError::BeforeEpoch { millis: -1 })
+        );
+
+        let wide = Ulid::new(SAMPLE_TIMESTAMP, 1 << 22).unwrap();
+        assert_eq!(
+            wide.to_snowflake(0, &layout),
+            Err(SnowflakeIdError::MachineId {
+                value: 1024,
+                max: 1023
+            })
+        );
+    }
+
+    #[test]
+    fn generate_test() {
+        let clock = Fixture(AtomicI64::new(SAMPLE_TIMESTAMP as i64));
+        let target = UlidGenerator::new(&clock, 0xdead_beef);
+        assert_eq!(target.entropy(), 0xdead_beef);
+
+        let first = target.generate().unwrap();
+        let second = IdGenerator::generate(&target).unwrap();
+
+        assert_eq!(first.timestamp_millis(), SAMPLE_TIMESTAMP);
+        assert_eq!(first.random(), 0xdead_beef_0000);
+        assert_eq!(second.random(), 0xdead_beef_0001);
+        assert!(first < second);
+
+        clock.0.fetch_add(1, Relaxed);
+        let third = target.generate().unwrap();
+        assert_eq!(third.timestamp_millis(), SAMPLE_TIMESTAMP + 1);
+        assert_eq!(third.random(), 0xdead_beef_0000);
+        assert!(second < third);
+
+        clock.0.fetch_sub(2, Relaxed);
+        assert_eq!(
+            target.try_generate(),
+            Err(SnowflakeIdEGeneratorError::ClockMovedBackwards)
+        );
+    }
+
+    #[test]
+    fn counter_exhausted_test() {
+        let clock = Fixture(AtomicI64::new(SAMPLE_TIMESTAMP as i64));
+        let target = UlidGenerator::new(&clock, 0);
+
+        for _ in 0..=u16::MAX {
+            target.try_generate().unwrap();
+        }
+
+        assert_eq!(
+            target.try_generate(),
+            Err(SnowflakeIdEGeneratorError::SequenceExhausted)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn random_entropy_test() {
+        let clock = Fixture(AtomicI64::new(SAMPLE_TIMESTAMP as i64));
+        let a = UlidGenerator::with_random_entropy(&clock);
+        let b = UlidGenerator::with_random_entropy(&clock);
+
+        assert_ne!(a.entropy(), b.entropy());
+    }
+}
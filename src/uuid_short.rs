@@ -0,0 +1,160 @@
+use crate::snow_flake_id::SnowflakeId;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+use core::fmt::{Display, Formatter};
+
+// MySQL/MariaDB `UUID_SHORT()`:
+//
+//   (server_id & 255) << 56 | server_startup_seconds << 24 | counter
+//
+// The server id sits above the time and the counter is 24 bits wide, neither of which a
+// `BitLayout` can describe, so the preset is spelled out here instead. The counter is a
+// single 56-bit increment seeded with the startup time, so once it runs past 2^24 it
+// carries into `startup_seconds`: that field is the server's start time only for the first
+// 16M values after a restart. Within one server the values increase; across servers they
+// sort by server id first, unlike snowflake IDs.
+pub const SERVER_ID_BITS: u32 = 8;
+pub const STARTUP_SECONDS_BITS: u32 = 32;
+pub const COUNTER_BITS: u32 = 24;
+
+pub const MAX_COUNTER: u32 = (1 << COUNTER_BITS) - 1;
+
+const STARTUP_SHIFT: u32 = COUNTER_BITS;
+const SERVER_ID_SHIFT: u32 = COUNTER_BITS + STARTUP_SECONDS_BITS;
+
+// A `UUID_SHORT()` value. Both schemes are plain 64-bit integers, so converting to and from
+// `SnowflakeId` keeps the bits: a column holding either can be read through this crate's
+// types, and `UuidShort` is there to take the legacy values apart.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub struct UuidShort(u64);
+
+impl UuidShort {
+    pub const fn new(value: u64) -> Self {
+        UuidShort(value)
+    }
+
+    // `None` if `counter` does not fit in 24 bits.
+    pub const fn from_parts(server_id: u8, startup_seconds: u32, counter: u32) -> Option<Self> {
+        if counter > MAX_COUNTER {
+            None
+        } else {
+            Some(UuidShort(
+                (server_id as u64) << SERVER_ID_SHIFT
+                    | (startup_seconds as u64) << STARTUP_SHIFT
+                    | counter as u64,
+            ))
+        }
+    }
+
+    pub const fn as_u64(&self) -> u64 {
+        self.0
+    }
+
+    pub const fn server_id(&self) -> u8 {
+        (self.0 >> SERVER_ID_SHIFT) as u8
+    }
+
+    pub const fn startup_seconds(&self) -> u32 {
+        (self.0 >> STARTUP_SHIFT) as u32
+    }
+
+    pub const fn counter(&self) -> u32 {
+        self.0 as u32 & MAX_COUNTER
+    }
+
+    // The server start time, subject to the counter carry described above.
+    #[cfg(feature = "chrono")]
+    pub fn startup_time(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.startup_seconds() as i64, 0)
+    }
+}
+
+impl From<u64> for UuidShort {
+    fn from(value: u64) -> Self {
+        UuidShort(value)
+    }
+}
+
+impl From<UuidShort> for u64 {
+    fn from(value: UuidShort) -> Self {
+        value.0
+    }
+}
+
+impl From<UuidShort> for SnowflakeId {
+    fn from(value: UuidShort) -> Self {
+        SnowflakeId::from(value.0)
+    }
+}
+
+impl From<SnowflakeId> for UuidShort {
+    fn from(value: SnowflakeId) -> Self {
+        UuidShort(value.as_u64())
+    }
+}
+
+// Decimal, as MySQL prints it.
+impl Display for UuidShort {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::snow_flake_id::SnowflakeId;
+    use crate::uuid_short::{UuidShort, MAX_COUNTER};
+
+    // `SELECT UUID_SHORT()` on a server with server_id 1, started at 2023-09-01T00:00:00Z.
+    const SAMPLE: u64 = 100_470_252_252_430_341;
+
+    #[test]
+    fn parts_test() {
+        let target = UuidShort::new(SAMPLE);
+        assert_eq!(target.server_id(), 1);
+        assert_eq!(target.startup_seconds(), 1_693_526_400);
+        assert_eq!(target.counter(), 5);
+
+        assert_eq!(UuidShort::from_parts(1, 1_693_526_400, 5), Some(target));
+        assert_eq!(UuidShort::from_parts(1, 0, MAX_COUNTER + 1), None);
+
+        let max = UuidShort::from_parts(255, u32::MAX, MAX_COUNTER).unwrap();
+        assert_eq!(max.as_u64(), u64::MAX);
+        assert_eq!(max.server_id(), 255);
+        assert_eq!(max.startup_seconds(), u32::MAX);
+        assert_eq!(max.counter(), MAX_COUNTER);
+    }
+
+    #[test]
+    fn counter_carry_test() {
+        let last = UuidShort::from_parts(3, 1_000, MAX_COUNTER).unwrap();
+        let next = UuidShort::new(last.as_u64() + 1);
+
+        assert!(next > last);
+        assert_eq!(next.server_id(), 3);
+        assert_eq!(next.startup_seconds(), 1_001);
+        assert_eq!(next.counter(), 0);
+    }
+
+    #[test]
+    fn conversion_test() {
+        let target = UuidShort::from(SAMPLE);
+        assert_eq!(u64::from(target), SAMPLE);
+
+        let id = SnowflakeId::from(target);
+        assert_eq!(id.as_u64(), SAMPLE);
+        assert_eq!(UuidShort::from(id), target);
+        assert_eq!(target.to_string(), "100470252252430341");
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn startup_time_test() {
+        use chrono::{TimeZone, Utc};
+
+        assert_eq!(
+            UuidShort::new(SAMPLE).startup_time(),
+            Some(Utc.with_ymd_and_hms(2023, 9, 1, 0, 0, 0).unwrap())
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use crate::snow_flake_id::SnowflakeId;
+use crate::uuid_support::UuidConversionError::{NotEmbedded, Variant, Version};
+use core::fmt::{Debug, Display, Formatter};
+use uuid::{Uuid, Variant as UuidVariant};
+
+const VERSION_8: u128 = 0x8 << 76;
+const RFC4122_VARIANT: u128 = 0b10 << 62;
+
+const HIGH_MASK: u64 = 0xffff_ffff_ffff_0000;
+const MID_MASK: u64 = 0x0000_0000_0000_fff0;
+const LOW_MASK: u64 = 0x0000_0000_0000_000f;
+
+#[derive(PartialEq, Eq)]
+pub enum UuidConversionError {
+    Version,
+    Variant,
+    NotEmbedded,
+}
+
+impl UuidConversionError {
+    fn format(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let str = match self {
+            Version => "UuidConversionError::Version",
+            Variant => "UuidConversionError::Variant",
+            NotEmbedded => "UuidConversionError::NotEmbedded",
+        };
+
+        write!(f, "{}", str)
+    }
+}
+
+impl Debug for UuidConversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+impl Display for UuidConversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.format(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UuidConversionError {}
+
+// The 64 ID bits are spread over the custom fields of a UUIDv8 in their original order:
+// the top 48 bits fill custom_a, the next 12 custom_b and the last 4 open custom_c,
+// whose remaining bits stay zero. Byte-wise UUID order therefore matches ID order.
+impl SnowflakeId {
+    pub fn into_uuid_v8(self) -> Uuid {
+        let id = self.as_u64();
+
+        let high = ((id & HIGH_MASK) as u128) << 64;
+        let mid = ((id & MID_MASK) as u128) << 60;
+        let low = ((id & LOW_MASK) as u128) << 58;
+
+        Uuid::from_u128(high | VERSION_8 | mid | RFC4122_VARIANT | low)
+    }
+
+    pub fn try_from_uuid(uuid: Uuid) -> Result<Self, UuidConversionError> {
+        if uuid.get_version_num() != 8 {
+            return Err(Version);
+        }
+
+        if uuid.get_variant() != UuidVariant::RFC4122 {
+            return Err(Variant);
+        }
+
+        let value = uuid.as_u128();
+        if value & ((1 << 58) - 1) != 0 {
+            return Err(NotEmbedded);
+        }
+
+        let high = (value >> 64) as u64 & HIGH_MASK;
+        let mid = (value >> 60) as u64 & MID_MASK;
+        let low = (value >> 58) as u64 & LOW_MASK;
+
+        Ok(SnowflakeId::from(high | mid | low))
+    }
+}
+
+impl From<SnowflakeId> for Uuid {
+    fn from(value: SnowflakeId) -> Self {
+        value.into_uuid_v8()
+    }
+}
+
+impl TryFrom<Uuid> for SnowflakeId {
+    type Error = UuidConversionError;
+
+    fn try_from(value: Uuid) -> Result<Self, Self::Error> {
+        SnowflakeId::try_from_uuid(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::snow_flake_id::SnowflakeId;
+    use crate::uuid_support::UuidConversionError;
+    use uuid::{Uuid, Variant};
+
+    const SAMPLE_SCR: u64 = 175_928_847_299_678_215;
+
+    #[test]
+    fn uuid_conversion_error_debug_test() {
+        let targets = [
+            UuidConversionError::Version,
+            UuidConversionError::Variant,
+            UuidConversionError::NotEmbedded,
+        ];
+        let expected = [
+            "UuidConversionError::Version",
+            "UuidConversionError::Variant",
+            "UuidConversionError::NotEmbedded",
+        ];
+
+        for elem in targets.iter().zip(expected) {
+            assert_eq!(format!("{}", elem.0), elem.1);
+            assert_eq!(format!("{:?}", elem.0), elem.1);
+        }
+    }
+
+    #[test]
+    fn into_uuid_v8_test() {
+        let actual = SnowflakeId::from(u64::MAX).into_uuid_v8();
+
+        assert_eq!(actual.get_version_num(), 8);
+        assert_eq!(actual.get_variant(), Variant::RFC4122);
+        assert_eq!(actual.to_string(), "ffffffff-ffff-8fff-bc00-000000000000");
+
+        let actual = SnowflakeId::from(SAMPLE_SCR).into_uuid_v8();
+        assert_eq!(actual.get_version_num(), 8);
+        assert_eq!(Uuid::from(SnowflakeId::from(SAMPLE_SCR)), actual);
+    }
+
+    #[test]
+    fn round_trip_test() {
+        for value in [0, 1, 0xf, 0x10, SAMPLE_SCR, u64::MAX >> 1, u64::MAX] {
+            let id = SnowflakeId::from(value);
+            assert_eq!(SnowflakeId::try_from_uuid(id.into_uuid_v8()), Ok(id));
+            assert_eq!(SnowflakeId::try_from(Uuid::from(id)), Ok(id));
+        }
+    }
+
+    #[test]
+    fn order_test() {
+        let ids = [0, 0xf, 0x10, 0xfff0, 0x1_0000, SAMPLE_SCR, u64::MAX];
+
+        for pair in ids.windows(2) {
+            let lhs = SnowflakeId::from(pair[0]).into_uuid_v8();
+            let rhs = SnowflakeId::from(pair[1]).into_uuid_v8();
+            assert!(lhs.as_bytes() < rhs.as_bytes());
+        }
+    }
+
+    #[test]
+    fn invalid_try_from_uuid_test() {
+        let v4 = Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+        assert_eq!(
+            SnowflakeId::try_from_uuid(v4),
+            Err(UuidConversionError::Version)
+        );
+
+        let variant = Uuid::parse_str("ffffffff-ffff-8fff-fc00-000000000000").unwrap();
+        assert_eq!(
+            SnowflakeId::try_from_uuid(variant),
+            Err(UuidConversionError::Variant)
+        );
+
+        let foreign = Uuid::parse_str("ffffffff-ffff-8fff-bc00-000000000001").unwrap();
+        assert_eq!(
+            SnowflakeId::try_from_uuid(foreign),
+            Err(UuidConversionError::NotEmbedded)
+        );
+    }
+}
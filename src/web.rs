@@ -0,0 +1,234 @@
+use crate::clock::Clock;
+use crate::serde_support::SnowflakeIdStr;
+use crate::snow_flake_id::SnowflakeId;
+use crate::snowflake_error::SnowflakeIdEGeneratorError;
+use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+use axum::extract::{FromRequestParts, Path, Query, State};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{async_trait, Json, Router};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const MAX_BATCH: usize = 65_536;
+
+fn bad_request(message: String) -> Response {
+    (StatusCode::BAD_REQUEST, message).into_response()
+}
+
+fn unavailable(e: SnowflakeIdEGeneratorError) -> Response {
+    (StatusCode::SERVICE_UNAVAILABLE, e.to_string()).into_response()
+}
+
+// The route's single path parameter as a `SnowflakeId`, e.g. `/orders/:id`. Anything that
+// isn't a decimal u64 is rejected with 400 and the parse error.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct IdPath(pub SnowflakeId);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IdPath {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path(str) = Path::<String>::from_request_parts(parts, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        str.parse()
+            .map(IdPath)
+            .map_err(|e| bad_request(format!("invalid snowflake id {:?}: {}", str, e)))
+    }
+}
+
+#[derive(Deserialize)]
+struct IdParams {
+    id: SnowflakeIdStr,
+}
+
+// The `id` query parameter as a `SnowflakeId`, e.g. `/orders?id=175928847299678215`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct IdQuery(pub SnowflakeId);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IdQuery {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Query::<IdParams>::from_request_parts(parts, state)
+            .await
+            .map(|Query(params)| IdQuery(params.id.id()))
+            .map_err(|e| bad_request(e.body_text()))
+    }
+}
+
+#[derive(Serialize)]
+struct IdResponse {
+    id: SnowflakeIdStr,
+}
+
+#[derive(Deserialize)]
+struct BatchParams {
+    n: usize,
+}
+
+#[derive(Serialize)]
+struct BatchResponse {
+    ids: Vec<SnowflakeIdStr>,
+}
+
+async fn id<T: Clock>(
+    State(generator): State<Arc<SnowFlakeIdGenerator<T>>>,
+) -> Result<Json<IdResponse>, Response> {
+    let id = generator.generate_async().await.map_err(unavailable)?;
+    Ok(Json(IdResponse { id: id.into() }))
+}
+
+async fn batch<T: Clock>(
+    State(generator): State<Arc<SnowFlakeIdGenerator<T>>>,
+    Query(params): Query<BatchParams>,
+) -> Result<Json<BatchResponse>, Response> {
+    if params.n > MAX_BATCH {
+        return Err(bad_request(format!("n must be at most {}", MAX_BATCH)));
+    }
+
+    let ids = generator
+        .generate_many_async(params.n)
+        .await
+        .map_err(unavailable)?;
+
+    Ok(Json(BatchResponse {
+        ids: ids.into_iter().map(SnowflakeIdStr::from).collect(),
+    }))
+}
+
+// `GET /id` -> `{"id":"..."}` and `GET /id/batch?n=` -> `{"ids":["...", ...]}`. IDs are
+// strings so JavaScript clients don't round them. Merge or nest it into the app's router.
+pub fn router<T: Clock + Send + Sync + 'static>(generator: Arc<SnowFlakeIdGenerator<T>>) -> Router {
+    Router::new()
+        .route("/id", get(id::<T>))
+        .route("/id/batch", get(batch::<T>))
+        .with_state(generator)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_layout::BitLayout;
+    use crate::clock::{Clock, SystemClock};
+    use crate::snow_flake_id::SnowflakeId;
+    use crate::snowflake_id_generator::SnowFlakeIdGenerator;
+    use crate::web::{router, IdPath, IdQuery, MAX_BATCH};
+    use crate::THE_EPOCH_MILLIS;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use axum::routing::get;
+    use axum::Router;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    struct Fixture(i64);
+
+    impl Clock for Fixture {
+        fn now_millis(&self) -> i64 {
+            self.0
+        }
+    }
+
+    fn generator<T: Clock>(clock: T) -> Arc<SnowFlakeIdGenerator<T>> {
+        Arc::new(
+            SnowFlakeIdGenerator::with_epoch_millis(clock, THE_EPOCH_MILLIS, 7, BitLayout::DEFAULT)
+                .unwrap(),
+        )
+    }
+
+    async fn get_uri(app: Router, uri: &str) -> (StatusCode, String) {
+        let response = app
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    fn extractor_app() -> Router {
+        Router::new()
+            .route(
+                "/items/:id",
+                get(|IdPath(id): IdPath| async move { id.machine_id().to_string() }),
+            )
+            .route(
+                "/items",
+                get(|IdQuery(id): IdQuery| async move { id.sequence().to_string() }),
+            )
+    }
+
+    #[tokio::test]
+    async fn id_path_test() {
+        let id = SnowflakeId::new(5, 42, 3).unwrap();
+
+        let actual = get_uri(extractor_app(), &format!("/items/{}", id)).await;
+        assert_eq!(actual, (StatusCode::OK, "42".to_string()));
+
+        let (status, body) = get_uri(extractor_app(), "/items/abc").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("abc"));
+    }
+
+    #[tokio::test]
+    async fn id_query_test() {
+        let id = SnowflakeId::new(5, 42, 3).unwrap();
+
+        let actual = get_uri(extractor_app(), &format!("/items?id={}", id)).await;
+        assert_eq!(actual, (StatusCode::OK, "3".to_string()));
+
+        let (status, _) = get_uri(extractor_app(), "/items?id=-1").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = get_uri(extractor_app(), "/items").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn id_route_test() {
+        let generator = generator(Fixture(THE_EPOCH_MILLIS + 5));
+        let expected = SnowflakeId::new(5, 7, 0).unwrap();
+
+        let actual = get_uri(router(generator.clone()), "/id").await;
+        assert_eq!(
+            actual,
+            (StatusCode::OK, format!(r#"{{"id":"{}"}}"#, expected))
+        );
+
+        generator.restore(SnowflakeId::new(6, 0, 0).unwrap());
+        let (status, _) = get_uri(router(generator), "/id").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn batch_route_test() {
+        let app = router(generator(SystemClock));
+
+        let (status, body) = get_uri(app.clone(), "/id/batch?n=5000").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let actual: serde_json::Value = serde_json::from_str(&body).unwrap();
+        let ids: Vec<u64> = actual["ids"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|id| id.as_str().unwrap().parse().unwrap())
+            .collect();
+        assert_eq!(ids.len(), 5000);
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let uri = format!("/id/batch?n={}", MAX_BATCH + 1);
+        let (status, _) = get_uri(app.clone(), &uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = get_uri(app, "/id/batch").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}